@@ -0,0 +1,67 @@
+//! Aggregation module for maintaining precomputed statistics.
+//!
+//! Computing metrics over the raw transactions collection on every API request
+//! is expensive, so a background task periodically rolls them up into the
+//! `daily_stats` collection. Each run recomputes the current day and the
+//! previous one, so blocks indexed late (for example by the catch-up task)
+//! are still reflected in yesterday's numbers.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Days, Utc};
+use log::{error, info};
+
+use crate::domain::storage::Storage;
+
+/// Number of most recent days recomputed on every run.
+const DAYS_PER_RUN: u64 = 2;
+
+/// Returns midnight UTC of the day containing `time`.
+pub fn start_of_day(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("Infallible")
+        .and_utc()
+}
+
+/// Starts the aggregation service.
+///
+/// # Arguments
+///
+/// * `storage` - Storage instance holding the transactions and rollups
+/// * `interval` - Time between aggregation runs
+///
+/// # Returns
+///
+/// * `eyre::Result<()>` - Runs indefinitely
+pub async fn start(storage: Arc<Storage>, interval: Duration) -> eyre::Result<()> {
+    info!("Starting aggregation service...");
+
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        let today = start_of_day(Utc::now());
+
+        for days_ago in (0..DAYS_PER_RUN).rev() {
+            let Some(day) = today.checked_sub_days(Days::new(days_ago)) else {
+                continue;
+            };
+
+            let result = async {
+                let stats = storage.compute_daily_stats(day).await?;
+                storage.upsert_daily_stats(&stats).await
+            }
+            .await;
+
+            if let Err(err) = result {
+                error!(
+                    "Error aggregating stats for {}: {:?}",
+                    day.date_naive(),
+                    err
+                );
+            }
+        }
+    }
+}
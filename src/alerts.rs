@@ -0,0 +1,82 @@
+//! Large-transfer ("whale") detection.
+//!
+//! Every stored transaction is checked against the alert rules from the
+//! runtime configuration. Matching transfers are recorded in the `alerts`
+//! collection and, when a webhook is configured, delivered to it as JSON.
+
+use log::{error, warn};
+use mongodb::bson;
+
+use crate::{
+    config::AlertRule,
+    domain::models::{
+        alert::Alert,
+        transaction::Transaction,
+        transfer::{extract_transfers, Transfer},
+    },
+};
+
+/// Checks whether a transfer is of the rule's asset and meets its threshold.
+fn matches(rule: &AlertRule, transfer: &Transfer) -> bool {
+    let asset_match = match &transfer.mint {
+        Some(mint) => *mint == rule.asset,
+        None => rule.asset.eq_ignore_ascii_case(AlertRule::SOL),
+    };
+
+    asset_match && transfer.ui_amount() >= rule.min_amount
+}
+
+/// Evaluates the alert rules against a transaction.
+///
+/// # Arguments
+///
+/// * `rules` - Alert rules to evaluate
+/// * `transaction` - Transaction to inspect
+/// * `slot` - Slot of the block containing the transaction
+///
+/// # Returns
+///
+/// * `Vec<Alert>` - One alert per matching rule and transfer
+pub fn evaluate(rules: &[AlertRule], transaction: &Transaction, slot: u64) -> Vec<Alert> {
+    if rules.is_empty() {
+        return Vec::new();
+    }
+
+    let created_at = bson::DateTime::now();
+
+    let mut alerts = Vec::new();
+
+    for transfer in extract_transfers(transaction) {
+        for rule in rules.iter().filter(|rule| matches(rule, &transfer)) {
+            alerts.push(Alert {
+                rule: rule.name.clone(),
+                signature: transaction.signature.clone(),
+                slot,
+                block_time: transaction.block_time,
+                transfer: transfer.clone(),
+                created_at,
+            });
+        }
+    }
+
+    alerts
+}
+
+/// Delivers alerts to a webhook as a JSON array.
+///
+/// Failures are logged rather than returned, since alert delivery must never
+/// hold up block processing.
+pub async fn notify(client: reqwest::Client, url: String, alerts: Vec<Alert>) {
+    let result = client
+        .post(&url)
+        .json(&alerts)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+
+    match result {
+        Ok(_) => {}
+        Err(e) if e.is_status() => warn!("Alert webhook {} rejected alerts: {}", url, e),
+        Err(e) => error!("Error delivering alerts to {}: {}", url, e),
+    }
+}
@@ -0,0 +1,137 @@
+//! Address activity endpoints.
+//!
+//! Serve the running totals kept for every address involved in a stored
+//! transaction, so the summary of even a very active address is a single
+//! document lookup, and the accounts an address exchanged SOL with,
+//! aggregated from the indexed SOL transfers.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{optional_number, validate_slot_range, Paginated, Validate},
+    AppState, NetworkQuery,
+};
+use crate::domain::models::{address_summary::AddressSummary, counterparty::Counterparty};
+
+/// Response format for the address summary endpoint.
+#[derive(Serialize, Debug)]
+pub struct AddressSummaryResponse {
+    /// Activity summary of the address
+    pub data: AddressSummary,
+}
+
+/// Handles requests for the activity summary of an address.
+///
+/// Returns `404 Not Found` if no stored transaction involves the address.
+///
+/// # Arguments
+///
+/// * `Path(pubkey)` - Account address
+/// * `params` - Network selection
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<AddressSummaryResponse>, (StatusCode, String)>` - The summary or error
+pub async fn fetch_address_summary(
+    Path(pubkey): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<AddressSummaryResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let summary = network
+        .storage
+        .get_address_summary(&pubkey)
+        .await
+        .map_err(|e| {
+            error!("Error fetching address summary: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching address summary".to_string(),
+            )
+        })?;
+
+    let data = summary.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("No activity indexed for address: {}", pubkey),
+        )
+    })?;
+
+    Ok(Json(AddressSummaryResponse { data }))
+}
+
+/// Query parameters for the counterparties endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct CounterpartyQuery {
+    /// Optional lowest slot of the transfers counted, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot of the transfers counted, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for CounterpartyQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_slot_range(self.from_slot, self.to_slot)
+    }
+}
+
+/// Response format for the counterparties endpoint.
+#[derive(Serialize, Debug)]
+pub struct CounterpartyResponse {
+    /// Counterparties, most transfers first
+    pub data: Vec<Counterparty>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for the accounts an address exchanged SOL with.
+///
+/// Counts and volumes are aggregated from the indexed SOL transfers in
+/// either direction, optionally restricted to a slot range.
+///
+/// # Arguments
+///
+/// * `Path(pubkey)` - Account address
+/// * `params` - Query parameters including pagination and slot range
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<CounterpartyResponse>, (StatusCode, String)>` - Counterparties or error
+pub async fn fetch_counterparties(
+    Path(pubkey): Path<String>,
+    params: Paginated<CounterpartyQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<CounterpartyResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_counterparties(
+            &pubkey,
+            params.data.from_slot,
+            params.data.to_slot,
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching counterparties: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching counterparties".to_string(),
+            )
+        })?;
+
+    Ok(Json(CounterpartyResponse { data, next }))
+}
@@ -0,0 +1,218 @@
+//! Administrative API endpoints.
+//!
+//! These routes are only mounted when an admin token is configured, and every
+//! request must carry it as a bearer token.
+
+use axum::{
+    extract::{Path, Query, Request, State},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use http::{header::AUTHORIZATION, StatusCode};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{AppState, NetworkQuery};
+use crate::{
+    config::{Filters, RateLimit},
+    domain::models::api_key::ApiKey,
+};
+
+/// Response format for the configuration reload endpoint.
+#[derive(Serialize, Debug)]
+pub struct ReloadResponse {
+    /// Number of watched addresses after the reload
+    pub watched_addresses: usize,
+    /// Number of watched programs after the reload
+    pub watched_programs: usize,
+    /// Whether API rate limiting is enabled after the reload
+    pub rate_limited: bool,
+}
+
+/// Handles requests to reload the runtime configuration.
+///
+/// Re-reads the configuration file, exactly as `SIGHUP` does.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the configuration handle
+///
+/// # Returns
+///
+/// * `Result<Json<ReloadResponse>, (StatusCode, String)>` - Summary of the applied configuration or error
+async fn reload_config(
+    State(state): State<AppState>,
+) -> Result<Json<ReloadResponse>, (StatusCode, String)> {
+    let config = state.config.reload().map_err(|e| {
+        error!("Error reloading configuration: {:?}", e);
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Error reloading configuration: {}", e),
+        )
+    })?;
+
+    Ok(Json(ReloadResponse {
+        watched_addresses: config.filters.addresses.len(),
+        watched_programs: config.filters.programs.len(),
+        rate_limited: config.rate_limit.is_some(),
+    }))
+}
+
+/// Request body for issuing an API key.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyRequest {
+    /// Tenant name
+    pub name: String,
+    /// Rate limit for the key; the global limit applies when unset
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Path prefixes the key may call; every endpoint when empty
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Addresses and programs the key's transactions and alerts are limited to
+    #[serde(default)]
+    pub scope: Option<Filters>,
+}
+
+/// Response format for the API key listing endpoint.
+#[derive(Serialize, Debug)]
+pub struct ApiKeysResponse {
+    /// API keys, ordered by tenant name
+    pub data: Vec<ApiKey>,
+}
+
+/// Handles requests listing all API keys.
+async fn fetch_api_keys(State(state): State<AppState>) -> Json<ApiKeysResponse> {
+    Json(ApiKeysResponse {
+        data: state.tenants.list(),
+    })
+}
+
+/// Handles requests issuing a new API key.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the tenant registry
+/// * `Json(body)` - Tenant name and access policy
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<ApiKey>), (StatusCode, String)>` - The issued key or error
+async fn create_api_key(
+    State(state): State<AppState>,
+    Json(body): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<ApiKey>), (StatusCode, String)> {
+    if body.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Name must not be empty".to_string(),
+        ));
+    }
+
+    let key = state
+        .tenants
+        .create(body.name, body.rate_limit, body.endpoints, body.scope)
+        .await
+        .map_err(|e| {
+            error!("Error creating API key: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error creating API key".to_string(),
+            )
+        })?;
+
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+/// Handles requests revoking an API key.
+///
+/// Returns `404 Not Found` if the key does not exist.
+async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let revoked = state.tenants.revoke(&key).await.map_err(|e| {
+        error!("Error revoking API key: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error revoking API key".to_string(),
+        )
+    })?;
+
+    if revoked {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "Unknown API key".to_string()))
+    }
+}
+
+/// Handles requests for the raw JSON of an archived block, exactly as it
+/// was received from the RPC.
+///
+/// Returns `404 Not Found` if the block wasn't archived; see
+/// `--archive-raw-blocks`.
+///
+/// # Arguments
+///
+/// * `Path(slot)` - Block slot
+/// * `params` - Network selection
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - The raw block or error
+async fn fetch_raw_block(
+    Path(slot): Path<u64>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let block = network.storage.get_raw_block(slot).await.map_err(|e| {
+        error!("Error fetching raw block: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error fetching raw block".to_string(),
+        )
+    })?;
+
+    block
+        .map(|block| Json(block).into_response())
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Raw block not archived: {}", slot),
+            )
+        })
+}
+
+/// Middleware rejecting requests without the configured admin bearer token.
+pub(super) async fn authorize(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (token, state.admin_token.as_deref()) {
+        (Some(token), Some(expected)) if token == expected => next.run(request).await,
+        _ => (StatusCode::UNAUTHORIZED, "Invalid admin token").into_response(),
+    }
+}
+
+/// Builds the admin router.
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/reload", post(reload_config))
+        .route("/keys", get(fetch_api_keys).post(create_api_key))
+        .route("/keys/{key}", delete(revoke_api_key))
+        .route("/raw-blocks/{slot}", get(fetch_raw_block))
+        .route_layer(middleware::from_fn_with_state(state, authorize))
+}
@@ -0,0 +1,84 @@
+//! Balance history endpoint.
+//!
+//! Serves the SOL balance changes of an account from the balance change
+//! ledger, most recent first, so clients can chart a balance over time
+//! without fetching and replaying full transactions.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{optional_number, validate_slot_range, Paginated, Validate},
+    AppState,
+};
+use crate::domain::models::balance_change::BalanceChange;
+
+/// Query parameters for the balance history endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct BalanceHistoryQuery {
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for BalanceHistoryQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_slot_range(self.from_slot, self.to_slot)
+    }
+}
+
+/// Response format for the balance history endpoint.
+#[derive(Serialize, Debug)]
+pub struct BalanceHistoryResponse {
+    /// Balance changes, most recent first
+    pub data: Vec<BalanceChange>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for the SOL balance history of an account.
+///
+/// # Arguments
+///
+/// * `Path(pubkey)` - Account address
+/// * `params` - Query parameters including pagination and slot bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<BalanceHistoryResponse>, (StatusCode, String)>` - Balance changes or error
+pub async fn fetch_balance_history(
+    Path(pubkey): Path<String>,
+    params: Paginated<BalanceHistoryQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<BalanceHistoryResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_balance_changes(
+            &pubkey,
+            params.data.from_slot,
+            params.data.to_slot,
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching balance history: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching balance history".to_string(),
+            )
+        })?;
+
+    Ok(Json(BalanceHistoryResponse { data, next }))
+}
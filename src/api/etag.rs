@@ -0,0 +1,68 @@
+//! Conditional request support.
+//!
+//! Responses carry an `ETag` derived from a hash of their JSON body. Clients
+//! that send the tag back in `If-None-Match` get an empty `304 Not Modified`
+//! when the content hasn't changed, which keeps polling cheap.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use axum::response::{IntoResponse, Response};
+use http::{
+    header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    HeaderMap, HeaderValue, StatusCode,
+};
+use log::error;
+use serde::Serialize;
+
+/// Computes the entity tag of a serialized body.
+pub fn tag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Checks whether an `If-None-Match` header value matches `etag`.
+///
+/// Handles `*`, comma-separated lists and weak (`W/`) validators.
+pub fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// Builds a JSON response with an `ETag`, or `304 Not Modified` if the
+/// request's `If-None-Match` already names the current content.
+pub fn respond<T: Serialize>(headers: &HeaderMap, body: &T) -> Response {
+    let bytes = match serde_json::to_vec(body) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Error serializing response: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error serializing response",
+            )
+                .into_response();
+        }
+    };
+
+    let etag = tag(&bytes);
+    let etag_header = HeaderValue::from_str(&etag).expect("Hex digest is a valid header value");
+
+    let not_modified = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| matches(value, &etag));
+
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag_header)]).into_response();
+    }
+
+    (
+        [
+            (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            (ETAG, etag_header),
+        ],
+        bytes,
+    )
+        .into_response()
+}
@@ -0,0 +1,136 @@
+//! Fee estimation endpoints.
+//!
+//! Combines the RPC node's view of recent prioritization fees with the
+//! compute unit prices of transactions SolDag has actually indexed, so
+//! clients can compare what the node suggests with what landed.
+
+use std::str::FromStr;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{TimeDelta, Utc};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_sdk::pubkey::Pubkey;
+
+use super::{rpc::rpc_error, AppState};
+use crate::domain::models::fee_stats::FeeStats;
+
+/// Maximum number of accounts accepted by `getRecentPrioritizationFees`.
+const MAX_ACCOUNTS: usize = 128;
+
+/// Maximum number of landed transactions sampled for the statistics.
+const MAX_LANDED_SAMPLES: i64 = 10_000;
+
+/// Query parameters for the prioritization fees endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrioritizationFeesQuery {
+    /// Comma-separated list of accounts the transaction will write-lock
+    accounts: Option<String>,
+    /// Number of seconds of indexed transactions to sample, defaults to 120
+    window: Option<u64>,
+    /// Network to query, defaults to the primary network
+    network: Option<String>,
+}
+
+/// Prioritization fees reported by the RPC node.
+#[derive(Serialize, Debug)]
+pub struct RpcFees {
+    /// Minimum prioritization fee per recent slot
+    pub fees: Vec<RpcPrioritizationFee>,
+    /// Percentiles over the per-slot fees
+    pub stats: Option<FeeStats>,
+}
+
+/// Compute unit prices of indexed transactions.
+#[derive(Serialize, Debug)]
+pub struct LandedFees {
+    /// Number of seconds of transactions sampled
+    pub window: u64,
+    /// Percentiles over the sampled compute unit prices
+    pub stats: Option<FeeStats>,
+}
+
+/// Response format for the prioritization fees endpoint.
+#[derive(Serialize, Debug)]
+pub struct PrioritizationFeesResponse {
+    /// What the RPC node suggests, in micro-lamports per compute unit
+    pub rpc: RpcFees,
+    /// What actually landed, in micro-lamports per compute unit
+    pub landed: LandedFees,
+}
+
+/// Handles requests for recent prioritization fees.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters with the accounts to consider and sampling window
+/// * `State(state)` - Application state containing the networks' storage and indexers
+///
+/// # Returns
+///
+/// * `Result<Json<PrioritizationFeesResponse>, (StatusCode, String)>` - Fee statistics or error
+pub async fn fetch_prioritization_fees(
+    Query(params): Query<PrioritizationFeesQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<PrioritizationFeesResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let accounts: Vec<String> = params
+        .accounts
+        .iter()
+        .flat_map(|accounts| accounts.split(','))
+        .filter(|account| !account.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if accounts.len() > MAX_ACCOUNTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} accounts are supported", MAX_ACCOUNTS),
+        ));
+    }
+
+    let pubkeys = accounts
+        .iter()
+        .map(|account| Pubkey::from_str(account))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid account: {}", e)))?;
+
+    let window = params.window.unwrap_or(120);
+    let since = Utc::now() - TimeDelta::seconds(window.min(i64::MAX as u64) as i64);
+
+    let (rpc_fees, landed) = tokio::join!(
+        network.indexer.get_recent_prioritization_fees(&pubkeys),
+        network
+            .storage
+            .get_landed_compute_unit_prices(&accounts, since, MAX_LANDED_SAMPLES)
+    );
+
+    let rpc_fees = rpc_fees.map_err(|e| rpc_error(e, "Error fetching fees"))?;
+    let landed = landed.map_err(|e| {
+        error!("Error fetching landed fees: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error fetching landed fees".to_string(),
+        )
+    })?;
+
+    let stats = FeeStats::from_samples(rpc_fees.iter().map(|fee| fee.prioritization_fee).collect());
+
+    Ok(Json(PrioritizationFeesResponse {
+        rpc: RpcFees {
+            fees: rpc_fees,
+            stats,
+        },
+        landed: LandedFees {
+            window,
+            stats: FeeStats::from_samples(landed),
+        },
+    }))
+}
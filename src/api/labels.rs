@@ -0,0 +1,199 @@
+//! Address label endpoints.
+//!
+//! Labels can be read by anyone. Creating, changing and deleting labels
+//! requires the admin bearer token, so the write routes are only mounted when
+//! an admin token is configured.
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use http::StatusCode;
+use log::error;
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::{
+    admin,
+    query::{Paginated, Validate},
+    AppState, NetworkQuery,
+};
+use crate::domain::models::label::AddressLabel;
+
+/// Query parameters for the label listing endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct LabelQuery {
+    /// Optional tag the labels must carry
+    tag: Option<String>,
+}
+
+impl Validate for LabelQuery {}
+
+/// Response format for the label listing endpoint.
+#[derive(Serialize, Debug)]
+pub struct LabelsResponse {
+    /// Labels, ordered by address
+    pub data: Vec<AddressLabel>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Request body for creating or replacing a label.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LabelRequest {
+    /// Display name
+    pub label: String,
+    /// Free-form tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Handles requests listing labels.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and tag filter
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<LabelsResponse>, (StatusCode, String)>` - Labels or error
+async fn fetch_labels(
+    params: Paginated<LabelQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<LabelsResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_labels(
+            params.data.tag,
+            params.count.unwrap_or(100),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching labels: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching labels".to_string(),
+            )
+        })?;
+
+    Ok(Json(LabelsResponse { data, next }))
+}
+
+/// Handles requests for the label of a single address.
+///
+/// Returns `404 Not Found` if the address has no label.
+async fn fetch_label(
+    Path(address): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<AddressLabel>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let label = network.storage.get_label(&address).await.map_err(|e| {
+        error!("Error fetching label: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error fetching label".to_string(),
+        )
+    })?;
+
+    label
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No label for {}", address)))
+}
+
+/// Handles requests creating or replacing the label of an address.
+///
+/// Returns `400 Bad Request` if the address is not a valid public key or the
+/// label is empty.
+async fn put_label(
+    Path(address): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+    Json(body): Json<LabelRequest>,
+) -> Result<Json<AddressLabel>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    address
+        .parse::<Pubkey>()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid address: {}", e)))?;
+
+    if body.label.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Label must not be empty".to_string(),
+        ));
+    }
+
+    let label = AddressLabel {
+        address,
+        label: body.label,
+        tags: body.tags,
+        updated_at: bson::DateTime::now(),
+    };
+
+    network.storage.upsert_label(&label).await.map_err(|e| {
+        error!("Error storing label: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error storing label".to_string(),
+        )
+    })?;
+
+    Ok(Json(label))
+}
+
+/// Handles requests deleting the label of an address.
+///
+/// Returns `404 Not Found` if the address has no label.
+async fn delete_label(
+    Path(address): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let deleted = network.storage.delete_label(&address).await.map_err(|e| {
+        error!("Error deleting label: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error deleting label".to_string(),
+        )
+    })?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, format!("No label for {}", address)))
+    }
+}
+
+/// Builds the label router.
+///
+/// The write methods are only added when an admin token is configured.
+pub fn router(state: AppState) -> Router<AppState> {
+    let mut label = get(fetch_label);
+
+    if state.admin_token.is_some() {
+        label = label.merge(
+            axum::routing::put(put_label)
+                .delete(delete_label)
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    admin::authorize,
+                )),
+        );
+    }
+
+    Router::new()
+        .route("/labels", get(fetch_labels))
+        .route("/labels/{address}", label)
+}
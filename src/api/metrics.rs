@@ -0,0 +1,28 @@
+//! Prometheus metrics endpoint.
+//!
+//! Exposes the storage operation counters and latency histograms of every
+//! network in the Prometheus text exposition format.
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use super::AppState;
+use crate::domain::metrics;
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Serves the storage metrics of all networks.
+///
+/// # Returns
+///
+/// * `impl IntoResponse` - Metrics in the Prometheus text format
+pub async fn fetch_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let body = metrics::render(
+        state
+            .networks
+            .iter()
+            .map(|network| (network.name.as_str(), &network.storage.metrics)),
+    );
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], body)
+}
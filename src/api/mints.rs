@@ -0,0 +1,86 @@
+//! Token supply events endpoint.
+//!
+//! Serves the mint and burn events of an SPL token, most recent first, so
+//! token issuers can audit supply changes without a third-party API.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{optional_number, validate_slot_range, Paginated, Validate},
+    AppState,
+};
+use crate::domain::models::supply_event::{SupplyEvent, SupplyEventKind};
+
+/// Query parameters for the supply events endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SupplyEventQuery {
+    /// Optional kind of events, `mint` or `burn`; both when unset
+    kind: Option<SupplyEventKind>,
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for SupplyEventQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_slot_range(self.from_slot, self.to_slot)
+    }
+}
+
+/// Response format for the supply events endpoint.
+#[derive(Serialize, Debug)]
+pub struct SupplyEventResponse {
+    /// Supply events, most recent first
+    pub data: Vec<SupplyEvent>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for the supply events of a token.
+///
+/// # Arguments
+///
+/// * `Path(mint)` - Mint address of the token
+/// * `params` - Query parameters including pagination, event kind and slot bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<SupplyEventResponse>, (StatusCode, String)>` - Supply events or error
+pub async fn fetch_supply_events(
+    Path(mint): Path<String>,
+    params: Paginated<SupplyEventQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SupplyEventResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_supply_events(
+            &mint,
+            params.data.kind,
+            params.data.from_slot,
+            params.data.to_slot,
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching supply events: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching supply events".to_string(),
+            )
+        })?;
+
+    Ok(Json(SupplyEventResponse { data, next }))
+}
@@ -0,0 +1,1247 @@
+//! API module for exposing block data through REST endpoints.
+//!
+//! This module provides HTTP endpoints for querying indexed Solana blockchain data.
+//! It uses the Axum framework to handle HTTP requests and supports features like
+//! pagination and filtering. The API provides access to transaction history and
+//! account information.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    response::Response,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use log::{error, info, warn};
+use mongodb::bson::{self, Document};
+use serde::{Deserialize, Serialize};
+
+use solana_client::client_error::ClientError;
+use solana_sdk::account::Account;
+
+use crate::{
+    config::ConfigHandle,
+    domain::{
+        models::{
+            alert::Alert, block::Block, daily_stats::DailyStats, enrichment::Enrichment,
+            label::AddressLabel, leader_stats::LeaderStats, signature::SignatureRecord,
+            summary::TransactionSummary, token_account::TokenAccount, transaction::Transaction,
+        },
+        storage::{BlockFilter, TransactionFilter},
+    },
+    network::{Network, Networks},
+};
+use query::{optional_number, validate_slot_range, Filtered, Paginated, Validate};
+use tenants::{Tenant, Tenants};
+
+pub mod addresses;
+mod admin;
+pub mod balances;
+pub mod etag;
+mod fees;
+mod labels;
+mod metrics;
+pub mod mints;
+pub mod query;
+mod rate_limit;
+pub mod request_id;
+pub mod rewards;
+mod rpc;
+pub mod search;
+mod stream;
+pub mod swaps;
+pub mod tenants;
+pub mod transfers;
+
+/// Shared state available to all API handlers.
+#[derive(Clone)]
+pub struct AppState {
+    /// Indexed networks, each with its own storage and indexer
+    pub networks: Arc<Networks>,
+    /// Runtime configuration handle
+    pub config: ConfigHandle,
+    /// Bearer token required by the admin endpoints, if enabled
+    pub admin_token: Option<String>,
+    /// Tenant API keys
+    pub tenants: Arc<Tenants>,
+}
+
+impl AppState {
+    /// Resolves the network selected by a request's `network` parameter.
+    ///
+    /// Returns `404 Not Found` if no network with that name is configured.
+    fn network(&self, name: Option<&str>) -> Result<&Network, (StatusCode, String)> {
+        self.networks.get(name).ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Unknown network: {}", name.unwrap_or_default()),
+            )
+        })
+    }
+}
+
+/// Query parameters for endpoints that only select a network.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkQuery {
+    /// Network to query, defaults to the primary network
+    network: Option<String>,
+}
+
+/// Query parameters for transaction endpoints.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct TransactionQuery {
+    /// Optional transaction signature to filter by
+    id: Option<String>,
+    /// Optional date in DD/MM/YYYY format to filter transactions
+    day: Option<String>,
+    /// Optional earliest block time in RFC 3339 format, inclusive
+    from: Option<String>,
+    /// Optional latest block time in RFC 3339 format, inclusive
+    to: Option<String>,
+    /// Optional text to search for in transaction memos
+    memo_contains: Option<String>,
+    /// Optional program that must have been invoked through a cross-program invocation
+    invoked_program: Option<String>,
+    /// Optional comma-separated list of fields to return, e.g. `signature,meta.fee`
+    fields: Option<String>,
+    /// Whether to attach the stored enhanced interpretations of the returned
+    /// transactions; ignored when `fields` is given
+    #[serde(default, deserialize_with = "optional_number")]
+    enriched: Option<bool>,
+    /// Whether to count all matching transactions, `true` by default; turn
+    /// it off to skip the count query for very large result sets
+    #[serde(default, deserialize_with = "optional_number")]
+    include_total: Option<bool>,
+}
+
+impl Validate for TransactionQuery {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(day) = &self.day {
+            parse_day(day).map_err(|(_, e)| e)?;
+
+            if self.from.is_some() || self.to.is_some() {
+                return Err("day cannot be combined with from or to".to_string());
+            }
+        }
+        let from = self
+            .from
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+        let to = self
+            .to
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+        validate_range(from, to)?;
+        if let Some(fields) = &self.fields {
+            parse_fields(fields).map_err(|(_, e)| e)?;
+        }
+        Ok(())
+    }
+}
+
+impl TransactionQuery {
+    /// Builds the storage filter for these parameters, restricted to the
+    /// tenant's data scope.
+    ///
+    /// Returns `400 Bad Request` if a date or timestamp is malformed.
+    fn to_filter(
+        &self,
+        tenant: Option<Arc<Tenant>>,
+    ) -> Result<TransactionFilter, (StatusCode, String)> {
+        Ok(TransactionFilter {
+            id: self.id.clone(),
+            signature_prefix: None,
+            slot: None,
+            day: self.day.as_deref().map(parse_day).transpose()?,
+            from: self.from.as_deref().map(parse_timestamp).transpose()?,
+            to: self.to.as_deref().map(parse_timestamp).transpose()?,
+            memo_contains: self.memo_contains.clone(),
+            address: None,
+            invoked_program: self.invoked_program.clone(),
+            scope: tenant.and_then(|tenant| tenant.scope().cloned()),
+        })
+    }
+}
+
+/// Transactions returned by the transaction endpoints.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum TransactionData {
+    /// Complete transactions
+    Full(Vec<Transaction>),
+    /// Only the fields selected with `fields=`
+    Projected(Vec<Document>),
+}
+
+/// Response format for transaction endpoints.
+#[derive(Serialize, Debug)]
+pub struct TransactionResponse {
+    /// List of transactions matching the query
+    pub data: TransactionData,
+    /// Labels of the addresses involved in the returned transactions.
+    /// Empty for projected responses.
+    pub labels: HashMap<String, AddressLabel>,
+    /// Enhanced interpretations of the returned transactions keyed by
+    /// signature, present when requested with `enriched=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrichments: Option<HashMap<String, Enrichment>>,
+    /// Number of transactions returned
+    pub count: u64,
+    /// Number of transactions matching the query, unless skipped with
+    /// `include_total=false`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Header carrying the number of transactions matching a query.
+pub const TOTAL_COUNT_HEADER: HeaderName = HeaderName::from_static("x-total-count");
+
+/// Longest date range, in days, accepted by endpoints filtering by date.
+const MAX_DATE_RANGE_DAYS: i64 = 366;
+
+/// Parses an RFC 3339 timestamp, e.g. `2025-03-12T08:00:00Z`.
+///
+/// Returns `400 Bad Request` if the timestamp is malformed.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, (StatusCode, String)> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid timestamp: {}", e)))
+}
+
+/// Rejects reversed date ranges and ranges longer than
+/// [`MAX_DATE_RANGE_DAYS`].
+fn validate_range(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<(), String> {
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err("from must not be after to".to_string());
+        }
+        if (to - from).num_days() > MAX_DATE_RANGE_DAYS {
+            return Err(format!(
+                "Date range must not exceed {} days",
+                MAX_DATE_RANGE_DAYS
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parses a date in DD/MM/YYYY format into midnight UTC of that day.
+///
+/// Returns `400 Bad Request` if the date is malformed.
+fn parse_day(day: &str) -> Result<DateTime<Utc>, (StatusCode, String)> {
+    let date = NaiveDate::parse_from_str(day, "%d/%m/%Y")
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid date: {}", e)))?;
+    Ok(date.and_hms_opt(0, 0, 0).expect("Infallible").and_utc())
+}
+
+/// Maximum number of fields that can be selected with `fields=`.
+const MAX_FIELDS: usize = 32;
+
+/// Parses a comma-separated `fields=` list into document paths.
+///
+/// Returns `400 Bad Request` for empty lists, too many fields, or paths that
+/// aren't plain dotted field names.
+pub fn parse_fields(fields: &str) -> Result<Vec<String>, (StatusCode, String)> {
+    let fields: Vec<String> = fields
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if fields.is_empty() || fields.len() > MAX_FIELDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Between 1 and {} fields must be selected", MAX_FIELDS),
+        ));
+    }
+
+    if let Some(field) = fields.iter().find(|field| {
+        field.split('.').any(|part| {
+            part.is_empty() || !part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    }) {
+        return Err((StatusCode::BAD_REQUEST, format!("Invalid field: {}", field)));
+    }
+
+    Ok(fields)
+}
+
+/// Looks up the labels of the addresses appearing in a response.
+async fn fetch_labels_for<'a>(
+    network: &Network,
+    addresses: impl Iterator<Item = &'a str>,
+) -> Result<HashMap<String, AddressLabel>, (StatusCode, String)> {
+    let addresses: HashSet<&str> = addresses.collect();
+
+    network
+        .storage
+        .get_labels_for(addresses)
+        .await
+        .map_err(|e| {
+            error!("Error fetching labels: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching labels".to_string(),
+            )
+        })
+}
+
+/// Looks up the stored enhanced interpretations of a set of transactions.
+async fn fetch_enrichments_for<'a>(
+    network: &Network,
+    signatures: impl Iterator<Item = &'a str>,
+) -> Result<HashMap<String, Enrichment>, (StatusCode, String)> {
+    network
+        .storage
+        .get_enrichments(signatures)
+        .await
+        .map_err(|e| {
+            error!("Error fetching enrichments: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching enrichments".to_string(),
+            )
+        })
+}
+
+/// Handles requests for transaction data.
+///
+/// Supports filtering by transaction ID, day or RFC 3339 time range, or memo
+/// text, with pagination.
+/// Tenants with a data scope only see transactions within it. When `fields`
+/// is given, only those fields are read from the database and returned.
+/// With `enriched=true`, complete transactions come with their stored
+/// enhanced interpretations. The number of matching transactions is returned
+/// as `total` and in the `X-Total-Count` header, unless `include_total=false`.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and filters
+/// * `State(state)` - Application state containing the networks' storage
+/// * `tenant` - Tenant making the request, if authenticated
+///
+/// # Returns
+///
+/// * `Result<(HeaderMap, Json<TransactionResponse>), (StatusCode, String)>` - Transaction data or error
+async fn fetch_transactions(
+    params: Paginated<TransactionQuery>,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<(HeaderMap, Json<TransactionResponse>), (StatusCode, String)> {
+    let fields = params
+        .data
+        .fields
+        .as_deref()
+        .map(parse_fields)
+        .transpose()?;
+
+    let network = state.network(params.network.as_deref())?;
+
+    let filter = params.data.to_filter(tenant)?;
+    let count = params.count.unwrap_or(10);
+    let offset = params.offset.unwrap_or(0);
+    let with_total = params.data.include_total.unwrap_or(true);
+
+    let result = match &fields {
+        Some(fields) => network
+            .storage
+            .get_transaction_fields(&filter, fields, count, offset, with_total)
+            .await
+            .map(|page| {
+                (
+                    TransactionData::Projected(page.items),
+                    page.next,
+                    page.total,
+                )
+            }),
+        None => network
+            .storage
+            .get_transactions(&filter, count, offset, with_total)
+            .await
+            .map(|page| (TransactionData::Full(page.items), page.next, page.total)),
+    };
+
+    let (data, next, total) = match result {
+        Ok(res) => res,
+        Err(e) => {
+            error!("Error fetching transactions: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching transactions".to_string(),
+            ));
+        }
+    };
+
+    let labels = match &data {
+        TransactionData::Full(transactions) => {
+            fetch_labels_for(
+                network,
+                transactions
+                    .iter()
+                    .flat_map(|transaction| transaction.account_keys()),
+            )
+            .await?
+        }
+        TransactionData::Projected(_) => HashMap::new(),
+    };
+
+    let enrichments = match &data {
+        TransactionData::Full(transactions) if params.data.enriched == Some(true) => Some(
+            fetch_enrichments_for(
+                network,
+                transactions
+                    .iter()
+                    .map(|transaction| transaction.signature.as_str()),
+            )
+            .await?,
+        ),
+        _ => None,
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(total) = total {
+        headers.insert(TOTAL_COUNT_HEADER, HeaderValue::from(total));
+    }
+
+    let response = TransactionResponse {
+        count: match &data {
+            TransactionData::Full(transactions) => transactions.len() as u64,
+            TransactionData::Projected(documents) => documents.len() as u64,
+        },
+        data,
+        labels,
+        enrichments,
+        total,
+        next,
+    };
+
+    Ok((headers, Json(response)))
+}
+
+/// Response format for the single transaction endpoint.
+#[derive(Serialize, Debug)]
+pub struct TransactionDetailResponse {
+    /// The transaction
+    pub data: Transaction,
+    /// Labels of the addresses involved in the transaction
+    pub labels: HashMap<String, AddressLabel>,
+    /// Enhanced interpretation of the transaction, when requested with
+    /// `enriched=true` and available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<Enrichment>,
+}
+
+/// Query parameters for the single transaction endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionDetailQuery {
+    /// Network to query, defaults to the primary network
+    network: Option<String>,
+    /// Whether to attach the stored enhanced interpretation of the transaction
+    enriched: Option<bool>,
+}
+
+/// Looks up a stored transaction by signature within the tenant's data scope.
+///
+/// Returns `404 Not Found` if the transaction isn't indexed or lies outside
+/// the scope.
+async fn find_transaction(
+    network: &Network,
+    signature: &str,
+    tenant: Option<Arc<Tenant>>,
+) -> Result<Transaction, (StatusCode, String)> {
+    let filter = TransactionFilter {
+        id: Some(signature.to_string()),
+        scope: tenant.and_then(|tenant| tenant.scope().cloned()),
+        ..Default::default()
+    };
+
+    let page = network
+        .storage
+        .get_transactions(&filter, 1, 0, false)
+        .await
+        .map_err(|e| {
+            error!("Error fetching transaction: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching transaction".to_string(),
+            )
+        })?;
+
+    page.items.into_iter().next().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Transaction not found: {}", signature),
+        )
+    })
+}
+
+/// Handles requests for a single transaction by signature.
+///
+/// Responses carry an `ETag`; see [`etag`]. Returns `404 Not Found` if the
+/// transaction isn't indexed or lies outside the tenant's data scope.
+/// With `enriched=true`, the stored enhanced interpretation of the
+/// transaction is attached.
+///
+/// # Arguments
+///
+/// * `Path(signature)` - Transaction signature
+/// * `params` - Network selection and enrichment flag
+/// * `headers` - Request headers, checked for `If-None-Match`
+/// * `State(state)` - Application state containing the networks' storage
+/// * `tenant` - Tenant making the request, if authenticated
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - The transaction, `304 Not Modified` or error
+async fn fetch_transaction(
+    Path(signature): Path<String>,
+    Query(params): Query<TransactionDetailQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let data = find_transaction(network, &signature, tenant).await?;
+
+    let labels = fetch_labels_for(network, data.account_keys().into_iter()).await?;
+
+    let enrichment = if params.enriched == Some(true) {
+        fetch_enrichments_for(network, [data.signature.as_str()].into_iter())
+            .await?
+            .remove(&data.signature)
+    } else {
+        None
+    };
+
+    Ok(etag::respond(
+        &headers,
+        &TransactionDetailResponse {
+            data,
+            labels,
+            enrichment,
+        },
+    ))
+}
+
+/// Response format for the transaction summary endpoint.
+#[derive(Serialize, Debug)]
+pub struct TransactionSummaryResponse {
+    /// The summary
+    pub data: TransactionSummary,
+    /// Labels of the participants and invoked programs
+    pub labels: HashMap<String, AddressLabel>,
+}
+
+/// Handles requests for the human-readable summary of a transaction.
+///
+/// Programs that aren't well known are named after their label, if they
+/// have one. Responses carry an `ETag`; see [`etag`]. Returns `404 Not Found`
+/// if the transaction isn't indexed or lies outside the tenant's data scope.
+///
+/// # Arguments
+///
+/// * `Path(signature)` - Transaction signature
+/// * `params` - Network selection
+/// * `headers` - Request headers, checked for `If-None-Match`
+/// * `State(state)` - Application state containing the networks' storage
+/// * `tenant` - Tenant making the request, if authenticated
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - The summary, `304 Not Modified` or error
+async fn fetch_transaction_summary(
+    Path(signature): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let transaction = find_transaction(network, &signature, tenant).await?;
+
+    let mut data = TransactionSummary::from_transaction(&transaction);
+    let labels = fetch_labels_for(
+        network,
+        data.participants
+            .iter()
+            .chain(data.programs.iter().map(|program| &program.id))
+            .map(String::as_str),
+    )
+    .await?;
+
+    for program in data.programs.iter_mut() {
+        if program.name.is_none() {
+            program.name = labels.get(&program.id).map(|label| label.label.clone());
+        }
+    }
+
+    Ok(etag::respond(
+        &headers,
+        &TransactionSummaryResponse { data, labels },
+    ))
+}
+
+/// Response format for the count endpoints.
+#[derive(Serialize, Debug)]
+pub struct CountResponse {
+    /// Number of matching documents
+    pub count: u64,
+}
+
+/// Handles requests for the number of transactions matching the list
+/// endpoint's filters.
+///
+/// # Arguments
+///
+/// * `params` - Network selection and transaction filters
+/// * `State(state)` - Application state containing the networks' storage
+/// * `tenant` - Tenant making the request, if authenticated
+///
+/// # Returns
+///
+/// * `Result<Json<CountResponse>, (StatusCode, String)>` - Number of transactions or error
+async fn fetch_transaction_count(
+    params: Filtered<TransactionQuery>,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<Json<CountResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let filter = params.data.to_filter(tenant)?;
+
+    let count = network
+        .storage
+        .count_transactions(&filter)
+        .await
+        .map_err(|e| {
+            error!("Error counting transactions: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error counting transactions".to_string(),
+            )
+        })?;
+
+    Ok(Json(CountResponse { count }))
+}
+
+/// Query parameters for block endpoints.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct BlockQuery {
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+    /// Optional earliest block time in RFC 3339 format, inclusive
+    from: Option<String>,
+    /// Optional latest block time in RFC 3339 format, inclusive
+    to: Option<String>,
+    /// Optional identity of the slot leader
+    leader: Option<String>,
+}
+
+impl BlockQuery {
+    /// Builds the storage filter for these parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `400 Bad Request` if a timestamp is malformed
+    fn filter(&self) -> Result<BlockFilter, (StatusCode, String)> {
+        Ok(BlockFilter {
+            from_slot: self.from_slot,
+            to_slot: self.to_slot,
+            from: self.from.as_deref().map(parse_timestamp).transpose()?,
+            to: self.to.as_deref().map(parse_timestamp).transpose()?,
+            leader: self.leader.clone(),
+        })
+    }
+}
+
+impl Validate for BlockQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_slot_range(self.from_slot, self.to_slot)?;
+        let from = self
+            .from
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+        let to = self
+            .to
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+        validate_range(from, to)
+    }
+}
+
+/// Handles requests for the number of indexed blocks.
+///
+/// # Arguments
+///
+/// * `params` - Network selection and slot or time bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<CountResponse>, (StatusCode, String)>` - Number of blocks or error
+async fn fetch_block_count(
+    params: Filtered<BlockQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<CountResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let filter = params.data.filter()?;
+
+    let count = network.storage.count_blocks(&filter).await.map_err(|e| {
+        error!("Error counting blocks: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error counting blocks".to_string(),
+        )
+    })?;
+
+    Ok(Json(CountResponse { count }))
+}
+
+/// Response format for the block endpoint.
+#[derive(Serialize, Debug)]
+pub struct BlockResponse {
+    /// Block header
+    pub data: Block,
+}
+
+/// Handles requests for an indexed block's header.
+///
+/// Responses carry an `ETag`; see [`etag`]. Returns `404 Not Found` if the
+/// block isn't indexed.
+///
+/// # Arguments
+///
+/// * `Path(slot)` - Block slot
+/// * `params` - Network selection
+/// * `headers` - Request headers, checked for `If-None-Match`
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - The block, `304 Not Modified` or error
+async fn fetch_block(
+    Path(slot): Path<u64>,
+    Query(params): Query<NetworkQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let block = network.storage.get_block(slot).await.map_err(|e| {
+        error!("Error fetching block: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error fetching block".to_string(),
+        )
+    })?;
+
+    let data =
+        block.ok_or_else(|| (StatusCode::NOT_FOUND, format!("Block not found: {}", slot)))?;
+
+    Ok(etag::respond(&headers, &BlockResponse { data }))
+}
+
+/// Response of the signature lookup endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SignatureResponse {
+    /// Compact record of the transaction
+    pub data: SignatureRecord,
+}
+
+/// Handles lookups of transactions indexed in signatures-only mode.
+///
+/// Returns `404 Not Found` if the signature isn't indexed.
+///
+/// # Arguments
+///
+/// * `Path(signature)` - Transaction signature
+/// * `params` - Network selection
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<SignatureResponse>, (StatusCode, String)>` - The signature record or error
+async fn fetch_signature(
+    Path(signature): Path<String>,
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SignatureResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let record = network
+        .storage
+        .get_signature(&signature)
+        .await
+        .map_err(|e| {
+            error!("Error fetching signature: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching signature".to_string(),
+            )
+        })?;
+
+    let data = record.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Signature not found: {}", signature),
+        )
+    })?;
+
+    Ok(Json(SignatureResponse { data }))
+}
+
+/// Query parameters for the daily statistics endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct DailyStatsQuery {
+    /// Optional first day in DD/MM/YYYY format
+    from: Option<String>,
+    /// Optional last day in DD/MM/YYYY format
+    to: Option<String>,
+}
+
+impl Validate for DailyStatsQuery {
+    /// Rejects malformed dates, reversed ranges, and ranges longer than
+    /// [`MAX_DATE_RANGE_DAYS`].
+    fn validate(&self) -> Result<(), String> {
+        let from = self
+            .from
+            .as_deref()
+            .map(parse_day)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+        let to = self
+            .to
+            .as_deref()
+            .map(parse_day)
+            .transpose()
+            .map_err(|(_, e)| e)?;
+
+        validate_range(from, to)
+    }
+}
+
+/// Response format for the daily statistics endpoint.
+#[derive(Serialize, Debug)]
+pub struct DailyStatsResponse {
+    /// Daily rollups, most recent first
+    pub data: Vec<DailyStats>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for precomputed daily statistics.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and date bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<DailyStatsResponse>, (StatusCode, String)>` - Daily rollups or error
+async fn fetch_daily_stats(
+    params: Paginated<DailyStatsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<DailyStatsResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let from = params.data.from.as_deref().map(parse_day).transpose()?;
+    let to = params.data.to.as_deref().map(parse_day).transpose()?;
+
+    let (data, next) = match network
+        .storage
+        .get_daily_stats(
+            from,
+            to,
+            params.count.unwrap_or(30),
+            params.offset.unwrap_or(0),
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => {
+            error!("Error fetching daily stats: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching daily stats".to_string(),
+            ));
+        }
+    };
+
+    Ok(Json(DailyStatsResponse { data, next }))
+}
+
+/// Response format for the leader statistics endpoint.
+#[derive(Serialize, Debug)]
+pub struct LeaderStatsResponse {
+    /// Slot leaders, most blocks produced first
+    pub data: Vec<LeaderStats>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for block production statistics by slot leader.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and slot, time or leader bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<LeaderStatsResponse>, (StatusCode, String)>` - Leader rollups or error
+async fn fetch_leader_stats(
+    params: Paginated<BlockQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<LeaderStatsResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let filter = params.data.filter()?;
+
+    let (data, next) = network
+        .storage
+        .get_leader_stats(
+            &filter,
+            params.count.unwrap_or(30),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching leader stats: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching leader stats".to_string(),
+            )
+        })?;
+
+    Ok(Json(LeaderStatsResponse { data, next }))
+}
+
+/// Query parameters for the alerts endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct AlertQuery {
+    /// Optional rule name to filter by
+    rule: Option<String>,
+}
+
+impl Validate for AlertQuery {}
+
+/// Response format for the alerts endpoint.
+#[derive(Serialize, Debug)]
+pub struct AlertResponse {
+    /// Alerts, most recent first
+    pub data: Vec<Alert>,
+    /// Labels of the transfer sources and destinations in the returned alerts
+    pub labels: HashMap<String, AddressLabel>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for recent large-transfer alerts.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and rule filter
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<AlertResponse>, (StatusCode, String)>` - Alerts or error
+async fn fetch_alerts(
+    params: Paginated<AlertQuery>,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<Json<AlertResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = match network
+        .storage
+        .get_alerts(
+            params.data.rule,
+            tenant.as_ref().and_then(|tenant| tenant.scope()),
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => {
+            error!("Error fetching alerts: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching alerts".to_string(),
+            ));
+        }
+    };
+
+    let labels = fetch_labels_for(
+        network,
+        data.iter().flat_map(|alert| {
+            [
+                alert.transfer.source.as_str(),
+                alert.transfer.destination.as_str(),
+            ]
+        }),
+    )
+    .await?;
+
+    Ok(Json(AlertResponse { data, labels, next }))
+}
+
+/// Query parameters for account information endpoints.
+#[derive(Serialize, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountQuery {
+    /// Public key of the account to fetch
+    pubkey: String,
+    /// Network to query, defaults to the primary network
+    network: Option<String>,
+    /// Commitment level of the state to read (processed, confirmed or
+    /// finalized), finalized by default
+    commitment: Option<String>,
+}
+
+/// Last account state the indexer saw, served when the RPC can't be reached.
+#[derive(Serialize, Debug)]
+pub struct IndexedAccount {
+    /// Balance in lamports after the last indexed balance change
+    pub lamports: u64,
+    /// Signature of the transaction that last changed the balance
+    pub signature: String,
+    /// Production time of the block of that transaction
+    pub block_time: Option<bson::DateTime>,
+}
+
+/// Account data returned by the account endpoint.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum AccountData {
+    /// Current state fetched from the RPC
+    Live(Account),
+    /// Last indexed state
+    Indexed(IndexedAccount),
+}
+
+/// Response format for account information endpoints.
+#[derive(Serialize, Debug)]
+pub struct AccountResponse {
+    /// Account data and metadata
+    pub data: AccountData,
+    /// Decoded fields, if the account is an SPL token account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<TokenAccount>,
+    /// Whether `data` is the last indexed state because the RPC call failed
+    pub stale: bool,
+    /// Slot of the indexed state, for stale responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+}
+
+/// Handles requests for account information.
+///
+/// Fetches current account state from the Solana blockchain. SPL token
+/// accounts are decoded into their mint, owner, amount, delegate and state
+/// alongside the raw data. Responses carry
+/// an `ETag`, so unchanged accounts can be answered with `304 Not Modified`.
+///
+/// The state is read at the `commitment` level given, finalized by default.
+/// When the RPC node can't be reached, the account's last indexed balance is
+/// returned instead, marked `stale` with the slot it was seen at. Errors the
+/// node answers with are returned like those of the other proxy routes.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters containing the account public key and
+///   optional commitment level
+/// * `headers` - Request headers, checked for `If-None-Match`
+/// * `State(state)` - Application state containing the networks' indexers
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - Account data, `304 Not Modified` or error
+async fn fetch_account(
+    Query(params): Query<AccountQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+    let commitment = rpc::parse_commitment(params.commitment.as_deref())?;
+
+    let response = match network
+        .indexer
+        .get_account(params.pubkey.clone(), commitment)
+        .await
+    {
+        Ok(data) => AccountResponse {
+            token: TokenAccount::decode(&data.owner, &data.data),
+            data: AccountData::Live(data),
+            stale: false,
+            slot: None,
+        },
+        Err(e) if rpc::is_unreachable(&e) => {
+            warn!(
+                "RPC unreachable fetching account {}, falling back to indexed state: {}",
+                params.pubkey, e
+            );
+            fetch_indexed_account(network, &params.pubkey).await?
+        }
+        Err(e) if e.downcast_ref::<ClientError>().is_some() => {
+            return Err(rpc::rpc_error(e, "Error fetching account"));
+        }
+        Err(e) => {
+            error!("Error fetching account: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching account".to_string(),
+            ));
+        }
+    };
+
+    Ok(etag::respond(&headers, &response))
+}
+
+/// Builds a stale account response from the account's last indexed balance
+/// change.
+///
+/// Returns `503 Service Unavailable` if the indexer never saw the account's
+/// balance change.
+async fn fetch_indexed_account(
+    network: &Network,
+    pubkey: &str,
+) -> Result<AccountResponse, (StatusCode, String)> {
+    let change = network
+        .storage
+        .get_latest_balance_change(pubkey)
+        .await
+        .map_err(|e| {
+            error!("Error fetching indexed account state: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching account".to_string(),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "RPC unavailable and no indexed state for the account".to_string(),
+            )
+        })?;
+
+    Ok(AccountResponse {
+        data: AccountData::Indexed(IndexedAccount {
+            lamports: change.post_balance,
+            signature: change.signature,
+            block_time: change.block_time,
+        }),
+        token: None,
+        stale: true,
+        slot: change.slot,
+    })
+}
+
+/// Starts the API server.
+///
+/// Sets up routes and begins listening for HTTP requests. Requests are
+/// authenticated by their API key, if any, and subject to the tenant's rate
+/// limit or the one from the runtime configuration. The admin routes are only
+/// mounted when an admin token is configured. Every request is assigned an
+/// `x-request-id` that is echoed in the response and attached to its logs.
+///
+/// # Arguments
+///
+/// * `listener` - TCP listener to accept connections on
+/// * `state` - Shared application state
+///
+/// # Returns
+///
+/// * `eyre::Result<()>` - Runs indefinitely unless an error occurs
+pub async fn start(listener: tokio::net::TcpListener, state: AppState) -> eyre::Result<()> {
+    let rate_limiter = Arc::new(rate_limit::RateLimiter::new(state.config.subscribe()));
+
+    let mut app = Router::new()
+        .route("/transactions", get(fetch_transactions))
+        .route("/transactions/count", get(fetch_transaction_count))
+        .route("/transactions/{signature}", get(fetch_transaction))
+        .route(
+            "/transactions/{signature}/summary",
+            get(fetch_transaction_summary),
+        )
+        .route("/transactions/send", post(rpc::send_transaction))
+        .route("/transactions/simulate", post(rpc::simulate_transaction))
+        .route("/accounts", get(fetch_account))
+        .route(
+            "/addresses/{pubkey}/summary",
+            get(addresses::fetch_address_summary),
+        )
+        .route(
+            "/addresses/{pubkey}/counterparties",
+            get(addresses::fetch_counterparties),
+        )
+        .route("/addresses/{pubkey}/rewards", get(rewards::fetch_rewards))
+        .route(
+            "/addresses/{pubkey}/balance-history",
+            get(balances::fetch_balance_history),
+        )
+        .route("/blocks/count", get(fetch_block_count))
+        .route("/blocks/{slot}", get(fetch_block))
+        .route("/signatures/{signature}", get(fetch_signature))
+        .route("/epoch", get(rpc::fetch_epoch))
+        .route("/validators", get(rpc::fetch_validators))
+        .route("/supply", get(rpc::fetch_supply))
+        .route("/fees/prioritization", get(fees::fetch_prioritization_fees))
+        .route(
+            "/addresses/{pubkey}/sol-transfers",
+            get(transfers::fetch_address_sol_transfers),
+        )
+        .route("/swaps", get(swaps::fetch_swaps))
+        .route("/sol-transfers", get(transfers::fetch_sol_transfers))
+        .route("/mints/{mint}/events", get(mints::fetch_supply_events))
+        .route("/stats/daily", get(fetch_daily_stats))
+        .route("/stats/leaders", get(fetch_leader_stats))
+        .route("/alerts", get(fetch_alerts))
+        .route("/search", get(search::search))
+        .route("/stream/transactions", get(stream::subscribe_transactions))
+        .route("/metrics", get(metrics::fetch_metrics))
+        .merge(labels::router(state.clone()));
+
+    if state.admin_token.is_some() {
+        app = app.nest("/admin", admin::router(state.clone()));
+    }
+
+    let app = app
+        .layer(middleware::from_fn_with_state(
+            (state.tenants.clone(), rate_limiter),
+            tenants::enforce,
+        ))
+        .layer(middleware::from_fn(request_id::assign))
+        .with_state(state);
+
+    info!("Starting API server on {}", listener.local_addr()?);
+
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
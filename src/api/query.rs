@@ -0,0 +1,158 @@
+//! Query parameter extraction and validation.
+//!
+//! Paginated endpoints take a [`Paginated`] extractor instead of a plain
+//! [`Query`]. It bounds the page size and offset, and runs the endpoint's own
+//! [`Validate`] checks, so invalid requests are rejected with `400 Bad Request`
+//! and an explanation before any database work is done. Endpoints that accept
+//! the same filters without pagination use [`Filtered`].
+
+use std::{fmt::Display, str::FromStr};
+
+use axum::extract::{FromRequestParts, Query};
+use http::{request::Parts, StatusCode};
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer,
+};
+
+/// Largest page size accepted by paginated endpoints.
+pub const MAX_PAGE_SIZE: u64 = 1_000;
+
+/// Largest offset accepted by paginated endpoints. Deeper pages should be
+/// reached by narrowing the filters instead.
+pub const MAX_OFFSET: u64 = 100_000;
+
+/// Endpoint-specific validation of query parameters.
+pub trait Validate {
+    /// Checks the parameters, describing the first problem found.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Checks that a slot range doesn't end before it starts.
+pub fn validate_slot_range(from_slot: Option<u64>, to_slot: Option<u64>) -> Result<(), String> {
+    match (from_slot, to_slot) {
+        (Some(from_slot), Some(to_slot)) if from_slot > to_slot => {
+            Err("from_slot must not be after to_slot".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Deserializes an optional number or boolean that may arrive as text.
+///
+/// Fields of the flattened endpoint parameters are buffered before their type
+/// is known, so query string values reach them as strings rather than being
+/// parsed as numbers. Use with `#[serde(default, deserialize_with = ...)]`.
+pub fn optional_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrText<T> {
+        Number(T),
+        Text(String),
+    }
+
+    match Option::<NumberOrText<T>>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(NumberOrText::Number(number)) => Ok(Some(number)),
+        Some(NumberOrText::Text(text)) => text.parse().map(Some).map_err(de::Error::custom),
+    }
+}
+
+/// Request parameters for paginated endpoints.
+///
+/// Generic struct that combines pagination parameters with endpoint-specific
+/// query parameters.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Paginated<T> {
+    /// Number of items to return
+    pub count: Option<u64>,
+    /// Number of items to skip
+    pub offset: Option<u64>,
+    /// Network to query, defaults to the primary network
+    pub network: Option<String>,
+    /// Additional query parameters
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T: Validate> Validate for Paginated<T> {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(count) = self.count {
+            if !(1..=MAX_PAGE_SIZE).contains(&count) {
+                return Err(format!(
+                    "count must be between 1 and {}, got {}",
+                    MAX_PAGE_SIZE, count
+                ));
+            }
+        }
+        if let Some(offset) = self.offset {
+            if offset > MAX_OFFSET {
+                return Err(format!(
+                    "offset must not exceed {}, got {}",
+                    MAX_OFFSET, offset
+                ));
+            }
+        }
+        self.data.validate()
+    }
+}
+
+impl<T, S> FromRequestParts<S> for Paginated<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<Self>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.body_text()))?;
+
+        params
+            .validate()
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid query: {}", e)))?;
+
+        Ok(params)
+    }
+}
+
+/// Request parameters for filtered endpoints without pagination.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Filtered<T> {
+    /// Network to query, defaults to the primary network
+    pub network: Option<String>,
+    /// Additional query parameters
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T, S> FromRequestParts<S> for Filtered<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<Self>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.body_text()))?;
+
+        params
+            .data
+            .validate()
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid query: {}", e)))?;
+
+        Ok(params)
+    }
+}
@@ -0,0 +1,94 @@
+//! Request rate limiting for the API.
+//!
+//! Anonymous clients share a single token bucket whose parameters come from
+//! the runtime configuration and take effect as soon as the configuration is
+//! reloaded. Tenants with their own limit get a bucket of their own, see
+//! [`super::tenants`].
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use tokio::sync::watch;
+
+use crate::config::{Config, RateLimit};
+
+/// Token bucket state.
+pub struct Bucket {
+    /// Limit the bucket was last filled with
+    limit: Option<RateLimit>,
+    /// Tokens currently available
+    tokens: f64,
+    /// Time of the last refill
+    last_refill: Instant,
+}
+
+impl Default for Bucket {
+    /// Creates an empty bucket that is filled on first use.
+    fn default() -> Self {
+        Self {
+            limit: None,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+        }
+    }
+}
+
+impl Bucket {
+    /// Refills the bucket under `limit` and attempts to take a token.
+    ///
+    /// The bucket is reset to full whenever the limit changes.
+    pub fn try_acquire(&mut self, limit: RateLimit) -> bool {
+        let now = Instant::now();
+
+        if self.limit != Some(limit) {
+            self.limit = Some(limit);
+            self.tokens = limit.burst as f64;
+        } else {
+            let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+            self.tokens =
+                (self.tokens + elapsed * limit.requests_per_second as f64).min(limit.burst as f64);
+        }
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Token bucket rate limiter driven by the runtime configuration.
+pub struct RateLimiter {
+    /// Runtime configuration providing the current limit
+    config: watch::Receiver<Arc<Config>>,
+    /// Bucket state
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter following the given configuration.
+    pub fn new(config: watch::Receiver<Arc<Config>>) -> Self {
+        Self {
+            config,
+            bucket: Mutex::new(Bucket::default()),
+        }
+    }
+
+    /// Attempts to take a token from the bucket.
+    ///
+    /// Always succeeds when no rate limit is configured.
+    pub fn try_acquire(&self) -> bool {
+        let Some(limit) = self.config.borrow().rate_limit else {
+            return true;
+        };
+
+        self.bucket
+            .lock()
+            .expect("Rate limiter lock poisoned")
+            .try_acquire(limit)
+    }
+}
@@ -0,0 +1,86 @@
+//! Request ids for tracing API requests.
+//!
+//! Every request is assigned an id, taken from its `x-request-id` header when
+//! the client or a proxy supplied a usable one and generated otherwise. The id
+//! is echoed in the response header, prefixed to every log line written while
+//! serving the request, and appended to plain-text error messages, so a
+//! failed request reported by a user can be found in the logs.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    middleware::Next,
+    response::Response,
+};
+use http::{header, HeaderName, HeaderValue};
+use log::error;
+
+use crate::logger;
+
+/// Header carrying the request id.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request id that is accepted.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Largest error body that is annotated with the request id.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Returns a request id supplied by the client, if it is safe to log and echo.
+pub fn parse(value: &HeaderValue) -> Option<String> {
+    let id = value.to_str().ok()?;
+
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+
+    valid.then(|| id.to_string())
+}
+
+/// Middleware assigning and propagating request ids.
+pub async fn assign(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(parse)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let response = logger::with_request_id(id.clone(), next.run(request)).await;
+    let mut response = annotate_error(response, &id).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Appends the request id to plain-text error messages.
+async fn annotate_error(response: Response, id: &str) -> Response {
+    let is_text = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/plain"));
+
+    if !(response.status().is_client_error() || response.status().is_server_error()) || !is_text {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let message = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            error!("Error reading error response body: {}", e);
+            String::new()
+        }
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(
+        parts,
+        Body::from(format!("{} (request id: {})", message, id)),
+    )
+}
@@ -0,0 +1,85 @@
+//! Block rewards endpoint.
+//!
+//! Lets validators and stakers query the fee, rent, staking and voting
+//! rewards credited to an account, most recent first.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{Paginated, Validate},
+    AppState,
+};
+use crate::domain::models::reward::{BlockReward, REWARD_TYPES};
+
+/// Query parameters for the rewards endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct RewardQuery {
+    /// Optional reward type to filter by: `fee`, `rent`, `staking` or `voting`
+    reward_type: Option<String>,
+}
+
+impl Validate for RewardQuery {
+    fn validate(&self) -> Result<(), String> {
+        match &self.reward_type {
+            Some(reward_type) if !REWARD_TYPES.contains(&reward_type.as_str()) => Err(format!(
+                "reward_type must be one of {}, got {}",
+                REWARD_TYPES.join(", "),
+                reward_type
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Response format for the rewards endpoint.
+#[derive(Serialize, Debug)]
+pub struct RewardResponse {
+    /// Rewards, most recent first
+    pub data: Vec<BlockReward>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for the rewards credited to an account.
+///
+/// # Arguments
+///
+/// * `Path(pubkey)` - Account address
+/// * `params` - Query parameters including pagination and reward type filter
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<RewardResponse>, (StatusCode, String)>` - Rewards or error
+pub async fn fetch_rewards(
+    Path(pubkey): Path<String>,
+    params: Paginated<RewardQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<RewardResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_rewards(
+            &pubkey,
+            params.data.reward_type.as_deref(),
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching rewards: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching rewards".to_string(),
+            )
+        })?;
+
+    Ok(Json(RewardResponse { data, next }))
+}
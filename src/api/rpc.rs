@@ -0,0 +1,355 @@
+//! RPC proxy endpoints.
+//!
+//! These routes forward requests to the selected network's RPC endpoint, so
+//! clients behind SolDag can submit transactions and query live cluster state
+//! without holding RPC credentials of their own.
+
+use std::cmp::Reverse;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_config::{
+        RpcSendTransactionConfig, RpcSimulateTransactionAccountsConfig,
+        RpcSimulateTransactionConfig,
+    },
+    rpc_request::RpcError,
+    rpc_response::{RpcInflationRate, RpcSimulateTransactionResult, RpcVoteAccountInfo},
+};
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+
+use super::{AppState, NetworkQuery};
+
+/// Converts an RPC failure into an API error.
+///
+/// Errors reported by the RPC node itself (for example a failed preflight
+/// check) are the caller's fault and are returned as `400 Bad Request` with
+/// the node's message. Anything else means the node could not be reached and
+/// is returned as `502 Bad Gateway`.
+pub(super) fn rpc_error(e: eyre::Report, context: &str) -> (StatusCode, String) {
+    if let Some(ClientError {
+        kind: ClientErrorKind::RpcError(RpcError::RpcResponseError { message, .. }),
+        ..
+    }) = e.downcast_ref::<ClientError>()
+    {
+        return (StatusCode::BAD_REQUEST, message.clone());
+    }
+
+    error!("{}: {:?}", context, e);
+    (StatusCode::BAD_GATEWAY, context.to_string())
+}
+
+/// Returns whether an RPC failure means the node couldn't be reached, as
+/// opposed to the node answering with an error.
+///
+/// Connection failures, timeouts and HTTP error statuses count as
+/// unreachable.
+pub(super) fn is_unreachable(e: &eyre::Report) -> bool {
+    matches!(
+        e.downcast_ref::<ClientError>().map(|e| &e.kind),
+        Some(ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+    )
+}
+
+/// Parses an optional commitment level.
+///
+/// Returns `400 Bad Request` for anything other than `processed`,
+/// `confirmed` or `finalized`.
+pub fn parse_commitment(
+    commitment: Option<&str>,
+) -> Result<Option<CommitmentLevel>, (StatusCode, String)> {
+    commitment
+        .map(|commitment| match commitment {
+            "processed" => Ok(CommitmentLevel::Processed),
+            "confirmed" => Ok(CommitmentLevel::Confirmed),
+            "finalized" => Ok(CommitmentLevel::Finalized),
+            _ => Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid commitment: {}", commitment),
+            )),
+        })
+        .transpose()
+}
+
+/// Request body for submitting a transaction.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SendTransactionRequest {
+    /// Base64-encoded, fully signed transaction
+    transaction: String,
+    /// Skip the RPC node's preflight simulation
+    #[serde(default)]
+    skip_preflight: bool,
+    /// Commitment level used for preflight (processed, confirmed or finalized)
+    preflight_commitment: Option<String>,
+    /// Maximum number of times the RPC node retries sending the transaction
+    max_retries: Option<usize>,
+}
+
+/// Response format for the transaction submission endpoint.
+#[derive(Serialize, Debug)]
+pub struct SendTransactionResponse {
+    /// Signature of the submitted transaction
+    pub signature: String,
+}
+
+/// Handles requests to submit a signed transaction.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters selecting the network
+/// * `State(state)` - Application state containing the networks' indexers
+/// * `body` - Transaction and submission options
+///
+/// # Returns
+///
+/// * `Result<Json<SendTransactionResponse>, (StatusCode, String)>` - Transaction signature or error
+pub async fn send_transaction(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+    Json(body): Json<SendTransactionRequest>,
+) -> Result<Json<SendTransactionResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let config = RpcSendTransactionConfig {
+        skip_preflight: body.skip_preflight,
+        preflight_commitment: parse_commitment(body.preflight_commitment.as_deref())?,
+        max_retries: body.max_retries,
+        ..Default::default()
+    };
+
+    let signature = network
+        .indexer
+        .send_transaction(body.transaction, config)
+        .await
+        .map_err(|e| rpc_error(e, "Error sending transaction"))?;
+
+    Ok(Json(SendTransactionResponse { signature }))
+}
+
+/// Request body for simulating a transaction.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulateTransactionRequest {
+    /// Base64-encoded transaction
+    transaction: String,
+    /// Verify the transaction's signatures
+    #[serde(default)]
+    sig_verify: bool,
+    /// Replace the transaction's blockhash with the most recent one
+    #[serde(default)]
+    replace_recent_blockhash: bool,
+    /// Commitment level to simulate against (processed, confirmed or finalized)
+    commitment: Option<String>,
+    /// Accounts to return the post-simulation state of
+    #[serde(default)]
+    accounts: Vec<String>,
+}
+
+/// Response format for the transaction simulation endpoint.
+#[derive(Serialize, Debug)]
+pub struct SimulateTransactionResponse {
+    /// Slot the simulation ran at
+    pub slot: u64,
+    /// Logs, compute units consumed, account states and error of the simulation
+    pub data: RpcSimulateTransactionResult,
+}
+
+/// Handles requests to simulate a transaction.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters selecting the network
+/// * `State(state)` - Application state containing the networks' indexers
+/// * `body` - Transaction and simulation options
+///
+/// # Returns
+///
+/// * `Result<Json<SimulateTransactionResponse>, (StatusCode, String)>` - Simulation result or error
+pub async fn simulate_transaction(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+    Json(body): Json<SimulateTransactionRequest>,
+) -> Result<Json<SimulateTransactionResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: body.sig_verify,
+        replace_recent_blockhash: body.replace_recent_blockhash,
+        commitment: parse_commitment(body.commitment.as_deref())?
+            .map(|commitment| CommitmentConfig { commitment }),
+        accounts: (!body.accounts.is_empty()).then_some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: body.accounts,
+        }),
+        ..Default::default()
+    };
+
+    let result = network
+        .indexer
+        .simulate_transaction(body.transaction, config)
+        .await
+        .map_err(|e| rpc_error(e, "Error simulating transaction"))?;
+
+    Ok(Json(SimulateTransactionResponse {
+        slot: result.context.slot,
+        data: result.value,
+    }))
+}
+
+/// Response format for the epoch endpoint.
+#[derive(Serialize, Debug)]
+pub struct EpochResponse {
+    /// Current epoch
+    pub epoch: u64,
+    /// Current slot relative to the start of the epoch
+    pub slot_index: u64,
+    /// Number of slots in the epoch
+    pub slots_in_epoch: u64,
+    /// Absolute current slot
+    pub absolute_slot: u64,
+    /// Current block height
+    pub block_height: u64,
+    /// Share of the epoch that has elapsed, between 0 and 1
+    pub progress: f64,
+}
+
+/// Handles requests for the current epoch progress.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters selecting the network
+/// * `State(state)` - Application state containing the networks' indexers
+///
+/// # Returns
+///
+/// * `Result<Json<EpochResponse>, (StatusCode, String)>` - Epoch progress or error
+pub async fn fetch_epoch(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<EpochResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let info = network
+        .indexer
+        .get_epoch_info()
+        .await
+        .map_err(|e| rpc_error(e, "Error fetching epoch info"))?;
+
+    Ok(Json(EpochResponse {
+        epoch: info.epoch,
+        slot_index: info.slot_index,
+        slots_in_epoch: info.slots_in_epoch,
+        absolute_slot: info.absolute_slot,
+        block_height: info.block_height,
+        progress: if info.slots_in_epoch == 0 {
+            0.0
+        } else {
+            info.slot_index as f64 / info.slots_in_epoch as f64
+        },
+    }))
+}
+
+/// Response format for the validators endpoint.
+#[derive(Serialize, Debug)]
+pub struct ValidatorsResponse {
+    /// Validators voting normally, by descending stake
+    pub current: Vec<RpcVoteAccountInfo>,
+    /// Validators that have fallen behind, by descending stake
+    pub delinquent: Vec<RpcVoteAccountInfo>,
+    /// Total active stake in lamports across both sets
+    pub total_stake: u64,
+}
+
+/// Handles requests for the validator set.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters selecting the network
+/// * `State(state)` - Application state containing the networks' indexers
+///
+/// # Returns
+///
+/// * `Result<Json<ValidatorsResponse>, (StatusCode, String)>` - Vote accounts or error
+pub async fn fetch_validators(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<ValidatorsResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let mut status = network
+        .indexer
+        .get_vote_accounts()
+        .await
+        .map_err(|e| rpc_error(e, "Error fetching validators"))?;
+
+    status
+        .current
+        .sort_by_key(|validator| Reverse(validator.activated_stake));
+    status
+        .delinquent
+        .sort_by_key(|validator| Reverse(validator.activated_stake));
+
+    let total_stake = status
+        .current
+        .iter()
+        .chain(status.delinquent.iter())
+        .map(|validator| validator.activated_stake)
+        .sum();
+
+    Ok(Json(ValidatorsResponse {
+        current: status.current,
+        delinquent: status.delinquent,
+        total_stake,
+    }))
+}
+
+/// Response format for the supply endpoint.
+#[derive(Serialize, Debug)]
+pub struct SupplyResponse {
+    /// Total supply in lamports
+    pub total: u64,
+    /// Circulating supply in lamports
+    pub circulating: u64,
+    /// Non-circulating supply in lamports
+    pub non_circulating: u64,
+    /// Current inflation rates
+    pub inflation: RpcInflationRate,
+}
+
+/// Handles requests for supply and inflation statistics.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters selecting the network
+/// * `State(state)` - Application state containing the networks' indexers
+///
+/// # Returns
+///
+/// * `Result<Json<SupplyResponse>, (StatusCode, String)>` - Supply and inflation or error
+pub async fn fetch_supply(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SupplyResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (supply, inflation) = tokio::try_join!(
+        network.indexer.get_supply(),
+        network.indexer.get_inflation_rate()
+    )
+    .map_err(|e| rpc_error(e, "Error fetching supply"))?;
+
+    Ok(Json(SupplyResponse {
+        total: supply.total,
+        circulating: supply.circulating,
+        non_circulating: supply.non_circulating,
+        inflation,
+    }))
+}
@@ -0,0 +1,175 @@
+//! Explorer search endpoint.
+//!
+//! A single query string is classified as a slot number, a full or partial
+//! transaction signature, or a 32-byte hash. Hashes are ambiguous between
+//! account addresses and block hashes, so both are looked up.
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+use solana_sdk::bs58;
+
+use super::{fetch_labels_for, tenants::Tenant, AppState};
+use crate::domain::{
+    models::{block::Block, label::AddressLabel, transaction::Transaction},
+    storage::TransactionFilter,
+};
+
+/// Maximum number of transactions returned for a search.
+const SEARCH_LIMIT: u64 = 10;
+
+/// Minimum length of a partial signature, to keep prefix scans selective.
+pub const MIN_SIGNATURE_PREFIX: usize = 8;
+
+/// What a search query was recognized as.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum SearchTarget {
+    /// Block slot number
+    Slot(u64),
+    /// Full transaction signature
+    Signature(String),
+    /// Leading characters of a transaction signature
+    SignaturePrefix(String),
+    /// Account address or block hash
+    Hash(String),
+}
+
+impl SearchTarget {
+    /// Classifies a search query.
+    ///
+    /// Returns `None` if the query is neither a number nor base58, or is too
+    /// short to be a useful signature prefix.
+    pub fn classify(query: &str) -> Option<Self> {
+        let query = query.trim();
+
+        if !query.is_empty() && query.bytes().all(|b| b.is_ascii_digit()) {
+            return query.parse().ok().map(Self::Slot);
+        }
+
+        let bytes = bs58::decode(query).into_vec().ok()?;
+
+        match bytes.len() {
+            64 => Some(Self::Signature(query.to_string())),
+            32 => Some(Self::Hash(query.to_string())),
+            _ if query.len() >= MIN_SIGNATURE_PREFIX && bytes.len() < 64 => {
+                Some(Self::SignaturePrefix(query.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Query parameters for the search endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchQuery {
+    /// Text entered in the search box
+    q: String,
+    /// Network to search, defaults to the primary network
+    network: Option<String>,
+}
+
+/// Response format for the search endpoint.
+#[derive(Serialize, Debug)]
+pub struct SearchResponse {
+    /// How the query was interpreted
+    pub query: SearchTarget,
+    /// Matching blocks
+    pub blocks: Vec<Block>,
+    /// Matching transactions, at most ten
+    pub transactions: Vec<Transaction>,
+    /// Labels of the searched address and of the addresses involved in the
+    /// matching transactions
+    pub labels: HashMap<String, AddressLabel>,
+}
+
+/// Handles explorer search requests.
+///
+/// Tenants with a data scope only find transactions within it.
+///
+/// # Arguments
+///
+/// * `params` - Search text and network
+/// * `State(state)` - Application state containing the networks' storage
+/// * `tenant` - Tenant making the request, if authenticated
+///
+/// # Returns
+///
+/// * `Result<Json<SearchResponse>, (StatusCode, String)>` - Matching entities or error
+pub async fn search(
+    Query(params): Query<SearchQuery>,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+) -> Result<Json<SearchResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let target = SearchTarget::classify(&params.q).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Unrecognized search query: {}", params.q),
+        )
+    })?;
+
+    let mut filter = TransactionFilter {
+        scope: tenant.and_then(|tenant| tenant.scope().cloned()),
+        ..Default::default()
+    };
+
+    let storage_error = |e: eyre::Report| {
+        error!("Error searching: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Error searching".to_string(),
+        )
+    };
+
+    let block = match &target {
+        SearchTarget::Slot(slot) => {
+            filter.slot = Some(*slot);
+            network.storage.get_block(*slot).await
+        }
+        SearchTarget::Signature(signature) => {
+            filter.id = Some(signature.clone());
+            Ok(None)
+        }
+        SearchTarget::SignaturePrefix(prefix) => {
+            filter.signature_prefix = Some(prefix.clone());
+            Ok(None)
+        }
+        SearchTarget::Hash(hash) => {
+            filter.address = Some(hash.clone());
+            network.storage.get_block_by_hash(hash).await
+        }
+    }
+    .map_err(storage_error)?;
+
+    let transactions = network
+        .storage
+        .get_transactions(&filter, SEARCH_LIMIT, 0, false)
+        .await
+        .map_err(storage_error)?
+        .items;
+
+    let labels = fetch_labels_for(
+        network,
+        transactions
+            .iter()
+            .flat_map(|transaction| transaction.account_keys())
+            .chain(filter.address.as_deref()),
+    )
+    .await?;
+
+    Ok(Json(SearchResponse {
+        query: target,
+        blocks: block.into_iter().collect(),
+        transactions,
+        labels,
+    }))
+}
@@ -0,0 +1,102 @@
+//! WebSocket feed of newly indexed transactions.
+//!
+//! Subscribers receive each transaction inserted on a network as a JSON text
+//! message, as published by the change stream bridge. Tenants only receive
+//! transactions within their scope. Subscribers that fall too far behind skip
+//! the missed transactions rather than slowing down everyone else.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Query, State, WebSocketUpgrade,
+    },
+    response::Response,
+    Extension,
+};
+use http::StatusCode;
+use log::{error, warn};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{tenants::Tenant, AppState, NetworkQuery};
+use crate::{config::Filters, domain::models::transaction::Transaction};
+
+/// Subscribes to newly indexed transactions over a WebSocket.
+///
+/// # Arguments
+///
+/// * `Query(params)` - Network to subscribe to
+/// * `State(state)` - Application state containing the networks
+/// * `tenant` - Tenant making the request, if authenticated
+/// * `ws` - WebSocket upgrade request
+///
+/// # Returns
+///
+/// * `Result<Response, (StatusCode, String)>` - Upgrade response or error
+///
+/// # Errors
+///
+/// Returns 503 if the change stream bridge is not enabled
+pub async fn subscribe_transactions(
+    Query(params): Query<NetworkQuery>,
+    State(state): State<AppState>,
+    Extension(tenant): Extension<Option<Arc<Tenant>>>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let events = network.events.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Transaction stream is not enabled".to_string(),
+        )
+    })?;
+
+    let receiver = events.subscribe();
+    let scope = tenant.and_then(|tenant| tenant.scope().cloned());
+
+    Ok(ws.on_upgrade(move |socket| forward(socket, receiver, scope)))
+}
+
+/// Forwards transactions to a subscriber until either side closes.
+async fn forward(
+    mut socket: WebSocket,
+    mut receiver: broadcast::Receiver<Arc<Transaction>>,
+    scope: Option<Filters>,
+) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let transaction = match event {
+                    Ok(transaction) => transaction,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Stream subscriber lagged, skipped {} transactions", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                if scope.as_ref().is_some_and(|scope| !scope.matches(&transaction)) {
+                    continue;
+                }
+
+                let text = match serde_json::to_string(&*transaction) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        error!("Error serializing transaction: {}", e);
+                        continue;
+                    }
+                };
+
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
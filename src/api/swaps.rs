@@ -0,0 +1,101 @@
+//! DEX swaps endpoint.
+//!
+//! Serves the normalized swaps extracted from indexed transactions, most
+//! recent first, filtered by trader, token, DEX or slot range.
+
+use axum::{extract::State, Json};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{optional_number, validate_slot_range, Paginated, Validate},
+    AppState,
+};
+use crate::domain::{
+    models::swap::{Swap, SWAP_DECODERS},
+    storage::SwapFilter,
+};
+
+/// Query parameters for the swaps endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SwapQuery {
+    /// Optional account that made the swaps
+    trader: Option<String>,
+    /// Optional mint sold or bought
+    mint: Option<String>,
+    /// Optional program ID of the DEX or aggregator
+    program_id: Option<String>,
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for SwapQuery {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(program_id) = &self.program_id {
+            if !SWAP_DECODERS
+                .iter()
+                .any(|decoder| decoder.program_id == program_id)
+            {
+                return Err(format!("Unsupported DEX program: {}", program_id));
+            }
+        }
+        validate_slot_range(self.from_slot, self.to_slot)
+    }
+}
+
+/// Response format for the swaps endpoint.
+#[derive(Serialize, Debug)]
+pub struct SwapResponse {
+    /// Swaps, most recent first
+    pub data: Vec<Swap>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for DEX swaps.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination and swap filters
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<SwapResponse>, (StatusCode, String)>` - Swaps or error
+pub async fn fetch_swaps(
+    params: Paginated<SwapQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SwapResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let filter = SwapFilter {
+        trader: params.data.trader.clone(),
+        mint: params.data.mint.clone(),
+        program_id: params.data.program_id.clone(),
+        from_slot: params.data.from_slot,
+        to_slot: params.data.to_slot,
+    };
+
+    let (data, next) = network
+        .storage
+        .get_swaps(
+            &filter,
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching swaps: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching swaps".to_string(),
+            )
+        })?;
+
+    Ok(Json(SwapResponse { data, next }))
+}
@@ -0,0 +1,230 @@
+//! Per-tenant API keys.
+//!
+//! Clients identify themselves with an `x-api-key` header. Each key carries
+//! its own rate limit, the endpoints it may call and an optional data scope
+//! restricting which transactions and alerts it sees. Keys are persisted in
+//! the primary network's database, cached in memory and managed through the
+//! admin endpoints.
+//!
+//! Requests without a key are served under the global rate limit unless keys
+//! are required, in which case only the admin endpoints accept them.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderName, StatusCode};
+use mongodb::bson;
+
+use super::rate_limit::{Bucket, RateLimiter};
+use crate::{
+    config::{Filters, RateLimit},
+    domain::{models::api_key::ApiKey, storage::Storage},
+};
+
+/// Header carrying a tenant's API key.
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// A tenant authenticated by its API key.
+pub struct Tenant {
+    /// Stored key and access policy
+    pub key: ApiKey,
+    /// Token bucket for the tenant's own rate limit
+    bucket: Mutex<Bucket>,
+}
+
+impl Tenant {
+    /// Returns the data scope of the tenant, if restricted.
+    pub fn scope(&self) -> Option<&Filters> {
+        self.key.scope.as_ref()
+    }
+}
+
+/// Registry of API keys.
+pub struct Tenants {
+    /// Storage the keys are persisted in
+    storage: Arc<Storage>,
+    /// Whether requests without an API key are rejected
+    required: bool,
+    /// Tenants keyed by API key
+    tenants: RwLock<HashMap<String, Arc<Tenant>>>,
+}
+
+impl Tenants {
+    /// Loads all stored API keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - Storage the keys are persisted in
+    /// * `required` - Whether requests without an API key are rejected
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keys cannot be read
+    pub async fn load(storage: Arc<Storage>, required: bool) -> eyre::Result<Self> {
+        let tenants = storage
+            .get_api_keys()
+            .await?
+            .into_iter()
+            .map(|key| (key.key.clone(), Self::tenant(key)))
+            .collect();
+
+        Ok(Self {
+            storage,
+            required,
+            tenants: RwLock::new(tenants),
+        })
+    }
+
+    fn tenant(key: ApiKey) -> Arc<Tenant> {
+        Arc::new(Tenant {
+            key,
+            bucket: Mutex::new(Bucket::default()),
+        })
+    }
+
+    /// Looks up the tenant owning an API key.
+    pub fn get(&self, key: &str) -> Option<Arc<Tenant>> {
+        self.tenants
+            .read()
+            .expect("Tenant lock poisoned")
+            .get(key)
+            .cloned()
+    }
+
+    /// Returns all API keys, ordered by tenant name.
+    pub fn list(&self) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self
+            .tenants
+            .read()
+            .expect("Tenant lock poisoned")
+            .values()
+            .map(|tenant| tenant.key.clone())
+            .collect();
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        keys
+    }
+
+    /// Issues a new API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be stored
+    pub async fn create(
+        &self,
+        name: String,
+        rate_limit: Option<RateLimit>,
+        endpoints: Vec<String>,
+        scope: Option<Filters>,
+    ) -> eyre::Result<ApiKey> {
+        let key = ApiKey {
+            key: format!("sk_{}", uuid::Uuid::new_v4().simple()),
+            name,
+            rate_limit,
+            endpoints,
+            scope,
+            created_at: bson::DateTime::now(),
+        };
+
+        self.storage.insert_api_key(&key).await?;
+
+        self.tenants
+            .write()
+            .expect("Tenant lock poisoned")
+            .insert(key.key.clone(), Self::tenant(key.clone()));
+
+        Ok(key)
+    }
+
+    /// Revokes an API key.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether the key existed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be removed from storage
+    pub async fn revoke(&self, key: &str) -> eyre::Result<bool> {
+        let deleted = self.storage.delete_api_key(key).await?;
+
+        let removed = self
+            .tenants
+            .write()
+            .expect("Tenant lock poisoned")
+            .remove(key)
+            .is_some();
+
+        Ok(deleted || removed)
+    }
+}
+
+/// Middleware authenticating API keys and enforcing rate limits.
+///
+/// Tenants are checked against their allowed endpoints and their own rate
+/// limit, falling back to the global limit when they have none. The tenant, or
+/// `None` for anonymous requests, is added to the request extensions so
+/// handlers can apply its data scope.
+/// Responds with `401 Unauthorized` for unknown or missing keys, `403
+/// Forbidden` for disallowed endpoints and `429 Too Many Requests` once the
+/// applicable limit is exhausted.
+pub async fn enforce(
+    State((tenants, limiter)): State<(Arc<Tenants>, Arc<RateLimiter>)>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let allowed = match key {
+        Some(key) => {
+            let Some(tenant) = tenants.get(key) else {
+                return (StatusCode::UNAUTHORIZED, "Invalid API key").into_response();
+            };
+
+            if !tenant.key.allows(path) {
+                return (
+                    StatusCode::FORBIDDEN,
+                    "Endpoint not allowed for this API key",
+                )
+                    .into_response();
+            }
+
+            let allowed = match tenant.key.rate_limit {
+                Some(limit) => tenant
+                    .bucket
+                    .lock()
+                    .expect("Rate limiter lock poisoned")
+                    .try_acquire(limit),
+                None => limiter.try_acquire(),
+            };
+
+            request.extensions_mut().insert(Some(tenant));
+
+            allowed
+        }
+        None if tenants.required && !path.starts_with("/admin") => {
+            return (StatusCode::UNAUTHORIZED, "Missing API key").into_response();
+        }
+        None => {
+            request.extensions_mut().insert(None::<Arc<Tenant>>);
+
+            limiter.try_acquire()
+        }
+    };
+
+    if !allowed {
+        return (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded").into_response();
+    }
+
+    next.run(request).await
+}
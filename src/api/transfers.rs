@@ -0,0 +1,203 @@
+//! SOL transfers endpoints.
+//!
+//! Serve the native SOL transfers extracted from indexed transactions, most
+//! recent first, filtered by sender, recipient, amount or slot range.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query::{optional_number, validate_slot_range, Paginated, Validate},
+    AppState,
+};
+use crate::domain::{models::sol_transfer::SolTransfer, storage::SolTransferFilter};
+
+/// Checks the amount and slot bounds shared by the SOL transfer queries.
+fn validate_bounds(
+    (min_lamports, max_lamports): (Option<u64>, Option<u64>),
+    (from_slot, to_slot): (Option<u64>, Option<u64>),
+) -> Result<(), String> {
+    if let (Some(min_lamports), Some(max_lamports)) = (min_lamports, max_lamports) {
+        if min_lamports > max_lamports {
+            return Err("min_lamports must not be above max_lamports".to_string());
+        }
+    }
+    validate_slot_range(from_slot, to_slot)
+}
+
+/// Query parameters for the SOL transfers endpoint.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct SolTransferQuery {
+    /// Optional account that sent the transfers
+    from: Option<String>,
+    /// Optional account that received the transfers
+    to: Option<String>,
+    /// Optional smallest amount in lamports, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    min_lamports: Option<u64>,
+    /// Optional largest amount in lamports, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    max_lamports: Option<u64>,
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for SolTransferQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_bounds(
+            (self.min_lamports, self.max_lamports),
+            (self.from_slot, self.to_slot),
+        )
+    }
+}
+
+/// Side of the transfers an address took.
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferDirection {
+    /// Transfers received by the address
+    In,
+    /// Transfers sent by the address
+    Out,
+}
+
+/// Query parameters for the SOL transfers of an address.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct AddressSolTransferQuery {
+    /// Optional side of the transfers, `in` or `out`; both when unset
+    direction: Option<TransferDirection>,
+    /// Optional smallest amount in lamports, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    min_lamports: Option<u64>,
+    /// Optional largest amount in lamports, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    max_lamports: Option<u64>,
+    /// Optional lowest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    from_slot: Option<u64>,
+    /// Optional highest slot, inclusive
+    #[serde(default, deserialize_with = "optional_number")]
+    to_slot: Option<u64>,
+}
+
+impl Validate for AddressSolTransferQuery {
+    fn validate(&self) -> Result<(), String> {
+        validate_bounds(
+            (self.min_lamports, self.max_lamports),
+            (self.from_slot, self.to_slot),
+        )
+    }
+}
+
+/// Response format for the SOL transfers endpoints.
+#[derive(Serialize, Debug)]
+pub struct SolTransferResponse {
+    /// SOL transfers, most recent first
+    pub data: Vec<SolTransfer>,
+    /// Offset for the next page of results, if any
+    pub next: Option<u64>,
+}
+
+/// Handles requests for SOL transfers.
+///
+/// # Arguments
+///
+/// * `params` - Query parameters including pagination, sender, recipient and bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<SolTransferResponse>, (StatusCode, String)>` - SOL transfers or error
+pub async fn fetch_sol_transfers(
+    params: Paginated<SolTransferQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SolTransferResponse>, (StatusCode, String)> {
+    let filter = SolTransferFilter {
+        from: params.data.from.clone(),
+        to: params.data.to.clone(),
+        min_lamports: params.data.min_lamports,
+        max_lamports: params.data.max_lamports,
+        from_slot: params.data.from_slot,
+        to_slot: params.data.to_slot,
+        ..Default::default()
+    };
+
+    query_sol_transfers(&state, &params, &filter).await
+}
+
+/// Handles requests for the SOL transfers sent or received by an address.
+///
+/// # Arguments
+///
+/// * `Path(pubkey)` - Account address
+/// * `params` - Query parameters including pagination, direction and bounds
+/// * `State(state)` - Application state containing the networks' storage
+///
+/// # Returns
+///
+/// * `Result<Json<SolTransferResponse>, (StatusCode, String)>` - SOL transfers or error
+pub async fn fetch_address_sol_transfers(
+    Path(pubkey): Path<String>,
+    params: Paginated<AddressSolTransferQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<SolTransferResponse>, (StatusCode, String)> {
+    let range = SolTransferFilter {
+        min_lamports: params.data.min_lamports,
+        max_lamports: params.data.max_lamports,
+        from_slot: params.data.from_slot,
+        to_slot: params.data.to_slot,
+        ..Default::default()
+    };
+    let filter = match params.data.direction {
+        Some(TransferDirection::In) => SolTransferFilter {
+            to: Some(pubkey),
+            ..range
+        },
+        Some(TransferDirection::Out) => SolTransferFilter {
+            from: Some(pubkey),
+            ..range
+        },
+        None => SolTransferFilter {
+            address: Some(pubkey),
+            ..range
+        },
+    };
+
+    query_sol_transfers(&state, &params, &filter).await
+}
+
+/// Fetches a page of SOL transfers from the requested network.
+async fn query_sol_transfers<Q: Validate>(
+    state: &AppState,
+    params: &Paginated<Q>,
+    filter: &SolTransferFilter,
+) -> Result<Json<SolTransferResponse>, (StatusCode, String)> {
+    let network = state.network(params.network.as_deref())?;
+
+    let (data, next) = network
+        .storage
+        .get_sol_transfers(
+            filter,
+            params.count.unwrap_or(10),
+            params.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            error!("Error fetching SOL transfers: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error fetching SOL transfers".to_string(),
+            )
+        })?;
+
+    Ok(Json(SolTransferResponse { data, next }))
+}
@@ -0,0 +1,403 @@
+//! Archival of old transactions to Parquet files.
+//!
+//! MongoDB is a poor fit for long-term retention and bulk analytics, so a
+//! background task periodically exports closed UTC days of transactions to
+//! Parquet files on S3-compatible object storage (or a local directory). Files
+//! are laid out as `<prefix>/<network>/transactions/date=YYYY-MM-DD/` so they
+//! can be queried as a Hive-partitioned dataset by Spark or DuckDB. A
+//! `manifest.json` next to them records every archived day, which also lets
+//! the archiver resume where it left off. Archived transactions can optionally
+//! be deleted from MongoDB once their file and the manifest are written.
+
+use std::{sync::Arc, time::Duration};
+
+use arrow_array::{
+    builder::{
+        BooleanBuilder, ListBuilder, StringBuilder, TimestampMillisecondBuilder, UInt64Builder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Days, Utc};
+use eyre::Context;
+use log::{error, info};
+use object_store::{
+    aws::AmazonS3Builder, buffered::BufWriter, path::Path, ObjectStore, PutPayload,
+};
+use parquet::{arrow::AsyncArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::{Deserialize, Serialize};
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use url::Url;
+
+use crate::{
+    aggregator::start_of_day,
+    domain::{
+        models::transaction::Transaction,
+        storage::{read_transaction, Storage},
+    },
+};
+
+/// Number of transactions per Parquet record batch.
+const BATCH_SIZE: usize = 10_000;
+
+/// Name of the manifest file in each network's archive directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A day of transactions written to the archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedWindow {
+    /// Start of the window, inclusive
+    pub start: DateTime<Utc>,
+    /// End of the window, exclusive
+    pub end: DateTime<Utc>,
+    /// Location of the Parquet file, relative to the store root
+    pub path: String,
+    /// Number of archived transactions
+    pub rows: u64,
+    /// Time the file was written
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Index of all archived windows of a network.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Archived windows, oldest first
+    pub windows: Vec<ArchivedWindow>,
+}
+
+/// Settings of the archival service.
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    /// Time between archival runs
+    pub interval: Duration,
+    /// How long after a day ends before it is archived, to leave room for
+    /// late blocks from the catch-up task
+    pub delay: Duration,
+    /// Whether archived transactions are deleted from MongoDB
+    pub delete: bool,
+}
+
+/// Exports closed days of a network's transactions to Parquet files.
+#[derive(Clone)]
+pub struct Archiver {
+    /// Destination object store
+    store: Arc<dyn ObjectStore>,
+    /// Directory of this network's archive within the store
+    prefix: Path,
+    /// Storage holding the transactions to archive
+    storage: Arc<Storage>,
+    /// Service settings
+    options: ArchiveOptions,
+}
+
+impl Archiver {
+    /// Creates an archiver for one network.
+    ///
+    /// `s3://bucket/prefix` URLs are configured from the standard `AWS_*`
+    /// environment variables, so S3-compatible services can be used by setting
+    /// `AWS_ENDPOINT`. Other URLs, such as `file:///var/lib/soldag`, are
+    /// resolved by [`object_store::parse_url`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL does not name a supported object store
+    pub fn new(
+        url: &Url,
+        network: &str,
+        storage: Arc<Storage>,
+        options: ArchiveOptions,
+    ) -> eyre::Result<Self> {
+        let (store, prefix): (Arc<dyn ObjectStore>, Path) = if url.scheme() == "s3" {
+            let store = AmazonS3Builder::from_env()
+                .with_url(url.as_str())
+                .build()
+                .wrap_err("Error configuring S3 archive")?;
+            (Arc::new(store), Path::from(url.path()))
+        } else {
+            let (store, prefix) =
+                object_store::parse_url(url).wrap_err("Error configuring archive")?;
+            (Arc::from(store), prefix)
+        };
+
+        Ok(Self {
+            store,
+            prefix: prefix.child(network),
+            storage,
+            options,
+        })
+    }
+
+    /// Starts the archival service.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<()>` - Runs indefinitely
+    pub async fn start(self) -> eyre::Result<()> {
+        info!("Starting archival service for {}...", self.prefix);
+
+        let mut interval = tokio::time::interval(self.options.interval);
+
+        loop {
+            interval.tick().await;
+
+            match self.run_once(Utc::now()).await {
+                Ok(0) => {}
+                Ok(archived) => info!("Archived {} day(s) to {}", archived, self.prefix),
+                Err(err) => error!("Error archiving transactions: {:?}", err),
+            }
+        }
+    }
+
+    /// Archives every closed day that isn't in the manifest yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time, used to decide which days are closed
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<usize>` - Number of archived days
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading transactions or writing the archive fails.
+    /// Days archived before the failure stay recorded in the manifest.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> eyre::Result<usize> {
+        let mut manifest = self.load_manifest().await?;
+
+        let Some(earliest) = self.storage.earliest_block_time().await? else {
+            return Ok(0);
+        };
+
+        let resume = manifest.windows.last().map(|window| window.end);
+        let windows = closed_windows(earliest, resume, now, self.options.delay);
+
+        let mut archived = 0;
+
+        for (start, end) in windows {
+            let Some(window) = self.archive_window(start, end).await? else {
+                continue;
+            };
+
+            manifest.windows.push(window);
+            self.save_manifest(&manifest).await?;
+            archived += 1;
+
+            if self.options.delete {
+                let deleted = self.storage.delete_transactions_between(start, end).await?;
+                info!("Deleted {} archived transactions from {}", deleted, start);
+            }
+        }
+
+        Ok(archived)
+    }
+
+    /// Writes the transactions of one window to a Parquet file.
+    ///
+    /// Returns `None` without writing anything if the window is empty.
+    async fn archive_window(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> eyre::Result<Option<ArchivedWindow>> {
+        let mut cursor = self.storage.transactions_between(start, end).await?;
+
+        let path = self
+            .prefix
+            .child("transactions")
+            .child(format!("date={}", start.format("%Y-%m-%d")))
+            .child("part-0.parquet");
+
+        let mut writer: Option<AsyncArrowWriter<BufWriter>> = None;
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let mut rows = 0;
+
+        loop {
+            let more = cursor.advance().await?;
+            if more {
+                batch.push(read_transaction(cursor.deserialize_current()?)?);
+            }
+
+            if batch.len() == BATCH_SIZE || (!more && !batch.is_empty()) {
+                let writer = match &mut writer {
+                    Some(writer) => writer,
+                    None => writer.insert(AsyncArrowWriter::try_new(
+                        BufWriter::new(self.store.clone(), path.clone()),
+                        schema(),
+                        Some(
+                            WriterProperties::builder()
+                                .set_compression(Compression::SNAPPY)
+                                .build(),
+                        ),
+                    )?),
+                };
+
+                writer.write(&to_record_batch(&batch)?).await?;
+                rows += batch.len() as u64;
+                batch.clear();
+            }
+
+            if !more {
+                break;
+            }
+        }
+
+        let Some(writer) = writer else {
+            return Ok(None);
+        };
+        writer
+            .close()
+            .await
+            .wrap_err("Error writing Parquet file")?;
+
+        Ok(Some(ArchivedWindow {
+            start,
+            end,
+            path: path.to_string(),
+            rows,
+            archived_at: Utc::now(),
+        }))
+    }
+
+    /// Reads the manifest, returning an empty one if none exists yet.
+    async fn load_manifest(&self) -> eyre::Result<Manifest> {
+        match self.store.get(&self.prefix.child(MANIFEST_FILE)).await {
+            Ok(result) => Ok(serde_json::from_slice(&result.bytes().await?)?),
+            Err(object_store::Error::NotFound { .. }) => Ok(Manifest::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replaces the manifest.
+    async fn save_manifest(&self, manifest: &Manifest) -> eyre::Result<()> {
+        self.store
+            .put(
+                &self.prefix.child(MANIFEST_FILE),
+                PutPayload::from(serde_json::to_vec_pretty(manifest)?),
+            )
+            .await
+            .wrap_err("Error writing archive manifest")?;
+
+        Ok(())
+    }
+}
+
+/// Lists the days that can be archived.
+///
+/// Starts at the day of the earliest transaction, or at `resume` if that is
+/// later, and ends with the last day that closed at least `delay` before
+/// `now`.
+pub fn closed_windows(
+    earliest: DateTime<Utc>,
+    resume: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    delay: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut start = start_of_day(earliest).max(resume.unwrap_or(DateTime::<Utc>::MIN_UTC));
+    let cutoff = now - chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+
+    let mut windows = Vec::new();
+    while let Some(end) = start.checked_add_days(Days::new(1)) {
+        if end > cutoff {
+            break;
+        }
+        windows.push((start, end));
+        start = end;
+    }
+
+    windows
+}
+
+/// Returns the Arrow schema of archived transactions.
+///
+/// Frequently queried fields get their own columns; the complete transaction
+/// is kept as JSON in the `transaction` column so nothing is lost.
+pub fn schema() -> SchemaRef {
+    let strings = || Arc::new(Field::new_list_field(DataType::Utf8, true));
+
+    Arc::new(Schema::new(vec![
+        Field::new("signature", DataType::Utf8, false),
+        Field::new("slot", DataType::UInt64, true),
+        Field::new(
+            "block_time",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            true,
+        ),
+        Field::new("fee", DataType::UInt64, false),
+        Field::new("success", DataType::Boolean, false),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("compute_units_consumed", DataType::UInt64, true),
+        Field::new("compute_unit_price", DataType::UInt64, true),
+        Field::new("account_keys", DataType::List(strings()), false),
+        Field::new("memos", DataType::List(strings()), false),
+        Field::new("transaction", DataType::Utf8, false),
+    ]))
+}
+
+/// Converts transactions into a record batch following [`schema`].
+///
+/// # Errors
+///
+/// Returns an error if a transaction can't be serialized
+pub fn to_record_batch(transactions: &[Transaction]) -> eyre::Result<RecordBatch> {
+    let mut signature = StringBuilder::new();
+    let mut slot = UInt64Builder::new();
+    let mut block_time = TimestampMillisecondBuilder::new().with_timezone("UTC");
+    let mut fee = UInt64Builder::new();
+    let mut success = BooleanBuilder::new();
+    let mut error = StringBuilder::new();
+    let mut compute_units_consumed = UInt64Builder::new();
+    let mut compute_unit_price = UInt64Builder::new();
+    let mut account_keys = ListBuilder::new(StringBuilder::new());
+    let mut memos = ListBuilder::new(StringBuilder::new());
+    let mut transaction_json = StringBuilder::new();
+
+    for transaction in transactions {
+        signature.append_value(&transaction.signature);
+        slot.append_option(transaction.slot);
+        block_time.append_option(transaction.block_time.map(|t| t.timestamp_millis()));
+        fee.append_value(transaction.meta.fee);
+        success.append_value(transaction.meta.err.is_none());
+        error.append_option(
+            transaction
+                .meta
+                .err
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        );
+        compute_units_consumed.append_option(match transaction.meta.compute_units_consumed {
+            OptionSerializer::Some(units) => Some(units),
+            _ => None,
+        });
+        compute_unit_price.append_option(transaction.compute_unit_price);
+
+        for key in transaction.account_keys() {
+            account_keys.values().append_value(key);
+        }
+        account_keys.append(true);
+
+        for memo in &transaction.memos {
+            memos.values().append_value(memo);
+        }
+        memos.append(true);
+
+        transaction_json.append_value(serde_json::to_string(transaction)?);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(signature.finish()),
+        Arc::new(slot.finish()),
+        Arc::new(block_time.finish()),
+        Arc::new(fee.finish()),
+        Arc::new(success.finish()),
+        Arc::new(error.finish()),
+        Arc::new(compute_units_consumed.finish()),
+        Arc::new(compute_unit_price.finish()),
+        Arc::new(account_keys.finish()),
+        Arc::new(memos.finish()),
+        Arc::new(transaction_json.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(schema(), columns)?)
+}
@@ -0,0 +1,426 @@
+//! Benchmark of the processing pipeline and the API.
+//!
+//! The `bench` command replays blocks, either synthetic or rebuilt from the
+//! transactions the primary network has stored, through the same pipeline the
+//! indexer uses, then serves the result with an in-process API and queries it
+//! concurrently. Everything is written to a separate database, so the numbers
+//! are repeatable and indexed data is left untouched.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use log::info;
+use solana_sdk::{hash::Hash, message::MessageHeader, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    UiCompiledInstruction, UiConfirmedBlock, UiMessage, UiRawMessage, UiTransaction,
+    UiTransactionStatusMeta,
+};
+
+use crate::{
+    api,
+    cli::BenchArgs,
+    config::ConfigHandle,
+    domain::{
+        models::transaction::Transaction,
+        storage::{Storage, TransactionFilter},
+    },
+    network::{self, Network, Networks},
+};
+
+/// First slot of synthetic blocks.
+const SYNTHETIC_START_SLOT: u64 = 1_000_000;
+
+/// Fee charged by synthetic transactions, in lamports.
+const SYNTHETIC_FEE: u64 = 5000;
+
+/// Latency percentiles of a set of requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    /// Number of requests
+    pub samples: usize,
+    /// 50th percentile
+    pub p50: Duration,
+    /// 90th percentile
+    pub p90: Duration,
+    /// 99th percentile
+    pub p99: Duration,
+    /// Slowest request
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Computes nearest-rank percentiles over `latencies`.
+    ///
+    /// Returns `None` for an empty sample.
+    pub fn from_samples(mut latencies: Vec<Duration>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        latencies.sort_unstable();
+
+        let percentile =
+            |p: usize| latencies[((latencies.len() * p).div_ceil(100)).saturating_sub(1)];
+
+        Some(Self {
+            samples: latencies.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: latencies[latencies.len() - 1],
+        })
+    }
+}
+
+/// Builds a block of SOL transfers between fresh accounts.
+///
+/// # Arguments
+///
+/// * `slot` - Slot of the block
+/// * `transactions` - Number of transactions in the block
+pub fn synthetic_block(slot: u64, transactions: usize) -> UiConfirmedBlock {
+    let system_program = Pubkey::default().to_string();
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(1_000_000u64.to_le_bytes());
+    let data = solana_sdk::bs58::encode(data).into_string();
+
+    let transactions = (0..transactions)
+        .map(|_| EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![Signature::new_unique().to_string()],
+                message: UiMessage::Raw(UiRawMessage {
+                    header: MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    },
+                    account_keys: vec![
+                        Pubkey::new_unique().to_string(),
+                        Pubkey::new_unique().to_string(),
+                        system_program.clone(),
+                    ],
+                    recent_blockhash: Hash::new_unique().to_string(),
+                    instructions: vec![UiCompiledInstruction {
+                        program_id_index: 2,
+                        accounts: vec![0, 1],
+                        data: data.clone(),
+                        stack_height: None,
+                    }],
+                    address_table_lookups: None,
+                }),
+            }),
+            meta: Some(synthetic_meta()),
+            version: None,
+        })
+        .collect();
+
+    UiConfirmedBlock {
+        previous_blockhash: Hash::new_unique().to_string(),
+        blockhash: Hash::new_unique().to_string(),
+        parent_slot: slot.saturating_sub(1),
+        transactions: Some(transactions),
+        signatures: None,
+        rewards: None,
+        num_reward_partitions: None,
+        block_time: Some(chrono::Utc::now().timestamp()),
+        block_height: Some(slot),
+    }
+}
+
+/// Metadata of a successful synthetic transfer.
+fn synthetic_meta() -> UiTransactionStatusMeta {
+    UiTransactionStatusMeta {
+        err: None,
+        status: Ok(()),
+        fee: SYNTHETIC_FEE,
+        pre_balances: vec![10_000_000_000, 0, 1],
+        post_balances: vec![10_000_000_000 - 1_000_000 - SYNTHETIC_FEE, 1_000_000, 1],
+        inner_instructions: OptionSerializer::Some(vec![]),
+        log_messages: OptionSerializer::Skip,
+        pre_token_balances: OptionSerializer::Some(vec![]),
+        post_token_balances: OptionSerializer::Some(vec![]),
+        rewards: OptionSerializer::Skip,
+        loaded_addresses: OptionSerializer::Skip,
+        return_data: OptionSerializer::Skip,
+        compute_units_consumed: OptionSerializer::Some(150),
+    }
+}
+
+/// Rebuilds blocks from stored transactions, grouped by slot.
+///
+/// # Arguments
+///
+/// * `storage` - Storage to read transactions from
+/// * `count` - Maximum number of transactions to read
+///
+/// # Errors
+///
+/// Returns an error if the transactions cannot be read
+async fn stored_blocks(
+    storage: &Storage,
+    count: u64,
+) -> eyre::Result<Vec<(u64, Arc<UiConfirmedBlock>)>> {
+    let transactions = storage
+        .get_transactions(&TransactionFilter::default(), count, 0, false)
+        .await?
+        .items;
+
+    let mut slots: BTreeMap<u64, Vec<Transaction>> = BTreeMap::new();
+    for transaction in transactions {
+        slots
+            .entry(transaction.slot.unwrap_or_default())
+            .or_default()
+            .push(transaction);
+    }
+
+    Ok(slots
+        .into_iter()
+        .map(|(slot, transactions)| {
+            let block_time = transactions[0]
+                .block_time
+                .map(|time| time.timestamp_millis() / 1000);
+            let transactions = transactions
+                .into_iter()
+                .map(|transaction| EncodedTransactionWithStatusMeta {
+                    transaction: EncodedTransaction::Json(UiTransaction {
+                        signatures: vec![transaction.signature],
+                        message: UiMessage::Raw(transaction.message),
+                    }),
+                    meta: Some(transaction.meta),
+                    version: None,
+                })
+                .collect();
+
+            let block = UiConfirmedBlock {
+                previous_blockhash: Hash::default().to_string(),
+                blockhash: Hash::new_unique().to_string(),
+                parent_slot: slot.saturating_sub(1),
+                transactions: Some(transactions),
+                signatures: None,
+                rewards: None,
+                num_reward_partitions: None,
+                block_time,
+                block_height: None,
+            };
+
+            (slot, Arc::new(block))
+        })
+        .collect())
+}
+
+/// Outcome of one API request.
+struct Sample {
+    /// Endpoint the request was sent to, without parameters
+    endpoint: &'static str,
+    /// Time until the response body was received
+    latency: Duration,
+    /// Whether the response had a success status
+    ok: bool,
+}
+
+/// Runs the benchmark and prints its report.
+///
+/// # Arguments
+///
+/// * `networks` - Indexed networks; the primary network's indexer settings
+///   are used, and its transactions when replaying stored blocks
+/// * `args` - Benchmark parameters
+///
+/// # Errors
+///
+/// Returns an error if the benchmark database cannot be used, no blocks are
+/// available, or the API cannot be started
+pub async fn run(networks: &Networks, args: BenchArgs) -> eyre::Result<()> {
+    let indexed = networks.iter().any(|network| {
+        args.database == network::PRIMARY_DATABASE
+            || args.database == format!("{}_{}", network::PRIMARY_DATABASE, network.name)
+    });
+    eyre::ensure!(
+        !indexed,
+        "--database must not be the database of an indexed network"
+    );
+
+    let primary = networks
+        .get(None)
+        .expect("Primary network is always registered");
+    // Runs start from an empty database, so earlier runs' blocks aren't
+    // skipped as already stored
+    let storage = Storage::init(&args.database).await?;
+    storage.drop_database().await?;
+    storage.create_indexes().await?;
+    let indexer = primary.indexer.clone().with_storage(storage.clone());
+
+    let blocks = if args.stored {
+        stored_blocks(
+            &primary.storage,
+            (args.blocks * args.transactions_per_block) as u64,
+        )
+        .await?
+    } else {
+        (0..args.blocks as u64)
+            .map(|i| {
+                let slot = SYNTHETIC_START_SLOT + i;
+                (
+                    slot,
+                    Arc::new(synthetic_block(slot, args.transactions_per_block)),
+                )
+            })
+            .collect()
+    };
+    eyre::ensure!(!blocks.is_empty(), "No blocks to replay");
+
+    let slots: Vec<u64> = blocks.iter().map(|(slot, _)| *slot).collect();
+    let signatures: Vec<String> = blocks
+        .iter()
+        .flat_map(|(_, block)| block.transactions.iter().flatten())
+        .filter_map(|transaction| match &transaction.transaction {
+            EncodedTransaction::Json(transaction) => transaction.signatures.first().cloned(),
+            _ => None,
+        })
+        .collect();
+
+    info!(
+        "Replaying {} blocks with {} transactions",
+        blocks.len(),
+        signatures.len()
+    );
+    let started = Instant::now();
+    indexer.process_blocks(blocks).await?;
+    let elapsed = started.elapsed();
+
+    println!("Processing");
+    println!(
+        "  {} blocks, {} transactions in {:.2?}",
+        slots.len(),
+        signatures.len(),
+        elapsed
+    );
+    println!(
+        "  {:.1} blocks/s, {:.1} transactions/s",
+        slots.len() as f64 / elapsed.as_secs_f64(),
+        signatures.len() as f64 / elapsed.as_secs_f64()
+    );
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+    let base = format!("http://{}", listener.local_addr()?);
+    let state = api::AppState {
+        networks: Arc::new(Networks::new(Network {
+            name: primary.name.clone(),
+            storage: storage.clone(),
+            indexer,
+            events: None,
+        })),
+        config: ConfigHandle::default(),
+        admin_token: None,
+        tenants: Arc::new(api::tenants::Tenants::load(storage.clone(), false).await?),
+    };
+    let server = tokio::spawn(api::start(listener, state));
+
+    let queries: Arc<Vec<(&'static str, String)>> = Arc::new(
+        (0..args.requests)
+            .map(|i| {
+                let signature = &signatures[i % signatures.len()];
+                let slot = slots[i % slots.len()];
+                match i % 5 {
+                    0 => ("/transactions", "/transactions?limit=20".to_string()),
+                    1 => (
+                        "/transactions/{signature}",
+                        format!("/transactions/{}", signature),
+                    ),
+                    2 => (
+                        "/transactions/{signature}/summary",
+                        format!("/transactions/{}/summary", signature),
+                    ),
+                    3 => ("/blocks/{slot}", format!("/blocks/{}", slot)),
+                    _ => ("/transactions/count", "/transactions/count".to_string()),
+                }
+            })
+            .collect(),
+    );
+
+    let http = reqwest::Client::new();
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let workers: Vec<_> = (0..args.concurrency.max(1))
+        .map(|_| {
+            let (http, base, queries, next) =
+                (http.clone(), base.clone(), queries.clone(), next.clone());
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((endpoint, path)) = queries.get(i) else {
+                        break;
+                    };
+
+                    let sent = Instant::now();
+                    let ok = match http.get(format!("{}{}", base, path)).send().await {
+                        Ok(response) => {
+                            let ok = response.status().is_success();
+                            ok && response.bytes().await.is_ok()
+                        }
+                        Err(_) => false,
+                    };
+                    samples.push(Sample {
+                        endpoint,
+                        latency: sent.elapsed(),
+                        ok,
+                    });
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::with_capacity(args.requests);
+    for worker in workers {
+        samples.extend(worker.await?);
+    }
+    let elapsed = started.elapsed();
+    server.abort();
+
+    let errors = samples.iter().filter(|sample| !sample.ok).count();
+    println!("API");
+    println!(
+        "  {} requests with concurrency {} in {:.2?}, {} errors",
+        samples.len(),
+        args.concurrency.max(1),
+        elapsed,
+        errors
+    );
+    println!(
+        "  {:.1} requests/s",
+        samples.len() as f64 / elapsed.as_secs_f64()
+    );
+
+    let mut endpoints: BTreeMap<&str, Vec<Duration>> = BTreeMap::new();
+    for sample in &samples {
+        endpoints.entry("all").or_default().push(sample.latency);
+        endpoints
+            .entry(sample.endpoint)
+            .or_default()
+            .push(sample.latency);
+    }
+
+    println!(
+        "  {:<36} {:>8} {:>10} {:>10} {:>10} {:>10}",
+        "endpoint", "requests", "p50", "p90", "p99", "max"
+    );
+    for (endpoint, latencies) in endpoints {
+        if let Some(stats) = LatencyStats::from_samples(latencies) {
+            println!(
+                "  {:<36} {:>8} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                endpoint, stats.samples, stats.p50, stats.p90, stats.p99, stats.max
+            );
+        }
+    }
+
+    Ok(())
+}
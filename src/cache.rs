@@ -0,0 +1,124 @@
+//! Short-lived caching of RPC responses.
+//!
+//! Network-level data such as epoch progress or the validator set changes far
+//! less often than API clients poll for it. Caching it for a few seconds keeps
+//! dashboards responsive without spending an RPC request per API request.
+//! Recently fetched blocks are kept in a small LRU so overlapping fetches are
+//! served from memory.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex as SyncMutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+/// A single cached value that expires after a fixed time-to-live.
+pub struct TtlCache<T> {
+    /// How long a fetched value stays fresh
+    ttl: Duration,
+    /// Cached value and the time it was fetched
+    entry: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T: Clone> TtlCache<T> {
+    /// Creates an empty cache.
+    pub const fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Mutex::const_new(None),
+        }
+    }
+
+    /// Returns the cached value, fetching a new one if it is missing or stale.
+    ///
+    /// Concurrent callers wait for a single in-flight fetch instead of each
+    /// issuing their own request. Failed fetches are not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fetch` if a fresh value was needed and fetching failed
+    pub async fn get_or_try_fetch<F, Fut>(&self, fetch: F) -> eyre::Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = eyre::Result<T>>,
+    {
+        let mut entry = self.entry.lock().await;
+
+        if let Some((fetched_at, value)) = entry.as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = fetch().await?;
+        *entry = Some((Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+}
+
+/// A bounded cache that evicts the least recently used entry when full.
+pub struct LruCache<K, V> {
+    /// Maximum number of entries
+    capacity: usize,
+    /// Cached values and their keys, from least to most recently used
+    entries: SyncMutex<(HashMap<K, V>, VecDeque<K>)>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    /// Creates an empty cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: SyncMutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Returns the cached value of `key`, marking it as most recently used.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().expect("LRU cache poisoned");
+        let (values, order) = &mut *entries;
+
+        let value = values.get(key)?.clone();
+        touch(order, key);
+
+        Some(value)
+    }
+
+    /// Caches `value` under `key`, evicting the least recently used entry if
+    /// the cache is full.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether `key` wasn't cached before
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let mut entries = self.entries.lock().expect("LRU cache poisoned");
+        let (values, order) = &mut *entries;
+
+        if values.insert(key.clone(), value).is_some() {
+            touch(order, &key);
+            return false;
+        }
+
+        order.push_back(key);
+        if order.len() > self.capacity {
+            if let Some(evicted) = order.pop_front() {
+                values.remove(&evicted);
+            }
+        }
+
+        true
+    }
+}
+
+/// Moves `key` to the most recently used end of `order`.
+fn touch<K: Eq + Clone>(order: &mut VecDeque<K>, key: &K) {
+    if let Some(position) = order.iter().position(|k| k == key) {
+        if let Some(key) = order.remove(position) {
+            order.push_back(key);
+        }
+    }
+}
@@ -5,11 +5,19 @@
 //! for argument parsing and supports both command-line flags and environment
 //! variables for configuration.
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use mongodb::options::Acknowledgment;
 use url::Url;
 
+use crate::{
+    domain::storage::{ConnectionOptions, QueryReadConcern, QueryReadPreference, StorageOptions},
+    helius,
+    indexer::Detail,
+    retry::RetryPolicy,
+};
+
 /// Command-line arguments for configuring the application.
 ///
 /// These arguments can be provided via command-line flags or environment
@@ -17,6 +25,10 @@ use url::Url;
 #[derive(Parser)]
 #[clap(author, version, about = "Solana data aggregator")]
 pub struct Args {
+    /// Maintenance command to run instead of the services
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
     /// Helios RPC API key for authenticated access to Solana RPC endpoints.
     /// Can be set via RPC_API_KEY environment variable.
     #[clap(short = 'k', long, env = "RPC_API_KEY")]
@@ -27,13 +39,489 @@ pub struct Args {
     #[clap(short, long, default_value = "https://mainnet.helius-rpc.com")]
     pub rpc_url: Url,
 
-    /// Time interval in milliseconds between block fetches.
-    /// Controls how frequently the indexer checks for new blocks.
+    /// Name of the network served by `--rpc-url`.
+    /// This is the network used when API requests don't specify one.
+    #[clap(long, default_value = "mainnet")]
+    pub network_name: String,
+
+    /// Long-term RPC endpoint (e.g. Bigtable-backed) or block archive
+    /// endpoint to index the primary network's history from before live
+    /// indexing starts, for fresh deployments whose RPC node has pruned it.
+    #[clap(long, env = "BOOTSTRAP_RPC_URL", requires = "bootstrap_from_slot")]
+    pub bootstrap_rpc_url: Option<Url>,
+
+    /// First slot of the history indexed from `--bootstrap-rpc-url`.
+    #[clap(long, env = "BOOTSTRAP_FROM_SLOT", requires = "bootstrap_rpc_url")]
+    pub bootstrap_from_slot: Option<u64>,
+
+    /// Additional network to index, in the format "name=url".
+    /// Can be repeated; each network is stored in its own database.
+    #[clap(long = "network", env = "SOLDAG_NETWORKS", value_delimiter = ',')]
+    pub networks: Vec<NetworkArg>,
+
+    /// Initial time interval in milliseconds between block fetches.
+    /// Controls how frequently the indexer checks for new blocks; the interval
+    /// adapts to the indexer's lag within the minimum and maximum bounds.
     #[clap(short, long, default_value = "400")]
     pub update_interval: u64,
 
+    /// Shortest interval in milliseconds between block fetches, used while
+    /// the indexer is behind the tip.
+    #[clap(long, env = "MIN_UPDATE_INTERVAL", default_value = "100")]
+    pub min_update_interval: u64,
+
+    /// Longest interval in milliseconds between block fetches, used while
+    /// the indexer is caught up and no new slots are produced.
+    #[clap(long, env = "MAX_UPDATE_INTERVAL", default_value = "2000")]
+    pub max_update_interval: u64,
+
+    /// Amount of transaction detail to index. `signatures` stores only compact
+    /// records (signature, slot, block time, error) in the `signatures`
+    /// collection, for lookups at a fraction of the storage and bandwidth.
+    #[clap(long, env = "DETAIL", value_enum, default_value = "full")]
+    pub detail: Detail,
+
+    /// Largest gap of missed slots fetched inline while indexing.
+    /// Larger gaps are recorded as backfill jobs, run with `soldag backfill`.
+    #[clap(long, env = "MAX_CATCH_UP_SLOTS", default_value = "1000")]
+    pub max_catch_up_slots: u64,
+
+    /// Number of `getBlock` calls sent per JSON-RPC batch request while
+    /// catching up or backfilling missed slots.
+    #[clap(long, env = "CATCH_UP_BATCH_SIZE", default_value = "20")]
+    pub catch_up_batch_size: usize,
+
+    /// Number of blocks whose transactions are decoded concurrently.
+    /// Blocks are still written to storage in slot order.
+    #[clap(long, env = "PROCESS_WORKERS", default_value = "4")]
+    pub process_workers: usize,
+
+    /// Largest number of historical signatures indexed for each watched
+    /// address when it is added to the watchlist. 0 disables the backfill.
+    #[clap(long, env = "ADDRESS_BACKFILL_LIMIT", default_value = "10000")]
+    pub address_backfill_limit: u64,
+
+    /// Time interval in seconds between daily statistics rollups.
+    #[clap(long, default_value = "300")]
+    pub aggregation_interval: u64,
+
+    /// Birdeye-compatible historical price API used to value transfers in
+    /// USD, e.g. "https://public-api.birdeye.so/defi/history_price".
+    /// Enrichment is disabled when unset.
+    #[clap(long, env = "PRICE_API_URL")]
+    pub price_api_url: Option<Url>,
+
+    /// API key sent to the price API in the `X-API-KEY` header.
+    #[clap(long, env = "PRICE_API_KEY")]
+    pub price_api_key: Option<String>,
+
+    /// Compress the log messages and inner instructions of stored
+    /// transactions with zstd. They are decompressed transparently on read.
+    #[clap(long, env = "COMPRESS_TRANSACTIONS")]
+    pub compress_transactions: bool,
+
+    /// zstd level used by --compress-transactions, from 1 (fastest) to 22
+    /// (smallest).
+    #[clap(long, env = "COMPRESSION_LEVEL", default_value = "3", value_parser = clap::value_parser!(i32).range(1..=22))]
+    pub compression_level: i32,
+
+    /// Drop the log messages of stored transactions entirely.
+    #[clap(long, env = "DROP_LOG_MESSAGES")]
+    pub drop_log_messages: bool,
+
+    /// Largest number of MongoDB connections per server, shared by the
+    /// indexer and the API.
+    #[clap(long, env = "MONGO_MAX_POOL_SIZE", default_value = "50")]
+    pub mongo_max_pool_size: u32,
+
+    /// Number of MongoDB connections per server kept open while idle, so
+    /// bursts of API traffic don't wait on new connections.
+    #[clap(long, env = "MONGO_MIN_POOL_SIZE", default_value = "5")]
+    pub mongo_min_pool_size: u32,
+
+    /// Longest time in milliseconds a storage operation waits for a
+    /// suitable MongoDB server before failing.
+    #[clap(long, env = "MONGO_SERVER_SELECTION_TIMEOUT", default_value = "10000")]
+    pub mongo_server_selection_timeout: u64,
+
+    /// Longest time in milliseconds establishing a MongoDB connection may
+    /// take.
+    #[clap(long, env = "MONGO_CONNECT_TIMEOUT", default_value = "5000")]
+    pub mongo_connect_timeout: u64,
+
+    /// Time in milliseconds after which an idle MongoDB connection is closed.
+    #[clap(long, env = "MONGO_MAX_IDLE_TIME", default_value = "300000")]
+    pub mongo_max_idle_time: u64,
+
+    /// Longest time in milliseconds MongoDB may spend on an API query, or 0
+    /// for no limit.
+    #[clap(long, env = "MONGO_QUERY_TIMEOUT", default_value = "30000")]
+    pub mongo_query_timeout: u64,
+
+    /// Replica set members the API's queries are read from, e.g.
+    /// `secondary-preferred` to keep heavy read traffic off the primary.
+    /// The indexer always reads from and writes to the primary.
+    #[clap(long, env = "READ_PREFERENCE", value_enum, default_value_t)]
+    pub read_preference: QueryReadPreference,
+
+    /// Read concern of the API's queries. Defaults to the server's.
+    #[clap(long, env = "READ_CONCERN", value_enum)]
+    pub read_concern: Option<QueryReadConcern>,
+
+    /// Acknowledgment required for writes: `majority` or a number of
+    /// replica set members. Defaults to the server's.
+    #[clap(long, env = "WRITE_CONCERN")]
+    pub write_concern: Option<WriteConcernArg>,
+
+    /// Archive every fetched block as zstd-compressed JSON in the
+    /// `raw_blocks` GridFS bucket, for inspection and reprocessing.
+    #[clap(long, env = "ARCHIVE_RAW_BLOCKS")]
+    pub archive_raw_blocks: bool,
+
+    /// Fetch Helius enhanced transaction interpretations (type, description,
+    /// token transfers) of indexed transactions on the primary network.
+    /// Requires a Helius API key.
+    #[clap(long, env = "HELIUS_ENRICHMENT")]
+    pub helius_enrichment: bool,
+
+    /// Helius API key used for enrichment. Defaults to the RPC API key.
+    #[clap(long, env = "HELIUS_API_KEY")]
+    pub helius_api_key: Option<String>,
+
+    /// Helius enhanced transactions endpoint.
+    #[clap(long, env = "HELIUS_API_URL", default_value = helius::DEFAULT_HELIUS_API_URL)]
+    pub helius_api_url: Url,
+
     /// Network address and port for the API server to listen on.
     /// Specify in the format "host:port".
     #[clap(short, long, default_value = "127.0.0.1:8081")]
     pub api_listen: SocketAddr,
+
+    /// Delay in milliseconds before restarting a failed service. The delay
+    /// doubles with each restart within the restart window.
+    #[clap(long, env = "RESTART_INITIAL_BACKOFF", default_value = "1000")]
+    pub restart_initial_backoff: u64,
+
+    /// Longest delay in milliseconds between restarts of a failed service.
+    #[clap(long, env = "RESTART_MAX_BACKOFF", default_value = "60000")]
+    pub restart_max_backoff: u64,
+
+    /// Restarts allowed per service within the restart window. A service
+    /// failing more often is considered permanently failed and the process
+    /// exits with code 2.
+    #[clap(long, env = "MAX_RESTARTS", default_value = "5")]
+    pub max_restarts: usize,
+
+    /// Time window in seconds over which service restarts are counted.
+    #[clap(long, env = "RESTART_WINDOW", default_value = "600")]
+    pub restart_window: u64,
+
+    /// Webhook notified with a JSON description when a service fails
+    /// permanently.
+    #[clap(long, env = "SUPERVISOR_WEBHOOK_URL")]
+    pub supervisor_webhook_url: Option<Url>,
+
+    /// Longest time in milliseconds an RPC request may take, connection
+    /// included.
+    #[clap(long, env = "RPC_REQUEST_TIMEOUT", default_value = "30000")]
+    pub rpc_request_timeout: u64,
+
+    /// Longest time in milliseconds establishing a connection to an RPC
+    /// node may take.
+    #[clap(long, env = "RPC_CONNECT_TIMEOUT", default_value = "10000")]
+    pub rpc_connect_timeout: u64,
+
+    /// Number of times a failed RPC call is retried, in the main loop,
+    /// catch-up, account fetches and health checks.
+    #[clap(long, env = "RPC_MAX_RETRIES", default_value = "5")]
+    pub rpc_max_retries: u32,
+
+    /// Delay in milliseconds before retrying a failed RPC call. The delay
+    /// doubles with each further retry.
+    #[clap(long, env = "RPC_BACKOFF_BASE", default_value = "200")]
+    pub rpc_backoff_base: u64,
+
+    /// Longest delay in milliseconds between retries of a failed RPC call.
+    #[clap(long, env = "RPC_BACKOFF_MAX", default_value = "10000")]
+    pub rpc_backoff_max: u64,
+
+    /// Time in seconds the indexer is given on shutdown to store blocks it
+    /// has already fetched. Slots left unstored are recorded as backfill jobs.
+    #[clap(long, env = "SHUTDOWN_TIMEOUT", default_value = "30")]
+    pub shutdown_timeout: u64,
+
+    /// Storage operations slower than this many milliseconds are logged.
+    #[clap(long, env = "SLOW_QUERY_THRESHOLD", default_value = "500")]
+    pub slow_query_threshold: u64,
+
+    /// File to write the process id to while running.
+    #[clap(long, env = "PID_FILE")]
+    pub pid_file: Option<PathBuf>,
+
+    /// Path to a TOML file with runtime settings (filters, rate limits, log level).
+    /// The file is re-read on SIGHUP or via the admin reload endpoint.
+    #[clap(short, long, env = "SOLDAG_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Object store URL that closed days of transactions are archived to as
+    /// Parquet, e.g. `s3://bucket/soldag` or `file:///var/lib/soldag`.
+    /// Archival is disabled when unset.
+    #[clap(long, env = "ARCHIVE_URL")]
+    pub archive_url: Option<Url>,
+
+    /// Seconds between archival runs
+    #[clap(long, env = "ARCHIVE_INTERVAL", default_value = "3600")]
+    pub archive_interval: u64,
+
+    /// Seconds to wait after a day ends before archiving it
+    #[clap(long, env = "ARCHIVE_DELAY", default_value = "3600")]
+    pub archive_delay: u64,
+
+    /// Delete transactions from MongoDB once they are archived
+    #[clap(long, env = "ARCHIVE_DELETE")]
+    pub archive_delete: bool,
+
+    /// Skip indexing, aggregation and archival, and only serve the API.
+    /// Useful for API nodes in front of a separately run indexer.
+    #[clap(long, env = "NO_INDEXER")]
+    pub no_indexer: bool,
+
+    /// Tail the MongoDB change stream of stored transactions and republish
+    /// them over `/stream/transactions` and the configured sinks.
+    /// Requires MongoDB to run as a replica set.
+    #[clap(long, env = "CHANGE_STREAM")]
+    pub change_stream: bool,
+
+    /// Webhook that every streamed transaction is POSTed to.
+    /// Can be repeated.
+    #[clap(long, env = "STREAM_WEBHOOK_URLS", value_delimiter = ',')]
+    pub stream_webhook_url: Vec<Url>,
+
+    /// Kafka bootstrap brokers that streamed transactions are produced to,
+    /// e.g. "localhost:9092". Kafka is disabled when unset.
+    #[clap(long, env = "KAFKA_BROKERS", value_delimiter = ',')]
+    pub kafka_brokers: Vec<String>,
+
+    /// Kafka topic for streamed transactions
+    #[clap(long, env = "KAFKA_TOPIC", default_value = "soldag.transactions")]
+    pub kafka_topic: String,
+
+    /// Bearer token required by the admin endpoints.
+    /// Admin endpoints are disabled when no token is set.
+    #[clap(long, env = "ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+
+    /// Reject API requests that do not carry a valid `x-api-key` header.
+    /// API keys are managed through the admin endpoints.
+    #[clap(long, env = "REQUIRE_API_KEY")]
+    pub require_api_key: bool,
+}
+
+impl Args {
+    /// Returns the timeout and retry policy of RPC calls.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            request_timeout: Duration::from_millis(self.rpc_request_timeout),
+            connect_timeout: Duration::from_millis(self.rpc_connect_timeout),
+            max_retries: self.rpc_max_retries,
+            backoff_base: Duration::from_millis(self.rpc_backoff_base),
+            backoff_max: Duration::from_millis(self.rpc_backoff_max),
+        }
+    }
+
+    /// Returns the settings applied when reading and writing documents.
+    pub fn storage_options(&self) -> StorageOptions {
+        StorageOptions {
+            compression_level: self.compress_transactions.then_some(self.compression_level),
+            drop_log_messages: self.drop_log_messages,
+            read_preference: self.read_preference,
+            read_concern: self.read_concern,
+            query_timeout: (self.mongo_query_timeout > 0)
+                .then(|| Duration::from_millis(self.mongo_query_timeout)),
+            write_concern: self
+                .write_concern
+                .as_ref()
+                .map(|write_concern| write_concern.0.clone()),
+            connection: ConnectionOptions {
+                max_pool_size: self.mongo_max_pool_size,
+                min_pool_size: self.mongo_min_pool_size,
+                server_selection_timeout: Duration::from_millis(
+                    self.mongo_server_selection_timeout,
+                ),
+                connect_timeout: Duration::from_millis(self.mongo_connect_timeout),
+                max_idle_time: Duration::from_millis(self.mongo_max_idle_time),
+            },
+        }
+    }
+
+    /// Returns the API key used for Helius enrichment, if enrichment is
+    /// enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if enrichment is enabled without an API key
+    pub fn helius_api_key(&self) -> eyre::Result<Option<&str>> {
+        if !self.helius_enrichment {
+            return Ok(None);
+        }
+
+        self.helius_api_key
+            .as_deref()
+            .or(self.rpc_api_key.as_deref())
+            .map(Some)
+            .ok_or_else(|| eyre::eyre!("--helius-enrichment requires a Helius API key"))
+    }
+}
+
+/// Maintenance commands.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Index the slot ranges recorded as backfill jobs, then exit.
+    /// Interrupted jobs resume where they stopped.
+    Backfill(BackfillArgs),
+
+    /// Upgrade every network's stored transactions to the current schema
+    /// version, then exit. Outdated transactions are otherwise upgraded as
+    /// they are read.
+    Migrate,
+
+    /// Dump every network's indexed collections and checkpoints to a
+    /// directory, then exit.
+    Export(ExportArgs),
+
+    /// Restore every network's collections from an export, then exit.
+    /// Documents already present are skipped.
+    Import(ImportArgs),
+
+    /// Reprocess the blocks archived in GridFS for a slot range, regenerating
+    /// the stored transactions and derived collections, then exit.
+    Replay(ReplayArgs),
+
+    /// Replay blocks through the processing pipeline into a scratch database,
+    /// then load the API with concurrent queries and report throughput and
+    /// latency percentiles.
+    Bench(BenchArgs),
+}
+
+/// Arguments of the `backfill` command.
+#[derive(clap::Args)]
+pub struct BackfillArgs {
+    /// First slot of a new job to record on the primary network before running
+    #[clap(long, requires = "to_slot")]
+    pub from_slot: Option<u64>,
+
+    /// Last slot, inclusive, of a new job to record on the primary network
+    #[clap(long, requires = "from_slot")]
+    pub to_slot: Option<u64>,
+}
+
+/// Arguments of the `replay` command.
+#[derive(clap::Args)]
+pub struct ReplayArgs {
+    /// First slot to replay
+    #[clap(long)]
+    pub from_slot: u64,
+
+    /// Last slot to replay, inclusive
+    #[clap(long)]
+    pub to_slot: u64,
+
+    /// Network whose archived blocks are replayed; defaults to the primary network
+    #[clap(long)]
+    pub network: Option<String>,
+}
+
+/// Arguments of the `export` command.
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    /// Directory to write the export to
+    #[clap(long)]
+    pub out: PathBuf,
+}
+
+/// Arguments of the `import` command.
+#[derive(clap::Args)]
+pub struct ImportArgs {
+    /// Directory holding the export
+    #[clap(long = "in")]
+    pub input: PathBuf,
+}
+
+/// Arguments of the `bench` command.
+#[derive(clap::Args)]
+pub struct BenchArgs {
+    /// Scratch MongoDB database the replayed blocks are written to, emptied
+    /// at the start of every run. Must differ from the databases of indexed
+    /// networks
+    #[clap(long, default_value = "soldag_bench")]
+    pub database: String,
+
+    /// Number of blocks to replay
+    #[clap(long, default_value_t = 100)]
+    pub blocks: usize,
+
+    /// Rebuild blocks from the primary network's most recent stored
+    /// transactions instead of generating synthetic transfers
+    #[clap(long)]
+    pub stored: bool,
+
+    /// Number of transactions per block; with --stored, bounds the number of
+    /// transactions read to blocks × this value
+    #[clap(long, default_value_t = 100)]
+    pub transactions_per_block: usize,
+
+    /// Number of API requests to send
+    #[clap(long, default_value_t = 1000)]
+    pub requests: usize,
+
+    /// Number of API requests in flight at once
+    #[clap(long, default_value_t = 16)]
+    pub concurrency: usize,
+}
+
+/// Additional network supplied on the command line.
+#[derive(Clone, Debug)]
+pub struct NetworkArg {
+    /// Network name
+    pub name: String,
+    /// Solana RPC endpoint URL
+    pub rpc_url: Url,
+}
+
+impl FromStr for NetworkArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, rpc_url) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected \"name=url\", got \"{}\"", s))?;
+
+        if name.is_empty() {
+            return Err("Network name cannot be empty".to_string());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            rpc_url: Url::parse(rpc_url).map_err(|e| format!("Invalid RPC URL: {}", e))?,
+        })
+    }
+}
+
+/// Write acknowledgment supplied on the command line: `majority` or a number
+/// of replica set members.
+#[derive(Clone, Debug)]
+pub struct WriteConcernArg(pub Acknowledgment);
+
+impl FromStr for WriteConcernArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "majority" => Ok(Self(Acknowledgment::Majority)),
+            nodes => nodes
+                .parse()
+                .map(|nodes| Self(Acknowledgment::Nodes(nodes)))
+                .map_err(|_| format!("Expected \"majority\" or a number, got \"{}\"", s)),
+        }
+    }
 }
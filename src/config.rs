@@ -0,0 +1,233 @@
+//! Runtime configuration for SolDag.
+//!
+//! Unlike the command-line arguments in [`crate::cli`], the settings in this
+//! module can be changed while the application is running. They are read from a
+//! TOML file and re-read whenever the process receives `SIGHUP` or an
+//! administrator calls the `/admin/reload` endpoint. Consumers subscribe to a
+//! watch channel and always see the most recently applied configuration.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use eyre::Context;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{domain::models::transaction::Transaction, logger};
+
+/// Settings that can be reloaded without restarting the application.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Log filter directives, in the same format as `RUST_LOG`
+    pub log_level: Option<String>,
+    /// Restricts which transactions the indexer stores
+    pub filters: Filters,
+    /// Request rate limit applied to the API
+    pub rate_limit: Option<RateLimit>,
+    /// Large-transfer alert rules
+    pub alerts: AlertConfig,
+}
+
+/// Watched addresses and programs.
+///
+/// When both lists are empty every transaction is stored. Otherwise a
+/// transaction is stored if it references any watched address or invokes any
+/// watched program.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filters {
+    /// Account addresses to watch
+    pub addresses: HashSet<String>,
+    /// Program IDs to watch
+    pub programs: HashSet<String>,
+}
+
+impl Filters {
+    /// Returns `true` if no filters are configured.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.programs.is_empty()
+    }
+
+    /// Checks whether a transaction should be stored under these filters.
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        let account_keys = transaction.account_keys();
+
+        let address_match = account_keys.iter().any(|key| self.addresses.contains(*key));
+
+        let program_match = transaction.message.instructions.iter().any(|ix| {
+            account_keys
+                .get(ix.program_id_index as usize)
+                .is_some_and(|program| self.programs.contains(*program))
+        });
+
+        address_match || program_match
+    }
+}
+
+/// Token bucket parameters for API rate limiting.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// Sustained number of requests allowed per second
+    pub requests_per_second: u32,
+    /// Maximum number of requests allowed in a single burst
+    pub burst: u32,
+}
+
+/// Large-transfer alerting settings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    /// URL that triggered alerts are POSTed to as JSON
+    pub webhook_url: Option<String>,
+    /// Rules evaluated against every stored transaction
+    pub rules: Vec<AlertRule>,
+}
+
+/// Flags transfers of an asset at or above a threshold.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// Rule name, recorded on every alert it triggers
+    pub name: String,
+    /// `"SOL"` for native transfers, otherwise the token mint address
+    #[serde(default = "AlertRule::default_asset")]
+    pub asset: String,
+    /// Threshold in whole units (SOL or UI token amount)
+    pub min_amount: f64,
+}
+
+impl AlertRule {
+    /// Asset name matching native SOL transfers.
+    pub const SOL: &'static str = "SOL";
+
+    fn default_asset() -> String {
+        Self::SOL.to_string()
+    }
+}
+
+impl Config {
+    /// Reads and parses a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the TOML configuration file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The file cannot be read
+    /// * The file is not valid TOML or contains unknown keys
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Error reading config file {}", path.display()))?;
+
+        toml::from_str(&contents)
+            .wrap_err_with(|| format!("Error parsing config file {}", path.display()))
+    }
+}
+
+/// Handle for reading and reloading the runtime configuration.
+///
+/// Cloning the handle is cheap; all clones share the same watch channel.
+#[derive(Clone)]
+pub struct ConfigHandle {
+    /// Location of the configuration file, if one was supplied
+    path: Option<PathBuf>,
+    /// Channel publishing the currently applied configuration
+    tx: Arc<watch::Sender<Arc<Config>>>,
+}
+
+impl Default for ConfigHandle {
+    /// Creates a handle with the default configuration and no backing file.
+    fn default() -> Self {
+        let (tx, _) = watch::channel(Arc::new(Config::default()));
+
+        Self {
+            path: None,
+            tx: Arc::new(tx),
+        }
+    }
+}
+
+impl ConfigHandle {
+    /// Loads the initial configuration and applies it.
+    ///
+    /// When no path is supplied the default configuration is used and reloads
+    /// are no-ops.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file cannot be loaded
+    pub fn init(path: Option<PathBuf>) -> eyre::Result<Self> {
+        let config = match &path {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        logger::set_filters(config.log_level.as_deref());
+
+        let (tx, _) = watch::channel(Arc::new(config));
+
+        Ok(Self {
+            path,
+            tx: Arc::new(tx),
+        })
+    }
+
+    /// Returns the currently applied configuration.
+    pub fn current(&self) -> Arc<Config> {
+        self.tx.borrow().clone()
+    }
+
+    /// Subscribes to configuration changes.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.tx.subscribe()
+    }
+
+    /// Re-reads the configuration file and publishes the new settings.
+    ///
+    /// The previous configuration stays in effect if the file is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file cannot be loaded
+    pub fn reload(&self) -> eyre::Result<Arc<Config>> {
+        let Some(path) = &self.path else {
+            return Ok(self.current());
+        };
+
+        let config = Arc::new(Config::load(path)?);
+
+        logger::set_filters(config.log_level.as_deref());
+        self.tx.send_replace(config.clone());
+
+        info!("Configuration reloaded from {}", path.display());
+
+        Ok(config)
+    }
+
+    /// Reloads the configuration every time the process receives `SIGHUP`.
+    ///
+    /// Runs until the signal stream is closed.
+    pub async fn reload_on_sighup(self) -> eyre::Result<()> {
+        let mut signals = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+
+        while signals.recv().await.is_some() {
+            if let Err(e) = self.reload() {
+                error!("Error reloading configuration: {:?}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+//! Integration with process managers.
+//!
+//! A PID file lets init scripts find the running process, and systemd's
+//! `sd_notify` protocol reports when startup has completed (`READY=1`) and
+//! that the process is still responsive (`WATCHDOG=1`). Notifications are
+//! datagrams sent to the socket named by `NOTIFY_SOCKET`; they are no-ops when
+//! the process isn't run by systemd. Termination signals start a graceful
+//! shutdown.
+
+use std::{
+    env, fs,
+    os::unix::net::{SocketAddr, UnixDatagram},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::WrapErr;
+use log::{error, info};
+use tokio::signal::unix::{signal, SignalKind};
+
+/// Environment variable naming the systemd notification socket.
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// Environment variable holding the watchdog timeout in microseconds.
+const WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+
+/// Environment variable naming the process the watchdog applies to.
+const WATCHDOG_PID: &str = "WATCHDOG_PID";
+
+/// A file holding the process id, removed when dropped.
+pub struct PidFile {
+    /// Location of the file
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the current process id to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn create(path: &Path) -> eyre::Result<Self> {
+        fs::write(path, format!("{}\n", std::process::id()))
+            .wrap_err_with(|| format!("Error writing PID file {}", path.display()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            error!("Error removing PID file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Sends a state update to systemd.
+///
+/// # Arguments
+///
+/// * `state` - Newline-separated assignments, e.g. `READY=1`
+///
+/// # Returns
+///
+/// * `eyre::Result<bool>` - Whether a notification socket was configured
+///
+/// # Errors
+///
+/// Returns an error if the notification cannot be sent
+pub fn notify(state: &str) -> eyre::Result<bool> {
+    let Some(socket) = env::var_os(NOTIFY_SOCKET) else {
+        return Ok(false);
+    };
+    let socket = socket.to_string_lossy();
+
+    let address = match socket.strip_prefix('@') {
+        Some(name) => abstract_address(name)?,
+        None => SocketAddr::from_pathname(socket.as_ref())?,
+    };
+
+    UnixDatagram::unbound()?
+        .send_to_addr(state.as_bytes(), &address)
+        .wrap_err("Error sending systemd notification")?;
+
+    Ok(true)
+}
+
+/// Builds the address of a socket in the abstract namespace.
+#[cfg(target_os = "linux")]
+fn abstract_address(name: &str) -> eyre::Result<SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+
+    Ok(SocketAddr::from_abstract_name(name)?)
+}
+
+/// Builds the address of a socket in the abstract namespace.
+#[cfg(not(target_os = "linux"))]
+fn abstract_address(_name: &str) -> eyre::Result<SocketAddr> {
+    eyre::bail!("Abstract notification sockets are only supported on Linux")
+}
+
+/// Reports completed startup to systemd, logging rather than failing if the
+/// notification cannot be delivered.
+pub fn notify_ready() {
+    match notify("READY=1") {
+        Ok(true) => info!("Notified systemd of completed startup"),
+        Ok(false) => {}
+        Err(e) => error!("{:?}", e),
+    }
+}
+
+/// Returns how often watchdog keep-alives should be sent, if systemd expects
+/// them from this process.
+///
+/// Keep-alives are sent at half the configured timeout, as systemd recommends.
+pub fn watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = env::var(WATCHDOG_PID) {
+        if pid.parse::<u32>().ok()? != std::process::id() {
+            return None;
+        }
+    }
+
+    let timeout = env::var(WATCHDOG_USEC).ok()?.parse::<u64>().ok()?;
+    (timeout > 0).then(|| Duration::from_micros(timeout / 2))
+}
+
+/// Resolves once the process receives `SIGTERM` or `SIGINT`.
+///
+/// # Errors
+///
+/// Returns an error if the signal handlers cannot be installed
+pub async fn terminated() -> eyre::Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+
+    tokio::select! {
+        _ = terminate.recv() => info!("Received SIGTERM"),
+        _ = interrupt.recv() => info!("Received SIGINT"),
+    }
+
+    Ok(())
+}
+
+/// Sends watchdog keep-alives to systemd until the process exits.
+///
+/// Keep-alives are sent from the async runtime, so a stalled runtime stops
+/// them and systemd restarts the service.
+///
+/// # Arguments
+///
+/// * `interval` - Time between keep-alives
+pub async fn watchdog(interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = notify("WATCHDOG=1") {
+            error!("{:?}", e);
+        }
+    }
+}
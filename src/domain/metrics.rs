@@ -0,0 +1,188 @@
+//! Latency and error metrics of storage operations.
+//!
+//! Every instrumented `Storage` method records its duration in a histogram
+//! and counts its calls and errors, so database degradation shows up before
+//! the indexer's channels back up. Operations slower than the configured
+//! threshold are logged individually.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+/// Upper bounds, in seconds, of the latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+
+/// Default duration above which storage operations are logged.
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Counters and latency histogram of one storage operation.
+#[derive(Debug, Default, Clone)]
+pub struct OperationStats {
+    /// Number of completed calls
+    pub calls: u64,
+    /// Number of calls that returned an error
+    pub errors: u64,
+    /// Number of calls no slower than each bucket bound
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    /// Total duration of all calls in seconds
+    pub seconds: f64,
+}
+
+/// Metrics of a storage instance's operations.
+#[derive(Debug)]
+pub struct StorageMetrics {
+    /// Statistics by operation name
+    operations: Mutex<BTreeMap<&'static str, OperationStats>>,
+    /// Duration in milliseconds above which operations are logged
+    slow_query_threshold: AtomicU64,
+}
+
+impl Default for StorageMetrics {
+    fn default() -> Self {
+        Self {
+            operations: Mutex::new(BTreeMap::new()),
+            slow_query_threshold: AtomicU64::new(DEFAULT_SLOW_QUERY_THRESHOLD.as_millis() as u64),
+        }
+    }
+}
+
+impl StorageMetrics {
+    /// Sets the duration above which operations are logged as slow.
+    pub fn set_slow_query_threshold(&self, threshold: Duration) {
+        self.slow_query_threshold
+            .store(threshold.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Runs a storage operation and records its duration and outcome.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Name of the operation
+    /// * `future` - The operation
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<T>` - Result of the operation
+    pub async fn observe<T>(
+        &self,
+        operation: &'static str,
+        future: impl Future<Output = eyre::Result<T>>,
+    ) -> eyre::Result<T> {
+        let started = Instant::now();
+        let result = future.await;
+        let elapsed = started.elapsed();
+
+        self.record(operation, elapsed, result.is_err());
+
+        let threshold = Duration::from_millis(self.slow_query_threshold.load(Ordering::Relaxed));
+        if elapsed > threshold {
+            warn!("Slow storage operation {} took {:?}", operation, elapsed);
+        }
+
+        result
+    }
+
+    /// Records a completed call of an operation.
+    pub fn record(&self, operation: &'static str, elapsed: Duration, failed: bool) {
+        let mut operations = self.operations.lock().expect("Storage metrics poisoned");
+        let stats = operations.entry(operation).or_default();
+
+        let seconds = elapsed.as_secs_f64();
+        stats.calls += 1;
+        stats.errors += failed as u64;
+        stats.seconds += seconds;
+        for (count, bound) in stats.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Returns the statistics of every operation called so far.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, OperationStats> {
+        self.operations
+            .lock()
+            .expect("Storage metrics poisoned")
+            .clone()
+    }
+}
+
+/// Renders storage metrics in the Prometheus text exposition format.
+///
+/// # Arguments
+///
+/// * `networks` - Metrics of each network's storage, by network name
+///
+/// # Returns
+///
+/// * `String` - Samples grouped by metric family, labeled with the network
+///   and operation
+pub fn render<'a>(networks: impl IntoIterator<Item = (&'a str, &'a StorageMetrics)>) -> String {
+    let samples: Vec<(String, OperationStats)> = networks
+        .into_iter()
+        .flat_map(|(network, metrics)| {
+            metrics
+                .snapshot()
+                .into_iter()
+                .map(move |(operation, stats)| {
+                    (
+                        format!("network=\"{}\",operation=\"{}\"", network, operation),
+                        stats,
+                    )
+                })
+        })
+        .collect();
+
+    let mut out = String::new();
+
+    out.push_str("# HELP soldag_storage_operations_total Storage operations completed.\n");
+    out.push_str("# TYPE soldag_storage_operations_total counter\n");
+    for (labels, stats) in &samples {
+        let _ = writeln!(
+            out,
+            "soldag_storage_operations_total{{{}}} {}",
+            labels, stats.calls
+        );
+    }
+
+    out.push_str("# HELP soldag_storage_errors_total Storage operations that failed.\n");
+    out.push_str("# TYPE soldag_storage_errors_total counter\n");
+    for (labels, stats) in &samples {
+        let _ = writeln!(
+            out,
+            "soldag_storage_errors_total{{{}}} {}",
+            labels, stats.errors
+        );
+    }
+
+    let histogram = "soldag_storage_operation_duration_seconds";
+    let _ = writeln!(out, "# HELP {} Duration of storage operations.", histogram);
+    let _ = writeln!(out, "# TYPE {} histogram", histogram);
+    for (labels, stats) in &samples {
+        for (count, bound) in stats.buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                histogram, labels, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            histogram, labels, stats.calls
+        );
+        let _ = writeln!(out, "{}_sum{{{}}} {}", histogram, labels, stats.seconds);
+        let _ = writeln!(out, "{}_count{{{}}} {}", histogram, labels, stats.calls);
+    }
+
+    out
+}
@@ -0,0 +1,136 @@
+//! Schema migrations of stored transactions.
+//!
+//! Every stored transaction records the `schema_version` of the layout it was
+//! written with; documents stored before versioning was introduced have none
+//! and count as version 1. Outdated documents are upgraded as they are read,
+//! or all at once with the `migrate` command. Migrations operate on raw BSON
+//! documents, so they also apply to documents the current [`Transaction`]
+//! model can no longer deserialize.
+//!
+//! [`Transaction`]: super::models::transaction::Transaction
+
+use eyre::Context;
+use mongodb::bson::{doc, Bson, Document};
+
+/// Schema version of transactions written by this build.
+pub const TRANSACTION_SCHEMA_VERSION: u32 = 2;
+
+/// Schema version of transactions stored without one.
+const UNVERSIONED_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrade of stored documents from the previous schema version.
+struct Migration {
+    /// Version the migration upgrades documents to
+    version: u32,
+    /// What the migration changes
+    description: &'static str,
+    /// Rewrites a document of the previous version in place
+    apply: fn(&mut Document) -> eyre::Result<()>,
+}
+
+/// Transaction migrations, in ascending version order.
+const TRANSACTION_MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    description:
+        "resolve the account keys of transactions stored before lookup tables were resolved",
+    apply: resolve_account_keys,
+}];
+
+/// Returns the schema version a stored transaction was written with.
+pub fn transaction_schema_version(transaction: &Document) -> u32 {
+    match transaction.get("schema_version") {
+        Some(Bson::Int32(version)) => *version as u32,
+        Some(Bson::Int64(version)) => *version as u32,
+        _ => UNVERSIONED_SCHEMA_VERSION,
+    }
+}
+
+/// Builds the query matching transactions older than the current schema
+/// version.
+pub fn outdated_transactions() -> Document {
+    doc! {
+        "$or": [
+            { "schema_version": { "$exists": false } },
+            { "schema_version": { "$lt": TRANSACTION_SCHEMA_VERSION as i64 } },
+        ]
+    }
+}
+
+/// Builds the query matching transactions written by a newer build.
+pub fn unsupported_transactions() -> Document {
+    doc! { "schema_version": { "$gt": TRANSACTION_SCHEMA_VERSION as i64 } }
+}
+
+/// Upgrades a stored transaction to the current schema version.
+///
+/// # Arguments
+///
+/// * `transaction` - Stored transaction document, rewritten in place
+///
+/// # Returns
+///
+/// * `eyre::Result<bool>` - Whether the document was changed
+///
+/// # Errors
+///
+/// Returns an error if the document was written by a newer build, or a
+/// migration can't make sense of it
+pub fn upgrade_transaction(transaction: &mut Document) -> eyre::Result<bool> {
+    let version = transaction_schema_version(transaction);
+    eyre::ensure!(
+        version <= TRANSACTION_SCHEMA_VERSION,
+        "Transaction has schema version {}, but this build supports up to {}",
+        version,
+        TRANSACTION_SCHEMA_VERSION
+    );
+    if version == TRANSACTION_SCHEMA_VERSION {
+        return Ok(false);
+    }
+
+    for migration in TRANSACTION_MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > version)
+    {
+        (migration.apply)(transaction).wrap_err_with(|| {
+            format!(
+                "Error migrating transaction to schema version {} ({})",
+                migration.version, migration.description
+            )
+        })?;
+    }
+    transaction.insert("schema_version", TRANSACTION_SCHEMA_VERSION as i64);
+
+    Ok(true)
+}
+
+/// Version 2: fills in `resolved_account_keys` from the static keys and the
+/// addresses loaded from lookup tables, so address queries and
+/// [`Transaction::account_keys`] see the same accounts for every document.
+///
+/// [`Transaction::account_keys`]: super::models::transaction::Transaction::account_keys
+fn resolve_account_keys(transaction: &mut Document) -> eyre::Result<()> {
+    if transaction
+        .get_array("resolved_account_keys")
+        .is_ok_and(|keys| !keys.is_empty())
+    {
+        return Ok(());
+    }
+
+    let mut keys = transaction
+        .get_document("message")?
+        .get_array("accountKeys")?
+        .clone();
+    if let Ok(loaded) = transaction
+        .get_document("meta")
+        .and_then(|meta| meta.get_document("loadedAddresses"))
+    {
+        for field in ["writable", "readonly"] {
+            if let Ok(addresses) = loaded.get_array(field) {
+                keys.extend(addresses.iter().cloned());
+            }
+        }
+    }
+    transaction.insert("resolved_account_keys", keys);
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub mod metrics;
+pub mod migrations;
 pub mod models;
 pub mod storage;
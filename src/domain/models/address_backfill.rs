@@ -0,0 +1,54 @@
+//! Address backfill model module.
+//!
+//! When an address is added to the watchlist, its history before that moment
+//! is indexed by paging through `getSignaturesForAddress`, newest first. The
+//! job records the oldest signature reached so an interrupted backfill resumes
+//! where it stopped.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+/// Progress of the history backfill of a watched address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressBackfill {
+    /// Watched address
+    pub address: String,
+    /// Oldest signature processed so far; the next page starts before it
+    pub before: Option<String>,
+    /// Number of signatures processed so far
+    pub fetched: u64,
+    /// Time the job was recorded
+    pub created_at: bson::DateTime,
+    /// Time the oldest signature was reached or the limit was hit
+    pub completed_at: Option<bson::DateTime>,
+}
+
+impl AddressBackfill {
+    /// Creates a pending job for an address.
+    pub fn new(address: String) -> Self {
+        Self {
+            address,
+            before: None,
+            fetched: 0,
+            created_at: bson::DateTime::now(),
+            completed_at: None,
+        }
+    }
+
+    /// Records a processed page of signatures, ordered newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `signatures` - Signatures of the page
+    /// * `limit` - Maximum number of signatures to backfill per address
+    pub fn advance(&mut self, signatures: &[String], limit: u64) {
+        if let Some(oldest) = signatures.last() {
+            self.before = Some(oldest.clone());
+        }
+        self.fetched += signatures.len() as u64;
+
+        if signatures.is_empty() || self.fetched >= limit {
+            self.completed_at = Some(bson::DateTime::now());
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Address summary model module.
+//!
+//! Running totals of an address's activity, maintained incrementally as
+//! transactions are stored so a summary is served with a single lookup. Each
+//! stored batch of transactions is folded into per-address [`AddressActivity`]
+//! deltas, which are then added to the stored summaries.
+//!
+//! Counterparties are the accounts an address sent SOL to or received SOL
+//! from. Each pair is recorded once as a [`CounterpartyLink`], so that only
+//! newly seen counterparties increment the count.
+
+use std::collections::BTreeMap;
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use super::{sol_transfer::SolTransfer, transaction::Transaction};
+
+/// Activity summary of an address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressSummary {
+    /// Address summarized
+    pub address: String,
+    /// Block time of the earliest stored transaction involving the address
+    pub first_seen: Option<bson::DateTime>,
+    /// Block time of the latest stored transaction involving the address
+    pub last_seen: Option<bson::DateTime>,
+    /// Slot of the earliest stored transaction involving the address
+    pub first_slot: Option<u64>,
+    /// Slot of the latest stored transaction involving the address
+    pub last_slot: Option<u64>,
+    /// Number of stored transactions involving the address
+    #[serde(default)]
+    pub transaction_count: u64,
+    /// Fees paid in lamports by transactions the address was fee payer of
+    #[serde(default)]
+    pub fees_paid: u64,
+    /// Number of distinct accounts the address exchanged SOL with
+    #[serde(default)]
+    pub counterparty_count: u64,
+}
+
+/// Activity of an address within a batch of stored transactions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddressActivity {
+    /// Earliest block time in the batch
+    pub first_seen: Option<bson::DateTime>,
+    /// Latest block time in the batch
+    pub last_seen: Option<bson::DateTime>,
+    /// Earliest slot in the batch
+    pub first_slot: Option<u64>,
+    /// Latest slot in the batch
+    pub last_slot: Option<u64>,
+    /// Number of transactions involving the address
+    pub transaction_count: u64,
+    /// Fees paid in lamports
+    pub fees_paid: u64,
+    /// Number of counterparties not seen before the batch
+    pub new_counterparties: u64,
+}
+
+impl AddressActivity {
+    /// Adds a transaction to the activity of every address it involves.
+    ///
+    /// # Arguments
+    ///
+    /// * `activity` - Activity of the batch so far, keyed by address
+    /// * `transaction` - Stored transaction, with its slot and block time set
+    pub fn record(activity: &mut BTreeMap<String, Self>, transaction: &Transaction) {
+        let mut addresses = transaction.account_keys();
+        let fee_payer = addresses.first().copied();
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        for address in addresses {
+            let entry = activity.entry(address.to_string()).or_default();
+            entry.transaction_count += 1;
+            if Some(address) == fee_payer {
+                entry.fees_paid += transaction.meta.fee;
+            }
+            if let Some(block_time) = transaction.block_time {
+                entry.first_seen = Some(entry.first_seen.map_or(block_time, |t| t.min(block_time)));
+                entry.last_seen = Some(entry.last_seen.map_or(block_time, |t| t.max(block_time)));
+            }
+            if let Some(slot) = transaction.slot {
+                entry.first_slot = Some(entry.first_slot.map_or(slot, |s| s.min(slot)));
+                entry.last_slot = Some(entry.last_slot.map_or(slot, |s| s.max(slot)));
+            }
+        }
+    }
+
+    /// Adds the activity of another batch of transactions.
+    pub fn merge(&mut self, other: &Self) {
+        self.first_seen = self.first_seen.into_iter().chain(other.first_seen).min();
+        self.last_seen = self.last_seen.into_iter().chain(other.last_seen).max();
+        self.first_slot = self.first_slot.into_iter().chain(other.first_slot).min();
+        self.last_slot = self.last_slot.into_iter().chain(other.last_slot).max();
+        self.transaction_count += other.transaction_count;
+        self.fees_paid += other.fees_paid;
+        self.new_counterparties += other.new_counterparties;
+    }
+}
+
+/// A pair of accounts that exchanged SOL, recorded in both directions.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CounterpartyLink {
+    /// Account the link belongs to
+    pub address: String,
+    /// Account it exchanged SOL with
+    pub counterparty: String,
+}
+
+impl CounterpartyLink {
+    /// Derives the distinct counterparty links of SOL transfers.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Self>` - Links of both parties of each transfer, without
+    ///   duplicates or self-transfers
+    pub fn from_transfers(transfers: &[SolTransfer]) -> Vec<Self> {
+        let mut links: Vec<Self> = transfers
+            .iter()
+            .filter(|transfer| transfer.from != transfer.to)
+            .flat_map(|transfer| {
+                [
+                    Self {
+                        address: transfer.from.clone(),
+                        counterparty: transfer.to.clone(),
+                    },
+                    Self {
+                        address: transfer.to.clone(),
+                        counterparty: transfer.from.clone(),
+                    },
+                ]
+            })
+            .collect();
+        links.sort_unstable();
+        links.dedup();
+        links
+    }
+}
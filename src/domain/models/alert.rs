@@ -0,0 +1,26 @@
+//! Alert model module.
+//!
+//! Alerts record transfers that matched one of the configured large-transfer
+//! rules while blocks were being processed.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use super::transfer::Transfer;
+
+/// A transfer flagged by an alert rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Name of the rule that triggered the alert
+    pub rule: String,
+    /// Signature of the transaction containing the transfer
+    pub signature: String,
+    /// Slot of the block containing the transaction
+    pub slot: u64,
+    /// Timestamp when the transaction was included in a block
+    pub block_time: Option<bson::DateTime>,
+    /// The flagged transfer
+    pub transfer: Transfer,
+    /// Time the alert was raised
+    pub created_at: bson::DateTime,
+}
@@ -0,0 +1,41 @@
+//! API key model module.
+//!
+//! Each API key identifies a tenant of a shared SolDag deployment and carries
+//! that tenant's rate limit, the endpoints it may call and the data it may see.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Filters, RateLimit};
+
+/// A tenant's API key and its access policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Secret presented in the `x-api-key` header
+    pub key: String,
+    /// Tenant name, for operators
+    pub name: String,
+    /// Rate limit for this key; the global limit applies when unset
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Path prefixes the key may call; every endpoint when empty
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Restricts transactions and alerts to those involving these addresses
+    /// or programs; all data is visible when unset
+    #[serde(default)]
+    pub scope: Option<Filters>,
+    /// Time the key was created
+    pub created_at: bson::DateTime,
+}
+
+impl ApiKey {
+    /// Checks whether the key may call the endpoint at `path`.
+    pub fn allows(&self, path: &str) -> bool {
+        self.endpoints.is_empty()
+            || self.endpoints.iter().any(|prefix| {
+                path.strip_prefix(prefix.trim_end_matches('/'))
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    }
+}
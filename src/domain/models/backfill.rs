@@ -0,0 +1,54 @@
+//! Backfill job model module.
+//!
+//! Gaps in the indexed slots that are too large to catch up on inline are
+//! recorded as backfill jobs and processed by the `backfill` subcommand. Each
+//! job tracks its progress so an interrupted backfill resumes where it stopped.
+//! The history indexed from a long-term source on a fresh deployment is
+//! tracked by a bootstrap job the same way.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+/// A range of slots waiting to be indexed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackfillJob {
+    /// Unique job identifier
+    pub id: String,
+    /// First slot of the range, inclusive
+    pub start_slot: u64,
+    /// Last slot of the range, inclusive
+    pub end_slot: u64,
+    /// Next slot to index; the job is done once it passes `end_slot`
+    pub next_slot: u64,
+    /// Time the job was recorded
+    pub created_at: bson::DateTime,
+    /// Time the last slot was indexed
+    pub completed_at: Option<bson::DateTime>,
+    /// Whether the job indexes history from the bootstrap source rather than
+    /// a gap left by the live indexer
+    #[serde(default)]
+    pub bootstrap: bool,
+}
+
+impl BackfillJob {
+    /// Creates a pending job for a slot range.
+    pub fn new(start_slot: u64, end_slot: u64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            start_slot,
+            end_slot,
+            next_slot: start_slot,
+            created_at: bson::DateTime::now(),
+            completed_at: None,
+            bootstrap: false,
+        }
+    }
+
+    /// Creates a pending job for indexing history from the bootstrap source.
+    pub fn bootstrap(start_slot: u64, end_slot: u64) -> Self {
+        Self {
+            bootstrap: true,
+            ..Self::new(start_slot, end_slot)
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Balance change model module.
+//!
+//! A ledger of SOL balance changes, one record per account whose balance a
+//! transaction changed, derived from the transaction's `pre_balances` and
+//! `post_balances`. It lets clients follow an address's balance over time
+//! without replaying full transactions.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use super::transaction::Transaction;
+
+/// Change of an account's SOL balance in a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    /// Account whose balance changed
+    pub pubkey: String,
+    /// Signature of the transaction
+    pub signature: String,
+    /// Position of the change among those of the transaction
+    #[serde(default)]
+    pub index: u32,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Position of the transaction in its block, if known; transactions
+    /// backfilled by address are fetched on their own and have none
+    #[serde(default)]
+    pub transaction_index: Option<u32>,
+    /// Balance in lamports before the transaction
+    pub pre_balance: u64,
+    /// Balance in lamports after the transaction
+    pub post_balance: u64,
+    /// Difference in lamports; negative when the balance decreased
+    pub change: i64,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+}
+
+impl BalanceChange {
+    /// Derives the balance changes of a transaction. Accounts whose balance
+    /// is unchanged are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Stored transaction, with its slot and block time set
+    /// * `transaction_index` - Position of the transaction in its block, if
+    ///   known
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Self>` - One record per account whose balance changed, in
+    ///   account order
+    pub fn from_transaction(
+        transaction: &Transaction,
+        transaction_index: Option<u32>,
+    ) -> Vec<Self> {
+        transaction
+            .account_keys()
+            .into_iter()
+            .zip(&transaction.meta.pre_balances)
+            .zip(&transaction.meta.post_balances)
+            .filter(|((_, pre), post)| pre != post)
+            .enumerate()
+            .map(|(index, ((pubkey, pre), post))| Self {
+                pubkey: pubkey.to_string(),
+                signature: transaction.signature.clone(),
+                index: index as u32,
+                slot: transaction.slot,
+                transaction_index,
+                pre_balance: *pre,
+                post_balance: *post,
+                change: *post as i64 - *pre as i64,
+                block_time: transaction.block_time,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+//! Block model module.
+//!
+//! A compact record of every indexed block, stored independently of the
+//! transaction filters so slots and block hashes can always be looked up.
+
+use chrono::{DateTime, Utc};
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_transaction_status_client_types::UiConfirmedBlock;
+
+/// Header data of an indexed block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    /// Slot the block was produced in
+    pub slot: u64,
+    /// Hash of the block
+    pub blockhash: String,
+    /// Hash of the parent block
+    pub previous_blockhash: String,
+    /// Slot of the parent block
+    pub parent_slot: u64,
+    /// Estimated production time of the block
+    pub block_time: Option<bson::DateTime>,
+    /// Number of blocks beneath this block
+    pub block_height: Option<u64>,
+    /// Number of transactions in the block
+    pub transaction_count: u64,
+    /// Identity of the validator scheduled to produce the block, if known
+    #[serde(default)]
+    pub leader: Option<String>,
+}
+
+impl Block {
+    /// Builds the block record for a block fetched from the RPC. The leader
+    /// isn't part of the block and is left unset.
+    pub fn from_confirmed(block: &UiConfirmedBlock, slot: u64) -> Self {
+        Self {
+            slot,
+            blockhash: block.blockhash.clone(),
+            previous_blockhash: block.previous_blockhash.clone(),
+            parent_slot: block.parent_slot,
+            block_time: block
+                .block_time
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+                .map(bson::DateTime::from_chrono),
+            block_height: block.block_height,
+            transaction_count: match (&block.transactions, &block.signatures) {
+                (Some(transactions), _) => transactions.len() as u64,
+                (None, Some(signatures)) => signatures.len() as u64,
+                (None, None) => 0,
+            },
+            leader: None,
+        }
+    }
+}
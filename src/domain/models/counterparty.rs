@@ -0,0 +1,27 @@
+//! Counterparty model module.
+//!
+//! Aggregated SOL flows between an address and each account it exchanged
+//! SOL with, computed on request from the `sol_transfers` collection. This
+//! is the starting point for tracing where an address's funds came from and
+//! went to. Token transfers are not included.
+
+use serde::{Deserialize, Serialize};
+
+/// SOL exchanged between an address and one of its counterparties.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counterparty {
+    /// Account the address exchanged SOL with
+    pub address: String,
+    /// Number of transfers between the two accounts, in either direction
+    pub transfer_count: u64,
+    /// Number of distinct transactions containing those transfers
+    pub transaction_count: u64,
+    /// Lamports sent by the address to the counterparty
+    pub sent_lamports: u64,
+    /// Lamports received by the address from the counterparty
+    pub received_lamports: u64,
+    /// Slot of the earliest transfer between the two accounts
+    pub first_slot: u64,
+    /// Slot of the latest transfer between the two accounts
+    pub last_slot: u64,
+}
@@ -0,0 +1,42 @@
+//! Daily statistics model module.
+//!
+//! Defines the per-day rollup of indexed transactions that is maintained by
+//! the aggregation job and served by the `/stats/daily` endpoint.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+/// Aggregated metrics for one calendar day (UTC).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyStats {
+    /// Start of the day (midnight UTC)
+    pub day: bson::DateTime,
+    /// Number of transactions in blocks produced that day
+    pub transaction_count: u64,
+    /// Number of distinct account addresses referenced that day
+    pub unique_addresses: u64,
+    /// Sum of all transaction fees in lamports
+    pub total_fees: u64,
+    /// Number of transactions that failed
+    pub failed_count: u64,
+    /// Share of transactions that failed, between 0 and 1
+    pub failure_rate: f64,
+    /// Most common causes of failure, most frequent first
+    #[serde(default)]
+    pub failures: Vec<FailureCount>,
+    /// Time the rollup was last recomputed
+    pub updated_at: bson::DateTime,
+}
+
+/// Number of failed transactions with the same cause.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureCount {
+    /// Error category, see [`TransactionFailure`](super::failure::TransactionFailure)
+    pub category: String,
+    /// Program of the failed instruction, for instruction errors
+    pub program_id: Option<String>,
+    /// Program-specific error code, for `Custom` errors
+    pub code: Option<u32>,
+    /// Number of failed transactions
+    pub count: u64,
+}
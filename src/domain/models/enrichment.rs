@@ -0,0 +1,54 @@
+//! Enrichment model module.
+//!
+//! Human-readable interpretations of indexed transactions fetched from the
+//! Helius enhanced transactions API: the kind of action a transaction took,
+//! a one-line description and its token and SOL movements. They are stored
+//! next to the raw transactions, keyed by signature, and never replace them.
+
+use serde::{Deserialize, Serialize};
+
+/// Token movement reported by the enrichment source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrichedTokenTransfer {
+    /// Mint of the token
+    pub mint: String,
+    /// Wallet the tokens left, if known
+    pub from_user_account: Option<String>,
+    /// Wallet the tokens went to, if known
+    pub to_user_account: Option<String>,
+    /// Token account the tokens left, if known
+    pub from_token_account: Option<String>,
+    /// Token account the tokens went to, if known
+    pub to_token_account: Option<String>,
+    /// Amount in whole tokens, adjusted for the token's decimals
+    pub token_amount: f64,
+}
+
+/// SOL movement reported by the enrichment source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrichedNativeTransfer {
+    /// Account the lamports left, if known
+    pub from_user_account: Option<String>,
+    /// Account the lamports went to, if known
+    pub to_user_account: Option<String>,
+    /// Amount in lamports
+    pub amount: u64,
+}
+
+/// Human-readable interpretation of a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enrichment {
+    /// Signature of the enriched transaction
+    pub signature: String,
+    /// Kind of action, e.g. `SWAP` or `NFT_SALE`; `UNKNOWN` when unrecognized
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Program or marketplace the action went through, e.g. `JUPITER`
+    pub source: Option<String>,
+    /// One-line description of the action; empty when none is available
+    pub description: String,
+    /// Token movements
+    pub token_transfers: Vec<EnrichedTokenTransfer>,
+    /// SOL movements
+    pub native_transfers: Vec<EnrichedNativeTransfer>,
+}
@@ -0,0 +1,65 @@
+//! Transaction failure model module.
+//!
+//! `meta.err` is stored the way the RPC reports it, which is awkward to
+//! aggregate on. Failed transactions additionally carry a flat
+//! classification of the error that can be grouped and filtered on directly.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// Category of errors raised by a program with a program-specific code.
+pub const CUSTOM_CATEGORY: &str = "Custom";
+
+/// Classified cause of a failed transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionFailure {
+    /// Error name, e.g. `InsufficientFunds`, `BlockhashNotFound` or `Custom`.
+    /// Instruction errors are classified by the instruction's error.
+    pub category: String,
+    /// Index of the failed instruction, for instruction errors
+    pub instruction_index: Option<u8>,
+    /// Program of the failed instruction, for instruction errors
+    pub program_id: Option<String>,
+    /// Program-specific error code, for `Custom` errors
+    pub code: Option<u32>,
+}
+
+impl TransactionFailure {
+    /// Classifies a transaction error.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - Error the transaction failed with
+    /// * `program_ids` - Program ID of each top-level instruction, by index
+    pub fn classify(err: &TransactionError, program_ids: &[&str]) -> Self {
+        match err {
+            TransactionError::InstructionError(index, error) => Self {
+                category: match error {
+                    InstructionError::Custom(_) => CUSTOM_CATEGORY.to_string(),
+                    error => variant_name(error),
+                },
+                instruction_index: Some(*index),
+                program_id: program_ids.get(*index as usize).map(|id| id.to_string()),
+                code: match error {
+                    InstructionError::Custom(code) => Some(*code),
+                    _ => None,
+                },
+            },
+            err => Self {
+                category: variant_name(err),
+                instruction_index: None,
+                program_id: None,
+                code: None,
+            },
+        }
+    }
+}
+
+/// Returns the name of an enum variant, as serde serializes it.
+fn variant_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(serde_json::Value::Object(fields)) => fields.keys().next().cloned().unwrap_or_default(),
+        _ => "Unknown".to_string(),
+    }
+}
@@ -0,0 +1,47 @@
+//! Fee statistics model module.
+//!
+//! Summarizes a sample of priority fees into the percentiles clients use to
+//! pick a compute unit price.
+
+use serde::Serialize;
+
+/// Percentile summary of a set of fees.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeeStats {
+    /// Number of fees in the sample
+    pub samples: usize,
+    /// Lowest fee
+    pub min: u64,
+    /// 50th percentile
+    pub median: u64,
+    /// 75th percentile
+    pub p75: u64,
+    /// 90th percentile
+    pub p90: u64,
+    /// Highest fee
+    pub max: u64,
+}
+
+impl FeeStats {
+    /// Computes nearest-rank percentiles over `fees`.
+    ///
+    /// Returns `None` for an empty sample.
+    pub fn from_samples(mut fees: Vec<u64>) -> Option<Self> {
+        if fees.is_empty() {
+            return None;
+        }
+
+        fees.sort_unstable();
+
+        let percentile = |p: usize| fees[((fees.len() * p).div_ceil(100)).saturating_sub(1)];
+
+        Some(Self {
+            samples: fees.len(),
+            min: fees[0],
+            median: percentile(50),
+            p75: percentile(75),
+            p90: percentile(90),
+            max: fees[fees.len() - 1],
+        })
+    }
+}
@@ -0,0 +1,22 @@
+//! Address label model module.
+//!
+//! Labels attach human-readable names and tags to addresses (for example
+//! exchange hot wallets or an organization's treasury) and are included in
+//! API responses wherever those addresses appear.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+/// Human-readable label and tags for an address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    /// Labeled address
+    pub address: String,
+    /// Display name
+    pub label: String,
+    /// Free-form tags for grouping addresses
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Time the label was last changed
+    pub updated_at: bson::DateTime,
+}
@@ -0,0 +1,24 @@
+//! Leader statistics model module.
+//!
+//! Per-validator rollup of the indexed blocks each validator produced as slot
+//! leader, served by the `/stats/leaders` endpoint for validator performance
+//! analysis.
+
+use serde::{Deserialize, Serialize};
+
+/// Block production of one slot leader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderStats {
+    /// Validator identity
+    pub leader: String,
+    /// Number of indexed blocks the validator produced
+    pub block_count: u64,
+    /// Number of transactions in those blocks
+    pub transaction_count: u64,
+    /// Average number of transactions per block
+    pub average_transactions: f64,
+    /// Lowest slot produced
+    pub first_slot: u64,
+    /// Highest slot produced
+    pub last_slot: u64,
+}
@@ -1 +1,24 @@
+pub mod address_backfill;
+pub mod address_summary;
+pub mod alert;
+pub mod api_key;
+pub mod backfill;
+pub mod balance_change;
+pub mod block;
+pub mod counterparty;
+pub mod daily_stats;
+pub mod enrichment;
+pub mod failure;
+pub mod fee_stats;
+pub mod label;
+pub mod leader_stats;
+pub mod replay_job;
+pub mod reward;
+pub mod signature;
+pub mod sol_transfer;
+pub mod summary;
+pub mod supply_event;
+pub mod swap;
+pub mod token_account;
 pub mod transaction;
+pub mod transfer;
@@ -0,0 +1,55 @@
+//! Replay job model module.
+//!
+//! A replay deletes and reprocesses the stored data of a slot range in
+//! batches. The job records the first slot not replayed yet, so an
+//! interrupted replay, which leaves the slots of its last batch without data,
+//! is reported and can be resumed.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+/// Progress of the replay of a slot range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayJob {
+    /// First slot of the range
+    pub from_slot: u64,
+    /// Last slot of the range, inclusive
+    pub to_slot: u64,
+    /// First slot not replayed yet
+    pub next_slot: u64,
+    /// Days, at midnight UTC, of the replayed blocks, whose daily statistics
+    /// are recomputed once the range is replayed
+    #[serde(default)]
+    pub days: Vec<bson::DateTime>,
+    /// Time the replay started
+    pub started_at: bson::DateTime,
+    /// Time the whole range was replayed
+    pub completed_at: Option<bson::DateTime>,
+}
+
+impl ReplayJob {
+    /// Creates a pending job for a slot range.
+    pub fn new(from_slot: u64, to_slot: u64) -> Self {
+        Self {
+            from_slot,
+            to_slot,
+            next_slot: from_slot,
+            days: Vec::new(),
+            started_at: bson::DateTime::now(),
+            completed_at: None,
+        }
+    }
+
+    /// Records a replayed batch of slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_slot` - Last slot of the batch
+    /// * `days` - Days of the batch's blocks, at midnight UTC
+    pub fn advance(&mut self, last_slot: u64, days: impl IntoIterator<Item = bson::DateTime>) {
+        self.next_slot = last_slot.saturating_add(1);
+        self.days.extend(days);
+        self.days.sort_unstable();
+        self.days.dedup();
+    }
+}
@@ -0,0 +1,56 @@
+//! Block reward model module.
+//!
+//! Rewards credited in a block (transaction fees to the leader, rent, and
+//! staking and voting rewards at epoch boundaries), one record per account.
+
+use chrono::{DateTime, Utc};
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_transaction_status_client_types::UiConfirmedBlock;
+
+/// Reward types reported by the RPC, as stored in [`BlockReward::reward_type`].
+pub const REWARD_TYPES: [&str; 4] = ["fee", "rent", "staking", "voting"];
+
+/// A reward credited to (or debited from) an account in a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockReward {
+    /// Slot of the block the reward was credited in
+    pub slot: u64,
+    /// Account that received the reward
+    pub pubkey: String,
+    /// Reward amount in lamports; negative for rent collected
+    pub lamports: i64,
+    /// Account balance in lamports after the reward
+    pub post_balance: u64,
+    /// One of [`REWARD_TYPES`]
+    pub reward_type: Option<String>,
+    /// Vote account commission, for staking and voting rewards
+    pub commission: Option<u8>,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+}
+
+impl BlockReward {
+    /// Builds the reward records of a block fetched from the RPC.
+    pub fn from_block(block: &UiConfirmedBlock, slot: u64) -> Vec<Self> {
+        let block_time = block
+            .block_time
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+            .map(bson::DateTime::from_chrono);
+
+        block
+            .rewards
+            .iter()
+            .flatten()
+            .map(|reward| Self {
+                slot,
+                pubkey: reward.pubkey.clone(),
+                lamports: reward.lamports,
+                post_balance: reward.post_balance,
+                reward_type: reward.reward_type.map(|t| t.to_string()),
+                commission: reward.commission,
+                block_time,
+            })
+            .collect()
+    }
+}
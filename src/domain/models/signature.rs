@@ -0,0 +1,22 @@
+//! Signature record model module.
+//!
+//! In signatures-only indexing mode, blocks are fetched without transaction
+//! details and each transaction is stored as a compact record that is enough
+//! to check whether, when and how successfully a transaction landed.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_sdk::transaction::TransactionError;
+
+/// Compact record of an indexed transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureRecord {
+    /// Transaction signature
+    pub signature: String,
+    /// Slot of the block the transaction was included in
+    pub slot: u64,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+    /// Error the transaction failed with, if any
+    pub err: Option<TransactionError>,
+}
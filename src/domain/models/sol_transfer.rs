@@ -0,0 +1,64 @@
+//! SOL transfer model module.
+//!
+//! Native SOL transfers made through the System Program, one record per
+//! `Transfer` or `TransferWithSeed` instruction, including those invoked
+//! through cross-program invocations. Storing them apart from the raw
+//! instructions makes them queryable by address and amount.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use super::{transaction::Transaction, transfer::extract_transfers};
+
+/// A transfer of native SOL between two accounts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolTransfer {
+    /// Account the lamports were debited from
+    pub from: String,
+    /// Account the lamports were credited to
+    pub to: String,
+    /// Amount in lamports
+    pub lamports: u64,
+    /// Signature of the transaction
+    pub signature: String,
+    /// Position of the transfer among those of the transaction
+    #[serde(default)]
+    pub index: u32,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+}
+
+impl SolTransfer {
+    /// Extracts the SOL transfers of a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Stored transaction, with its slot and block time set
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Self>` - Transfers in instruction order; empty if the
+    ///   transaction failed, as its instructions had no effect
+    pub fn from_transaction(transaction: &Transaction) -> Vec<Self> {
+        if transaction.meta.err.is_some() {
+            return Vec::new();
+        }
+
+        extract_transfers(transaction)
+            .into_iter()
+            .filter(|transfer| transfer.mint.is_none())
+            .enumerate()
+            .map(|(index, transfer)| Self {
+                from: transfer.source,
+                to: transfer.destination,
+                lamports: transfer.amount,
+                signature: transaction.signature.clone(),
+                index: index as u32,
+                slot: transaction.slot,
+                block_time: transaction.block_time,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,280 @@
+//! Transaction summary model module.
+//!
+//! Summaries are the simplified view explorers display for a transaction:
+//! what kind of activity it is, who took part, which assets moved and which
+//! programs were invoked. They are derived from the decoded instruction data
+//! of a stored transaction.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    failure::TransactionFailure,
+    supply_event::{decode_supply_events, SupplyEventKind},
+    transaction::Transaction,
+    transfer::{extract_transfers, Transfer},
+};
+
+/// Names of well-known programs, by program ID.
+pub const KNOWN_PROGRAMS: [(&str, &str); 20] = [
+    ("11111111111111111111111111111111", "System Program"),
+    (
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        "Token Program",
+    ),
+    (
+        "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+        "Token-2022 Program",
+    ),
+    (
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+        "Associated Token Account Program",
+    ),
+    (
+        "ComputeBudget111111111111111111111111111111",
+        "Compute Budget Program",
+    ),
+    (
+        "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+        "Memo Program",
+    ),
+    (
+        "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo",
+        "Memo Program v1",
+    ),
+    (
+        "Vote111111111111111111111111111111111111111",
+        "Vote Program",
+    ),
+    (
+        "Stake11111111111111111111111111111111111111",
+        "Stake Program",
+    ),
+    (
+        "AddressLookupTab1e1111111111111111111111111",
+        "Address Lookup Table Program",
+    ),
+    (
+        "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+        "Metaplex Token Metadata",
+    ),
+    (
+        "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+        "Jupiter Aggregator v6",
+    ),
+    (
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        "Raydium AMM v4",
+    ),
+    (
+        "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+        "Raydium CLMM",
+    ),
+    (
+        "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C",
+        "Raydium CPMM",
+    ),
+    (
+        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+        "Orca Whirlpools",
+    ),
+    (
+        "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo",
+        "Meteora DLMM",
+    ),
+    ("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "Pump.fun"),
+    ("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", "Phoenix"),
+    ("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb", "OpenBook v2"),
+];
+
+/// Program IDs of DEXes and aggregators whose invocation marks a swap.
+pub const DEX_PROGRAM_IDS: [&str; 9] = [
+    "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+    "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+    "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C",
+    "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+    "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo",
+    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+    "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY",
+    "opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb",
+];
+
+/// Returns the name of a well-known program.
+pub fn program_name(program_id: &str) -> Option<&'static str> {
+    KNOWN_PROGRAMS
+        .iter()
+        .find(|(id, _)| *id == program_id)
+        .map(|(_, name)| *name)
+}
+
+/// Kind of activity a transaction performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    /// Invokes a DEX or swap aggregator
+    Swap,
+    /// Mints SPL tokens
+    Mint,
+    /// Moves SOL or SPL tokens
+    Transfer,
+    /// Anything else
+    Other,
+}
+
+/// Outcome of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Executed successfully
+    Success,
+    /// Failed; the fee was still charged
+    Failed,
+}
+
+/// SPL tokens minted by a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenMint {
+    /// Mint of the created tokens
+    pub mint: String,
+    /// Token account credited with the tokens
+    pub destination: String,
+    /// Amount in the token's smallest unit
+    pub amount: u64,
+    /// Decimals of the token
+    pub decimals: u8,
+}
+
+/// A program invoked by a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvokedProgram {
+    /// Program ID
+    pub id: String,
+    /// Display name, for well-known or labeled programs
+    pub name: Option<String>,
+}
+
+/// Simplified, display-oriented view of a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    /// Transaction signature
+    pub signature: String,
+    /// Kind of activity
+    pub kind: TransactionKind,
+    /// Outcome
+    pub status: TransactionStatus,
+    /// Classified cause of the failure, for failed transactions
+    pub failure: Option<TransactionFailure>,
+    /// Fee paid in lamports
+    pub fee: u64,
+    /// Account that paid the fee
+    pub fee_payer: Option<String>,
+    /// Signers and the accounts funds moved between, in order of appearance
+    pub participants: Vec<String>,
+    /// SOL and SPL token transfers
+    pub transfers: Vec<Transfer>,
+    /// SPL tokens minted
+    pub mints: Vec<TokenMint>,
+    /// Programs invoked, top-level first, in order of first invocation
+    pub programs: Vec<InvokedProgram>,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Timestamp of the block the transaction was included in
+    pub block_time: Option<bson::DateTime>,
+}
+
+impl TransactionSummary {
+    /// Summarizes a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Stored transaction
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - Summary, with names only for well-known programs
+    pub fn from_transaction(transaction: &Transaction) -> Self {
+        let account_keys = transaction.account_keys();
+
+        let mut programs: Vec<InvokedProgram> = Vec::new();
+        for ix in transaction.compiled_instructions() {
+            let Some(id) = account_keys.get(ix.program_id_index as usize) else {
+                continue;
+            };
+            if !programs.iter().any(|program| program.id == *id) {
+                programs.push(InvokedProgram {
+                    id: id.to_string(),
+                    name: program_name(id).map(str::to_string),
+                });
+            }
+        }
+
+        let transfers = extract_transfers(transaction);
+        let mints = extract_mints(transaction);
+
+        let signers = account_keys
+            .iter()
+            .take(transaction.message.header.num_required_signatures as usize)
+            .map(|key| key.to_string());
+        let moved = transfers
+            .iter()
+            .flat_map(|transfer| [transfer.source.clone(), transfer.destination.clone()])
+            .chain(mints.iter().map(|mint| mint.destination.clone()));
+
+        let mut participants: Vec<String> = Vec::new();
+        for account in signers.chain(moved) {
+            if !participants.contains(&account) {
+                participants.push(account);
+            }
+        }
+
+        let kind = if programs
+            .iter()
+            .any(|program| DEX_PROGRAM_IDS.contains(&program.id.as_str()))
+        {
+            TransactionKind::Swap
+        } else if !mints.is_empty() {
+            TransactionKind::Mint
+        } else if !transfers.is_empty() {
+            TransactionKind::Transfer
+        } else {
+            TransactionKind::Other
+        };
+
+        Self {
+            signature: transaction.signature.clone(),
+            kind,
+            status: match transaction.meta.err {
+                Some(_) => TransactionStatus::Failed,
+                None => TransactionStatus::Success,
+            },
+            failure: transaction.failure.clone(),
+            fee: transaction.meta.fee,
+            fee_payer: account_keys.first().map(|key| key.to_string()),
+            participants,
+            transfers,
+            mints,
+            programs,
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+        }
+    }
+}
+
+/// Extracts the SPL token mints of a transaction, including those made
+/// through cross-program invocations.
+///
+/// `MintTo` instructions whose destination has no token balance in the
+/// transaction metadata are skipped, as their decimals are unknown.
+pub fn extract_mints(transaction: &Transaction) -> Vec<TokenMint> {
+    decode_supply_events(transaction)
+        .into_iter()
+        .filter(|event| event.kind == SupplyEventKind::Mint)
+        .map(|event| TokenMint {
+            mint: event.mint,
+            destination: event.account,
+            amount: event.amount,
+            decimals: event.decimals,
+        })
+        .collect()
+}
@@ -0,0 +1,150 @@
+//! Supply event model module.
+//!
+//! Supply-changing events of SPL tokens: the `MintTo` and `Burn` instructions
+//! of the Token and Token-2022 programs, including those made through
+//! cross-program invocations. Recording them per mint lets token issuers
+//! audit how their supply changed over time.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_sdk::bs58;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+
+use super::{
+    transaction::Transaction,
+    transfer::{read_u64, TOKEN_PROGRAM_IDS},
+};
+
+/// Direction of a supply change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SupplyEventKind {
+    /// Tokens created
+    Mint,
+    /// Tokens destroyed
+    Burn,
+}
+
+/// Change of an SPL token's supply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplyEvent {
+    /// Mint of the token
+    pub mint: String,
+    /// Whether tokens were created or destroyed
+    pub kind: SupplyEventKind,
+    /// Token account credited with minted tokens, or debited with burned ones
+    pub account: String,
+    /// Mint authority of a mint, or owner or delegate of the burned account
+    pub authority: Option<String>,
+    /// Amount in the token's smallest unit
+    pub amount: u64,
+    /// Decimals of the token
+    pub decimals: u8,
+    /// Signature of the transaction
+    pub signature: String,
+    /// Position of the event among those of the transaction
+    #[serde(default)]
+    pub index: u32,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+}
+
+impl SupplyEvent {
+    /// Extracts the supply changes of a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Stored transaction, with its slot and block time set
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Self>` - Supply changes in instruction order; empty if the
+    ///   transaction failed, as its instructions had no effect
+    pub fn from_transaction(transaction: &Transaction) -> Vec<Self> {
+        if transaction.meta.err.is_some() {
+            return Vec::new();
+        }
+
+        decode_supply_events(transaction)
+    }
+}
+
+/// Decodes the `MintTo` and `Burn` instructions of a transaction, whether or
+/// not it succeeded.
+///
+/// Unchecked instructions whose token account has no token balance in the
+/// transaction metadata are skipped, as their decimals are unknown.
+pub(crate) fn decode_supply_events(transaction: &Transaction) -> Vec<SupplyEvent> {
+    let account_keys = transaction.account_keys();
+    let key = |index: Option<&u8>| {
+        index
+            .and_then(|index| account_keys.get(*index as usize))
+            .map(|key| key.to_string())
+    };
+    // Burned accounts may be closed in the same transaction, so their
+    // decimals fall back to the balances before it
+    let decimals = |index: Option<&u8>| {
+        let index = index?;
+        [
+            &transaction.meta.post_token_balances,
+            &transaction.meta.pre_token_balances,
+        ]
+        .into_iter()
+        .find_map(|balances| match balances {
+            OptionSerializer::Some(balances) => balances
+                .iter()
+                .find(|balance| balance.account_index == *index)
+                .map(|balance| balance.ui_token_amount.decimals),
+            _ => None,
+        })
+    };
+
+    let mut events = Vec::new();
+
+    for ix in transaction.compiled_instructions() {
+        let is_token_program = account_keys
+            .get(ix.program_id_index as usize)
+            .is_some_and(|program| TOKEN_PROGRAM_IDS.contains(program));
+        if !is_token_program {
+            continue;
+        }
+        let Ok(data) = bs58::decode(&ix.data).into_vec() else {
+            continue;
+        };
+
+        let (kind, mint, account, decimals) = match data.first() {
+            // MintTo { amount }: [mint, destination, authority]
+            Some(7) => (SupplyEventKind::Mint, 0, 1, decimals(ix.accounts.get(1))),
+            // MintToChecked { amount, decimals }: [mint, destination, authority]
+            Some(14) => (SupplyEventKind::Mint, 0, 1, data.get(9).copied()),
+            // Burn { amount }: [account, mint, authority]
+            Some(8) => (SupplyEventKind::Burn, 1, 0, decimals(ix.accounts.first())),
+            // BurnChecked { amount, decimals }: [account, mint, authority]
+            Some(15) => (SupplyEventKind::Burn, 1, 0, data.get(9).copied()),
+            _ => continue,
+        };
+
+        let event = key(ix.accounts.get(mint))
+            .zip(key(ix.accounts.get(account)))
+            .zip(read_u64(&data, 1))
+            .zip(decimals)
+            .map(|(((mint, account), amount), decimals)| SupplyEvent {
+                mint,
+                kind,
+                account,
+                authority: key(ix.accounts.get(2)),
+                amount,
+                decimals,
+                signature: transaction.signature.clone(),
+                index: events.len() as u32,
+                slot: transaction.slot,
+                block_time: transaction.block_time,
+            });
+
+        events.extend(event);
+    }
+
+    events
+}
@@ -0,0 +1,252 @@
+//! Swap model module.
+//!
+//! Normalized swap events of common DEXes and aggregators. A decoder per
+//! program recognizes its swap instructions by their discriminator; the
+//! amounts are then taken from the trader's net token and SOL balance changes,
+//! which is uniform across programs and includes any routing through other
+//! pools. A transaction yields at most one swap: when an aggregator routes
+//! through several pools, the swap is attributed to the outermost program.
+
+use std::collections::BTreeMap;
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+use solana_sdk::bs58;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+
+use super::{summary::program_name, transaction::Transaction};
+
+/// Mint of wrapped SOL, under which native SOL balance changes are reported.
+pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Decimals of SOL.
+const NATIVE_DECIMALS: u8 = 9;
+
+/// Anchor discriminators of the Jupiter v6 route instructions.
+const JUPITER_SWAPS: [[u8; 8]; 6] = [
+    // route
+    [229, 23, 203, 151, 122, 227, 173, 42],
+    // route_with_token_ledger
+    [150, 86, 71, 116, 167, 93, 14, 104],
+    // shared_accounts_route
+    [193, 32, 155, 51, 65, 214, 156, 129],
+    // shared_accounts_route_with_token_ledger
+    [230, 121, 143, 80, 119, 159, 106, 170],
+    // exact_out_route
+    [208, 51, 239, 151, 123, 43, 237, 92],
+    // shared_accounts_exact_out_route
+    [176, 209, 105, 168, 154, 125, 69, 62],
+];
+
+/// Anchor discriminators of the Orca Whirlpools swap instructions.
+const WHIRLPOOL_SWAPS: [[u8; 8]; 4] = [
+    // swap
+    [248, 198, 158, 145, 225, 117, 135, 200],
+    // swap_v2
+    [43, 4, 237, 11, 26, 201, 30, 98],
+    // two_hop_swap
+    [195, 96, 237, 108, 68, 162, 219, 230],
+    // two_hop_swap_v2
+    [186, 143, 209, 29, 254, 2, 194, 117],
+];
+
+/// Anchor discriminators of the Raydium CLMM swap instructions.
+const RAYDIUM_CLMM_SWAPS: [[u8; 8]; 2] = [
+    // swap
+    [248, 198, 158, 145, 225, 117, 135, 200],
+    // swap_v2
+    [43, 4, 237, 11, 26, 201, 30, 98],
+];
+
+/// Anchor discriminators of the Raydium CPMM swap instructions.
+const RAYDIUM_CPMM_SWAPS: [[u8; 8]; 2] = [
+    // swap_base_input
+    [143, 190, 90, 218, 196, 30, 51, 222],
+    // swap_base_output
+    [55, 217, 98, 86, 163, 74, 180, 173],
+];
+
+/// Instruction tags of the Raydium AMM v4 swaps: `SwapBaseIn`, `SwapBaseOut`,
+/// `SwapBaseInV2` and `SwapBaseOutV2`.
+const RAYDIUM_AMM_SWAPS: [u8; 4] = [9, 11, 16, 17];
+
+/// Recognizes the swap instructions of a DEX program.
+pub struct SwapDecoder {
+    /// Program ID of the DEX
+    pub program_id: &'static str,
+    /// Whether the instruction data is a swap
+    pub is_swap: fn(&[u8]) -> bool,
+}
+
+/// Checks whether instruction data starts with one of the Anchor
+/// discriminators.
+fn has_discriminator(data: &[u8], discriminators: &[[u8; 8]]) -> bool {
+    data.get(..8)
+        .is_some_and(|prefix| discriminators.iter().any(|d| d[..] == *prefix))
+}
+
+/// Swap decoders of the supported DEXes.
+pub const SWAP_DECODERS: [SwapDecoder; 5] = [
+    SwapDecoder {
+        program_id: "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
+        is_swap: |data| has_discriminator(data, &JUPITER_SWAPS),
+    },
+    SwapDecoder {
+        program_id: "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        is_swap: |data| {
+            data.first()
+                .is_some_and(|tag| RAYDIUM_AMM_SWAPS.contains(tag))
+        },
+    },
+    SwapDecoder {
+        program_id: "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+        is_swap: |data| has_discriminator(data, &RAYDIUM_CLMM_SWAPS),
+    },
+    SwapDecoder {
+        program_id: "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C",
+        is_swap: |data| has_discriminator(data, &RAYDIUM_CPMM_SWAPS),
+    },
+    SwapDecoder {
+        program_id: "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+        is_swap: |data| has_discriminator(data, &WHIRLPOOL_SWAPS),
+    },
+];
+
+/// A swap of one token for another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Swap {
+    /// Signature of the transaction
+    pub signature: String,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Production time of the block
+    pub block_time: Option<bson::DateTime>,
+    /// Program ID of the DEX or aggregator
+    pub program_id: String,
+    /// Name of the DEX or aggregator
+    pub dex: String,
+    /// Account that signed and paid for the swap
+    pub trader: String,
+    /// Mint of the token sold; [`NATIVE_MINT`] for SOL
+    pub input_mint: String,
+    /// Amount sold, in the token's smallest unit
+    pub input_amount: u64,
+    /// Decimals of the token sold
+    pub input_decimals: u8,
+    /// Mint of the token bought; [`NATIVE_MINT`] for SOL
+    pub output_mint: String,
+    /// Amount bought, in the token's smallest unit
+    pub output_amount: u64,
+    /// Decimals of the token bought
+    pub output_decimals: u8,
+}
+
+impl Swap {
+    /// Extracts the swap performed by a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Stored transaction, with its slot and block time set
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - The swap, or `None` if the transaction failed, invokes
+    ///   no supported swap instruction, or its trader didn't both sell and buy
+    ///   a token
+    pub fn from_transaction(transaction: &Transaction) -> Option<Self> {
+        if transaction.meta.err.is_some() {
+            return None;
+        }
+
+        let account_keys = transaction.account_keys();
+        let decoder = transaction
+            .compiled_instructions()
+            .into_iter()
+            .find_map(|ix| {
+                let program_id = account_keys.get(ix.program_id_index as usize)?;
+                let decoder = SWAP_DECODERS
+                    .iter()
+                    .find(|decoder| decoder.program_id == *program_id)?;
+                let data = bs58::decode(&ix.data).into_vec().ok()?;
+
+                (decoder.is_swap)(&data).then_some(decoder)
+            })?;
+
+        let trader = account_keys.first()?.to_string();
+        let mut deltas = token_deltas(transaction, &trader);
+
+        // SOL sold or bought, net of the fee; rent of accounts opened or
+        // closed along the way is ignored when tokens moved both ways
+        let sol_delta = transaction.meta.post_balances.first().copied().unwrap_or(0) as i128
+            - transaction.meta.pre_balances.first().copied().unwrap_or(0) as i128
+            + transaction.meta.fee as i128;
+        let tokens_both_ways = deltas.values().any(|(delta, _)| *delta < 0)
+            && deltas.values().any(|(delta, _)| *delta > 0);
+        if !tokens_both_ways || deltas.contains_key(NATIVE_MINT) {
+            deltas
+                .entry(NATIVE_MINT.to_string())
+                .or_insert((0, NATIVE_DECIMALS))
+                .0 += sol_delta;
+        }
+
+        let input = deltas
+            .iter()
+            .filter(|(_, (delta, _))| *delta < 0)
+            .min_by_key(|(_, (delta, _))| *delta)?;
+        let output = deltas
+            .iter()
+            .filter(|(_, (delta, _))| *delta > 0)
+            .max_by_key(|(_, (delta, _))| *delta)?;
+
+        Some(Self {
+            signature: transaction.signature.clone(),
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            program_id: decoder.program_id.to_string(),
+            dex: program_name(decoder.program_id)
+                .unwrap_or(decoder.program_id)
+                .to_string(),
+            trader,
+            input_mint: input.0.clone(),
+            input_amount: input.1 .0.unsigned_abs() as u64,
+            input_decimals: input.1 .1,
+            output_mint: output.0.clone(),
+            output_amount: output.1 .0 as u64,
+            output_decimals: output.1 .1,
+        })
+    }
+}
+
+/// Sums the token balance changes of the accounts an owner holds, by mint.
+///
+/// # Returns
+///
+/// * `BTreeMap<String, (i128, u8)>` - Net change and decimals of each mint
+fn token_deltas(transaction: &Transaction, owner: &str) -> BTreeMap<String, (i128, u8)> {
+    let mut deltas: BTreeMap<String, (i128, u8)> = BTreeMap::new();
+
+    for (balances, sign) in [
+        (&transaction.meta.pre_token_balances, -1),
+        (&transaction.meta.post_token_balances, 1),
+    ] {
+        let OptionSerializer::Some(balances) = balances else {
+            continue;
+        };
+
+        for balance in balances {
+            if !matches!(&balance.owner, OptionSerializer::Some(o) if o == owner) {
+                continue;
+            }
+            let Ok(amount) = balance.ui_token_amount.amount.parse::<u64>() else {
+                continue;
+            };
+
+            let entry = deltas
+                .entry(balance.mint.clone())
+                .or_insert((0, balance.ui_token_amount.decimals));
+            entry.0 += sign * amount as i128;
+        }
+    }
+
+    deltas
+}
@@ -0,0 +1,104 @@
+//! SPL token account model.
+//!
+//! Token accounts owned by the SPL Token program or Token-2022 share the same
+//! 165-byte base layout; Token-2022 accounts may append extensions after it,
+//! which are not decoded.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::transfer::TOKEN_PROGRAM_IDS;
+
+/// Length of the base token account layout.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// State of a token account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenAccountState {
+    /// Not yet initialized
+    Uninitialized,
+    /// Initialized and usable
+    Initialized,
+    /// Frozen by the mint's freeze authority
+    Frozen,
+}
+
+/// Decoded fields of an SPL token account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenAccount {
+    /// Token program owning the account
+    pub program: String,
+    /// Mint of the held token
+    pub mint: String,
+    /// Owner of the held tokens
+    pub owner: String,
+    /// Balance in the token's smallest unit
+    pub amount: u64,
+    /// Account allowed to transfer up to `delegated_amount`, if any
+    pub delegate: Option<String>,
+    /// Amount the delegate may transfer
+    pub delegated_amount: u64,
+    /// Account state
+    pub state: TokenAccountState,
+    /// Rent-exempt reserve in lamports, for wrapped SOL accounts
+    pub is_native: Option<u64>,
+    /// Account allowed to close the token account, if any
+    pub close_authority: Option<String>,
+}
+
+impl TokenAccount {
+    /// Decodes a token account.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - Owner of the account
+    /// * `data` - Account data, at least the base layout
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Self>` - Decoded account, or `None` if the account isn't
+    ///   owned by a token program or isn't a token account (e.g. a mint)
+    pub fn decode(program: &Pubkey, data: &[u8]) -> Option<Self> {
+        let program = program.to_string();
+        if !TOKEN_PROGRAM_IDS.contains(&program.as_str()) || data.len() < TOKEN_ACCOUNT_LEN {
+            return None;
+        }
+
+        let state = match data[108] {
+            0 => TokenAccountState::Uninitialized,
+            1 => TokenAccountState::Initialized,
+            2 => TokenAccountState::Frozen,
+            _ => return None,
+        };
+
+        Some(Self {
+            program,
+            mint: pubkey(&data[0..32]),
+            owner: pubkey(&data[32..64]),
+            amount: u64_le(&data[64..72]),
+            delegate: option(&data[72..108]).map(pubkey),
+            state,
+            is_native: option(&data[109..121]).map(u64_le),
+            delegated_amount: u64_le(&data[121..129]),
+            close_authority: option(&data[129..165]).map(pubkey),
+        })
+    }
+}
+
+/// Decodes a `COption`: a 4-byte little-endian tag followed by the value.
+fn option(data: &[u8]) -> Option<&[u8]> {
+    (data[..4] == [1, 0, 0, 0]).then(|| &data[4..])
+}
+
+/// Decodes a 32-byte public key.
+fn pubkey(data: &[u8]) -> String {
+    Pubkey::try_from(data)
+        .expect("Public keys are 32 bytes")
+        .to_string()
+}
+
+/// Decodes a little-endian `u64`.
+fn u64_le(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data.try_into().expect("u64 fields are 8 bytes"))
+}
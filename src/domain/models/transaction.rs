@@ -5,11 +5,20 @@
 //! Solana's encoded transaction format to our internal representation.
 
 use eyre::{bail, OptionExt};
-use mongodb::bson;
+use mongodb::bson::{self, spec::BinarySubtype, Binary};
 use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    bs58,
+    message::{MessageHeader, VersionedMessage},
+};
+
+use super::failure::TransactionFailure;
+use crate::domain::migrations::TRANSACTION_SCHEMA_VERSION;
 use solana_transaction_status_client_types::{
-    EncodedTransaction, EncodedTransactionWithStatusMeta, UiMessage, UiRawMessage,
-    UiTransactionStatusMeta,
+    option_serializer::OptionSerializer, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    ParsedAccount, ParsedAccountSource, UiCompiledInstruction, UiInnerInstructions, UiInstruction,
+    UiLoadedAddresses, UiMessage, UiParsedInstruction, UiParsedMessage,
+    UiPartiallyDecodedInstruction, UiRawMessage, UiTransactionStatusMeta,
 };
 
 /// Represents a Solana transaction in our database.
@@ -26,6 +35,282 @@ pub struct Transaction {
     pub meta: UiTransactionStatusMeta,
     /// Timestamp when the transaction was included in a block
     pub block_time: Option<bson::DateTime>,
+    /// Slot of the block the transaction was included in
+    #[serde(default)]
+    pub slot: Option<u64>,
+    /// Text of the SPL Memo instructions in the transaction
+    #[serde(default)]
+    pub memos: Vec<String>,
+    /// Priority fee in micro-lamports per compute unit, if one was set
+    #[serde(default)]
+    pub compute_unit_price: Option<u64>,
+    /// All accounts the transaction references, including those loaded from
+    /// address lookup tables
+    #[serde(default)]
+    pub resolved_account_keys: Vec<String>,
+    /// Cross-program invocations made by the transaction, with resolved
+    /// program IDs and accounts
+    #[serde(default)]
+    pub inner_instructions: Vec<InnerInstruction>,
+    /// Classified cause of the failure, for failed transactions
+    #[serde(default)]
+    pub failure: Option<TransactionFailure>,
+    /// Top-level instructions the RPC decoded, for transactions fetched with
+    /// the `jsonParsed` encoding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsed_instructions: Vec<DecodedInstruction>,
+    /// zstd-compressed log messages and inner instructions of `meta`, for
+    /// transactions stored with compression; see [`Transaction::compress`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed_meta: Option<Binary>,
+    /// Version of the layout the document was stored with; outdated documents
+    /// are upgraded by [`crate::domain::migrations`]
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+/// Bulky fields of the transaction metadata, moved into
+/// [`Transaction::compressed_meta`] when compressed.
+#[derive(Serialize, Deserialize)]
+struct BulkyMeta {
+    log_messages: Option<Vec<String>>,
+    inner_instructions: Option<Vec<UiInnerInstructions>>,
+}
+
+/// A cross-program invocation made while executing a transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InnerInstruction {
+    /// Index of the top-level instruction that made the invocation
+    pub index: u8,
+    /// Program that was invoked
+    pub program_id: String,
+    /// Accounts passed to the program
+    pub accounts: Vec<String>,
+    /// Base58-encoded instruction data
+    pub data: String,
+    /// Invocation depth, starting at 1 for top-level instructions
+    pub stack_height: Option<u32>,
+}
+
+/// A top-level instruction decoded by the RPC.
+///
+/// The RPC replaces the data and accounts of instructions it can decode, so
+/// their entries in the stored message have neither; the decoded form is kept
+/// here instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedInstruction {
+    /// Index of the instruction in the message
+    pub index: u8,
+    /// Name of the program, e.g. `system` or `spl-token`
+    pub program: String,
+    /// Program that was invoked
+    pub program_id: String,
+    /// Decoded instruction, as returned by the RPC
+    pub parsed: serde_json::Value,
+}
+
+impl Transaction {
+    /// Returns all account keys referenced by the transaction.
+    ///
+    /// For versioned transactions this includes the addresses loaded from
+    /// lookup tables, in the order instruction account indexes refer to them:
+    /// static keys, then writable and finally readonly loaded addresses.
+    pub fn account_keys(&self) -> Vec<&str> {
+        if !self.resolved_account_keys.is_empty() {
+            return self
+                .resolved_account_keys
+                .iter()
+                .map(String::as_str)
+                .collect();
+        }
+
+        let mut keys: Vec<&str> = self
+            .message
+            .account_keys
+            .iter()
+            .map(String::as_str)
+            .collect();
+
+        if let OptionSerializer::Some(loaded) = &self.meta.loaded_addresses {
+            keys.extend(loaded.writable.iter().map(String::as_str));
+            keys.extend(loaded.readonly.iter().map(String::as_str));
+        }
+
+        keys
+    }
+
+    /// Classifies the error of a failed transaction.
+    pub fn classify_failure(&self) -> Option<TransactionFailure> {
+        let err = self.meta.err.as_ref()?;
+
+        let account_keys = self.account_keys();
+        let program_ids: Vec<&str> = self
+            .message
+            .instructions
+            .iter()
+            .map(|ix| {
+                account_keys
+                    .get(ix.program_id_index as usize)
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        Some(TransactionFailure::classify(err, &program_ids))
+    }
+
+    /// Builds the structured inner instructions of the transaction, resolving
+    /// account indexes against [`Self::account_keys`].
+    ///
+    /// Instructions referring to accounts outside the key list are skipped.
+    pub fn normalize_inner_instructions(&self) -> Vec<InnerInstruction> {
+        let OptionSerializer::Some(inner) = &self.meta.inner_instructions else {
+            return vec![];
+        };
+
+        let account_keys = self.account_keys();
+        let key = |index: u8| account_keys.get(index as usize).map(|key| key.to_string());
+
+        inner
+            .iter()
+            .flat_map(|inner| {
+                inner.instructions.iter().filter_map(move |ix| match ix {
+                    UiInstruction::Compiled(ix) => Some((inner.index, ix)),
+                    UiInstruction::Parsed(_) => None,
+                })
+            })
+            .filter_map(|(index, ix)| {
+                Some(InnerInstruction {
+                    index,
+                    program_id: key(ix.program_id_index)?,
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|account| key(*account))
+                        .collect::<Option<_>>()?,
+                    data: ix.data.clone(),
+                    stack_height: ix.stack_height,
+                })
+            })
+            .collect()
+    }
+
+    /// Moves the log messages and inner instructions of the metadata into
+    /// [`Self::compressed_meta`], compressed with zstd at the given level.
+    ///
+    /// Must be called after everything derived from those fields has been
+    /// extracted; [`Self::decompress`] restores them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fields cannot be encoded or compressed
+    pub fn compress(&mut self, level: i32) -> eyre::Result<()> {
+        if self.compressed_meta.is_some() {
+            return Ok(());
+        }
+
+        let bulky = BulkyMeta {
+            log_messages: std::mem::replace(&mut self.meta.log_messages, OptionSerializer::Skip)
+                .into(),
+            inner_instructions: std::mem::replace(
+                &mut self.meta.inner_instructions,
+                OptionSerializer::Skip,
+            )
+            .into(),
+        };
+        let bytes = zstd::encode_all(serde_json::to_vec(&bulky)?.as_slice(), level)?;
+
+        self.compressed_meta = Some(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes,
+        });
+
+        Ok(())
+    }
+
+    /// Restores the metadata fields moved by [`Self::compress`]. Transactions
+    /// stored without compression are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the compressed fields are corrupt
+    pub fn decompress(&mut self) -> eyre::Result<()> {
+        let Some(compressed) = self.compressed_meta.take() else {
+            return Ok(());
+        };
+
+        let bulky: BulkyMeta =
+            serde_json::from_slice(&zstd::decode_all(compressed.bytes.as_slice())?)?;
+        self.meta.log_messages = bulky.log_messages.into();
+        self.meta.inner_instructions = bulky.inner_instructions.into();
+
+        Ok(())
+    }
+
+    /// Returns the top-level instructions followed by all compiled inner
+    /// (cross-program invocation) instructions.
+    pub fn compiled_instructions(&self) -> Vec<&UiCompiledInstruction> {
+        let mut instructions: Vec<&UiCompiledInstruction> =
+            self.message.instructions.iter().collect();
+
+        if let OptionSerializer::Some(inner) = &self.meta.inner_instructions {
+            instructions.extend(inner.iter().flat_map(|inner| {
+                inner.instructions.iter().filter_map(|ix| match ix {
+                    UiInstruction::Compiled(ix) => Some(ix),
+                    UiInstruction::Parsed(_) => None,
+                })
+            }));
+        }
+
+        instructions
+    }
+}
+
+/// Program IDs of the SPL Memo program (v2 and the legacy v1).
+pub const MEMO_PROGRAM_IDS: [&str; 2] = [
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+    "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo",
+];
+
+/// Program ID of the Compute Budget program.
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// Extracts the compute unit price set by a `SetComputeUnitPrice` instruction.
+pub fn extract_compute_unit_price(message: &UiRawMessage) -> Option<u64> {
+    message
+        .instructions
+        .iter()
+        .filter(|ix| {
+            message
+                .account_keys
+                .get(ix.program_id_index as usize)
+                .is_some_and(|program| program == COMPUTE_BUDGET_PROGRAM_ID)
+        })
+        .filter_map(|ix| bs58::decode(&ix.data).into_vec().ok())
+        .find_map(|data| match data.split_first() {
+            // SetComputeUnitPrice { micro_lamports: u64 }
+            Some((3, price)) => price.try_into().ok().map(u64::from_le_bytes),
+            _ => None,
+        })
+}
+
+/// Extracts the text of all SPL Memo instructions in a message.
+///
+/// Memo instruction data is the raw memo bytes; invalid UTF-8 sequences are
+/// replaced rather than dropping the memo.
+pub fn extract_memos(message: &UiRawMessage) -> Vec<String> {
+    message
+        .instructions
+        .iter()
+        .filter(|ix| {
+            message
+                .account_keys
+                .get(ix.program_id_index as usize)
+                .is_some_and(|program| MEMO_PROGRAM_IDS.contains(&program.as_str()))
+        })
+        .filter_map(|ix| bs58::decode(&ix.data).into_vec().ok())
+        .map(|data| String::from_utf8_lossy(&data).into_owned())
+        .collect()
 }
 
 impl TryFrom<EncodedTransactionWithStatusMeta> for Transaction {
@@ -45,26 +330,200 @@ impl TryFrom<EncodedTransactionWithStatusMeta> for Transaction {
     ///
     /// Returns an error if:
     /// * Transaction metadata is missing
-    /// * Transaction encoding is not JSON
-    /// * Message encoding is not Raw format
+    /// * Transaction encoding is not JSON, base58 or base64
+    /// * A parsed message refers to an account missing from its account keys
+    /// * A binary transaction cannot be decoded
     fn try_from(encoded: EncodedTransactionWithStatusMeta) -> eyre::Result<Self> {
-        let meta = encoded.meta.ok_or_eyre("Transaction meta is missing")?;
+        let mut meta = encoded.meta.ok_or_eyre("Transaction meta is missing")?;
+        let mut parsed_instructions = vec![];
 
-        let transaction_data = match encoded.transaction {
-            EncodedTransaction::Json(tx) => tx,
+        let (signature, message) = match encoded.transaction {
+            EncodedTransaction::Json(tx) => {
+                let message = match tx.message {
+                    UiMessage::Raw(raw) => raw,
+                    UiMessage::Parsed(parsed) => {
+                        let (raw, decoded) = raw_from_parsed(parsed, &mut meta)?;
+                        parsed_instructions = decoded;
+                        raw
+                    }
+                };
+                (tx.signatures[0].clone(), message)
+            }
+            binary @ (EncodedTransaction::Binary(..) | EncodedTransaction::LegacyBinary(_)) => {
+                let tx = binary.decode().ok_or_eyre("Invalid binary transaction")?;
+                let signature = tx
+                    .signatures
+                    .first()
+                    .ok_or_eyre("Transaction has no signatures")?
+                    .to_string();
+                (signature, raw_message(&tx.message))
+            }
             _ => bail!("Unsupported transaction encoding"),
         };
 
-        let message = match transaction_data.message {
-            UiMessage::Raw(raw) => raw,
-            _ => bail!("Unsupported message encoding"),
-        };
-
         Ok(Self {
-            signature: transaction_data.signatures[0].clone(),
+            signature,
+            memos: extract_memos(&message),
+            compute_unit_price: extract_compute_unit_price(&message),
             message,
             meta,
             block_time: None,
+            slot: None,
+            resolved_account_keys: vec![],
+            inner_instructions: vec![],
+            failure: None,
+            parsed_instructions,
+            compressed_meta: None,
+            schema_version: TRANSACTION_SCHEMA_VERSION,
         })
     }
 }
+
+/// Builds the message of a decoded binary transaction in the form the RPC
+/// returns for JSON-encoded transactions.
+fn raw_message(message: &VersionedMessage) -> UiRawMessage {
+    UiRawMessage {
+        header: *message.header(),
+        account_keys: message
+            .static_account_keys()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        recent_blockhash: message.recent_blockhash().to_string(),
+        instructions: message
+            .instructions()
+            .iter()
+            .map(|ix| UiCompiledInstruction::from(ix, None))
+            .collect(),
+        address_table_lookups: message
+            .address_table_lookups()
+            .map(|lookups| lookups.iter().map(Into::into).collect()),
+    }
+}
+
+/// Builds the raw form of a message fetched with the `jsonParsed` encoding.
+///
+/// Parsed messages list every account with its signer and writable flags,
+/// including those loaded from lookup tables, so the header and the
+/// transaction's loaded addresses are derived from them. Partially decoded
+/// instructions, top-level and inner, are compiled against the account list.
+/// Instructions the RPC fully decoded are returned separately; their message
+/// entries keep only the program.
+///
+/// # Arguments
+///
+/// * `message` - Parsed message
+/// * `meta` - Metadata of the transaction, updated with its loaded addresses
+///   and compiled inner instructions
+///
+/// # Errors
+///
+/// Returns an error if an instruction refers to an account missing from the
+/// account keys
+fn raw_from_parsed(
+    message: UiParsedMessage,
+    meta: &mut UiTransactionStatusMeta,
+) -> eyre::Result<(UiRawMessage, Vec<DecodedInstruction>)> {
+    let keys: Vec<&str> = message
+        .account_keys
+        .iter()
+        .map(|account| account.pubkey.as_str())
+        .collect();
+    let index = |key: &str| -> eyre::Result<u8> {
+        keys.iter()
+            .position(|k| *k == key)
+            .map(|i| i as u8)
+            .ok_or_else(|| eyre::eyre!("Account {} missing from account keys", key))
+    };
+    let compile = |ix: &UiPartiallyDecodedInstruction| -> eyre::Result<UiCompiledInstruction> {
+        Ok(UiCompiledInstruction {
+            program_id_index: index(&ix.program_id)?,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|account| index(account))
+                .collect::<eyre::Result<_>>()?,
+            data: ix.data.clone(),
+            stack_height: ix.stack_height,
+        })
+    };
+
+    let (loaded, static_keys): (Vec<&ParsedAccount>, Vec<&ParsedAccount>) = message
+        .account_keys
+        .iter()
+        .partition(|account| account.source == Some(ParsedAccountSource::LookupTable));
+
+    let mut header = MessageHeader::default();
+    for account in &static_keys {
+        match (account.signer, account.writable) {
+            (true, writable) => {
+                header.num_required_signatures += 1;
+                header.num_readonly_signed_accounts += !writable as u8;
+            }
+            (false, false) => header.num_readonly_unsigned_accounts += 1,
+            (false, true) => {}
+        }
+    }
+
+    if !loaded.is_empty() {
+        let (writable, readonly): (Vec<&ParsedAccount>, Vec<&ParsedAccount>) =
+            loaded.into_iter().partition(|account| account.writable);
+        let pubkeys = |accounts: Vec<&ParsedAccount>| {
+            accounts
+                .into_iter()
+                .map(|account| account.pubkey.clone())
+                .collect()
+        };
+        meta.loaded_addresses = OptionSerializer::Some(UiLoadedAddresses {
+            writable: pubkeys(writable),
+            readonly: pubkeys(readonly),
+        });
+    }
+
+    let mut instructions = Vec::with_capacity(message.instructions.len());
+    let mut decoded = vec![];
+    for (i, ix) in message.instructions.iter().enumerate() {
+        instructions.push(match ix {
+            UiInstruction::Compiled(ix) => ix.clone(),
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => compile(ix)?,
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(ix)) => {
+                decoded.push(DecodedInstruction {
+                    index: i as u8,
+                    program: ix.program.clone(),
+                    program_id: ix.program_id.clone(),
+                    parsed: ix.parsed.clone(),
+                });
+                UiCompiledInstruction {
+                    program_id_index: index(&ix.program_id)?,
+                    accounts: vec![],
+                    data: String::new(),
+                    stack_height: ix.stack_height,
+                }
+            }
+        });
+    }
+
+    if let OptionSerializer::Some(inner) = &mut meta.inner_instructions {
+        for ix in inner
+            .iter_mut()
+            .flat_map(|inner| inner.instructions.iter_mut())
+        {
+            if let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) = ix {
+                *ix = UiInstruction::Compiled(compile(partial)?);
+            }
+        }
+    }
+
+    let raw = UiRawMessage {
+        header,
+        account_keys: static_keys
+            .into_iter()
+            .map(|account| account.pubkey.clone())
+            .collect(),
+        recent_blockhash: message.recent_blockhash,
+        instructions,
+        address_table_lookups: message.address_table_lookups,
+    };
+
+    Ok((raw, decoded))
+}
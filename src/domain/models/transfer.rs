@@ -0,0 +1,161 @@
+//! Transfer model module for SOL and SPL token transfers.
+//!
+//! Transfers are decoded from the System Program and SPL Token program
+//! instructions of a transaction, including instructions invoked through
+//! cross-program invocations.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::bs58;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+
+use super::transaction::Transaction;
+
+/// Program ID of the System Program.
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+/// Program IDs of the SPL Token program and Token-2022.
+pub const TOKEN_PROGRAM_IDS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+];
+
+/// Number of decimals of native SOL.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// A single transfer of SOL or an SPL token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+    /// Mint of the transferred token, `None` for native SOL
+    pub mint: Option<String>,
+    /// Account the funds were debited from
+    pub source: String,
+    /// Account the funds were credited to
+    pub destination: String,
+    /// Amount in the smallest unit (lamports for SOL)
+    pub amount: u64,
+    /// Decimals of the transferred asset
+    pub decimals: u8,
+    /// Approximate value in USD at block time, if a price was available
+    #[serde(default)]
+    pub usd_value: Option<f64>,
+}
+
+impl Transfer {
+    /// Returns the amount in whole units (SOL or UI token amount).
+    pub fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// Reads a little-endian `u64` at `offset`.
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("Infallible")))
+}
+
+/// Extracts all SOL and SPL token transfers from a transaction.
+///
+/// Token transfers whose mint can't be determined from the transaction's
+/// token balances are skipped.
+pub fn extract_transfers(transaction: &Transaction) -> Vec<Transfer> {
+    let account_keys = transaction.account_keys();
+    let key = |index: Option<&u8>| {
+        index
+            .and_then(|index| account_keys.get(*index as usize))
+            .map(|key| key.to_string())
+    };
+
+    let token_balances = match (
+        &transaction.meta.pre_token_balances,
+        &transaction.meta.post_token_balances,
+    ) {
+        (OptionSerializer::Some(pre), OptionSerializer::Some(post)) => {
+            pre.iter().chain(post.iter()).collect()
+        }
+        (OptionSerializer::Some(balances), _) | (_, OptionSerializer::Some(balances)) => {
+            balances.iter().collect()
+        }
+        _ => Vec::new(),
+    };
+    let token_info = |index: Option<&u8>| {
+        index.and_then(|index| {
+            token_balances
+                .iter()
+                .find(|balance| balance.account_index == *index)
+                .map(|balance| (balance.mint.clone(), balance.ui_token_amount.decimals))
+        })
+    };
+
+    let mut transfers = Vec::new();
+
+    for ix in transaction.compiled_instructions() {
+        let Some(program) = account_keys.get(ix.program_id_index as usize) else {
+            continue;
+        };
+        let Ok(data) = bs58::decode(&ix.data).into_vec() else {
+            continue;
+        };
+
+        let sol_transfer = |source: Option<&u8>, destination: Option<&u8>| {
+            key(source)
+                .zip(key(destination))
+                .zip(read_u64(&data, 4))
+                .map(|((source, destination), amount)| Transfer {
+                    mint: None,
+                    source,
+                    destination,
+                    amount,
+                    decimals: SOL_DECIMALS,
+                    usd_value: None,
+                })
+        };
+
+        let transfer = if *program == SYSTEM_PROGRAM_ID {
+            match data
+                .get(..4)
+                .map(|tag| u32::from_le_bytes(tag.try_into().expect("Infallible")))
+            {
+                // Transfer { lamports }: [source, destination]
+                Some(2) => sol_transfer(ix.accounts.first(), ix.accounts.get(1)),
+                // TransferWithSeed { lamports, .. }: [source, base, destination]
+                Some(11) => sol_transfer(ix.accounts.first(), ix.accounts.get(2)),
+                _ => None,
+            }
+        } else if TOKEN_PROGRAM_IDS.contains(program) {
+            let (destination, mint) = match data.first() {
+                // Transfer { amount }: [source, destination, authority]
+                Some(3) => (
+                    ix.accounts.get(1),
+                    token_info(ix.accounts.first()).or(token_info(ix.accounts.get(1))),
+                ),
+                // TransferChecked { amount, decimals }: [source, mint, destination, authority]
+                Some(12) => (
+                    ix.accounts.get(2),
+                    key(ix.accounts.get(1)).zip(data.get(9).copied()),
+                ),
+                _ => (None, None),
+            };
+
+            key(ix.accounts.first())
+                .zip(key(destination))
+                .zip(read_u64(&data, 1))
+                .zip(mint)
+                .map(
+                    |(((source, destination), amount), (mint, decimals))| Transfer {
+                        mint: Some(mint),
+                        source,
+                        destination,
+                        amount,
+                        decimals,
+                        usd_value: None,
+                    },
+                )
+        } else {
+            None
+        };
+
+        transfers.extend(transfer);
+    }
+
+    transfers
+}
@@ -4,35 +4,732 @@
 //! managing transaction storage and retrieval operations. It uses MongoDB as the backend
 //! and provides an abstraction layer for database operations.
 
-use std::{env, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use chrono::{DateTime, Days, Utc};
 use eyre::Context;
+use futures::{stream, AsyncReadExt, AsyncWriteExt, StreamExt, TryStreamExt};
+use log::{info, warn};
 use mongodb::{
-    bson::{doc, Document},
-    options::FindOptions,
-    results::InsertOneResult,
-    Client, Collection,
+    bson::{self, doc, Bson, Document},
+    change_stream::{event::ChangeStreamEvent, event::ResumeToken, ChangeStream},
+    error::{ErrorKind, InsertManyError, WriteFailure},
+    gridfs::GridFsBucket,
+    options::{
+        Acknowledgment, ClientOptions, CountOptions, DatabaseOptions,
+        EstimatedDocumentCountOptions, FindOptions, GridFsBucketOptions, IndexOptions, ReadConcern,
+        ReadPreference, SelectionCriteria, WriteConcern,
+    },
+    Client, Collection, Cursor, Database, IndexModel,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiConfirmedBlock,
+};
+
+use super::metrics::StorageMetrics;
+use super::migrations;
+use super::models::{
+    address_backfill::AddressBackfill,
+    address_summary::{AddressActivity, AddressSummary, CounterpartyLink},
+    alert::Alert,
+    api_key::ApiKey,
+    backfill::BackfillJob,
+    balance_change::BalanceChange,
+    block::Block,
+    counterparty::Counterparty,
+    daily_stats::DailyStats,
+    enrichment::Enrichment,
+    label::AddressLabel,
+    leader_stats::LeaderStats,
+    replay_job::ReplayJob,
+    reward::BlockReward,
+    signature::SignatureRecord,
+    sol_transfer::SolTransfer,
+    supply_event::{SupplyEvent, SupplyEventKind},
+    swap::Swap,
+    transaction::Transaction,
 };
+use crate::config::Filters;
+
+/// Number of distinct failure causes kept in each daily rollup.
+const MAX_FAILURE_CAUSES: i64 = 20;
+
+/// Server error code of writes rejected for a duplicate key.
+const DUPLICATE_KEY_ERROR: i32 = 11000;
+
+/// GridFS bucket holding the JSON of fetched blocks.
+pub const RAW_BLOCKS_BUCKET: &str = "raw_blocks";
+
+/// zstd level at which raw blocks are compressed.
+const RAW_BLOCK_COMPRESSION_LEVEL: i32 = 3;
+
+/// Name of the unique `(signature, index)` index of derived records.
+const RECORD_INDEX_NAME: &str = "signature_1_index_1";
+
+/// Number of transactions whose records are numbered concurrently.
+const RECORD_NUMBERING_CONCURRENCY: usize = 32;
+
+/// Number of address summaries updated concurrently.
+const ADDRESS_SUMMARY_WRITE_CONCURRENCY: usize = 32;
+
+/// Replica set members API queries are read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueryReadPreference {
+    /// The primary only
+    #[default]
+    Primary,
+    /// The primary, or a secondary while it's unavailable
+    PrimaryPreferred,
+    /// Secondaries only
+    Secondary,
+    /// Secondaries, or the primary while none is available
+    SecondaryPreferred,
+    /// The member with the lowest latency
+    Nearest,
+}
+
+impl From<QueryReadPreference> for SelectionCriteria {
+    fn from(preference: QueryReadPreference) -> Self {
+        let options = None;
+        SelectionCriteria::ReadPreference(match preference {
+            QueryReadPreference::Primary => ReadPreference::Primary,
+            QueryReadPreference::PrimaryPreferred => ReadPreference::PrimaryPreferred { options },
+            QueryReadPreference::Secondary => ReadPreference::Secondary { options },
+            QueryReadPreference::SecondaryPreferred => {
+                ReadPreference::SecondaryPreferred { options }
+            }
+            QueryReadPreference::Nearest => ReadPreference::Nearest { options },
+        })
+    }
+}
+
+/// Consistency of the data API queries return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueryReadConcern {
+    /// The most recent data of the member queried
+    Local,
+    /// Like `local`, without waiting on sharded cluster metadata
+    Available,
+    /// Data acknowledged by a majority of the replica set
+    Majority,
+}
+
+impl From<QueryReadConcern> for ReadConcern {
+    fn from(concern: QueryReadConcern) -> Self {
+        match concern {
+            QueryReadConcern::Local => ReadConcern::local(),
+            QueryReadConcern::Available => ReadConcern::available(),
+            QueryReadConcern::Majority => ReadConcern::majority(),
+        }
+    }
+}
+
+/// Connection pool and timeout settings of the MongoDB client.
+///
+/// Settings given in the connection string take precedence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionOptions {
+    /// Largest number of connections per server
+    pub max_pool_size: u32,
+    /// Number of connections per server kept open while idle
+    pub min_pool_size: u32,
+    /// Longest time an operation waits for a suitable server
+    pub server_selection_timeout: Duration,
+    /// Longest time establishing a connection may take
+    pub connect_timeout: Duration,
+    /// Time after which an idle connection is closed
+    pub max_idle_time: Duration,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            max_pool_size: 50,
+            min_pool_size: 5,
+            server_selection_timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(5),
+            max_idle_time: Duration::from_secs(300),
+        }
+    }
+}
+
+impl ConnectionOptions {
+    /// Fills in the settings the connection string left unset.
+    pub fn apply(&self, options: &mut ClientOptions) {
+        options.max_pool_size.get_or_insert(self.max_pool_size);
+        options.min_pool_size.get_or_insert(self.min_pool_size);
+        options
+            .server_selection_timeout
+            .get_or_insert(self.server_selection_timeout);
+        options.connect_timeout.get_or_insert(self.connect_timeout);
+        options.max_idle_time.get_or_insert(self.max_idle_time);
+    }
+}
+
+/// Settings applied when reading and writing documents.
+#[derive(Debug, Default, Clone)]
+pub struct StorageOptions {
+    /// zstd level at which the log messages and inner instructions of stored
+    /// transactions are compressed; they are stored uncompressed when unset
+    pub compression_level: Option<i32>,
+    /// Whether the log messages of stored transactions are dropped
+    pub drop_log_messages: bool,
+    /// Members API queries are read from. Everything else, including the
+    /// indexer's own reads, goes to the primary
+    pub read_preference: QueryReadPreference,
+    /// Read concern of API queries; the server default when unset
+    pub read_concern: Option<QueryReadConcern>,
+    /// Longest time the server may spend on an API query; unbounded when unset
+    pub query_timeout: Option<Duration>,
+    /// Acknowledgment required for writes; the server default when unset
+    pub write_concern: Option<Acknowledgment>,
+    /// Connection pool and timeout settings
+    pub connection: ConnectionOptions,
+}
+
+/// Page of transactions returned by a query.
+#[derive(Debug)]
+pub struct TransactionPage<T> {
+    /// Transactions of the page
+    pub items: Vec<T>,
+    /// Offset of the next page, if any
+    pub next: Option<u64>,
+    /// Number of transactions matching the query, if counted
+    pub total: Option<u64>,
+}
+
+/// Filters applied when querying transactions.
+#[derive(Debug, Default, Clone)]
+pub struct TransactionFilter {
+    /// Transaction signature to match
+    pub id: Option<String>,
+    /// Leading characters of the transaction signature, matched as an
+    /// anchored regular expression so the signature index bounds the scan
+    pub signature_prefix: Option<String>,
+    /// Slot of the block the transaction was included in
+    pub slot: Option<u64>,
+    /// Day (starting at midnight UTC) the transaction's block was produced.
+    /// Takes precedence over `from` and `to`.
+    pub day: Option<DateTime<Utc>>,
+    /// Earliest block time, inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Latest block time, inclusive
+    pub to: Option<DateTime<Utc>>,
+    /// Case-insensitive text that must appear in one of the transaction's memos
+    pub memo_contains: Option<String>,
+    /// Address the transaction must involve
+    pub address: Option<String>,
+    /// Program the transaction must have invoked through a cross-program invocation
+    pub invoked_program: Option<String>,
+    /// Addresses or programs the transaction must involve
+    pub scope: Option<Filters>,
+}
+
+impl TransactionFilter {
+    /// Builds the MongoDB query document for this filter.
+    pub fn to_document(&self) -> Document {
+        let mut query = Document::new();
+        if let Some(id) = &self.id {
+            query.insert("signature", id);
+        } else if let Some(prefix) = &self.signature_prefix {
+            query.insert(
+                "signature",
+                doc! { "$regex": format!("^{}", escape_regex(prefix)) },
+            );
+        }
+        if let Some(slot) = self.slot {
+            query.insert("slot", slot as i64);
+        }
+        if let Some(day) = self.day {
+            let start_of_day = day;
+            let end_of_day = day
+                .checked_add_days(Days::new(1))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            query.insert(
+                "block_time",
+                doc! {
+                    "$gte": start_of_day,
+                    "$lte": end_of_day,
+                },
+            );
+        } else if self.from.is_some() || self.to.is_some() {
+            let mut range = Document::new();
+            if let Some(from) = self.from {
+                range.insert("$gte", from);
+            }
+            if let Some(to) = self.to {
+                range.insert("$lte", to);
+            }
+            query.insert("block_time", range);
+        }
+        if let Some(memo) = &self.memo_contains {
+            query.insert(
+                "memos",
+                doc! {
+                    "$regex": escape_regex(memo),
+                    "$options": "i",
+                },
+            );
+        }
+        if let Some(program) = &self.invoked_program {
+            query.insert("inner_instructions.program_id", program);
+        }
+        let mut involving = Vec::new();
+        if let Some(address) = &self.address {
+            involving.push(involving_any(&[address]));
+        }
+        if let Some(scope) = &self.scope {
+            // Program IDs are always static account keys, so a single `$in`
+            // covers both watched addresses and watched programs.
+            let keys: Vec<&String> = scope.addresses.iter().chain(&scope.programs).collect();
+            involving.push(involving_any(&keys));
+        }
+        match involving.len() {
+            0 => {}
+            1 => query.extend(involving.remove(0)),
+            _ => {
+                query.insert("$and", involving);
+            }
+        }
+        query
+    }
+}
+
+/// Filters applied when querying blocks.
+#[derive(Debug, Default)]
+pub struct BlockFilter {
+    /// Lowest slot, inclusive
+    pub from_slot: Option<u64>,
+    /// Highest slot, inclusive
+    pub to_slot: Option<u64>,
+    /// Earliest block time, inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Latest block time, inclusive
+    pub to: Option<DateTime<Utc>>,
+    /// Identity of the slot leader
+    pub leader: Option<String>,
+}
+
+impl BlockFilter {
+    /// Builds the MongoDB query document for this filter.
+    pub fn to_document(&self) -> Document {
+        let mut query = Document::new();
+        if self.from_slot.is_some() || self.to_slot.is_some() {
+            let mut range = Document::new();
+            if let Some(from_slot) = self.from_slot {
+                range.insert("$gte", from_slot as i64);
+            }
+            if let Some(to_slot) = self.to_slot {
+                range.insert("$lte", to_slot as i64);
+            }
+            query.insert("slot", range);
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let mut range = Document::new();
+            if let Some(from) = self.from {
+                range.insert("$gte", from);
+            }
+            if let Some(to) = self.to {
+                range.insert("$lte", to);
+            }
+            query.insert("block_time", range);
+        }
+        if let Some(leader) = &self.leader {
+            query.insert("leader", leader);
+        }
+        query
+    }
+}
+
+/// Filters applied when querying swaps.
+#[derive(Debug, Default)]
+pub struct SwapFilter {
+    /// Account that made the swap
+    pub trader: Option<String>,
+    /// Mint sold or bought
+    pub mint: Option<String>,
+    /// Program ID of the DEX or aggregator
+    pub program_id: Option<String>,
+    /// Lowest slot, inclusive
+    pub from_slot: Option<u64>,
+    /// Highest slot, inclusive
+    pub to_slot: Option<u64>,
+}
+
+impl SwapFilter {
+    /// Builds the MongoDB query document for this filter.
+    pub fn to_document(&self) -> Document {
+        let mut query = Document::new();
+        if let Some(trader) = &self.trader {
+            query.insert("trader", trader);
+        }
+        if let Some(mint) = &self.mint {
+            query.insert(
+                "$or",
+                vec![doc! { "input_mint": mint }, doc! { "output_mint": mint }],
+            );
+        }
+        if let Some(program_id) = &self.program_id {
+            query.insert("program_id", program_id);
+        }
+        if self.from_slot.is_some() || self.to_slot.is_some() {
+            let mut range = Document::new();
+            if let Some(from_slot) = self.from_slot {
+                range.insert("$gte", from_slot as i64);
+            }
+            if let Some(to_slot) = self.to_slot {
+                range.insert("$lte", to_slot as i64);
+            }
+            query.insert("slot", range);
+        }
+        query
+    }
+}
+
+/// Filters applied when querying SOL transfers.
+#[derive(Debug, Default)]
+pub struct SolTransferFilter {
+    /// Account that either sent or received the transfers
+    pub address: Option<String>,
+    /// Account the lamports were debited from
+    pub from: Option<String>,
+    /// Account the lamports were credited to
+    pub to: Option<String>,
+    /// Smallest amount in lamports, inclusive
+    pub min_lamports: Option<u64>,
+    /// Largest amount in lamports, inclusive
+    pub max_lamports: Option<u64>,
+    /// Lowest slot, inclusive
+    pub from_slot: Option<u64>,
+    /// Highest slot, inclusive
+    pub to_slot: Option<u64>,
+}
+
+impl SolTransferFilter {
+    /// Builds the MongoDB query document for this filter.
+    pub fn to_document(&self) -> Document {
+        let mut query = Document::new();
+        if let Some(address) = &self.address {
+            query.insert(
+                "$or",
+                vec![doc! { "from": address }, doc! { "to": address }],
+            );
+        }
+        if let Some(from) = &self.from {
+            query.insert("from", from);
+        }
+        if let Some(to) = &self.to {
+            query.insert("to", to);
+        }
+        if self.min_lamports.is_some() || self.max_lamports.is_some() {
+            let mut range = Document::new();
+            if let Some(min_lamports) = self.min_lamports {
+                range.insert("$gte", min_lamports as i64);
+            }
+            if let Some(max_lamports) = self.max_lamports {
+                range.insert("$lte", max_lamports as i64);
+            }
+            query.insert("lamports", range);
+        }
+        if self.from_slot.is_some() || self.to_slot.is_some() {
+            let mut range = Document::new();
+            if let Some(from_slot) = self.from_slot {
+                range.insert("$gte", from_slot as i64);
+            }
+            if let Some(to_slot) = self.to_slot {
+                range.insert("$lte", to_slot as i64);
+            }
+            query.insert("slot", range);
+        }
+        query
+    }
+}
+
+/// Builds a query matching transactions that reference any of `keys`, either
+/// directly or through an address lookup table.
+fn involving_any(keys: &[&String]) -> Document {
+    doc! {
+        "$or": [
+            { "message.accountKeys": { "$in": keys } },
+            { "meta.loadedAddresses.writable": { "$in": keys } },
+            { "meta.loadedAddresses.readonly": { "$in": keys } },
+            { "resolved_account_keys": { "$in": keys } },
+        ]
+    }
+}
+
+/// Deserializes a stored transaction, upgrading it to the current schema
+/// version and decompressing its metadata first.
+///
+/// # Errors
+///
+/// Returns an error if the document was written by a newer build or can't
+/// be upgraded, deserialized or decompressed
+pub fn read_transaction(mut document: Document) -> eyre::Result<Transaction> {
+    migrations::upgrade_transaction(&mut document)?;
+    let mut transaction: Transaction = bson::from_document(document)?;
+    transaction.decompress()?;
+
+    Ok(transaction)
+}
+
+/// Copies the values at the dotted `fields` paths of a document, like an
+/// inclusion projection: a path through an array applies to each of its
+/// documents.
+fn project(document: &Document, fields: &[String]) -> Document {
+    let mut projected = Bson::Document(Document::new());
+    for field in fields {
+        let path: Vec<&str> = field.split('.').collect();
+        if let Some(value) = project_path(&Bson::Document(document.clone()), &path) {
+            merge_projection(&mut projected, value);
+        }
+    }
+
+    match projected {
+        Bson::Document(projected) => projected,
+        _ => Document::new(),
+    }
+}
+
+/// Returns the part of `value` at `path`, keeping the enclosing documents.
+fn project_path(value: &Bson, path: &[&str]) -> Option<Bson> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+
+    match value {
+        Bson::Document(document) => {
+            let inner = project_path(document.get(*first)?, rest)?;
+            Some(Bson::Document(doc! { *first: inner }))
+        }
+        Bson::Array(items) => Some(Bson::Array(
+            items
+                .iter()
+                .filter(|item| matches!(item, Bson::Document(_)))
+                .map(|item| project_path(item, path).unwrap_or(Bson::Document(Document::new())))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Merges the projection of another path into `target`.
+fn merge_projection(target: &mut Bson, value: Bson) {
+    match (target, value) {
+        (Bson::Document(target), Bson::Document(value)) => {
+            for (key, value) in value {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_projection(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Bson::Array(target), Bson::Array(value)) => {
+            for (existing, value) in target.iter_mut().zip(value) {
+                merge_projection(existing, value);
+            }
+        }
+        (target, value) => *target = value,
+    }
+}
+
+/// Counts the documents of a collection matching `query`.
+///
+/// Unfiltered counts use the collection metadata instead of scanning.
+async fn count<T: Send + Sync>(
+    collection: &Collection<T>,
+    query: Document,
+    max_time: Option<Duration>,
+) -> eyre::Result<u64> {
+    let count = if query.is_empty() {
+        collection
+            .estimated_document_count()
+            .with_options(
+                EstimatedDocumentCountOptions::builder()
+                    .max_time(max_time)
+                    .build(),
+            )
+            .await?
+    } else {
+        collection
+            .count_documents(query)
+            .with_options(CountOptions::builder().max_time(max_time).build())
+            .await?
+    };
+
+    Ok(count)
+}
+
+/// Inserts documents, skipping those a unique index rejects as already
+/// stored.
+///
+/// # Returns
+///
+/// * `mongodb::error::Result<u64>` - Number of documents inserted
+///
+/// # Errors
+///
+/// Returns an error if an insertion fails for a reason other than a
+/// duplicate key
+async fn insert_new<T: Serialize + Send + Sync>(
+    collection: &Collection<T>,
+    documents: &[T],
+) -> mongodb::error::Result<u64> {
+    if documents.is_empty() {
+        return Ok(0);
+    }
+
+    match collection.insert_many(documents).ordered(false).await {
+        Ok(result) => Ok(result.inserted_ids.len() as u64),
+        Err(e) => match *e.kind {
+            ErrorKind::InsertMany(InsertManyError {
+                write_errors: Some(ref errors),
+                write_concern_error: None,
+                ..
+            }) if errors.iter().all(|error| error.code == DUPLICATE_KEY_ERROR) => {
+                Ok((documents.len() - errors.len()) as u64)
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+/// Returns the length of the shortest sequence that `records` repeats a
+/// whole number of times, or the length of `records` if there is none.
+fn repeated_len<T: PartialEq>(records: &[T]) -> usize {
+    (1..records.len())
+        .filter(|len| records.len().is_multiple_of(*len))
+        .find(|&len| {
+            records
+                .iter()
+                .enumerate()
+                .all(|(i, record)| *record == records[i % len])
+        })
+        .unwrap_or(records.len())
+}
+
+/// Escapes regular expression metacharacters so `text` is matched literally.
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
 
-use super::models::transaction::Transaction;
+/// Names of the collections holding indexed data and indexing checkpoints.
+pub const COLLECTIONS: [&str; 18] = [
+    "transactions",
+    "daily_stats",
+    "alerts",
+    "labels",
+    "api_keys",
+    "blocks",
+    "backfill_jobs",
+    "address_backfills",
+    "replay_jobs",
+    "signatures",
+    "rewards",
+    "balance_changes",
+    "swaps",
+    "supply_events",
+    "sol_transfers",
+    "address_summaries",
+    "counterparty_links",
+    "enrichments",
+];
 
 /// Storage struct representing the MongoDB database connection and collections.
 ///
 /// This struct holds the MongoDB collections and provides methods for database operations.
 /// It is designed to be thread-safe and can be shared across different parts of the application.
 pub struct Storage {
+    /// Database holding the collections
+    database: Database,
+    /// Handle of the same database routed with the read preference and
+    /// concern of API queries
+    queries: Database,
+    /// Latency and error metrics of storage operations
+    pub metrics: StorageMetrics,
+    /// Settings applied when writing documents
+    options: StorageOptions,
     /// Collection for storing Solana transactions
     pub transactions: Collection<Transaction>,
+    /// Collection for storing per-day transaction rollups
+    pub daily_stats: Collection<DailyStats>,
+    /// Collection for storing large-transfer alerts
+    pub alerts: Collection<Alert>,
+    /// Collection for storing address labels
+    pub labels: Collection<AddressLabel>,
+    /// Collection for storing tenant API keys
+    pub api_keys: Collection<ApiKey>,
+    /// Collection for storing block headers
+    pub blocks: Collection<Block>,
+    /// Collection for storing slot ranges left to backfill
+    pub backfill_jobs: Collection<BackfillJob>,
+    /// Collection for storing the history backfill progress of watched addresses
+    pub address_backfills: Collection<AddressBackfill>,
+    /// Collection for storing the progress of replays
+    pub replay_jobs: Collection<ReplayJob>,
+    /// Collection for storing compact transaction records in signatures-only mode
+    pub signatures: Collection<SignatureRecord>,
+    /// Collection for storing per-account block rewards
+    pub rewards: Collection<BlockReward>,
+    /// Collection for storing per-account SOL balance changes
+    pub balance_changes: Collection<BalanceChange>,
+    /// Collection for storing normalized DEX swaps
+    pub swaps: Collection<Swap>,
+    /// Collection for storing SPL token mint and burn events
+    pub supply_events: Collection<SupplyEvent>,
+    /// Collection for storing native SOL transfers
+    pub sol_transfers: Collection<SolTransfer>,
+    /// Collection for storing incrementally maintained address summaries
+    pub address_summaries: Collection<AddressSummary>,
+    /// Collection for storing the distinct accounts each address exchanged SOL with
+    pub counterparty_links: Collection<CounterpartyLink>,
+    /// Collection for storing enhanced transaction interpretations
+    pub enrichments: Collection<Enrichment>,
+    /// GridFS bucket for storing compressed raw blocks, keyed by slot
+    pub raw_blocks: GridFsBucket,
 }
 
 impl Storage {
+    /// Initializes a new Storage instance with the default
+    /// [`StorageOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails; see
+    /// [`Storage::init_with_options`]
+    pub async fn init(db_name: &str) -> eyre::Result<Arc<Self>> {
+        Self::init_with_options(db_name, StorageOptions::default()).await
+    }
+
     /// Initializes a new Storage instance with MongoDB connection.
     ///
     /// This function creates a new connection to MongoDB using either the MONGO_URI
     /// environment variable or a default localhost connection string. It sets up the
     /// database and collections needed for the application.
     ///
+    /// # Arguments
+    ///
+    /// * `db_name` - Name of the database
+    /// * `options` - Settings applied when reading and writing documents
+    ///
     /// # Returns
     ///
     /// * `eyre::Result<Arc<Self>>` - A thread-safe reference to the Storage instance
@@ -42,95 +739,130 @@ impl Storage {
     /// Returns an error if:
     /// * MongoDB connection fails
     /// * Database initialization fails
-    pub async fn init(db_name: &str) -> eyre::Result<Arc<Self>> {
+    pub async fn init_with_options(
+        db_name: &str,
+        options: StorageOptions,
+    ) -> eyre::Result<Arc<Self>> {
         let uri = match env::var("MONGO_URI") {
             Ok(v) => v.to_string(),
             Err(_) => "mongodb://localhost:27017/?directConnection=true".to_string(),
         };
 
-        let client = Client::with_uri_str(uri).await?;
-        let db = client.database(db_name);
+        let mut client_options = ClientOptions::parse(uri).await?;
+        options.connection.apply(&mut client_options);
+        let client = Client::with_options(client_options)?;
+        let db = client.database_with_options(
+            db_name,
+            DatabaseOptions::builder()
+                .write_concern(
+                    options
+                        .write_concern
+                        .clone()
+                        .map(|w| WriteConcern::builder().w(w).build()),
+                )
+                .build(),
+        );
+        let queries = client.database_with_options(
+            db_name,
+            DatabaseOptions::builder()
+                .selection_criteria(SelectionCriteria::from(options.read_preference))
+                .read_concern(options.read_concern.map(ReadConcern::from))
+                .build(),
+        );
 
         let transactions: Collection<Transaction> = db.collection("transactions");
+        let daily_stats: Collection<DailyStats> = db.collection("daily_stats");
+        let alerts: Collection<Alert> = db.collection("alerts");
+        let labels: Collection<AddressLabel> = db.collection("labels");
+        let api_keys: Collection<ApiKey> = db.collection("api_keys");
+        let blocks: Collection<Block> = db.collection("blocks");
+        let backfill_jobs: Collection<BackfillJob> = db.collection("backfill_jobs");
+        let address_backfills: Collection<AddressBackfill> = db.collection("address_backfills");
+        let replay_jobs: Collection<ReplayJob> = db.collection("replay_jobs");
+        let signatures: Collection<SignatureRecord> = db.collection("signatures");
+        let rewards: Collection<BlockReward> = db.collection("rewards");
+        let balance_changes: Collection<BalanceChange> = db.collection("balance_changes");
+        let swaps: Collection<Swap> = db.collection("swaps");
+        let supply_events: Collection<SupplyEvent> = db.collection("supply_events");
+        let sol_transfers: Collection<SolTransfer> = db.collection("sol_transfers");
+        let address_summaries: Collection<AddressSummary> = db.collection("address_summaries");
+        let counterparty_links: Collection<CounterpartyLink> = db.collection("counterparty_links");
+        let enrichments: Collection<Enrichment> = db.collection("enrichments");
+        let raw_blocks = db.gridfs_bucket(
+            GridFsBucketOptions::builder()
+                .bucket_name(RAW_BLOCKS_BUCKET.to_string())
+                .build(),
+        );
 
-        Ok(Arc::new(Storage { transactions }))
+        Ok(Arc::new(Storage {
+            database: db,
+            queries,
+            metrics: StorageMetrics::default(),
+            options,
+            transactions,
+            daily_stats,
+            alerts,
+            labels,
+            api_keys,
+            blocks,
+            backfill_jobs,
+            address_backfills,
+            replay_jobs,
+            signatures,
+            rewards,
+            balance_changes,
+            swaps,
+            supply_events,
+            sol_transfers,
+            address_summaries,
+            counterparty_links,
+            enrichments,
+            raw_blocks,
+        }))
     }
 
-    /// Inserts a single transaction into the database.
-    ///
-    /// # Arguments
-    ///
-    /// * `transaction` - The transaction to insert
-    ///
-    /// # Returns
-    ///
-    /// * `eyre::Result<InsertOneResult>` - The result of the insertion operation
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the insertion fails
-    pub async fn insert_transaction(
-        &self,
-        transaction: Transaction,
-    ) -> eyre::Result<InsertOneResult> {
-        let result = self
-            .transactions
-            .insert_one(transaction)
-            .await
-            .wrap_err("Error inserting transaction")?;
-
-        Ok(result)
+    /// Returns a handle of `collection` routed like API queries, per the
+    /// configured read preference and concern.
+    fn query<T: Send + Sync>(&self, collection: &Collection<T>) -> Collection<T> {
+        self.queries.collection(collection.name())
     }
 
-    /// Retrieves transactions from the database with pagination support.
+    /// Retrieves a page of the documents matching an API query.
     ///
     /// # Arguments
     ///
-    /// * `id` - Optional transaction signature to filter by
-    /// * `day` - Optional date to filter transactions by day
-    /// * `count` - Number of transactions to return
-    /// * `offset` - Number of transactions to skip
+    /// * `collection` - Collection to query
+    /// * `query` - Filter the documents must match
+    /// * `sort` - Order of the documents
+    /// * `count` - Number of documents to return
+    /// * `offset` - Number of documents to skip
     ///
     /// # Returns
     ///
-    /// * `eyre::Result<(Vec<Transaction>, Option<u64>)>` - A tuple containing:
-    ///   - Vector of transactions
-    ///   - Optional next offset for pagination
+    /// * `eyre::Result<(Vec<T>, Option<u64>)>` - Documents and the optional next offset
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// * Database query fails
-    /// * Deserialization of results fails
-    pub async fn get_transactions(
+    /// Returns an error if the count or the query fails
+    async fn find_page<T: DeserializeOwned + Send + Sync>(
         &self,
-        id: Option<String>,
-        day: Option<DateTime<Utc>>,
+        collection: &Collection<T>,
+        query: Document,
+        sort: Document,
         count: u64,
         offset: u64,
-    ) -> eyre::Result<(Vec<Transaction>, Option<u64>)> {
-        let mut query = Document::new();
-        if let Some(id) = id {
-            query.insert("signature", id);
-        }
-        if let Some(day) = day {
-            let start_of_day = day;
-            let end_of_day = day
-                .checked_add_days(Days::new(1))
-                .unwrap_or(DateTime::<Utc>::MAX_UTC);
-            query.insert(
-                "block_time",
-                doc! {
-                    "$gte": start_of_day,
-                    "$lte": end_of_day,
-                },
-            );
-        }
-
+    ) -> eyre::Result<(Vec<T>, Option<u64>)> {
+        let collection = self.query(collection);
         let (total, mut cursor) = tokio::try_join!(
-            self.transactions.count_documents(query.clone()),
-            self.transactions.find(query).with_options(
+            collection.count_documents(query.clone()).with_options(
+                CountOptions::builder()
+                    .max_time(self.options.query_timeout)
+                    .build()
+            ),
+            collection.find(query).with_options(
                 FindOptions::builder()
+                    .sort(sort)
+                    .max_time(self.options.query_timeout)
                     .limit(count as i64)
                     .skip(offset)
                     .build(),
@@ -140,12 +872,2772 @@ impl Storage {
         let next = count.saturating_add(offset);
         let next = (next < total).then_some(next);
 
-        let mut transactions: Vec<Transaction> = Vec::new();
-
+        let mut documents = Vec::new();
         while cursor.advance().await? {
-            transactions.push(cursor.deserialize_current()?);
+            documents.push(cursor.deserialize_current()?);
+        }
+
+        Ok((documents, next))
+    }
+
+    /// Checks that the database server is reachable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server doesn't respond to a ping
+    pub async fn ping(&self) -> eyre::Result<()> {
+        self.metrics
+            .observe("ping", async {
+                self.database
+                    .run_command(doc! { "ping": 1 })
+                    .await
+                    .wrap_err("Error connecting to the database")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Drops the database with everything stored in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be dropped
+    pub async fn drop_database(&self) -> eyre::Result<()> {
+        self.metrics
+            .observe("drop_database", async {
+                self.database
+                    .drop()
+                    .await
+                    .wrap_err("Error dropping the database")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Creates the indexes used by transaction queries, if missing.
+    ///
+    /// Unique indexes built over data stored before they existed first
+    /// remove the duplicates they would reject; see
+    /// [`Storage::create_unique_index`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an index cannot be created
+    pub async fn create_indexes(&self) -> eyre::Result<()> {
+        self.metrics
+            .observe("create_indexes", async {
+                // Also serves signature prefix searches, which are anchored
+                // `^` regular expressions and so scan only the matching range
+                self.create_unique_index(&self.transactions, doc! { "signature": 1 })
+                    .await
+                    .wrap_err("Error creating transaction signature index")?;
+
+                self.transactions
+                    .create_index(IndexModel::builder().keys(doc! { "slot": 1 }).build())
+                    .await
+                    .wrap_err("Error creating transaction slot index")?;
+
+                self.transactions
+                    .create_index(IndexModel::builder().keys(doc! { "block_time": 1 }).build())
+                    .await
+                    .wrap_err("Error creating transaction block time index")?;
+
+                self.transactions
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "inner_instructions.program_id": 1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating inner instruction index")?;
+
+                self.transactions
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "schema_version": 1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating schema version index")?;
+
+                self.create_unique_index(&self.blocks, doc! { "slot": 1 })
+                    .await
+                    .wrap_err("Error creating block slot index")?;
+
+                self.blocks
+                    .create_index(IndexModel::builder().keys(doc! { "blockhash": 1 }).build())
+                    .await
+                    .wrap_err("Error creating blockhash index")?;
+
+                self.blocks
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "leader": 1, "slot": -1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating block leader index")?;
+
+                self.balance_changes
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "pubkey": 1, "slot": -1, "transaction_index": -1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating balance change index")?;
+
+                self.create_record_index(&self.balance_changes)
+                    .await
+                    .wrap_err("Error creating unique balance change index")?;
+
+                self.rewards
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "pubkey": 1, "slot": -1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating reward index")?;
+
+                // An account can receive rewards of several types in a slot
+                self.create_unique_index(
+                    &self.rewards,
+                    doc! { "slot": 1, "pubkey": 1, "reward_type": 1 },
+                )
+                .await
+                .wrap_err("Error creating unique reward index")?;
+
+                for key in ["trader", "input_mint", "output_mint"] {
+                    self.swaps
+                        .create_index(
+                            IndexModel::builder()
+                                .keys(doc! { key: 1, "slot": -1 })
+                                .build(),
+                        )
+                        .await
+                        .wrap_err("Error creating swap index")?;
+                }
+
+                // A transaction performs at most one swap
+                self.create_unique_index(&self.swaps, doc! { "signature": 1 })
+                    .await
+                    .wrap_err("Error creating unique swap index")?;
+
+                self.supply_events
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "mint": 1, "slot": -1 })
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating supply event index")?;
+
+                self.create_record_index(&self.supply_events)
+                    .await
+                    .wrap_err("Error creating unique supply event index")?;
+
+                for key in ["from", "to", "lamports"] {
+                    self.sol_transfers
+                        .create_index(
+                            IndexModel::builder()
+                                .keys(doc! { key: 1, "slot": -1 })
+                                .build(),
+                        )
+                        .await
+                        .wrap_err("Error creating SOL transfer index")?;
+                }
+
+                self.create_record_index(&self.sol_transfers)
+                    .await
+                    .wrap_err("Error creating unique SOL transfer index")?;
+
+                self.create_unique_index(&self.signatures, doc! { "signature": 1 })
+                    .await
+                    .wrap_err("Error creating signature index")?;
+
+                self.create_unique_index(&self.labels, doc! { "address": 1 })
+                    .await
+                    .wrap_err("Error creating label index")?;
+
+                self.address_summaries
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "address": 1 })
+                            .options(IndexOptions::builder().unique(true).build())
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating address summary index")?;
+
+                self.counterparty_links
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "address": 1, "counterparty": 1 })
+                            .options(IndexOptions::builder().unique(true).build())
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating counterparty link index")?;
+
+                self.enrichments
+                    .create_index(
+                        IndexModel::builder()
+                            .keys(doc! { "signature": 1 })
+                            .options(IndexOptions::builder().unique(true).build())
+                            .build(),
+                    )
+                    .await
+                    .wrap_err("Error creating enrichment index")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Creates a unique index, removing duplicate documents first if the
+    /// collection already holds some.
+    ///
+    /// Databases written before the index existed may hold documents sharing
+    /// a key, e.g. of reprocessed slots, on which the index build fails. Of
+    /// each set of duplicates the first inserted document is kept, and the
+    /// build is retried. Once the index exists, creating it again is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `collection` - Collection to index
+    /// * `keys` - Fields whose values must be unique together
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duplicates cannot be removed or the index
+    /// cannot be created
+    async fn create_unique_index<T: Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+        keys: Document,
+    ) -> eyre::Result<()> {
+        let index = || {
+            IndexModel::builder()
+                .keys(keys.clone())
+                .options(IndexOptions::builder().unique(true).build())
+                .build()
+        };
+
+        match collection.create_index(index()).await {
+            Ok(_) => return Ok(()),
+            Err(e) => match *e.kind {
+                ErrorKind::Command(ref error) if error.code == DUPLICATE_KEY_ERROR => {}
+                _ => return Err(e.into()),
+            },
         }
 
-        Ok((transactions, next))
+        warn!(
+            "Collection {} holds duplicates of {}, removing them before indexing",
+            collection.name(),
+            keys
+        );
+        let removed = self.remove_duplicates(collection, &keys).await?;
+        info!(
+            "Removed {} duplicates from collection {}",
+            removed,
+            collection.name()
+        );
+
+        collection.create_index(index()).await?;
+
+        Ok(())
+    }
+
+    /// Creates the unique `(signature, index)` index of a collection of
+    /// records derived from transactions.
+    ///
+    /// Records stored before they carried an `index` are numbered first; see
+    /// [`Storage::number_records`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the records cannot be numbered or the index
+    /// cannot be created
+    async fn create_record_index<T: Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+    ) -> eyre::Result<()> {
+        let exists = collection
+            .list_index_names()
+            .await?
+            .iter()
+            .any(|name| name == RECORD_INDEX_NAME);
+        if !exists {
+            let removed = self.number_records(collection).await?;
+            if removed > 0 {
+                info!(
+                    "Removed {} duplicate records from collection {}",
+                    removed,
+                    collection.name()
+                );
+            }
+        }
+
+        self.create_unique_index(collection, doc! { "signature": 1, "index": 1 })
+            .await
+    }
+
+    /// Numbers the records of a collection stored without an `index`, and
+    /// removes those stored again when their transaction was reprocessed.
+    ///
+    /// Reprocessing a transaction inserted all of its records again, in the
+    /// same order, so a transaction's stored records repeat its original
+    /// sequence. Only the first repetition is kept. Records that don't repeat
+    /// evenly, e.g. after a partial write, are all kept.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of records removed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aggregation or a write fails
+    async fn number_records<T: Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+    ) -> eyre::Result<u64> {
+        let pipeline = vec![
+            doc! { "$match": { "index": { "$exists": false } } },
+            doc! { "$sort": { "_id": 1 } },
+            doc! { "$group": { "_id": "$signature", "records": { "$push": "$$ROOT" } } },
+        ];
+
+        let removed = AtomicU64::new(0);
+        collection
+            .aggregate(pipeline)
+            .allow_disk_use(true)
+            .await
+            .wrap_err("Error finding unnumbered records")?
+            .map_err(eyre::Report::from)
+            .try_for_each_concurrent(RECORD_NUMBERING_CONCURRENCY, |group| {
+                let removed = &removed;
+                async move {
+                    let mut records: Vec<Document> = group
+                        .get_array("records")?
+                        .iter()
+                        .filter_map(|record| record.as_document().cloned())
+                        .collect();
+                    let ids: Vec<Bson> = records
+                        .iter_mut()
+                        .filter_map(|record| record.remove("_id"))
+                        .collect();
+                    let kept = repeated_len(&records);
+
+                    for (index, id) in ids[..kept].iter().enumerate() {
+                        collection
+                            .update_one(
+                                doc! { "_id": id },
+                                doc! { "$set": { "index": index as i64 } },
+                            )
+                            .await?;
+                    }
+                    if kept < ids.len() {
+                        let deleted = collection
+                            .delete_many(doc! { "_id": { "$in": &ids[kept..] } })
+                            .await?
+                            .deleted_count;
+                        removed.fetch_add(deleted, Ordering::Relaxed);
+                    }
+
+                    Ok(())
+                }
+            })
+            .await
+            .wrap_err_with(|| format!("Error numbering records of {}", collection.name()))?;
+
+        Ok(removed.into_inner())
+    }
+
+    /// Deletes the documents sharing the values of `keys` with a document
+    /// inserted before them.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of documents deleted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aggregation or a deletion fails
+    async fn remove_duplicates<T: Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+        keys: &Document,
+    ) -> eyre::Result<u64> {
+        let group: Document = keys
+            .keys()
+            .map(|key| (key.clone(), Bson::String(format!("${}", key))))
+            .collect();
+        let pipeline = vec![
+            doc! { "$sort": { "_id": 1 } },
+            doc! {
+                "$group": {
+                    "_id": group,
+                    "ids": { "$push": "$_id" },
+                    "count": { "$sum": 1 },
+                },
+            },
+            doc! { "$match": { "count": { "$gt": 1 } } },
+        ];
+
+        let mut duplicates = collection
+            .aggregate(pipeline)
+            .allow_disk_use(true)
+            .await
+            .wrap_err("Error finding duplicates")?;
+
+        let mut removed = 0;
+        while let Some(duplicate) = duplicates.try_next().await? {
+            let ids = duplicate.get_array("ids")?;
+            removed += collection
+                .delete_many(doc! { "_id": { "$in": &ids[1..] } })
+                .await
+                .wrap_err("Error removing duplicates")?
+                .deleted_count;
+        }
+
+        Ok(removed)
+    }
+
+    /// Opens a cursor over every document of a collection, in natural order.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the collection, one of [`COLLECTIONS`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn collection_documents(&self, name: &str) -> eyre::Result<Cursor<Document>> {
+        self.metrics
+            .observe("collection_documents", async {
+                self.database
+                    .collection::<Document>(name)
+                    .find(doc! {})
+                    .await
+                    .wrap_err_with(|| format!("Error reading collection {}", name))
+            })
+            .await
+    }
+
+    /// Inserts raw documents into a collection, skipping documents whose
+    /// `_id` is already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the collection, one of [`COLLECTIONS`]
+    /// * `documents` - Documents to insert
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of documents inserted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an insertion fails for a reason other than a
+    /// duplicate `_id`
+    pub async fn insert_documents(
+        &self,
+        name: &str,
+        documents: Vec<Document>,
+    ) -> eyre::Result<u64> {
+        self.metrics
+            .observe("insert_documents", async {
+                insert_new(&self.database.collection::<Document>(name), &documents)
+                    .await
+                    .wrap_err_with(|| format!("Error inserting into {}", name))
+            })
+            .await
+    }
+
+    /// Inserts a single transaction into the database.
+    ///
+    /// Log messages are dropped and bulky metadata fields compressed as set
+    /// in the [`StorageOptions`]. A transaction whose signature is already
+    /// stored, e.g. when a block is reprocessed, is left as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction to insert
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether the transaction was inserted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if compression or the insertion fails for a reason
+    /// other than a duplicate signature
+    pub async fn insert_transaction(&self, transaction: Transaction) -> eyre::Result<bool> {
+        self.metrics
+            .observe("insert_transaction", async {
+                let mut transaction = transaction;
+                if self.options.drop_log_messages {
+                    transaction.meta.log_messages = OptionSerializer::Skip;
+                }
+                if let Some(level) = self.options.compression_level {
+                    transaction.compress(level)?;
+                }
+
+                match self.transactions.insert_one(transaction).await {
+                    Ok(_) => Ok(true),
+                    Err(e) => match *e.kind {
+                        ErrorKind::Write(WriteFailure::WriteError(ref error))
+                            if error.code == DUPLICATE_KEY_ERROR =>
+                        {
+                            Ok(false)
+                        }
+                        _ => Err(e).wrap_err("Error inserting transaction"),
+                    },
+                }
+            })
+            .await
+    }
+
+    /// Archives a fetched block in GridFS as JSON, compressed with zstd.
+    /// The block is re-encoded from its parsed form, so fields the RPC
+    /// client doesn't know are not kept. Blocks already archived are kept as
+    /// they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - Slot of the block, used as the file id
+    /// * `block` - Block as received from the RPC
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block cannot be encoded or the upload fails
+    pub async fn insert_raw_block(&self, slot: u64, block: &UiConfirmedBlock) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_raw_block", async {
+                let id = Bson::Int64(slot as i64);
+                if self
+                    .raw_blocks
+                    .find_one(doc! { "_id": &id })
+                    .await?
+                    .is_some()
+                {
+                    return Ok(());
+                }
+
+                let bytes = zstd::encode_all(
+                    serde_json::to_vec(block)?.as_slice(),
+                    RAW_BLOCK_COMPRESSION_LEVEL,
+                )?;
+
+                let mut upload = self
+                    .raw_blocks
+                    .open_upload_stream(slot.to_string())
+                    .id(id)
+                    .metadata(doc! { "encoding": "json+zstd" })
+                    .await?;
+                upload.write_all(&bytes).await?;
+                upload.close().await.wrap_err("Error archiving raw block")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the archived raw JSON of a block.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Option<UiConfirmedBlock>>` - The block as received from the RPC, if archived
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the download fails or the archived block is corrupt
+    pub async fn get_raw_block(&self, slot: u64) -> eyre::Result<Option<UiConfirmedBlock>> {
+        self.metrics
+            .observe("get_raw_block", async {
+                let id = Bson::Int64(slot as i64);
+                if self
+                    .raw_blocks
+                    .find_one(doc! { "_id": &id })
+                    .await?
+                    .is_none()
+                {
+                    return Ok(None);
+                }
+
+                let mut download = self.raw_blocks.open_download_stream(id).await?;
+                let mut bytes = Vec::new();
+                download.read_to_end(&mut bytes).await?;
+
+                Ok(Some(serde_json::from_slice(&zstd::decode_all(
+                    bytes.as_slice(),
+                )?)?))
+            })
+            .await
+    }
+
+    /// Lists the slots of the blocks archived in a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_slot` - First slot of the range
+    /// * `to_slot` - Last slot of the range, inclusive
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Vec<u64>>` - Archived slots in ascending order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_raw_block_slots(
+        &self,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> eyre::Result<Vec<u64>> {
+        self.metrics
+            .observe("get_raw_block_slots", async {
+                let files: Vec<_> = self
+                    .raw_blocks
+                    .find(doc! {
+                        "_id": { "$gte": from_slot as i64, "$lte": to_slot as i64 },
+                    })
+                    .sort(doc! { "_id": 1 })
+                    .await?
+                    .try_collect()
+                    .await?;
+
+                Ok(files
+                    .into_iter()
+                    .filter_map(|file| file.id.as_i64())
+                    .map(|slot| slot as u64)
+                    .collect())
+            })
+            .await
+    }
+
+    /// Deletes the transactions of a set of slots and the records derived
+    /// from them, so the slots can be processed again without duplicates.
+    ///
+    /// The transaction counts and fees of the involved addresses' summaries
+    /// are reverted. Block headers, enrichments and counterparty links are
+    /// kept, as reprocessing overwrites or deduplicates them.
+    ///
+    /// # Arguments
+    ///
+    /// * `slots` - Slots whose data is deleted
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of transactions deleted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a query or deletion fails
+    pub async fn delete_slots(&self, slots: &[u64]) -> eyre::Result<u64> {
+        self.metrics
+            .observe("delete_slots", async {
+                let slots: Vec<i64> = slots.iter().map(|&slot| slot as i64).collect();
+                let query = doc! { "slot": { "$in": slots } };
+
+                let mut activity = BTreeMap::new();
+                let mut transactions = self
+                    .transactions
+                    .clone_with_type::<Document>()
+                    .find(query.clone())
+                    .await?;
+                while let Some(transaction) = transactions.try_next().await? {
+                    AddressActivity::record(&mut activity, &read_transaction(transaction)?);
+                }
+
+                stream::iter(activity)
+                    .map(|(address, activity)| {
+                        let summaries = self.address_summaries.clone();
+                        async move {
+                            summaries
+                                .update_one(
+                                    doc! { "address": address },
+                                    doc! {
+                                        "$inc": {
+                                            "transaction_count": -(activity.transaction_count as i64),
+                                            "fees_paid": -(activity.fees_paid as i64),
+                                        },
+                                    },
+                                )
+                                .await
+                        }
+                    })
+                    .buffer_unordered(ADDRESS_SUMMARY_WRITE_CONCURRENCY)
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .wrap_err("Error reverting address summaries")?;
+
+                let deleted = self
+                    .transactions
+                    .delete_many(query.clone())
+                    .await?
+                    .deleted_count;
+                self.signatures.delete_many(query.clone()).await?;
+                self.rewards.delete_many(query.clone()).await?;
+                self.alerts.delete_many(query.clone()).await?;
+                self.balance_changes.delete_many(query.clone()).await?;
+                self.swaps.delete_many(query.clone()).await?;
+                self.supply_events.delete_many(query.clone()).await?;
+                self.sol_transfers.delete_many(query).await?;
+
+                Ok(deleted)
+            })
+            .await
+    }
+
+    /// Inserts the compact records of a block's transactions. Records whose
+    /// signature is already stored, e.g. when a block is reprocessed, are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate signature
+    pub async fn insert_signatures(&self, records: &[SignatureRecord]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_signatures", async {
+                insert_new(&self.signatures, records)
+                    .await
+                    .wrap_err("Error inserting signatures")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Inserts the rewards credited in a block. Rewards already stored, e.g.
+    /// when a block is reprocessed, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate reward
+    pub async fn insert_rewards(&self, rewards: &[BlockReward]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_rewards", async {
+                insert_new(&self.rewards, rewards)
+                    .await
+                    .wrap_err("Error inserting rewards")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves an account's rewards, most recent first, with pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `pubkey` - Account that received the rewards
+    /// * `reward_type` - Optional reward type to filter by
+    /// * `count` - Number of rewards to return
+    /// * `offset` - Number of rewards to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<BlockReward>, Option<u64>)>` - Rewards and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_rewards(
+        &self,
+        pubkey: &str,
+        reward_type: Option<&str>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<BlockReward>, Option<u64>)> {
+        self.metrics
+            .observe("get_rewards", async {
+                let mut query = doc! { "pubkey": pubkey };
+                if let Some(reward_type) = reward_type {
+                    query.insert("reward_type", reward_type);
+                }
+
+                self.find_page(&self.rewards, query, doc! { "slot": -1 }, count, offset)
+                    .await
+            })
+            .await
+    }
+
+    /// Inserts SOL balance changes into the ledger.
+    /// Records already stored, e.g. when a transaction is stored again, are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate change
+    pub async fn insert_balance_changes(&self, changes: &[BalanceChange]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_balance_changes", async {
+                insert_new(&self.balance_changes, changes)
+                    .await
+                    .wrap_err("Error inserting balance changes")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves an account's SOL balance changes, most recent first, with
+    /// pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `pubkey` - Account whose balance changed
+    /// * `from_slot` - Optional lowest slot, inclusive
+    /// * `to_slot` - Optional highest slot, inclusive
+    /// * `count` - Number of changes to return
+    /// * `offset` - Number of changes to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<BalanceChange>, Option<u64>)>` - Balance changes and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_balance_changes(
+        &self,
+        pubkey: &str,
+        from_slot: Option<u64>,
+        to_slot: Option<u64>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<BalanceChange>, Option<u64>)> {
+        self.metrics
+            .observe("get_balance_changes", async {
+                let mut query = doc! { "pubkey": pubkey };
+                let mut range = Document::new();
+                if let Some(from_slot) = from_slot {
+                    range.insert("$gte", from_slot as i64);
+                }
+                if let Some(to_slot) = to_slot {
+                    range.insert("$lte", to_slot as i64);
+                }
+                if !range.is_empty() {
+                    query.insert("slot", range);
+                }
+
+                self.find_page(
+                    &self.balance_changes,
+                    query,
+                    doc! { "slot": -1 },
+                    count,
+                    offset,
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Retrieves an account's most recent SOL balance change, the last
+    /// balance the indexer saw for it. Changes of the same slot are ordered
+    /// by the position of their transaction in the block.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Option<BalanceChange>>` - The change, or `None` if no
+    ///   indexed transaction changed the account's balance
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_latest_balance_change(
+        &self,
+        pubkey: &str,
+    ) -> eyre::Result<Option<BalanceChange>> {
+        self.metrics
+            .observe("get_latest_balance_change", async {
+                self.query(&self.balance_changes)
+                    .find_one(doc! { "pubkey": pubkey })
+                    .sort(doc! { "slot": -1, "transaction_index": -1 })
+                    .await
+                    .wrap_err("Error fetching latest balance change")
+            })
+            .await
+    }
+
+    /// Inserts SPL token supply events.
+    /// Records already stored, e.g. when a transaction is stored again, are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate event
+    pub async fn insert_supply_events(&self, events: &[SupplyEvent]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_supply_events", async {
+                insert_new(&self.supply_events, events)
+                    .await
+                    .wrap_err("Error inserting supply events")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves a token's supply events, most recent first, with pagination
+    /// support.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Mint of the token
+    /// * `kind` - Optional kind of events to return; both when unset
+    /// * `from_slot` - Optional lowest slot, inclusive
+    /// * `to_slot` - Optional highest slot, inclusive
+    /// * `count` - Number of events to return
+    /// * `offset` - Number of events to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<SupplyEvent>, Option<u64>)>` - Supply events and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_supply_events(
+        &self,
+        mint: &str,
+        kind: Option<SupplyEventKind>,
+        from_slot: Option<u64>,
+        to_slot: Option<u64>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<SupplyEvent>, Option<u64>)> {
+        self.metrics
+            .observe("get_supply_events", async {
+                let mut query = doc! { "mint": mint };
+                if let Some(kind) = kind {
+                    query.insert("kind", bson::to_bson(&kind)?);
+                }
+                let mut range = Document::new();
+                if let Some(from_slot) = from_slot {
+                    range.insert("$gte", from_slot as i64);
+                }
+                if let Some(to_slot) = to_slot {
+                    range.insert("$lte", to_slot as i64);
+                }
+                if !range.is_empty() {
+                    query.insert("slot", range);
+                }
+
+                self.find_page(
+                    &self.supply_events,
+                    query,
+                    doc! { "slot": -1 },
+                    count,
+                    offset,
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Inserts native SOL transfers.
+    /// Records already stored, e.g. when a transaction is stored again, are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate transfer
+    pub async fn insert_sol_transfers(&self, transfers: &[SolTransfer]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_sol_transfers", async {
+                insert_new(&self.sol_transfers, transfers)
+                    .await
+                    .wrap_err("Error inserting SOL transfers")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves SOL transfers matching a filter, most recent first, with
+    /// pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Address, amount and slot filters
+    /// * `count` - Number of transfers to return
+    /// * `offset` - Number of transfers to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<SolTransfer>, Option<u64>)>` - SOL transfers and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_sol_transfers(
+        &self,
+        filter: &SolTransferFilter,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<SolTransfer>, Option<u64>)> {
+        self.metrics
+            .observe("get_sol_transfers", async {
+                let query = filter.to_document();
+
+                self.find_page(
+                    &self.sol_transfers,
+                    query,
+                    doc! { "slot": -1 },
+                    count,
+                    offset,
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Records counterparty links, skipping pairs already recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `links` - Distinct links to record
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Vec<&CounterpartyLink>>` - Links that weren't recorded before
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an insertion fails for a reason other than a
+    /// duplicate pair
+    pub async fn insert_counterparty_links<'a>(
+        &self,
+        links: &'a [CounterpartyLink],
+    ) -> eyre::Result<Vec<&'a CounterpartyLink>> {
+        self.metrics
+            .observe("insert_counterparty_links", async {
+                if links.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                match self
+                    .counterparty_links
+                    .insert_many(links)
+                    .ordered(false)
+                    .await
+                {
+                    Ok(_) => Ok(links.iter().collect()),
+                    Err(e) => match *e.kind {
+                        ErrorKind::InsertMany(InsertManyError {
+                            write_errors: Some(ref errors),
+                            write_concern_error: None,
+                            ..
+                        }) if errors.iter().all(|error| error.code == DUPLICATE_KEY_ERROR) => {
+                            let duplicates: HashSet<usize> =
+                                errors.iter().map(|error| error.index).collect();
+                            Ok(links
+                                .iter()
+                                .enumerate()
+                                .filter(|(index, _)| !duplicates.contains(index))
+                                .map(|(_, link)| link)
+                                .collect())
+                        }
+                        _ => Err(e).wrap_err("Error inserting counterparty links"),
+                    },
+                }
+            })
+            .await
+    }
+
+    /// Adds the activity of a batch of stored transactions to the address
+    /// summaries, creating summaries of addresses not seen before.
+    ///
+    /// # Arguments
+    ///
+    /// * `activity` - Activity of the batch, keyed by address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an update fails
+    pub async fn update_address_summaries(
+        &self,
+        activity: &BTreeMap<String, AddressActivity>,
+    ) -> eyre::Result<()> {
+        self.metrics
+            .observe("update_address_summaries", async {
+                let updates: Vec<(String, Document)> = activity
+                    .iter()
+                    .map(|(address, activity)| {
+                        let mut update = doc! {
+                            "$inc": {
+                                "transaction_count": activity.transaction_count as i64,
+                                "fees_paid": activity.fees_paid as i64,
+                                "counterparty_count": activity.new_counterparties as i64,
+                            },
+                        };
+                        let mut earliest = Document::new();
+                        let mut latest = Document::new();
+                        if let Some(first_seen) = activity.first_seen {
+                            earliest.insert("first_seen", first_seen);
+                        }
+                        if let Some(first_slot) = activity.first_slot {
+                            earliest.insert("first_slot", first_slot as i64);
+                        }
+                        if let Some(last_seen) = activity.last_seen {
+                            latest.insert("last_seen", last_seen);
+                        }
+                        if let Some(last_slot) = activity.last_slot {
+                            latest.insert("last_slot", last_slot as i64);
+                        }
+                        if !earliest.is_empty() {
+                            update.insert("$min", earliest);
+                        }
+                        if !latest.is_empty() {
+                            update.insert("$max", latest);
+                        }
+                        (address.clone(), update)
+                    })
+                    .collect();
+
+                stream::iter(updates)
+                    .map(|(address, update)| {
+                        let summaries = self.address_summaries.clone();
+                        async move {
+                            summaries
+                                .update_one(doc! { "address": address }, update)
+                                .upsert(true)
+                                .await
+                        }
+                    })
+                    .buffer_unordered(ADDRESS_SUMMARY_WRITE_CONCURRENCY)
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .wrap_err("Error updating address summaries")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the activity summary of an address.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Option<AddressSummary>>` - The summary, or `None` if no
+    ///   stored transaction involves the address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_address_summary(&self, address: &str) -> eyre::Result<Option<AddressSummary>> {
+        self.metrics
+            .observe("get_address_summary", async {
+                self.query(&self.address_summaries)
+                    .find_one(doc! { "address": address })
+                    .await
+                    .wrap_err("Error fetching address summary")
+            })
+            .await
+    }
+
+    /// Aggregates the SOL transfers of an address by counterparty. Token
+    /// transfers are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address whose counterparties to aggregate
+    /// * `from_slot` - Optional lowest slot of the transfers, inclusive
+    /// * `to_slot` - Optional highest slot of the transfers, inclusive
+    /// * `count` - Number of counterparties to return
+    /// * `offset` - Number of counterparties to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<Counterparty>, Option<u64>)>` - Counterparties
+    ///   with the most transfers first, and the offset of the next page, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aggregation fails
+    pub async fn get_counterparties(
+        &self,
+        address: &str,
+        from_slot: Option<u64>,
+        to_slot: Option<u64>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<Counterparty>, Option<u64>)> {
+        self.metrics
+            .observe("get_counterparties", async {
+                let query = SolTransferFilter {
+                    address: Some(address.to_string()),
+                    from_slot,
+                    to_slot,
+                    ..Default::default()
+                }
+                .to_document();
+                let sent = doc! { "$eq": ["$from", address] };
+                let received = doc! { "$eq": ["$to", address] };
+
+                let pipeline = vec![
+                    doc! { "$match": query },
+                    doc! {
+                        "$project": {
+                            "counterparty": { "$cond": [sent.clone(), "$to", "$from"] },
+                            "sent": { "$cond": [sent, "$lamports", 0_i64] },
+                            "received": { "$cond": [received, "$lamports", 0_i64] },
+                            "signature": 1,
+                            "slot": 1,
+                        }
+                    },
+                    doc! { "$match": { "counterparty": { "$ne": address } } },
+                    // Transfers are first grouped by transaction, so distinct
+                    // transactions are counted without collecting their
+                    // signatures
+                    doc! {
+                        "$group": {
+                            "_id": { "counterparty": "$counterparty", "signature": "$signature" },
+                            "transfer_count": { "$sum": 1 },
+                            "sent_lamports": { "$sum": "$sent" },
+                            "received_lamports": { "$sum": "$received" },
+                            "slot": { "$min": "$slot" },
+                        }
+                    },
+                    doc! {
+                        "$group": {
+                            "_id": "$_id.counterparty",
+                            "transfer_count": { "$sum": "$transfer_count" },
+                            "transaction_count": { "$sum": 1 },
+                            "sent_lamports": { "$sum": "$sent_lamports" },
+                            "received_lamports": { "$sum": "$received_lamports" },
+                            "first_slot": { "$min": "$slot" },
+                            "last_slot": { "$max": "$slot" },
+                        }
+                    },
+                    doc! { "$sort": { "transfer_count": -1, "_id": 1 } },
+                    doc! {
+                        "$facet": {
+                            "total": [{ "$count": "count" }],
+                            "counterparties": [
+                                { "$skip": offset as i64 },
+                                { "$limit": count as i64 },
+                            ],
+                        }
+                    },
+                ];
+
+                let mut cursor = self
+                    .query(&self.sol_transfers)
+                    .aggregate(pipeline)
+                    .await
+                    .wrap_err("Error aggregating counterparties")?;
+
+                let result = if cursor.advance().await? {
+                    cursor.deserialize_current()?
+                } else {
+                    Document::new()
+                };
+
+                let total = result
+                    .get_array("total")
+                    .ok()
+                    .and_then(|total| total.first())
+                    .and_then(Bson::as_document)
+                    .map_or(0, |total| get_u64(total, "count"));
+
+                let counterparties = result
+                    .get_array("counterparties")
+                    .map(|counterparties| counterparties.to_vec())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(Bson::as_document)
+                    .map(|counterparty| Counterparty {
+                        address: counterparty.get_str("_id").unwrap_or_default().to_string(),
+                        transfer_count: get_u64(counterparty, "transfer_count"),
+                        transaction_count: get_u64(counterparty, "transaction_count"),
+                        sent_lamports: get_u64(counterparty, "sent_lamports"),
+                        received_lamports: get_u64(counterparty, "received_lamports"),
+                        first_slot: get_u64(counterparty, "first_slot"),
+                        last_slot: get_u64(counterparty, "last_slot"),
+                    })
+                    .collect();
+
+                let next = count.saturating_add(offset);
+                let next = (next < total).then_some(next);
+
+                Ok((counterparties, next))
+            })
+            .await
+    }
+
+    /// Stores enhanced transaction interpretations, replacing earlier ones
+    /// of the same transactions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a write fails
+    pub async fn upsert_enrichments(&self, enrichments: &[Enrichment]) -> eyre::Result<()> {
+        self.metrics
+            .observe("upsert_enrichments", async {
+                for enrichment in enrichments {
+                    self.enrichments
+                        .replace_one(doc! { "signature": &enrichment.signature }, enrichment)
+                        .upsert(true)
+                        .await
+                        .wrap_err("Error storing enrichment")?;
+                }
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Looks up the stored interpretations of a set of transactions.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<HashMap<String, Enrichment>>` - Enrichments keyed by signature; transactions without one are omitted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_enrichments<'a>(
+        &self,
+        signatures: impl IntoIterator<Item = &'a str>,
+    ) -> eyre::Result<HashMap<String, Enrichment>> {
+        self.metrics
+            .observe("get_enrichments", async {
+                let signatures: Vec<&str> = signatures.into_iter().collect();
+                if signatures.is_empty() {
+                    return Ok(HashMap::new());
+                }
+
+                let mut cursor = self
+                    .query(&self.enrichments)
+                    .find(doc! { "signature": { "$in": signatures } })
+                    .await?;
+
+                let mut enrichments = HashMap::new();
+                while cursor.advance().await? {
+                    let enrichment: Enrichment = cursor.deserialize_current()?;
+                    enrichments.insert(enrichment.signature.clone(), enrichment);
+                }
+
+                Ok(enrichments)
+            })
+            .await
+    }
+
+    /// Inserts normalized DEX swaps.
+    /// Records already stored, e.g. when a transaction is stored again, are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails for a reason other than a
+    /// duplicate swap
+    pub async fn insert_swaps(&self, swaps: &[Swap]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_swaps", async {
+                insert_new(&self.swaps, swaps)
+                    .await
+                    .wrap_err("Error inserting swaps")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves swaps matching a filter, most recent first, with pagination
+    /// support.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Trader, mint, DEX and slot filters
+    /// * `count` - Number of swaps to return
+    /// * `offset` - Number of swaps to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<Swap>, Option<u64>)>` - Swaps and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_swaps(
+        &self,
+        filter: &SwapFilter,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<Swap>, Option<u64>)> {
+        self.metrics
+            .observe("get_swaps", async {
+                let query = filter.to_document();
+
+                self.find_page(&self.swaps, query, doc! { "slot": -1 }, count, offset)
+                    .await
+            })
+            .await
+    }
+
+    /// Retrieves the compact record of a transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_signature(&self, signature: &str) -> eyre::Result<Option<SignatureRecord>> {
+        self.metrics
+            .observe("get_signature", async {
+                Ok(self
+                    .query(&self.signatures)
+                    .find_one(doc! { "signature": signature })
+                    .await?)
+            })
+            .await
+    }
+
+    /// Stores the header of an indexed block, replacing any previous record
+    /// for the same slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails
+    pub async fn upsert_block(&self, block: &Block) -> eyre::Result<()> {
+        self.metrics
+            .observe("upsert_block", async {
+                self.blocks
+                    .replace_one(doc! { "slot": block.slot as i64 }, block)
+                    .upsert(true)
+                    .await
+                    .wrap_err("Error storing block")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the header of the block at a slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_block(&self, slot: u64) -> eyre::Result<Option<Block>> {
+        self.metrics
+            .observe("get_block", async {
+                Ok(self
+                    .query(&self.blocks)
+                    .find_one(doc! { "slot": slot as i64 })
+                    .await?)
+            })
+            .await
+    }
+
+    /// Counts the indexed blocks matching a filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn count_blocks(&self, filter: &BlockFilter) -> eyre::Result<u64> {
+        self.metrics
+            .observe("count_blocks", async {
+                count(
+                    &self.query(&self.blocks),
+                    filter.to_document(),
+                    self.options.query_timeout,
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Aggregates block production by slot leader over the blocks matching a
+    /// filter. Blocks without a known leader are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Slot, time and leader bounds
+    /// * `count` - Number of leaders to return
+    /// * `offset` - Number of leaders to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<LeaderStats>, Option<u64>)>` - Leaders with the
+    ///   most blocks first, and the offset of the next page, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aggregation fails
+    pub async fn get_leader_stats(
+        &self,
+        filter: &BlockFilter,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<LeaderStats>, Option<u64>)> {
+        self.metrics
+            .observe("get_leader_stats", async {
+                let mut query = filter.to_document();
+                if !query.contains_key("leader") {
+                    query.insert("leader", doc! { "$ne": null });
+                }
+
+                let pipeline = vec![
+                    doc! { "$match": query },
+                    doc! {
+                        "$group": {
+                            "_id": "$leader",
+                            "block_count": { "$sum": 1 },
+                            "transaction_count": { "$sum": "$transaction_count" },
+                            "first_slot": { "$min": "$slot" },
+                            "last_slot": { "$max": "$slot" },
+                        }
+                    },
+                    doc! { "$sort": { "block_count": -1, "_id": 1 } },
+                    doc! {
+                        "$facet": {
+                            "total": [{ "$count": "count" }],
+                            "leaders": [
+                                { "$skip": offset as i64 },
+                                { "$limit": count as i64 },
+                            ],
+                        }
+                    },
+                ];
+
+                let mut cursor = self
+                    .query(&self.blocks)
+                    .aggregate(pipeline)
+                    .await
+                    .wrap_err("Error aggregating leader stats")?;
+
+                let result = if cursor.advance().await? {
+                    cursor.deserialize_current()?
+                } else {
+                    Document::new()
+                };
+
+                let total = result
+                    .get_array("total")
+                    .ok()
+                    .and_then(|total| total.first())
+                    .and_then(Bson::as_document)
+                    .map_or(0, |total| get_u64(total, "count"));
+
+                let stats = result
+                    .get_array("leaders")
+                    .map(|leaders| leaders.to_vec())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(Bson::as_document)
+                    .map(|leader| {
+                        let block_count = get_u64(leader, "block_count");
+                        let transaction_count = get_u64(leader, "transaction_count");
+                        LeaderStats {
+                            leader: leader.get_str("_id").unwrap_or_default().to_string(),
+                            block_count,
+                            transaction_count,
+                            average_transactions: transaction_count as f64
+                                / block_count.max(1) as f64,
+                            first_slot: get_u64(leader, "first_slot"),
+                            last_slot: get_u64(leader, "last_slot"),
+                        }
+                    })
+                    .collect();
+
+                let next = count.saturating_add(offset);
+                let next = (next < total).then_some(next);
+
+                Ok((stats, next))
+            })
+            .await
+    }
+
+    /// Returns the slot of the most recently indexed block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn latest_block_slot(&self) -> eyre::Result<Option<u64>> {
+        self.metrics
+            .observe("latest_block_slot", async {
+                let block = self
+                    .blocks
+                    .find_one(doc! {})
+                    .sort(doc! { "slot": -1 })
+                    .await?;
+
+                Ok(block.map(|block| block.slot))
+            })
+            .await
+    }
+
+    /// Retrieves the header of the block with the given hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_block_by_hash(&self, blockhash: &str) -> eyre::Result<Option<Block>> {
+        self.metrics
+            .observe("get_block_by_hash", async {
+                Ok(self
+                    .query(&self.blocks)
+                    .find_one(doc! { "blockhash": blockhash })
+                    .await?)
+            })
+            .await
+    }
+
+    /// Retrieves transactions from the database with pagination support.
+    ///
+    /// Compressed metadata fields are decompressed transparently.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Signature, day and memo filters
+    /// * `count` - Number of transactions to return
+    /// * `offset` - Number of transactions to skip
+    /// * `with_total` - Whether to count all matching transactions, which
+    ///   can be slow for broad filters
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<TransactionPage<Transaction>>` - The transactions, the
+    ///   optional next offset for pagination and, if counted, the total
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * Database query fails
+    /// * Deserialization of results fails
+    pub async fn get_transactions(
+        &self,
+        filter: &TransactionFilter,
+        count: u64,
+        offset: u64,
+        with_total: bool,
+    ) -> eyre::Result<TransactionPage<Transaction>> {
+        self.metrics
+            .observe("get_transactions", async {
+                let page: TransactionPage<Document> = self
+                    .find_transactions(filter, None, count, offset, with_total)
+                    .await?;
+                let items = page
+                    .items
+                    .into_iter()
+                    .map(read_transaction)
+                    .collect::<eyre::Result<_>>()?;
+
+                Ok(TransactionPage {
+                    items,
+                    next: page.next,
+                    total: page.total,
+                })
+            })
+            .await
+    }
+
+    /// Upgrades every stored transaction older than the current schema
+    /// version.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of upgraded transactions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a document can't be upgraded or written back
+    pub async fn migrate_transactions(&self) -> eyre::Result<u64> {
+        self.metrics
+            .observe("migrate_transactions", async {
+                let transactions = self.transactions.clone_with_type::<Document>();
+                let mut cursor = transactions
+                    .find(migrations::outdated_transactions())
+                    .await?;
+
+                let mut migrated = 0;
+                while let Some(mut transaction) = cursor.try_next().await? {
+                    let id = transaction.get("_id").cloned().unwrap_or(Bson::Null);
+                    if migrations::upgrade_transaction(&mut transaction)? {
+                        transactions
+                            .replace_one(doc! { "_id": id }, transaction)
+                            .await
+                            .wrap_err("Error writing migrated transaction")?;
+                        migrated += 1;
+                    }
+                }
+
+                Ok(migrated)
+            })
+            .await
+    }
+
+    /// Checks that the stored transactions can be read by this build.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether some transactions are older than the
+    ///   current schema version and will be upgraded as they are read
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if transactions were written by a newer build, or the
+    /// query fails
+    pub async fn check_schema_version(&self) -> eyre::Result<bool> {
+        self.metrics
+            .observe("check_schema_version", async {
+                let transactions = self.transactions.clone_with_type::<Document>();
+
+                if let Some(transaction) = transactions
+                    .find_one(migrations::unsupported_transactions())
+                    .await?
+                {
+                    eyre::bail!(
+                        "The database holds transactions with schema version {}, but this build supports up to {}; upgrade SolDag",
+                        migrations::transaction_schema_version(&transaction),
+                        migrations::TRANSACTION_SCHEMA_VERSION
+                    );
+                }
+
+                Ok(transactions
+                    .find_one(migrations::outdated_transactions())
+                    .await?
+                    .is_some())
+            })
+            .await
+    }
+
+    /// Counts the transactions matching a filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn count_transactions(&self, filter: &TransactionFilter) -> eyre::Result<u64> {
+        self.metrics
+            .observe("count_transactions", async {
+                count(
+                    &self.query(&self.transactions),
+                    filter.to_document(),
+                    self.options.query_timeout,
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Retrieves selected fields of transactions with pagination support.
+    ///
+    /// Only the requested fields are read from the database, so callers get
+    /// raw documents rather than full [`Transaction`]s. Transactions of an
+    /// older schema version are read in full and upgraded before the fields
+    /// are selected, so they look the same as current ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Signature, day and memo filters
+    /// * `fields` - Dotted paths of the fields to return, e.g. `meta.fee`
+    /// * `count` - Number of transactions to return
+    /// * `offset` - Number of transactions to skip
+    /// * `with_total` - Whether to count all matching transactions
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<TransactionPage<Document>>` - Projected documents, the optional next offset and total
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_transaction_fields(
+        &self,
+        filter: &TransactionFilter,
+        fields: &[String],
+        count: u64,
+        offset: u64,
+        with_total: bool,
+    ) -> eyre::Result<TransactionPage<Document>> {
+        self.metrics
+            .observe("get_transaction_fields", async {
+                let mut projection: Document = fields
+                    .iter()
+                    .map(|field| (field.clone(), Bson::Int32(1)))
+                    .collect();
+                projection.insert("schema_version", 1);
+
+                let mut page = self
+                    .find_transactions::<Document>(
+                        filter,
+                        Some(projection),
+                        count,
+                        offset,
+                        with_total,
+                    )
+                    .await?;
+
+                for item in page.items.iter_mut() {
+                    if migrations::transaction_schema_version(item)
+                        == migrations::TRANSACTION_SCHEMA_VERSION
+                    {
+                        item.remove("_id");
+                        if !fields.iter().any(|field| field == "schema_version") {
+                            item.remove("schema_version");
+                        }
+                        continue;
+                    }
+
+                    let id = item.get("_id").cloned().unwrap_or(Bson::Null);
+                    let mut transaction = self
+                        .query(&self.transactions)
+                        .clone_with_type::<Document>()
+                        .find_one(doc! { "_id": id })
+                        .await?
+                        .ok_or_else(|| eyre::eyre!("Transaction disappeared while being read"))?;
+                    migrations::upgrade_transaction(&mut transaction)?;
+                    *item = project(&transaction, fields);
+                }
+
+                Ok(page)
+            })
+            .await
+    }
+
+    /// Runs a paginated transaction query, deserializing results as `T`.
+    ///
+    /// Without `with_total`, the matching transactions aren't counted, and
+    /// whether there is a next page is found by reading one extra result.
+    async fn find_transactions<T>(
+        &self,
+        filter: &TransactionFilter,
+        projection: Option<Document>,
+        count: u64,
+        offset: u64,
+        with_total: bool,
+    ) -> eyre::Result<TransactionPage<T>>
+    where
+        T: DeserializeOwned + Send + Sync,
+    {
+        let query = filter.to_document();
+        let transactions = self.query(&self.transactions).clone_with_type::<T>();
+
+        let total = async {
+            match with_total {
+                true => transactions
+                    .count_documents(query.clone())
+                    .with_options(
+                        CountOptions::builder()
+                            .max_time(self.options.query_timeout)
+                            .build(),
+                    )
+                    .await
+                    .map(Some),
+                false => Ok(None),
+            }
+        };
+        let (total, mut cursor) = tokio::try_join!(
+            total,
+            transactions.find(query.clone()).with_options(
+                FindOptions::builder()
+                    .projection(projection)
+                    .max_time(self.options.query_timeout)
+                    .limit(count.saturating_add(1) as i64)
+                    .skip(offset)
+                    .build(),
+            )
+        )?;
+
+        let mut items = Vec::new();
+
+        while cursor.advance().await? {
+            items.push(cursor.deserialize_current()?);
+        }
+
+        let more = items.len() as u64 > count;
+        items.truncate(count as usize);
+        let next = more.then_some(count.saturating_add(offset));
+
+        Ok(TransactionPage { items, next, total })
+    }
+
+    /// Opens a change stream of newly inserted transactions.
+    ///
+    /// Requires MongoDB to run as a replica set. Events carry transactions
+    /// as stored, to be read with [`read_transaction`].
+    ///
+    /// # Arguments
+    ///
+    /// * `resume_after` - Token of the last event seen, to continue a previous stream
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change stream cannot be opened
+    pub async fn watch_transactions(
+        &self,
+        resume_after: Option<ResumeToken>,
+    ) -> eyre::Result<ChangeStream<ChangeStreamEvent<Document>>> {
+        self.metrics
+            .observe("watch_transactions", async {
+                self.transactions
+                    .clone_with_type::<Document>()
+                    .watch()
+                    .pipeline([doc! { "$match": { "operationType": "insert" } }])
+                    .resume_after(resume_after)
+                    .await
+                    .wrap_err("Error opening transaction change stream")
+            })
+            .await
+    }
+
+    /// Returns the block time of the oldest indexed transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn earliest_block_time(&self) -> eyre::Result<Option<DateTime<Utc>>> {
+        self.metrics
+            .observe("earliest_block_time", async {
+                let transaction = self
+                    .transactions
+                    .clone_with_type::<Document>()
+                    .find_one(doc! { "block_time": { "$ne": null } })
+                    .projection(doc! { "block_time": 1 })
+                    .sort(doc! { "block_time": 1 })
+                    .await?;
+
+                Ok(transaction
+                    .and_then(|transaction| transaction.get_datetime("block_time").ok().copied())
+                    .map(|block_time| block_time.to_chrono()))
+            })
+            .await
+    }
+
+    /// Opens a cursor over the transactions produced in `[from, to)`, oldest
+    /// first. Documents are returned as stored, to be read with
+    /// [`read_transaction`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn transactions_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> eyre::Result<Cursor<Document>> {
+        self.metrics
+            .observe("transactions_between", async {
+                Ok(self
+                    .transactions
+                    .clone_with_type::<Document>()
+                    .find(doc! { "block_time": { "$gte": from, "$lt": to } })
+                    .sort(doc! { "block_time": 1 })
+                    .await?)
+            })
+            .await
+    }
+
+    /// Deletes the transactions produced in `[from, to)`.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of deleted transactions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deletion fails
+    pub async fn delete_transactions_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> eyre::Result<u64> {
+        self.metrics
+            .observe("delete_transactions_between", async {
+                let result = self
+                    .transactions
+                    .delete_many(doc! { "block_time": { "$gte": from, "$lt": to } })
+                    .await
+                    .wrap_err("Error deleting transactions")?;
+
+                Ok(result.deleted_count)
+            })
+            .await
+    }
+
+    /// Inserts alerts into the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails
+    pub async fn insert_alerts(&self, alerts: &[Alert]) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_alerts", async {
+                if alerts.is_empty() {
+                    return Ok(());
+                }
+
+                self.alerts
+                    .insert_many(alerts)
+                    .await
+                    .wrap_err("Error inserting alerts")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves alerts, most recent first, with pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - Optional rule name to filter by
+    /// * `scope` - Optional scope the alert must lie within: the transfer is
+    ///   sent from or to one of its addresses, or the transaction involves one
+    ///   of its programs
+    /// * `count` - Number of alerts to return
+    /// * `offset` - Number of alerts to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<Alert>, Option<u64>)>` - Alerts and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_alerts(
+        &self,
+        rule: Option<String>,
+        scope: Option<&Filters>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<Alert>, Option<u64>)> {
+        self.metrics
+            .observe("get_alerts", async {
+                let mut pipeline = Vec::new();
+                if let Some(rule) = rule {
+                    pipeline.push(doc! { "$match": { "rule": rule } });
+                }
+                if let Some(scope) = scope {
+                    let addresses: Vec<&String> = scope.addresses.iter().collect();
+                    let mut within = vec![
+                        doc! { "transfer.source": { "$in": &addresses } },
+                        doc! { "transfer.destination": { "$in": &addresses } },
+                    ];
+                    if !scope.programs.is_empty() {
+                        // Alerts don't record programs, so they are looked up
+                        // on the alerted transaction
+                        let programs: Vec<&String> = scope.programs.iter().collect();
+                        pipeline.push(doc! {
+                            "$lookup": {
+                                "from": self.transactions.name(),
+                                "localField": "signature",
+                                "foreignField": "signature",
+                                "pipeline": [
+                                    { "$match": involving_any(&programs) },
+                                    { "$project": { "_id": 1 } },
+                                ],
+                                "as": "scope_transactions",
+                            }
+                        });
+                        within.push(doc! { "scope_transactions": { "$ne": [] } });
+                    }
+                    pipeline.push(doc! { "$match": { "$or": within } });
+                    pipeline.push(doc! { "$project": { "scope_transactions": 0 } });
+                }
+                pipeline.push(doc! { "$sort": { "created_at": -1 } });
+                pipeline.push(doc! {
+                    "$facet": {
+                        "total": [{ "$count": "count" }],
+                        "alerts": [
+                            { "$skip": offset as i64 },
+                            { "$limit": count as i64 },
+                        ],
+                    }
+                });
+
+                let mut cursor = self
+                    .query(&self.alerts)
+                    .aggregate(pipeline)
+                    .await
+                    .wrap_err("Error querying alerts")?;
+
+                let result = if cursor.advance().await? {
+                    cursor.deserialize_current()?
+                } else {
+                    Document::new()
+                };
+
+                let total = result
+                    .get_array("total")
+                    .ok()
+                    .and_then(|total| total.first())
+                    .and_then(Bson::as_document)
+                    .map_or(0, |total| get_u64(total, "count"));
+
+                let alerts = result
+                    .get_array("alerts")
+                    .map(|alerts| alerts.to_vec())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|alert| match alert {
+                        Bson::Document(alert) => Some(alert),
+                        _ => None,
+                    })
+                    .map(bson::from_document)
+                    .collect::<Result<Vec<Alert>, _>>()?;
+
+                let next = count.saturating_add(offset);
+                let next = (next < total).then_some(next);
+
+                Ok((alerts, next))
+            })
+            .await
+    }
+
+    /// Retrieves the compute unit prices of recently landed transactions.
+    ///
+    /// Transactions that didn't set a compute unit price count as zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `accounts` - If not empty, only transactions referencing one of these accounts are sampled
+    /// * `since` - Only transactions in blocks produced at or after this time are sampled
+    /// * `limit` - Maximum number of (most recent) transactions to sample
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Vec<u64>>` - Compute unit prices in micro-lamports
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_landed_compute_unit_prices(
+        &self,
+        accounts: &[String],
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> eyre::Result<Vec<u64>> {
+        self.metrics
+            .observe("get_landed_compute_unit_prices", async {
+                let mut query = doc! {
+                    "block_time": { "$gte": since },
+                    "compute_unit_price": { "$exists": true },
+                };
+                if !accounts.is_empty() {
+                    query.insert("message.accountKeys", doc! { "$in": accounts });
+                }
+
+                let mut cursor = self
+                    .query(&self.transactions)
+                    .clone_with_type::<Document>()
+                    .find(query)
+                    .with_options(
+                        FindOptions::builder()
+                            .projection(doc! { "compute_unit_price": 1 })
+                            .sort(doc! { "block_time": -1 })
+                            .limit(limit)
+                            .build(),
+                    )
+                    .await?;
+
+                let mut prices = Vec::new();
+                while cursor.advance().await? {
+                    let document: Document = cursor.deserialize_current()?;
+                    prices.push(get_u64(&document, "compute_unit_price"));
+                }
+
+                Ok(prices)
+            })
+            .await
+    }
+
+    /// Inserts or replaces the label of an address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails
+    pub async fn upsert_label(&self, label: &AddressLabel) -> eyre::Result<()> {
+        self.metrics
+            .observe("upsert_label", async {
+                self.labels
+                    .replace_one(doc! { "address": &label.address }, label)
+                    .upsert(true)
+                    .await
+                    .wrap_err("Error storing label")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Removes the label of an address.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether a label existed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deletion fails
+    pub async fn delete_label(&self, address: &str) -> eyre::Result<bool> {
+        self.metrics
+            .observe("delete_label", async {
+                let result = self
+                    .labels
+                    .delete_one(doc! { "address": address })
+                    .await
+                    .wrap_err("Error deleting label")?;
+
+                Ok(result.deleted_count > 0)
+            })
+            .await
+    }
+
+    /// Retrieves the label of a single address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_label(&self, address: &str) -> eyre::Result<Option<AddressLabel>> {
+        self.metrics
+            .observe("get_label", async {
+                Ok(self.labels.find_one(doc! { "address": address }).await?)
+            })
+            .await
+    }
+
+    /// Retrieves labels, ordered by address, with pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Optional tag the labels must carry
+    /// * `count` - Number of labels to return
+    /// * `offset` - Number of labels to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<AddressLabel>, Option<u64>)>` - Labels and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_labels(
+        &self,
+        tag: Option<String>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<AddressLabel>, Option<u64>)> {
+        self.metrics
+            .observe("get_labels", async {
+                let mut query = Document::new();
+                if let Some(tag) = tag {
+                    query.insert("tags", tag);
+                }
+
+                self.find_page(&self.labels, query, doc! { "address": 1 }, count, offset)
+                    .await
+            })
+            .await
+    }
+
+    /// Looks up the labels of a set of addresses.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<HashMap<String, AddressLabel>>` - Labels keyed by address; unlabeled addresses are omitted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_labels_for<'a>(
+        &self,
+        addresses: impl IntoIterator<Item = &'a str>,
+    ) -> eyre::Result<HashMap<String, AddressLabel>> {
+        self.metrics
+            .observe("get_labels_for", async {
+                let addresses: Vec<&str> = addresses.into_iter().collect();
+                if addresses.is_empty() {
+                    return Ok(HashMap::new());
+                }
+
+                let mut cursor = self
+                    .query(&self.labels)
+                    .find(doc! { "address": { "$in": addresses } })
+                    .await?;
+
+                let mut labels = HashMap::new();
+                while cursor.advance().await? {
+                    let label: AddressLabel = cursor.deserialize_current()?;
+                    labels.insert(label.address.clone(), label);
+                }
+
+                Ok(labels)
+            })
+            .await
+    }
+
+    /// Stores a new API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails
+    pub async fn insert_api_key(&self, key: &ApiKey) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_api_key", async {
+                self.api_keys
+                    .insert_one(key)
+                    .await
+                    .wrap_err("Error inserting API key")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Removes an API key.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether the key existed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deletion fails
+    pub async fn delete_api_key(&self, key: &str) -> eyre::Result<bool> {
+        self.metrics
+            .observe("delete_api_key", async {
+                let result = self
+                    .api_keys
+                    .delete_one(doc! { "key": key })
+                    .await
+                    .wrap_err("Error deleting API key")?;
+
+                Ok(result.deleted_count > 0)
+            })
+            .await
+    }
+
+    /// Retrieves all API keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_api_keys(&self) -> eyre::Result<Vec<ApiKey>> {
+        self.metrics
+            .observe("get_api_keys", async {
+                let mut cursor = self.api_keys.find(doc! {}).await?;
+
+                let mut keys = Vec::new();
+                while cursor.advance().await? {
+                    keys.push(cursor.deserialize_current()?);
+                }
+
+                Ok(keys)
+            })
+            .await
+    }
+
+    /// Records a slot range to be backfilled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion fails
+    pub async fn insert_backfill_job(&self, job: &BackfillJob) -> eyre::Result<()> {
+        self.metrics
+            .observe("insert_backfill_job", async {
+                self.backfill_jobs
+                    .insert_one(job)
+                    .await
+                    .wrap_err("Error inserting backfill job")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the backfill jobs that are not completed, oldest first.
+    /// The bootstrap job is not included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_pending_backfill_jobs(&self) -> eyre::Result<Vec<BackfillJob>> {
+        self.metrics
+            .observe("get_pending_backfill_jobs", async {
+                let mut cursor = self
+                    .backfill_jobs
+                    .find(doc! { "completed_at": null, "bootstrap": { "$ne": true } })
+                    .sort(doc! { "created_at": 1 })
+                    .await?;
+
+                let mut jobs = Vec::new();
+                while cursor.advance().await? {
+                    jobs.push(cursor.deserialize_current()?);
+                }
+
+                Ok(jobs)
+            })
+            .await
+    }
+
+    /// Retrieves the bootstrap job, completed or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_bootstrap_job(&self) -> eyre::Result<Option<BackfillJob>> {
+        self.metrics
+            .observe("get_bootstrap_job", async {
+                self.backfill_jobs
+                    .find_one(doc! { "bootstrap": true })
+                    .await
+                    .wrap_err("Error fetching bootstrap job")
+            })
+            .await
+    }
+
+    /// Records the progress of a backfill job, completing it once every slot
+    /// has been indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - Job with its `next_slot` advanced, or its `end_slot` extended
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails
+    pub async fn update_backfill_progress(&self, job: &BackfillJob) -> eyre::Result<()> {
+        self.metrics
+            .observe("update_backfill_progress", async {
+                let completed_at = (job.next_slot > job.end_slot).then(bson::DateTime::now);
+
+                self.backfill_jobs
+                    .update_one(
+                        doc! { "id": &job.id },
+                        doc! { "$set": {
+                            "end_slot": job.end_slot as i64,
+                            "next_slot": job.next_slot as i64,
+                            "completed_at": completed_at,
+                        } },
+                    )
+                    .await
+                    .wrap_err("Error updating backfill job")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the history backfill job of a watched address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_address_backfill(
+        &self,
+        address: &str,
+    ) -> eyre::Result<Option<AddressBackfill>> {
+        self.metrics
+            .observe("get_address_backfill", async {
+                self.address_backfills
+                    .find_one(doc! { "address": address })
+                    .await
+                    .wrap_err("Error fetching address backfill")
+            })
+            .await
+    }
+
+    /// Records the history backfill job of a watched address, replacing any
+    /// previous progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails
+    pub async fn save_address_backfill(&self, job: &AddressBackfill) -> eyre::Result<()> {
+        self.metrics
+            .observe("save_address_backfill", async {
+                self.address_backfills
+                    .replace_one(doc! { "address": &job.address }, job)
+                    .upsert(true)
+                    .await
+                    .wrap_err("Error saving address backfill")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves the replay that was started but not completed, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_unfinished_replay(&self) -> eyre::Result<Option<ReplayJob>> {
+        self.metrics
+            .observe("get_unfinished_replay", async {
+                self.replay_jobs
+                    .find_one(doc! { "completed_at": null })
+                    .await
+                    .wrap_err("Error fetching unfinished replay")
+            })
+            .await
+    }
+
+    /// Records the progress of a replay, replacing any previous progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails
+    pub async fn save_replay_job(&self, job: &ReplayJob) -> eyre::Result<()> {
+        self.metrics
+            .observe("save_replay_job", async {
+                self.replay_jobs
+                    .replace_one(
+                        doc! {
+                            "from_slot": job.from_slot as i64,
+                            "to_slot": job.to_slot as i64,
+                            "started_at": job.started_at,
+                        },
+                        job,
+                    )
+                    .upsert(true)
+                    .await
+                    .wrap_err("Error saving replay job")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Returns which of the given signatures are already stored.
+    ///
+    /// Only the signature is projected, so the lookup is answered from the
+    /// unique signature index created by [`Storage::create_indexes`] without
+    /// reading the transactions themselves.
+    ///
+    /// # Arguments
+    ///
+    /// * `signatures` - Transaction signatures to look up
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn existing_signatures(
+        &self,
+        signatures: &[String],
+    ) -> eyre::Result<HashSet<String>> {
+        self.metrics
+            .observe("existing_signatures", async {
+                let mut cursor = self
+                    .transactions
+                    .clone_with_type::<Document>()
+                    .find(doc! { "signature": { "$in": signatures } })
+                    .projection(doc! { "signature": 1, "_id": 0 })
+                    .await?;
+
+                let mut existing = HashSet::new();
+                while cursor.advance().await? {
+                    existing.insert(cursor.current().get_str("signature")?.to_string());
+                }
+
+                Ok(existing)
+            })
+            .await
+    }
+
+    /// Computes the daily rollup for a day from the raw transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `day` - Start of the day (midnight UTC) to aggregate
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<DailyStats>` - Metrics for the day, all zero if no transactions were indexed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the aggregation query fails
+    pub async fn compute_daily_stats(&self, day: DateTime<Utc>) -> eyre::Result<DailyStats> {
+        self.metrics
+            .observe("compute_daily_stats", async {
+            let end_of_day = day
+                .checked_add_days(Days::new(1))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+            let pipeline = [
+                doc! { "$match": { "block_time": { "$gte": day, "$lt": end_of_day } } },
+                doc! {
+                    "$facet": {
+                        "totals": [{
+                            "$group": {
+                                "_id": Bson::Null,
+                                "transaction_count": { "$sum": 1 },
+                                "total_fees": { "$sum": "$meta.fee" },
+                                "failed_count": {
+                                    "$sum": {
+                                        "$cond": [{ "$eq": [{ "$ifNull": ["$meta.err", Bson::Null] }, Bson::Null] }, 0, 1]
+                                    }
+                                },
+                            }
+                        }],
+                        "addresses": [
+                            { "$unwind": "$message.accountKeys" },
+                            { "$group": { "_id": "$message.accountKeys" } },
+                            { "$count": "unique_addresses" },
+                        ],
+                        "failures": [
+                            { "$match": { "failure": { "$ne": Bson::Null } } },
+                            {
+                                "$group": {
+                                    "_id": {
+                                        "category": "$failure.category",
+                                        "program_id": "$failure.program_id",
+                                        "code": "$failure.code",
+                                    },
+                                    "count": { "$sum": 1 },
+                                }
+                            },
+                            { "$sort": { "count": -1 } },
+                            { "$limit": MAX_FAILURE_CAUSES },
+                            {
+                                "$project": {
+                                    "_id": 0,
+                                    "category": "$_id.category",
+                                    "program_id": "$_id.program_id",
+                                    "code": "$_id.code",
+                                    "count": 1,
+                                }
+                            },
+                        ],
+                    }
+                },
+            ];
+
+            let mut cursor = self
+                .transactions
+                .aggregate(pipeline)
+                .await
+                .wrap_err("Error aggregating daily stats")?;
+
+            let result = if cursor.advance().await? {
+                cursor.deserialize_current()?
+            } else {
+                Document::new()
+            };
+
+            let first = |facet: &str| {
+                result
+                    .get_array(facet)
+                    .ok()
+                    .and_then(|values| values.first())
+                    .and_then(Bson::as_document)
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let totals = first("totals");
+            let addresses = first("addresses");
+
+            let transaction_count = get_u64(&totals, "transaction_count");
+            let failed_count = get_u64(&totals, "failed_count");
+
+            let failures = result
+                .get_array("failures")
+                .map(|failures| failures.to_vec())
+                .unwrap_or_default()
+                .into_iter()
+                .map(bson::from_bson)
+                .collect::<Result<_, _>>()
+                .wrap_err("Error reading failure breakdown")?;
+
+            Ok(DailyStats {
+                day: bson::DateTime::from_chrono(day),
+                transaction_count,
+                unique_addresses: get_u64(&addresses, "unique_addresses"),
+                total_fees: get_u64(&totals, "total_fees"),
+                failed_count,
+                failure_rate: if transaction_count == 0 {
+                    0.0
+                } else {
+                    failed_count as f64 / transaction_count as f64
+                },
+                failures,
+                updated_at: bson::DateTime::now(),
+            })
+            })
+            .await
+    }
+
+    /// Inserts or replaces the rollup for a day.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write fails
+    pub async fn upsert_daily_stats(&self, stats: &DailyStats) -> eyre::Result<()> {
+        self.metrics
+            .observe("upsert_daily_stats", async {
+                self.daily_stats
+                    .replace_one(doc! { "day": stats.day }, stats)
+                    .upsert(true)
+                    .await
+                    .wrap_err("Error storing daily stats")?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Retrieves daily rollups, most recent first, with pagination support.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Optional first day to include
+    /// * `to` - Optional last day to include
+    /// * `count` - Number of days to return
+    /// * `offset` - Number of days to skip
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<(Vec<DailyStats>, Option<u64>)>` - Rollups and the optional next offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails
+    pub async fn get_daily_stats(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        count: u64,
+        offset: u64,
+    ) -> eyre::Result<(Vec<DailyStats>, Option<u64>)> {
+        self.metrics
+            .observe("get_daily_stats", async {
+                let mut range = Document::new();
+                if let Some(from) = from {
+                    range.insert("$gte", from);
+                }
+                if let Some(to) = to {
+                    range.insert("$lte", to);
+                }
+
+                let mut query = Document::new();
+                if !range.is_empty() {
+                    query.insert("day", range);
+                }
+
+                self.find_page(&self.daily_stats, query, doc! { "day": -1 }, count, offset)
+                    .await
+            })
+            .await
+    }
+}
+
+/// Reads a non-negative integer from an aggregation result, defaulting to zero.
+fn get_u64(document: &Document, key: &str) -> u64 {
+    match document.get(key) {
+        Some(Bson::Int32(v)) => (*v).max(0) as u64,
+        Some(Bson::Int64(v)) => (*v).max(0) as u64,
+        Some(Bson::Double(v)) => v.max(0.0) as u64,
+        _ => 0,
     }
 }
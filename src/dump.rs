@@ -0,0 +1,152 @@
+//! Export and import of indexed data for disaster recovery.
+//!
+//! Exports hold one directory per network, with one newline-delimited file
+//! per collection (`<network>/<collection>.jsonl`). Each line is a document in
+//! MongoDB canonical Extended JSON, which keeps BSON types such as 64-bit
+//! integers and dates intact, so an export can be restored into any MongoDB
+//! deployment, or inspected with standard JSON tools. Checkpoints such as
+//! backfill jobs are collections like any other, so indexing resumes where it
+//! stopped after an import.
+
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use eyre::{bail, WrapErr};
+use log::{info, warn};
+use mongodb::bson::{Bson, Document};
+
+use crate::{domain::storage::COLLECTIONS, network::Networks};
+
+/// Number of documents inserted per request when importing.
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Encodes a document as a line of canonical Extended JSON.
+pub fn encode_document(document: Document) -> String {
+    Bson::Document(document)
+        .into_canonical_extjson()
+        .to_string()
+}
+
+/// Decodes a line of Extended JSON into a document.
+///
+/// # Errors
+///
+/// Returns an error if the line isn't valid Extended JSON or isn't a document
+pub fn decode_document(line: &str) -> eyre::Result<Document> {
+    let value: serde_json::Value = serde_json::from_str(line)?;
+
+    match Bson::try_from(value)? {
+        Bson::Document(document) => Ok(document),
+        other => bail!("Expected a document, got {:?}", other.element_type()),
+    }
+}
+
+/// Writes every network's collections to `dir`.
+///
+/// # Arguments
+///
+/// * `networks` - Networks to export
+/// * `dir` - Directory to write the export to, created if missing
+///
+/// # Errors
+///
+/// Returns an error if a collection cannot be read or a file cannot be written
+pub async fn export(networks: &Networks, dir: &Path) -> eyre::Result<()> {
+    for network in networks.iter() {
+        let dir = dir.join(&network.name);
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| format!("Error creating directory {}", dir.display()))?;
+
+        for name in COLLECTIONS {
+            let path = dir.join(format!("{}.jsonl", name));
+            let mut out = BufWriter::new(
+                File::create(&path)
+                    .wrap_err_with(|| format!("Error creating {}", path.display()))?,
+            );
+
+            let mut cursor = network.storage.collection_documents(name).await?;
+            let mut count = 0u64;
+            while cursor.advance().await? {
+                writeln!(out, "{}", encode_document(cursor.deserialize_current()?))?;
+                count += 1;
+            }
+            out.flush()
+                .wrap_err_with(|| format!("Error writing {}", path.display()))?;
+
+            info!(
+                "Exported {} documents from {}/{}",
+                count, network.name, name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores every network's collections from an export in `dir`.
+///
+/// Documents already present, e.g. when an interrupted import is rerun, are
+/// skipped. Networks and collections missing from the export are left as
+/// they are.
+///
+/// # Arguments
+///
+/// * `networks` - Networks to import into
+/// * `dir` - Directory holding the export
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read, a line cannot be decoded or an
+/// insertion fails
+pub async fn import(networks: &Networks, dir: &Path) -> eyre::Result<()> {
+    for network in networks.iter() {
+        let dir = dir.join(&network.name);
+        if !dir.is_dir() {
+            warn!("No export found for network {}", network.name);
+            continue;
+        }
+
+        for name in COLLECTIONS {
+            let path = dir.join(format!("{}.jsonl", name));
+            if !path.is_file() {
+                continue;
+            }
+
+            let reader = BufReader::new(
+                File::open(&path).wrap_err_with(|| format!("Error opening {}", path.display()))?,
+            );
+
+            let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+            let (mut read, mut inserted) = (0u64, 0u64);
+            for (number, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                batch.push(decode_document(&line).wrap_err_with(|| {
+                    format!("Invalid document at {}:{}", path.display(), number + 1)
+                })?);
+                read += 1;
+
+                if batch.len() == IMPORT_BATCH_SIZE {
+                    inserted += network
+                        .storage
+                        .insert_documents(name, std::mem::take(&mut batch))
+                        .await?;
+                }
+            }
+            inserted += network.storage.insert_documents(name, batch).await?;
+
+            info!(
+                "Imported {} of {} documents into {}/{}",
+                inserted, read, network.name, name
+            );
+        }
+    }
+
+    Ok(())
+}
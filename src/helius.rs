@@ -0,0 +1,260 @@
+//! Helius enhanced transactions enrichment.
+//!
+//! When enabled, signatures of newly indexed transactions are sent to the
+//! Helius enhanced transactions API (`POST <url>?api-key=<key>` with
+//! `{"transactions": [...]}`), and the returned interpretations are stored in
+//! the `enrichments` collection. Enrichment runs off the indexing path:
+//! signatures are queued for a background worker, and failures are logged
+//! and leave the raw transactions untouched.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use log::{debug, warn};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use url::Url;
+
+use crate::{
+    domain::{
+        models::enrichment::{EnrichedNativeTransfer, EnrichedTokenTransfer, Enrichment},
+        storage::Storage,
+    },
+    retry::{is_retryable_http_error, RetryPolicy},
+};
+
+/// Default Helius enhanced transactions endpoint.
+pub const DEFAULT_HELIUS_API_URL: &str = "https://api.helius.xyz/v0/transactions";
+
+/// Largest number of signatures the API accepts per request.
+const MAX_SIGNATURES_PER_REQUEST: usize = 100;
+
+/// Largest number of signature lists waiting to be enriched.
+const ENRICHMENT_QUEUE_CAPACITY: usize = 1024;
+
+/// Number of signature lists enriched at once.
+const ENRICHMENT_CONCURRENCY: usize = 4;
+
+/// Signatures waiting to be enriched, with the storage of their network.
+type EnrichmentRequest = (Arc<Storage>, Vec<String>);
+
+/// Token transfer in the API response.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenTransferEntry {
+    mint: String,
+    from_user_account: Option<String>,
+    to_user_account: Option<String>,
+    from_token_account: Option<String>,
+    to_token_account: Option<String>,
+    #[serde(default)]
+    token_amount: f64,
+}
+
+/// Native transfer in the API response.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeTransferEntry {
+    from_user_account: Option<String>,
+    to_user_account: Option<String>,
+    #[serde(default)]
+    amount: u64,
+}
+
+/// Enhanced transaction in the API response. Fields SolDag doesn't store
+/// are ignored.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnhancedTransaction {
+    signature: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    source: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    token_transfers: Vec<TokenTransferEntry>,
+    #[serde(default)]
+    native_transfers: Vec<NativeTransferEntry>,
+}
+
+impl From<EnhancedTransaction> for Enrichment {
+    fn from(transaction: EnhancedTransaction) -> Self {
+        Self {
+            signature: transaction.signature,
+            kind: transaction.kind.unwrap_or_else(|| "UNKNOWN".to_string()),
+            source: transaction.source,
+            description: transaction.description.unwrap_or_default(),
+            token_transfers: transaction
+                .token_transfers
+                .into_iter()
+                .map(|transfer| EnrichedTokenTransfer {
+                    mint: transfer.mint,
+                    from_user_account: transfer.from_user_account,
+                    to_user_account: transfer.to_user_account,
+                    from_token_account: transfer.from_token_account,
+                    to_token_account: transfer.to_token_account,
+                    token_amount: transfer.token_amount,
+                })
+                .collect(),
+            native_transfers: transaction
+                .native_transfers
+                .into_iter()
+                .map(|transfer| EnrichedNativeTransfer {
+                    from_user_account: transfer.from_user_account,
+                    to_user_account: transfer.to_user_account,
+                    amount: transfer.amount,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Client of the Helius enhanced transactions API.
+struct HeliusApi {
+    /// HTTP client for API requests
+    client: reqwest::Client,
+    /// Enhanced transactions endpoint, with the API key applied
+    url: Url,
+    /// Timeouts and retries of API requests
+    retry_policy: RetryPolicy,
+}
+
+impl HeliusApi {
+    /// Requests the interpretations of a batch of transactions.
+    ///
+    /// Errors don't include the URL, which holds the API key.
+    async fn request(&self, batch: &[String]) -> reqwest::Result<Vec<Option<EnhancedTransaction>>> {
+        async {
+            self.client
+                .post(self.url.clone())
+                .json(&serde_json::json!({ "transactions": batch }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await
+        }
+        .await
+        .map_err(reqwest::Error::without_url)
+    }
+
+    /// Requests the interpretations of a list of transactions, retrying
+    /// failed requests under the retry policy.
+    async fn fetch(&self, signatures: &[String]) -> eyre::Result<Vec<Enrichment>> {
+        let mut enrichments = Vec::with_capacity(signatures.len());
+
+        for batch in signatures.chunks(MAX_SIGNATURES_PER_REQUEST) {
+            let response = self
+                .retry_policy
+                .retry("Fetching enrichments", is_retryable_http_error, || {
+                    self.request(batch)
+                })
+                .await?;
+
+            enrichments.extend(response.into_iter().flatten().map(Enrichment::from));
+        }
+
+        Ok(enrichments)
+    }
+
+    /// Fetches and stores the interpretations of a list of transactions.
+    ///
+    /// Failures are logged rather than returned, as enrichment is optional.
+    async fn enrich(&self, storage: &Storage, signatures: Vec<String>) {
+        let enrichments = match self.fetch(&signatures).await {
+            Ok(enrichments) => enrichments,
+            Err(e) => {
+                warn!(
+                    "Error fetching enrichments of {} transactions: {:?}",
+                    signatures.len(),
+                    e
+                );
+                return;
+            }
+        };
+
+        match storage.upsert_enrichments(&enrichments).await {
+            Ok(()) => debug!("Stored {} enrichments", enrichments.len()),
+            Err(e) => warn!("Error storing enrichments: {:?}", e),
+        }
+    }
+}
+
+/// Enriches indexed transactions in the background.
+pub struct HeliusEnricher {
+    /// Enhanced transactions API
+    api: Arc<HeliusApi>,
+    /// Signatures waiting to be enriched
+    queue: mpsc::Sender<EnrichmentRequest>,
+}
+
+impl HeliusEnricher {
+    /// Creates an enricher backed by the given endpoint and starts its
+    /// background worker.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Enhanced transactions endpoint
+    /// * `api_key` - Helius API key
+    /// * `retry_policy` - Timeouts and retries of API requests
+    pub fn new(mut url: Url, api_key: &str, retry_policy: &RetryPolicy) -> Arc<Self> {
+        url.query_pairs_mut().append_pair("api-key", api_key);
+        let api = Arc::new(HeliusApi {
+            client: retry_policy.http_client(),
+            url,
+            retry_policy: retry_policy.clone(),
+        });
+
+        let (queue, requests) = mpsc::channel(ENRICHMENT_QUEUE_CAPACITY);
+        tokio::spawn(Self::run(api.clone(), requests));
+
+        Arc::new(Self { api, queue })
+    }
+
+    /// Enriches queued signatures, a few lists at a time, until the enricher
+    /// is dropped.
+    async fn run(api: Arc<HeliusApi>, requests: mpsc::Receiver<EnrichmentRequest>) {
+        futures::stream::unfold(requests, |mut requests| async move {
+            requests.recv().await.map(|request| (request, requests))
+        })
+        .for_each_concurrent(ENRICHMENT_CONCURRENCY, |(storage, signatures)| {
+            let api = api.clone();
+            async move { api.enrich(&storage, signatures).await }
+        })
+        .await;
+    }
+
+    /// Requests the interpretations of a list of transactions.
+    ///
+    /// Signatures are sent in batches of at most 100, and failed requests are
+    /// retried under the retry policy. Transactions the API doesn't know are
+    /// omitted from the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request still fails after retrying or its
+    /// response can't be decoded
+    pub async fn fetch(&self, signatures: &[String]) -> eyre::Result<Vec<Enrichment>> {
+        self.api.fetch(signatures).await
+    }
+
+    /// Queues newly indexed transactions to be enriched in the background.
+    ///
+    /// When the queue is full the transactions are skipped with a warning,
+    /// so a slow API never holds up indexing.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - Storage of the transactions' network
+    /// * `signatures` - Signatures of the transactions
+    pub fn enqueue(&self, storage: Arc<Storage>, signatures: Vec<String>) {
+        if signatures.is_empty() {
+            return;
+        }
+
+        let count = signatures.len();
+        if self.queue.try_send((storage, signatures)).is_err() {
+            warn!("Enrichment queue is full, skipping {} transactions", count);
+        }
+    }
+}
@@ -5,30 +5,283 @@
 //! and catch up with missed blocks. The indexer maintains consistency by tracking the
 //! last processed block and ensuring no blocks are missed.
 
-use std::{str::FromStr, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
-use log::{error, info};
-use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use eyre::Context;
+use log::{error, info, warn};
+use serde::Deserialize;
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
 use solana_client::{
     nonblocking::rpc_client::RpcClient,
-    rpc_config::{RpcAccountInfoConfig, RpcBlockConfig},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcBlockConfig, RpcSendTransactionConfig,
+        RpcSimulateTransactionConfig, RpcSupplyConfig, RpcTransactionConfig,
+    },
     rpc_request::RpcRequest,
-    rpc_response::RpcBlockhash,
+    rpc_response::{
+        RpcBlockhash, RpcInflationRate, RpcPrioritizationFee, RpcSimulateTransactionResult,
+        RpcSupply, RpcVoteAccountStatus,
+    },
+};
+use solana_rpc_client_api::{
+    client_error::{Error as ClientError, ErrorKind as ClientErrorKind},
+    custom_error::{
+        JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+    },
+    request::{RpcError, RpcResponseErrorData, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS},
+    response::Response,
 };
-use solana_rpc_client_api::response::Response;
 use solana_sdk::{
     account::Account,
     commitment_config::{CommitmentConfig, CommitmentLevel},
+    epoch_info::EpochInfo,
     pubkey::Pubkey,
+    signature::Signature,
 };
 use solana_transaction_status_client_types::{
-    TransactionDetails, UiConfirmedBlock, UiTransactionEncoding,
+    EncodedTransactionWithStatusMeta, TransactionDetails, UiConfirmedBlock, UiTransactionEncoding,
+};
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
+    task::JoinHandle,
 };
-use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use url::Url;
 
-use crate::domain::{models::transaction::Transaction, storage::Storage};
+use crate::{
+    alerts,
+    cache::{LruCache, TtlCache},
+    config::{Config, ConfigHandle},
+    domain::{
+        models::{
+            address_backfill::AddressBackfill,
+            address_summary::{AddressActivity, CounterpartyLink},
+            alert::Alert,
+            backfill::BackfillJob,
+            balance_change::BalanceChange,
+            block::Block,
+            reward::BlockReward,
+            signature::SignatureRecord,
+            sol_transfer::SolTransfer,
+            supply_event::SupplyEvent,
+            swap::Swap,
+            token_account::TOKEN_ACCOUNT_LEN,
+            transaction::Transaction,
+            transfer::TOKEN_PROGRAM_IDS,
+        },
+        storage::Storage,
+    },
+    helius::HeliusEnricher,
+    leaders::LeaderSchedules,
+    lookup_tables::LookupTables,
+    prices::PriceOracle,
+    retry::{is_retryable_rpc_error, RetryPolicy},
+};
+
+/// How long epoch progress is served from cache.
+const EPOCH_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// How long the validator set is served from cache.
+const VALIDATORS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long supply and inflation figures are served from cache.
+const SUPPLY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// URL scheme selecting the in-process mock RPC server.
+pub const MOCK_RPC_SCHEME: &str = "mock";
+
+/// Default maximum number of missed slots fetched inline.
+pub const DEFAULT_MAX_CATCH_UP_SLOTS: u64 = 1000;
+
+/// Number of slots between backfill progress updates.
+const BACKFILL_PROGRESS_INTERVAL: u64 = 100;
+
+/// Default number of blocks requested per JSON-RPC batch when catching up.
+pub const DEFAULT_CATCH_UP_BATCH_SIZE: usize = 20;
+
+/// Number of recently fetched blocks kept in memory.
+const BLOCK_CACHE_SIZE: usize = 64;
+
+/// Number of recently stored slots remembered to suppress duplicate stores.
+const STORED_SLOTS_CACHE_SIZE: usize = 1024;
+
+/// Number of bytes of account data returned for accounts that aren't decoded.
+const ACCOUNT_DATA_PREVIEW_LEN: usize = 20;
+
+/// Default number of blocks prepared concurrently for storage.
+pub const DEFAULT_PROCESS_WORKERS: usize = 4;
+
+/// Default number of signatures backfilled for each watched address.
+pub const DEFAULT_ADDRESS_BACKFILL_LIMIT: u64 = 10_000;
+
+/// Number of signatures requested per `getSignaturesForAddress` page, the
+/// largest the RPC allows.
+const ADDRESS_SIGNATURES_PAGE_SIZE: u64 = 1000;
+
+/// Default time allowed for draining in-flight blocks on shutdown.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Recently fetched blocks, keyed by slot.
+type BlockCache = LruCache<u64, Arc<UiConfirmedBlock>>;
+
+/// Sending half of the store channel, tracking which slots are queued but
+/// not yet stored.
+#[derive(Clone)]
+struct StoreQueue {
+    /// Channel sender for block processing
+    tx: UnboundedSender<(Arc<UiConfirmedBlock>, u64)>,
+    /// Slots sent but not yet stored
+    pending: Arc<Mutex<BTreeSet<u64>>>,
+}
+
+impl StoreQueue {
+    /// Queues a block for storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store task has stopped
+    fn send(&self, block: Arc<UiConfirmedBlock>, slot: u64) -> eyre::Result<()> {
+        self.pending
+            .lock()
+            .expect("Pending slots poisoned")
+            .insert(slot);
+        self.tx.send((block, slot))?;
+        Ok(())
+    }
+}
+
+/// Groups slots into inclusive ranges of consecutive slots.
+pub fn slot_ranges(slots: impl IntoIterator<Item = u64>) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = vec![];
+
+    for slot in slots {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == slot => *end = slot,
+            _ => ranges.push((slot, slot)),
+        }
+    }
+
+    ranges
+}
+
+/// Resolves once shutdown is requested, or never if no signal is configured.
+async fn shutdown_requested(shutdown: Option<watch::Receiver<bool>>) {
+    match shutdown {
+        Some(mut shutdown) => {
+            // A dropped sender means no one is left to keep the indexer running
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Default bounds, in milliseconds, of the adaptive polling interval.
+pub const DEFAULT_UPDATE_INTERVAL_BOUNDS: (u64, u64) = (100, 2000);
+
+/// Amount of transaction detail requested and stored for each block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Detail {
+    /// Full transactions with metadata
+    #[default]
+    Full,
+    /// Compact signature records only. Transaction filters and alert rules
+    /// don't apply, since account keys aren't fetched.
+    Signatures,
+}
+
+/// How a gap between the last indexed slot and the latest slot is filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUp {
+    /// No slots were missed
+    None,
+    /// Missed slots, inclusive, fetched inline by the catch-up task
+    Inline(u64, u64),
+    /// Missed slots, inclusive, too many to fetch inline and left to a backfill job
+    Backfill(u64, u64),
+}
+
+/// Decides how to fill the gap before `latest_slot`.
+///
+/// # Arguments
+///
+/// * `previous_slot` - Last indexed slot
+/// * `latest_slot` - Slot about to be indexed
+/// * `max_slots` - Largest gap fetched inline
+pub fn plan_catch_up(previous_slot: u64, latest_slot: u64, max_slots: u64) -> CatchUp {
+    if latest_slot <= previous_slot + 1 {
+        return CatchUp::None;
+    }
+
+    let (start, end) = (previous_slot + 1, latest_slot - 1);
+
+    if end - start + 1 > max_slots {
+        CatchUp::Backfill(start, end)
+    } else {
+        CatchUp::Inline(start, end)
+    }
+}
+
+/// Adjusts the polling interval to how far the indexer is behind the tip.
+///
+/// The interval is halved while slots are being missed, doubled while no new
+/// slot was produced since the last poll, and kept while keeping pace.
+///
+/// # Arguments
+///
+/// * `interval` - Current interval in milliseconds
+/// * `lag` - Slots produced since the last poll
+/// * `(min, max)` - Bounds of the interval in milliseconds
+pub fn adapt_update_interval(interval: u64, lag: u64, (min, max): (u64, u64)) -> u64 {
+    let interval = match lag {
+        0 => interval.saturating_mul(2),
+        1 => interval,
+        _ => interval / 2,
+    };
+
+    interval.clamp(min, max)
+}
+
+/// Cached network-level RPC responses.
+struct RpcCache {
+    /// Current epoch progress
+    epoch: TtlCache<EpochInfo>,
+    /// Current and delinquent vote accounts
+    validators: TtlCache<RpcVoteAccountStatus>,
+    /// Total and circulating supply
+    supply: TtlCache<RpcSupply>,
+    /// Current inflation rates
+    inflation: TtlCache<RpcInflationRate>,
+}
+
+impl RpcCache {
+    fn new() -> Self {
+        Self {
+            epoch: TtlCache::new(EPOCH_CACHE_TTL),
+            validators: TtlCache::new(VALIDATORS_CACHE_TTL),
+            supply: TtlCache::new(SUPPLY_CACHE_TTL),
+            inflation: TtlCache::new(SUPPLY_CACHE_TTL),
+        }
+    }
+}
+
+/// Long-term block source, e.g. a Bigtable-backed RPC node, that history is
+/// indexed from before live indexing starts.
+#[derive(Clone)]
+struct Bootstrap {
+    /// RPC client of the long-term source
+    client: Arc<RpcClient>,
+    /// First slot of the history to index
+    from_slot: u64,
+}
 
 /// Core indexer struct managing blockc data processing.
 ///
@@ -40,11 +293,71 @@ pub struct Indexer {
     client: Arc<RpcClient>,
     /// Storage interface for persisting processed data
     storage: Arc<Storage>,
+    /// Runtime configuration, including transaction filters
+    config: ConfigHandle,
+    /// HTTP client for outgoing webhook notifications
+    http: reqwest::Client,
+    /// Optional USD price source for transfer enrichment
+    prices: Option<Arc<PriceOracle>>,
+    /// Optional source of enhanced transaction interpretations
+    enricher: Option<Arc<HeliusEnricher>>,
+    /// Whether the raw JSON of fetched blocks is archived in GridFS
+    archive_raw_blocks: bool,
+    /// Short-lived cache of network-level RPC responses
+    cache: Arc<RpcCache>,
+    /// Cached address lookup tables for resolving versioned transactions
+    lookup_tables: Arc<LookupTables>,
+    /// Cached leader schedules for attributing blocks to validators
+    leaders: Arc<LeaderSchedules>,
+    /// Recently fetched blocks, shared by the main loop and the catch-up task
+    blocks: Arc<BlockCache>,
     /// Last processed block slot for tracking progress
     previous_block_slot: Option<u64>,
+    /// Largest gap of missed slots fetched inline
+    max_catch_up_slots: u64,
+    /// Amount of transaction detail fetched for each block
+    detail: Detail,
+    /// Number of blocks requested per JSON-RPC batch when catching up
+    catch_up_batch_size: usize,
+    /// Number of blocks prepared concurrently for storage
+    process_workers: usize,
+    /// Bounds of the adaptive polling interval in milliseconds
+    update_interval_bounds: (u64, u64),
+    /// Largest number of historical signatures backfilled per watched address
+    address_backfill_limit: u64,
+    /// Source of history indexed before live indexing, if configured
+    bootstrap: Option<Bootstrap>,
+    /// Signal to stop indexing and drain in-flight blocks
+    shutdown: Option<watch::Receiver<bool>>,
+    /// Time allowed for draining in-flight blocks on shutdown
+    shutdown_timeout: Duration,
+    /// Timeouts and retries of RPC calls
+    retry_policy: RetryPolicy,
 }
 
 impl Indexer {
+    /// Creates a new Indexer instance with the default [`RetryPolicy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC endpoint is unreachable or unhealthy; see
+    /// [`Indexer::new_with_retry_policy`]
+    pub async fn new(
+        rpc_url: Url,
+        rpc_api_key: Option<&str>,
+        storage: Arc<Storage>,
+        config: ConfigHandle,
+    ) -> eyre::Result<Self> {
+        Self::new_with_retry_policy(
+            rpc_url,
+            rpc_api_key,
+            storage,
+            config,
+            RetryPolicy::default(),
+        )
+        .await
+    }
+
     /// Creates a new Indexer instance.
     ///
     /// # Arguments
@@ -52,6 +365,8 @@ impl Indexer {
     /// * `rpc_url` - URL of the Solana RPC endpoint
     /// * `rpc_api_key` - Optional API key for RPC access
     /// * `storage` - Storage instance for persisting data
+    /// * `config` - Runtime configuration handle
+    /// * `retry_policy` - Timeouts and retries of RPC calls
     ///
     /// # Returns
     ///
@@ -61,14 +376,26 @@ impl Indexer {
     ///
     /// Returns an error if:
     /// * RPC endpoint is unreachable
-    /// * Health check fails
-    pub async fn new(
+    /// * Health check fails after retrying
+    pub async fn new_with_retry_policy(
         rpc_url: Url,
         rpc_api_key: Option<&str>,
         storage: Arc<Storage>,
+        config: ConfigHandle,
+        retry_policy: RetryPolicy,
     ) -> eyre::Result<Self> {
         let mut rpc_url = rpc_url;
 
+        // Serve mock:// URLs from an in-process mock cluster
+        if rpc_url.scheme() == MOCK_RPC_SCHEME {
+            #[cfg(any(test, feature = "mock-rpc"))]
+            {
+                rpc_url = crate::mock_rpc::spawn().await?;
+            }
+            #[cfg(not(any(test, feature = "mock-rpc")))]
+            eyre::bail!("mock:// RPC URLs require the mock-rpc feature");
+        }
+
         // Construct rpc url if api key is supplied
         if let Some(rpc_api_key) = rpc_api_key {
             rpc_url
@@ -76,17 +403,129 @@ impl Indexer {
                 .append_pair("api-key", rpc_api_key);
         }
 
-        let client = Arc::new(RpcClient::new(rpc_url.to_string()));
+        let client = Arc::new(retry_policy.rpc_client(rpc_url.to_string()));
 
-        client.get_health().await?;
+        retry_policy
+            .retry("RPC health check", is_retryable_rpc_error, || {
+                client.get_health()
+            })
+            .await?;
 
         Ok(Self {
-            client,
+            client: client.clone(),
             storage,
+            config,
+            http: retry_policy.http_client(),
+            prices: None,
+            enricher: None,
+            archive_raw_blocks: false,
+            cache: Arc::new(RpcCache::new()),
+            lookup_tables: Arc::new(LookupTables::new(client.clone())),
+            leaders: Arc::new(LeaderSchedules::new(client.clone())),
+            blocks: Arc::new(LruCache::new(BLOCK_CACHE_SIZE)),
             previous_block_slot: None,
+            max_catch_up_slots: DEFAULT_MAX_CATCH_UP_SLOTS,
+            detail: Detail::default(),
+            catch_up_batch_size: DEFAULT_CATCH_UP_BATCH_SIZE,
+            process_workers: DEFAULT_PROCESS_WORKERS,
+            update_interval_bounds: DEFAULT_UPDATE_INTERVAL_BOUNDS,
+            address_backfill_limit: DEFAULT_ADDRESS_BACKFILL_LIMIT,
+            bootstrap: None,
+            shutdown: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            retry_policy,
         })
     }
 
+    /// Enables USD price enrichment of flagged transfers.
+    pub fn with_prices(mut self, prices: Arc<PriceOracle>) -> Self {
+        self.prices = Some(prices);
+        self
+    }
+
+    /// Enables enrichment of indexed transactions with enhanced
+    /// interpretations.
+    pub fn with_enricher(mut self, enricher: Arc<HeliusEnricher>) -> Self {
+        self.enricher = Some(enricher);
+        self
+    }
+
+    /// Sets the largest gap of missed slots fetched inline. Larger gaps are
+    /// recorded as backfill jobs.
+    pub fn with_max_catch_up_slots(mut self, max_catch_up_slots: u64) -> Self {
+        self.max_catch_up_slots = max_catch_up_slots;
+        self
+    }
+
+    /// Enables archiving the raw JSON of every fetched block in GridFS, so
+    /// blocks can be inspected and reprocessed without fetching them again.
+    pub fn with_raw_block_archive(mut self, archive_raw_blocks: bool) -> Self {
+        self.archive_raw_blocks = archive_raw_blocks;
+        self
+    }
+
+    /// Sets the amount of transaction detail fetched and stored for each block.
+    pub fn with_detail(mut self, detail: Detail) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    /// Sets the number of blocks requested per JSON-RPC batch when catching
+    /// up or backfilling. A size of 1 fetches blocks one request at a time.
+    pub fn with_catch_up_batch_size(mut self, catch_up_batch_size: usize) -> Self {
+        self.catch_up_batch_size = catch_up_batch_size.max(1);
+        self
+    }
+
+    /// Sets the number of blocks whose transactions are decoded concurrently.
+    /// Blocks are still committed to storage in the order they were received.
+    pub fn with_process_workers(mut self, process_workers: usize) -> Self {
+        self.process_workers = process_workers.max(1);
+        self
+    }
+
+    /// Sets the largest number of historical signatures backfilled for each
+    /// watched address. A limit of 0 disables address backfilling.
+    pub fn with_address_backfill_limit(mut self, address_backfill_limit: u64) -> Self {
+        self.address_backfill_limit = address_backfill_limit;
+        self
+    }
+
+    /// Indexes history from `from_slot` onwards from a long-term source, such
+    /// as a Bigtable-backed RPC node or a block archive endpoint, before live
+    /// indexing starts. Progress is checkpointed, and once the bootstrap has
+    /// completed it is not run again.
+    pub fn with_bootstrap(mut self, rpc_url: Url, from_slot: u64) -> Self {
+        self.bootstrap = Some(Bootstrap {
+            client: Arc::new(self.retry_policy.rpc_client(rpc_url.to_string())),
+            from_slot,
+        });
+        self
+    }
+
+    /// Writes processed blocks to `storage` instead of the storage the
+    /// indexer was created with.
+    pub fn with_storage(mut self, storage: Arc<Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Stops the indexer once `shutdown` turns true. Blocks in flight are
+    /// drained for up to `timeout`; slots left over are recorded as backfill
+    /// jobs.
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>, timeout: Duration) -> Self {
+        self.shutdown = Some(shutdown);
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Sets the bounds, in milliseconds, within which the polling interval
+    /// adapts to the indexer's lag behind the tip.
+    pub fn with_update_interval_bounds(mut self, min: u64, max: u64) -> Self {
+        self.update_interval_bounds = (min.max(1), max.max(min.max(1)));
+        self
+    }
+
     /// Starts the indexer service.
     ///
     /// This function initiates three concurrent tasks:
@@ -94,9 +533,19 @@ impl Indexer {
     /// 2. Block data processing and storage
     /// 3. Missing block detection and catch-up
     ///
+    /// When a bootstrap source is configured, history is indexed from it
+    /// first. Indexing resumes after the most recently stored block. Gaps larger
+    /// than the maximum catch-up window are recorded as backfill jobs instead
+    /// of being fetched inline.
+    ///
+    /// When the indexer stops, because shutdown was requested or polling
+    /// failed, blocks already queued are drained before returning.
+    ///
     /// # Arguments
     ///
-    /// * `update_interval` - Milliseconds between block checks
+    /// * `update_interval` - Initial milliseconds between block checks. The
+    ///   interval shortens while the indexer is behind the tip and lengthens
+    ///   while it is caught up, within the configured bounds
     ///
     /// # Returns
     ///
@@ -104,204 +553,1635 @@ impl Indexer {
     pub async fn start(mut self, update_interval: u64) -> eyre::Result<()> {
         info!("Starting indexer service...");
 
+        self.storage.create_indexes().await?;
+
+        if let Some(bootstrap) = self.bootstrap.clone() {
+            if !self.bootstrap(&bootstrap).await? {
+                info!("Stopping indexer service...");
+                return Ok(());
+            }
+        }
+
         let (store_tx, store_rx) = mpsc::unbounded_channel();
-        tokio::spawn(process_block(self.storage.clone(), store_rx));
+        let pending = Arc::new(Mutex::new(BTreeSet::new()));
+        let store_queue = StoreQueue {
+            tx: store_tx,
+            pending: pending.clone(),
+        };
+        let store_handle = tokio::spawn(process_block(
+            self.block_store(),
+            self.config.subscribe(),
+            store_rx,
+            pending.clone(),
+            self.process_workers,
+        ));
 
         let (catch_up_tx, catch_up_rx) = mpsc::unbounded_channel();
-        tokio::spawn(catch_up(self.client.clone(), store_tx.clone(), catch_up_rx));
+        let catch_up_handle = tokio::spawn(catch_up(
+            self.block_fetcher(),
+            self.storage.clone(),
+            store_queue.clone(),
+            catch_up_rx,
+            self.shutdown.clone(),
+        ));
+
+        // Signature-only indexing stores no transactions to backfill
+        let address_handle = (self.detail == Detail::Full && self.address_backfill_limit > 0)
+            .then(|| tokio::spawn(self.clone().watch_addresses(self.config.subscribe())));
+
+        let result = self.poll(update_interval, &store_queue, &catch_up_tx).await;
+
+        // Address backfills record their progress, so they resume on restart
+        if let Some(handle) = address_handle {
+            handle.abort();
+        }
+
+        // Stop producers so the pipeline tasks finish once drained
+        drop(catch_up_tx);
+        drop(store_queue);
+        self.flush(catch_up_handle, store_handle, pending).await?;
+
+        result
+    }
+
+    /// Polls for new blocks until shutdown is requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `update_interval` - Initial milliseconds between block checks
+    /// * `store_queue` - Queue of blocks to store
+    /// * `catch_up_tx` - Channel sender for inclusive ranges of missed slots
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the latest block cannot be fetched or queued
+    async fn poll(
+        &mut self,
+        update_interval: u64,
+        store_queue: &StoreQueue,
+        catch_up_tx: &UnboundedSender<(u64, u64)>,
+    ) -> eyre::Result<()> {
+        let config = get_block_config(self.detail);
 
-        let mut interval =
-            tokio::time::interval(tokio::time::Duration::from_millis(update_interval as u64));
+        if self.previous_block_slot.is_none() {
+            self.previous_block_slot = self.storage.latest_block_slot().await?;
+        }
 
-        let config = get_block_config();
+        let mut update_interval =
+            update_interval.clamp(self.update_interval_bounds.0, self.update_interval_bounds.1);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(update_interval)) => {}
+                _ = shutdown_requested(self.shutdown.clone()) => {
+                    info!("Stopping indexer service...");
+                    return Ok(());
+                }
+            }
 
             // Data fetching and processing
             let latest_blockhash_resp = self
-                .client
-                .send::<Response<RpcBlockhash>>(
-                    RpcRequest::GetLatestBlockhash,
-                    serde_json::json!([]),
+                .retry_policy
+                .retry(
+                    "Fetching the latest blockhash",
+                    is_retryable_rpc_error,
+                    || {
+                        self.client.send::<Response<RpcBlockhash>>(
+                            RpcRequest::GetLatestBlockhash,
+                            serde_json::json!([]),
+                        )
+                    },
                 )
                 .await?;
 
             let latest_block_slot = latest_blockhash_resp.context.slot;
             info!("Latest block slot: {}", latest_block_slot);
 
-            let previous_slot = self.previous_block_slot.get_or_insert_default();
+            if let Some(previous_slot) = self.previous_block_slot {
+                update_interval = adapt_update_interval(
+                    update_interval,
+                    latest_block_slot.saturating_sub(previous_slot),
+                    self.update_interval_bounds,
+                );
 
-            if !(latest_block_slot == *previous_slot + 1 || *previous_slot == 0) {
-                catch_up_tx.send((*previous_slot, latest_block_slot))?;
+                match plan_catch_up(previous_slot, latest_block_slot, self.max_catch_up_slots) {
+                    CatchUp::None => {}
+                    CatchUp::Inline(start, end) => catch_up_tx.send((start, end))?,
+                    CatchUp::Backfill(start, end) => {
+                        warn!(
+                            "Missing {} blocks {} -> {}, recording backfill job",
+                            end - start + 1,
+                            start,
+                            end
+                        );
+                        self.storage
+                            .insert_backfill_job(&BackfillJob::new(start, end))
+                            .await?;
+                    }
+                }
             }
 
-            *previous_slot = latest_block_slot;
+            self.previous_block_slot = Some(latest_block_slot);
 
-            let block =
-                get_block(&self.client, config, latest_block_slot, &mut interval, 1).await?;
+            let block = match self.blocks.get(&latest_block_slot) {
+                Some(block) => block,
+                None => {
+                    let block = Arc::new(
+                        get_block(&self.client, config, latest_block_slot, &self.retry_policy)
+                            .await?,
+                    );
+                    self.blocks.insert(latest_block_slot, block.clone());
+                    block
+                }
+            };
 
-            store_tx.send((block, latest_block_slot))?;
+            store_queue.send(block, latest_block_slot)?;
         }
     }
 
-    /// Retrieves account information from the Solana blockchain.
+    /// Drains the pipeline tasks after their producers have stopped.
+    ///
+    /// The catch-up task stops fetching and the store task finishes the
+    /// blocks already queued, both within the shutdown timeout. Missed slots
+    /// that were never fetched, and fetched blocks that were never stored,
+    /// are recorded as backfill jobs so they don't become silent gaps.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a backfill job cannot be recorded
+    async fn flush(
+        &self,
+        catch_up_handle: JoinHandle<Vec<(u64, u64)>>,
+        store_handle: JoinHandle<()>,
+        pending: Arc<Mutex<BTreeSet<u64>>>,
+    ) -> eyre::Result<()> {
+        let deadline = tokio::time::Instant::now() + self.shutdown_timeout;
+
+        let abort = catch_up_handle.abort_handle();
+        let mut ranges = match tokio::time::timeout_at(deadline, catch_up_handle).await {
+            Ok(Ok(ranges)) => ranges,
+            Ok(Err(e)) => {
+                error!("Catch-up task failed: {:?}", e);
+                vec![]
+            }
+            Err(_) => {
+                warn!("Timed out waiting for the catch-up task");
+                abort.abort();
+                vec![]
+            }
+        };
+
+        let abort = store_handle.abort_handle();
+        if tokio::time::timeout_at(deadline, store_handle)
+            .await
+            .is_err()
+        {
+            warn!("Timed out draining queued blocks");
+            abort.abort();
+        }
+
+        let undrained = std::mem::take(&mut *pending.lock().expect("Pending slots poisoned"));
+        ranges.extend(slot_ranges(undrained));
+
+        for (start, end) in ranges {
+            warn!(
+                "Recording unindexed blocks {} -> {} for backfill",
+                start, end
+            );
+            self.storage
+                .insert_backfill_job(&BackfillJob::new(start, end))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Indexes the remaining slots of a backfill job.
+    ///
+    /// Progress is recorded periodically, so an interrupted job resumes close
+    /// to where it stopped. Skipped slots are passed over.
     ///
     /// # Arguments
     ///
-    /// * `pubkey` - Public key of the account to fetch
+    /// * `job` - Pending backfill job
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block cannot be fetched or stored
+    pub async fn backfill(&self, mut job: BackfillJob) -> eyre::Result<()> {
+        info!(
+            "Backfilling blocks {} -> {} from {}",
+            job.start_slot, job.end_slot, job.next_slot
+        );
+
+        if self.run_job(&self.block_fetcher(), &mut job).await? {
+            info!("Backfilled blocks {} -> {}", job.start_slot, job.end_slot);
+        }
+
+        Ok(())
+    }
+
+    /// Indexes history from the bootstrap source.
+    ///
+    /// The bootstrap job covers the slots from the configured first slot to
+    /// the source's finalized tip when the job was recorded. Once they are
+    /// indexed, the job is extended to the new tip until the remaining gap is
+    /// small enough for the live indexer to catch up on inline.
+    ///
+    /// # Arguments
+    ///
+    /// * `bootstrap` - Long-term block source
     ///
     /// # Returns
     ///
-    /// * `eyre::Result<Account>` - Account data if found
+    /// * `eyre::Result<bool>` - Whether the bootstrap completed, `false` if
+    ///   shutdown was requested first
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// * Public key is invalid
-    /// * Account does not exist
-    /// * RPC request fails
-    pub async fn get_account(&self, pubkey: String) -> eyre::Result<Account> {
-        let pubkey = Pubkey::from_str(&pubkey)?;
-        let config = RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64Zstd),
-            data_slice: Some(UiDataSliceConfig {
-                offset: 0,
-                length: 20,
-            }),
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Finalized,
-                ..Default::default()
-            }),
-            min_context_slot: None,
+    /// Returns an error if the tip, a block or the job cannot be fetched or
+    /// stored
+    async fn bootstrap(&self, bootstrap: &Bootstrap) -> eyre::Result<bool> {
+        let tip = || {
+            bootstrap
+                .client
+                .get_slot_with_commitment(CommitmentConfig::finalized())
         };
 
-        if let Some(account) = self
-            .client
-            .get_account_with_config(&pubkey, config)
-            .await?
-            .value
-        {
-            Ok(account)
-        } else {
-            Err(eyre::eyre!("Account not found"))
-        }
-    }
-}
+        let mut job = match self.storage.get_bootstrap_job().await? {
+            Some(job) if job.completed_at.is_some() => return Ok(true),
+            Some(job) => job,
+            None => {
+                let job = BackfillJob::bootstrap(bootstrap.from_slot, tip().await?);
+                self.storage.insert_backfill_job(&job).await?;
+                job
+            }
+        };
 
-/// Creates a configuration for block fetching.
-///
-/// Sets up the RPC configuration for retrieving block data with full
-/// transaction details and finalized commitment.
-fn get_block_config() -> RpcBlockConfig {
-    RpcBlockConfig {
-        encoding: Some(UiTransactionEncoding::Json),
-        transaction_details: Some(TransactionDetails::Full),
-        rewards: Some(true),
-        commitment: Some(CommitmentConfig {
-            commitment: CommitmentLevel::Finalized,
-        }),
-        max_supported_transaction_version: Some(0),
-    }
-}
+        let fetcher = BlockFetcher {
+            client: bootstrap.client.clone(),
+            ..self.block_fetcher()
+        };
 
-/// Processes blocks and stores transactions.
-///
-/// This function runs in a separate task and handles the storage of
-/// transaction data from processed blocks.
-///
-/// # Arguments
-///
-/// * `storage` - Storage instance for persisting data
-/// * `rx` - Channel receiver for block data
-async fn process_block(storage: Arc<Storage>, mut rx: UnboundedReceiver<(UiConfirmedBlock, u64)>) {
-    let task = |storage: Arc<Storage>, block: UiConfirmedBlock, slot: u64| async move {
-        match &block.transactions {
-            Some(transactions) => {
-                for transaction in transactions.iter() {
-                    let mut transaction = Transaction::try_from(transaction.clone())?;
-
-                    transaction.block_time = block
-                        .block_time
-                        .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
-                        .map(bson::DateTime::from_chrono);
-
-                    storage.insert_transaction(transaction).await?;
-                }
-                info!("Block Slot: {:?} stored", slot);
+        loop {
+            info!(
+                "Bootstrapping blocks {} -> {} from {}",
+                job.start_slot, job.end_slot, job.next_slot
+            );
+
+            if !self.run_job(&fetcher, &mut job).await? {
+                return Ok(false);
             }
-            None => {
-                log::warn!("Block {} has no transactions", block.parent_slot);
+
+            let tip = tip().await?;
+            if tip.saturating_sub(job.end_slot) <= self.max_catch_up_slots {
+                break;
             }
-        }
-        eyre::Ok(())
-    };
 
-    while let Some((block, slot)) = rx.recv().await {
-        if let Err(err) = task(storage.clone(), block, slot).await {
-            error!("Error processing block: {:?}", err);
+            // Blocks produced meanwhile are still too many to catch up on inline
+            job.end_slot = tip;
+            self.storage.update_backfill_progress(&job).await?;
         }
-    }
-}
 
-/// Handles missed block detection and processing.
-///
-/// This function runs in a separate task and ensures no blocks are missed
-/// during normal operation. If gaps are detected, it processes the missing blocks.
-///
-/// # Arguments
-///
-/// * `client` - RPC client for fetching missed blocks
-/// * `store_tx` - Channel sender for block processing
-/// * `rx` - Channel receiver for missed block ranges
-async fn catch_up(
-    client: Arc<RpcClient>,
-    store_tx: UnboundedSender<(UiConfirmedBlock, u64)>,
-    mut rx: UnboundedReceiver<(u64, u64)>,
-) {
-    let task = |client: Arc<RpcClient>,
-                store_tx: UnboundedSender<(UiConfirmedBlock, u64)>,
-                (previous_block_slot, current_block_slot)| async move {
-        let start_slot = previous_block_slot + 1;
-        let end_slot = current_block_slot - 1;
-        info!(
-            "Missing {} blocks {} -> {}",
-            end_slot - start_slot + 1,
-            start_slot,
-            end_slot
-        );
+        info!("Bootstrapped blocks {} -> {}", job.start_slot, job.end_slot);
 
-        let config = get_block_config();
+        Ok(true)
+    }
 
+    /// Fetches and stores the remaining slots of a job, recording its
+    /// progress periodically.
+    ///
+    /// # Arguments
+    ///
+    /// * `fetcher` - Fetcher for the job's block source
+    /// * `job` - Job to run, advanced as slots are indexed
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<bool>` - Whether every slot was indexed, `false` if
+    ///   shutdown was requested first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block cannot be fetched or stored
+    async fn run_job(&self, fetcher: &BlockFetcher, job: &mut BackfillJob) -> eyre::Result<bool> {
+        let store = self.block_store();
         let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(200));
+        let mut saved_slot = job.next_slot;
 
-        for slot in start_slot..=end_slot {
+        while job.next_slot <= job.end_slot {
             interval.tick().await;
-            let block = get_block(&client, config, slot, &mut interval, 5).await?;
 
-            store_tx.send((block, slot))?;
-        }
-        interval.tick().await;
+            if self
+                .shutdown
+                .as_ref()
+                .is_some_and(|shutdown| *shutdown.borrow())
+            {
+                self.storage.update_backfill_progress(job).await?;
+                return Ok(false);
+            }
 
-        eyre::Ok(())
-    };
+            let end_slot = job
+                .end_slot
+                .min(job.next_slot + fetcher.batch_size as u64 - 1);
 
-    while let Some(value) = rx.recv().await {
-        if let Err(err) = task(client.clone(), store_tx.clone(), value).await {
-            error!("Error processing block: {:?}", err);
-        }
-    }
-}
+            for (slot, block) in fetcher.fetch(job.next_slot, end_slot).await? {
+                store
+                    .store_block(&self.config.current(), &block, slot)
+                    .await?;
+            }
 
-/// Fetches a block from the Solana blockchain with retry logic.
+            job.next_slot = end_slot + 1;
+            if job.next_slot - saved_slot >= BACKFILL_PROGRESS_INTERVAL {
+                self.storage.update_backfill_progress(job).await?;
+                saved_slot = job.next_slot;
+            }
+        }
+
+        self.storage.update_backfill_progress(job).await?;
+
+        Ok(true)
+    }
+
+    /// Backfills the history of every watched address, then of each address
+    /// added to the watchlist when the configuration is reloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Runtime configuration providing the watched addresses
+    async fn watch_addresses(self, mut config: watch::Receiver<Arc<Config>>) {
+        loop {
+            let addresses = config.borrow_and_update().filters.addresses.clone();
+
+            for address in addresses {
+                if let Err(e) = self.backfill_address(&address).await {
+                    error!("Error backfilling address {}: {:?}", address, e);
+                }
+            }
+
+            if config.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Indexes the historical transactions of a watched address.
+    ///
+    /// Signatures are paged newest first with `getSignaturesForAddress`, and
+    /// transactions not stored yet are fetched and stored. Progress is
+    /// recorded after every page, so an interrupted backfill resumes where it
+    /// stopped, and a completed one is not repeated. A page is only recorded
+    /// once all of its transactions are stored. Historical transactions
+    /// don't trigger alerts.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Watched address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is invalid, a page of signatures or a
+    /// transaction cannot be fetched after retrying, or a write fails. The
+    /// backfill then resumes from the failed page when it is run again
+    pub async fn backfill_address(&self, address: &str) -> eyre::Result<()> {
+        let mut job = match self.storage.get_address_backfill(address).await? {
+            Some(job) if job.completed_at.is_some() => return Ok(()),
+            Some(job) => job,
+            None => AddressBackfill::new(address.to_string()),
+        };
+
+        info!(
+            "Backfilling history of address {} from signature {}",
+            address, job.fetched
+        );
+
+        let pubkey = Pubkey::from_str(address)?;
+        let store = self.block_store();
+
+        while job.completed_at.is_none() {
+            let remaining = self.address_backfill_limit.saturating_sub(job.fetched);
+            let before = job.before.as_deref().map(Signature::from_str).transpose()?;
+            let page = self
+                .retry_policy
+                .retry(
+                    &format!("Fetching signatures of {}", address),
+                    is_retryable_rpc_error,
+                    || {
+                        self.client.get_signatures_for_address_with_config(
+                            &pubkey,
+                            GetConfirmedSignaturesForAddress2Config {
+                                before,
+                                until: None,
+                                limit: Some(remaining.min(ADDRESS_SIGNATURES_PAGE_SIZE) as usize),
+                                commitment: Some(CommitmentConfig::finalized()),
+                            },
+                        )
+                    },
+                )
+                .await?;
+
+            let signatures: Vec<String> = page.into_iter().map(|s| s.signature).collect();
+            let existing = self.storage.existing_signatures(&signatures).await?;
+            let mut stored = Vec::new();
+
+            for signature in signatures.iter().filter(|s| !existing.contains(*s)) {
+                let transaction = match store.fetch_transaction(signature).await {
+                    Ok(transaction) => transaction,
+                    Err(e) => {
+                        // Progress isn't advanced past this page, so the
+                        // transaction is fetched again when the backfill
+                        // resumes
+                        store.enrich(stored);
+                        return Err(e.wrap_err(format!("Error fetching transaction {}", signature)));
+                    }
+                };
+
+                let derived = DerivedRecords::from_transaction(&transaction, None);
+                // The live indexer may have stored it meanwhile
+                if self.storage.insert_transaction(transaction).await? {
+                    derived.store(&self.storage).await?;
+                    stored.push(signature.clone());
+                }
+            }
+            store.enrich(stored);
+
+            job.advance(&signatures, self.address_backfill_limit);
+            self.storage.save_address_backfill(&job).await?;
+        }
+
+        info!(
+            "Backfilled {} signatures of address {}",
+            job.fetched, address
+        );
+
+        Ok(())
+    }
+
+    /// Runs already fetched blocks through the processing pipeline, as if the
+    /// indexer had just fetched them, and waits until all are handled.
+    ///
+    /// Blocks that fail are recorded as backfill jobs, as in live indexing.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - Blocks with their slots, in the order they are queued
+    pub async fn process_blocks(
+        &self,
+        blocks: Vec<(u64, Arc<UiConfirmedBlock>)>,
+    ) -> eyre::Result<()> {
+        let (store_tx, store_rx) = mpsc::unbounded_channel();
+        let pending = Arc::new(Mutex::new(BTreeSet::new()));
+        let store_queue = StoreQueue {
+            tx: store_tx,
+            pending: pending.clone(),
+        };
+        let store_handle = tokio::spawn(process_block(
+            self.block_store(),
+            self.config.subscribe(),
+            store_rx,
+            pending,
+            self.process_workers,
+        ));
+
+        for (slot, block) in blocks {
+            store_queue.send(block, slot)?;
+        }
+        drop(store_queue);
+
+        Ok(store_handle.await?)
+    }
+
+    /// Replaces the stored data of already fetched blocks by processing them
+    /// again.
+    ///
+    /// Every block is decoded before the data stored for their slots is
+    /// deleted, so a block that can't be decoded leaves storage untouched.
+    /// The blocks are then stored one at a time, stopping at the first
+    /// failure. Alert rules are evaluated as usual, but triggered alerts are
+    /// not sent to the webhook, as they were already delivered when the
+    /// blocks were first indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - Blocks with their slots, in the order they are stored
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<u64>` - Number of stored transactions replaced
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block cannot be decoded or stored. Slots from
+    /// the failed block on are left without data until they are replayed.
+    pub async fn replay(&self, blocks: Vec<(u64, UiConfirmedBlock)>) -> eyre::Result<u64> {
+        let mut config = Config::clone(&self.config.current());
+        config.alerts.webhook_url = None;
+        let config = Arc::new(config);
+
+        let store = self.block_store();
+        let mut prepared = Vec::with_capacity(blocks.len());
+        for (slot, block) in &blocks {
+            let block = store
+                .prepare_block(config.clone(), block, *slot)
+                .await
+                .wrap_err_with(|| format!("Error decoding block {}", slot))?;
+            prepared.push(block);
+        }
+
+        let slots: Vec<u64> = blocks.iter().map(|(slot, _)| *slot).collect();
+        let deleted = self.storage.delete_slots(&slots).await?;
+
+        for block in prepared {
+            let slot = block.slot;
+            store
+                .commit_block(block)
+                .await
+                .wrap_err_with(|| format!("Error storing block {}", slot))?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Returns a fetcher for catching up on ranges of blocks.
+    fn block_fetcher(&self) -> BlockFetcher {
+        BlockFetcher {
+            client: self.client.clone(),
+            http: self.http.clone(),
+            config: get_block_config(self.detail),
+            batch_size: self.catch_up_batch_size,
+            blocks: self.blocks.clone(),
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+
+    /// Returns the dependencies needed to store fetched blocks.
+    fn block_store(&self) -> BlockStore {
+        BlockStore {
+            client: self.client.clone(),
+            lookup_tables: self.lookup_tables.clone(),
+            leaders: self.leaders.clone(),
+            storage: self.storage.clone(),
+            http: self.http.clone(),
+            prices: self.prices.clone(),
+            enricher: self.enricher.clone(),
+            archive_raw_blocks: self.archive_raw_blocks,
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+
+    /// Retrieves account information from the Solana blockchain.
+    ///
+    /// Only a short prefix of the account data is returned, except for SPL
+    /// token accounts, whose base layout is returned in full so it can be
+    /// decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `pubkey` - Public key of the account to fetch
+    /// * `commitment` - Commitment level of the state to read, finalized by
+    ///   default
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Account>` - Account data if found
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * Public key is invalid
+    /// * Account does not exist
+    /// * RPC request fails
+    pub async fn get_account(
+        &self,
+        pubkey: String,
+        commitment: Option<CommitmentLevel>,
+    ) -> eyre::Result<Account> {
+        let pubkey = Pubkey::from_str(&pubkey)?;
+        let commitment = CommitmentConfig {
+            commitment: commitment.unwrap_or(CommitmentLevel::Finalized),
+        };
+        let account = self
+            .get_account_slice(&pubkey, ACCOUNT_DATA_PREVIEW_LEN, commitment)
+            .await?;
+
+        // Token accounts are fetched again with enough data to be decoded
+        if TOKEN_PROGRAM_IDS.contains(&account.owner.to_string().as_str()) {
+            return self
+                .get_account_slice(&pubkey, TOKEN_ACCOUNT_LEN, commitment)
+                .await;
+        }
+
+        Ok(account)
+    }
+
+    /// Fetches an account with the first `length` bytes of its data.
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        length: usize,
+        commitment: CommitmentConfig,
+    ) -> eyre::Result<Account> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            data_slice: Some(UiDataSliceConfig { offset: 0, length }),
+            commitment: Some(commitment),
+            min_context_slot: None,
+        };
+
+        // Sent directly, as `get_account_with_config` folds every failure into
+        // a generic error, hiding whether the node could be reached at all
+        let account = self
+            .retry_policy
+            .retry(
+                &format!("Fetching account {}", pubkey),
+                is_retryable_rpc_error,
+                || {
+                    self.client.send::<Response<Option<UiAccount>>>(
+                        RpcRequest::GetAccountInfo,
+                        serde_json::json!([pubkey.to_string(), config.clone()]),
+                    )
+                },
+            )
+            .await?
+            .value
+            .ok_or_else(|| eyre::eyre!("Account not found"))?;
+
+        account
+            .decode()
+            .ok_or_else(|| eyre::eyre!("Error decoding account {}", pubkey))
+    }
+
+    /// Submits a signed transaction to the network.
+    ///
+    /// The transaction is forwarded unchanged to the RPC endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Base64-encoded, fully signed transaction
+    /// * `config` - Preflight and retry options for the submission
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<String>` - Signature of the submitted transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The RPC node rejects the transaction (including preflight failures)
+    /// * RPC request fails
+    pub async fn send_transaction(
+        &self,
+        transaction: String,
+        config: RpcSendTransactionConfig,
+    ) -> eyre::Result<String> {
+        let config = RpcSendTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..config
+        };
+
+        let signature = self
+            .client
+            .send::<String>(
+                RpcRequest::SendTransaction,
+                serde_json::json!([transaction, config]),
+            )
+            .await?;
+
+        Ok(signature)
+    }
+
+    /// Retrieves the current epoch progress.
+    ///
+    /// Responses are cached for a couple of seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC request fails
+    pub async fn get_epoch_info(&self) -> eyre::Result<EpochInfo> {
+        self.cache
+            .epoch
+            .get_or_try_fetch(|| async { Ok(self.client.get_epoch_info().await?) })
+            .await
+    }
+
+    /// Retrieves the current and delinquent vote accounts.
+    ///
+    /// Responses are cached for 30 seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC request fails
+    pub async fn get_vote_accounts(&self) -> eyre::Result<RpcVoteAccountStatus> {
+        self.cache
+            .validators
+            .get_or_try_fetch(|| async { Ok(self.client.get_vote_accounts().await?) })
+            .await
+    }
+
+    /// Retrieves the total, circulating and non-circulating supply.
+    ///
+    /// The list of non-circulating accounts is not requested. Responses are
+    /// cached for a minute.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC request fails
+    pub async fn get_supply(&self) -> eyre::Result<RpcSupply> {
+        self.cache
+            .supply
+            .get_or_try_fetch(|| async {
+                let config = RpcSupplyConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    exclude_non_circulating_accounts_list: true,
+                };
+                let response = self
+                    .client
+                    .send::<Response<RpcSupply>>(RpcRequest::GetSupply, serde_json::json!([config]))
+                    .await?;
+                Ok(response.value)
+            })
+            .await
+    }
+
+    /// Retrieves the current inflation rates.
+    ///
+    /// Responses are cached for a minute.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC request fails
+    pub async fn get_inflation_rate(&self) -> eyre::Result<RpcInflationRate> {
+        self.cache
+            .inflation
+            .get_or_try_fetch(|| async { Ok(self.client.get_inflation_rate().await?) })
+            .await
+    }
+
+    /// Retrieves the prioritization fees of recent slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `accounts` - If not empty, only fees of transactions locking these accounts are considered
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC request fails
+    pub async fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> eyre::Result<Vec<RpcPrioritizationFee>> {
+        Ok(self.client.get_recent_prioritization_fees(accounts).await?)
+    }
+
+    /// Simulates a transaction without submitting it.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Base64-encoded transaction
+    /// * `config` - Simulation options, including accounts to return post-simulation state for
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Response<RpcSimulateTransactionResult>>` - Logs, compute units,
+    ///   account states and error of the simulated execution, with the slot it ran at
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The RPC node rejects the transaction (e.g. it can't be decoded)
+    /// * RPC request fails
+    pub async fn simulate_transaction(
+        &self,
+        transaction: String,
+        config: RpcSimulateTransactionConfig,
+    ) -> eyre::Result<Response<RpcSimulateTransactionResult>> {
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..config
+        };
+
+        let result = self
+            .client
+            .send(
+                RpcRequest::SimulateTransaction,
+                serde_json::json!([transaction, config]),
+            )
+            .await?;
+
+        Ok(result)
+    }
+}
+
+/// Creates a configuration for block fetching.
+///
+/// Sets up the RPC configuration for retrieving block data with the given
+/// transaction details and finalized commitment. Transactions are requested
+/// base64-encoded, which is cheaper for the RPC node to produce than JSON.
+fn get_block_config(detail: Detail) -> RpcBlockConfig {
+    RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        transaction_details: Some(match detail {
+            Detail::Full => TransactionDetails::Full,
+            Detail::Signatures => TransactionDetails::Signatures,
+        }),
+        rewards: Some(detail == Detail::Full),
+        commitment: Some(CommitmentConfig {
+            commitment: CommitmentLevel::Finalized,
+        }),
+        max_supported_transaction_version: Some(0),
+    }
+}
+
+/// Shared dependencies for storing fetched blocks.
+#[derive(Clone)]
+struct BlockStore {
+    /// RPC client for fetching transaction statuses
+    client: Arc<RpcClient>,
+    /// Address lookup table cache for resolving versioned transactions
+    lookup_tables: Arc<LookupTables>,
+    /// Leader schedule cache for attributing blocks to validators
+    leaders: Arc<LeaderSchedules>,
+    /// Storage instance for persisting data
+    storage: Arc<Storage>,
+    /// HTTP client for alert webhooks
+    http: reqwest::Client,
+    /// Optional price source for valuing flagged transfers
+    prices: Option<Arc<PriceOracle>>,
+    /// Optional source of enhanced transaction interpretations
+    enricher: Option<Arc<HeliusEnricher>>,
+    /// Whether the raw JSON of fetched blocks is archived
+    archive_raw_blocks: bool,
+    /// Timeouts and retries of RPC calls
+    retry_policy: RetryPolicy,
+}
+
+/// A block decoded and checked against the runtime configuration, ready to be
+/// written to storage.
+struct PreparedBlock {
+    /// Slot of the block
+    slot: u64,
+    /// Block header
+    header: Block,
+    /// Rewards paid out in the block
+    rewards: Vec<BlockReward>,
+    /// Transaction data to store
+    content: BlockContent,
+    /// Configuration the block was prepared with
+    config: Arc<Config>,
+}
+
+/// Transaction data of a prepared block.
+enum BlockContent {
+    /// Compact signature records, for blocks fetched with signature details
+    Signatures(Vec<SignatureRecord>),
+    /// Transactions matching the filters, with their position in the block
+    /// and the alerts they triggered
+    Transactions(Vec<(u32, Transaction, Vec<Alert>)>),
+    /// The block has no transactions
+    Empty,
+}
+
+/// Records derived from stored transactions into their own collections.
+#[derive(Default)]
+struct DerivedRecords {
+    /// SOL balance changes
+    balance_changes: Vec<BalanceChange>,
+    /// Normalized DEX swaps
+    swaps: Vec<Swap>,
+    /// SPL token mint and burn events
+    supply_events: Vec<SupplyEvent>,
+    /// Native SOL transfers
+    sol_transfers: Vec<SolTransfer>,
+    /// Activity of the addresses involved, added to their summaries
+    activity: BTreeMap<String, AddressActivity>,
+}
+
+impl DerivedRecords {
+    /// Derives the records of a transaction about to be stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Transaction, with its slot and block time set
+    /// * `position` - Position of the transaction in its block, if known
+    fn from_transaction(transaction: &Transaction, position: Option<u32>) -> Self {
+        let mut activity = BTreeMap::new();
+        AddressActivity::record(&mut activity, transaction);
+
+        Self {
+            balance_changes: BalanceChange::from_transaction(transaction, position),
+            swaps: Swap::from_transaction(transaction).into_iter().collect(),
+            supply_events: SupplyEvent::from_transaction(transaction),
+            sol_transfers: SolTransfer::from_transaction(transaction),
+            activity,
+        }
+    }
+
+    /// Adds the records of other transactions.
+    fn append(&mut self, other: Self) {
+        self.balance_changes.extend(other.balance_changes);
+        self.swaps.extend(other.swaps);
+        self.supply_events.extend(other.supply_events);
+        self.sol_transfers.extend(other.sol_transfers);
+        for (address, activity) in other.activity {
+            self.activity.entry(address).or_default().merge(&activity);
+        }
+    }
+
+    /// Writes the records to storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a write fails
+    async fn store(mut self, storage: &Storage) -> eyre::Result<()> {
+        storage
+            .insert_balance_changes(&self.balance_changes)
+            .await?;
+        storage.insert_swaps(&self.swaps).await?;
+        storage.insert_supply_events(&self.supply_events).await?;
+        storage.insert_sol_transfers(&self.sol_transfers).await?;
+
+        let links = CounterpartyLink::from_transfers(&self.sol_transfers);
+        for link in storage.insert_counterparty_links(&links).await? {
+            self.activity
+                .entry(link.address.clone())
+                .or_default()
+                .new_counterparties += 1;
+        }
+        storage.update_address_summaries(&self.activity).await?;
+
+        Ok(())
+    }
+}
+
+impl BlockStore {
+    /// Stores a block's header and its transactions.
+    ///
+    /// Every block's header and rewards are recorded, and stored transactions are checked
+    /// against the large-transfer alert rules. Blocks fetched with signature
+    /// details only are stored as compact signature records instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Runtime configuration providing the transaction filters and alert rules
+    /// * `block` - Block to store
+    /// * `slot` - Slot of the block
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a transaction cannot be converted or a write fails
+    async fn store_block(
+        &self,
+        config: &Arc<Config>,
+        block: &UiConfirmedBlock,
+        slot: u64,
+    ) -> eyre::Result<()> {
+        self.archive_raw_block(block, slot).await;
+        let prepared = self.prepare_block(config.clone(), block, slot).await?;
+        self.commit_block(prepared).await
+    }
+
+    /// Archives a fetched block as JSON, if enabled.
+    ///
+    /// Blocks are archived before their transactions are decoded, so blocks
+    /// with transactions that fail to decode can be inspected. Failures are logged rather than returned.
+    async fn archive_raw_block(&self, block: &UiConfirmedBlock, slot: u64) {
+        if !self.archive_raw_blocks {
+            return;
+        }
+
+        if let Err(e) = self.storage.insert_raw_block(slot, block).await {
+            warn!("Error archiving raw block {}: {:?}", slot, e);
+        }
+    }
+
+    /// Decodes a block's transactions and derives everything stored with
+    /// them, without writing to storage.
+    ///
+    /// Transactions are normalized, filtered and evaluated against the alert
+    /// rules here, so blocks can be prepared concurrently.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Runtime configuration providing the transaction filters and alert rules
+    /// * `block` - Block to prepare
+    /// * `slot` - Slot of the block
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a transaction cannot be converted or a lookup fails
+    async fn prepare_block(
+        &self,
+        config: Arc<Config>,
+        block: &UiConfirmedBlock,
+        slot: u64,
+    ) -> eyre::Result<PreparedBlock> {
+        let content = if let Some(signatures) = &block.signatures {
+            BlockContent::Signatures(
+                signature_records(&self.client, signatures, slot, block.block_time).await?,
+            )
+        } else if let Some(transactions) = &block.transactions {
+            let block_time = block
+                .block_time
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+                .map(bson::DateTime::from_chrono);
+
+            let mut prepared = vec![];
+            for (position, transaction) in transactions.iter().enumerate() {
+                let mut transaction = self.decode_transaction(transaction.clone()).await?;
+
+                if !config.filters.matches(&transaction) {
+                    continue;
+                }
+
+                transaction.block_time = block_time;
+                transaction.slot = Some(slot);
+
+                let mut triggered = alerts::evaluate(&config.alerts.rules, &transaction, slot);
+
+                if let Some(prices) = &self.prices {
+                    for alert in triggered.iter_mut() {
+                        prices.enrich(&mut alert.transfer, alert.block_time).await;
+                    }
+                }
+
+                prepared.push((position as u32, transaction, triggered));
+            }
+
+            BlockContent::Transactions(prepared)
+        } else {
+            BlockContent::Empty
+        };
+
+        let mut header = Block::from_confirmed(block, slot);
+        header.leader = self
+            .leaders
+            .leader(slot)
+            .await
+            .wrap_err_with(|| format!("Error looking up leader of slot {}", slot))?;
+
+        Ok(PreparedBlock {
+            slot,
+            header,
+            rewards: BlockReward::from_block(block, slot),
+            content,
+            config,
+        })
+    }
+
+    /// Converts an encoded transaction, resolving its lookup table accounts
+    /// and deriving its normalized inner instructions and failure reason.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be converted
+    async fn decode_transaction(
+        &self,
+        encoded: EncodedTransactionWithStatusMeta,
+    ) -> eyre::Result<Transaction> {
+        let mut transaction = Transaction::try_from(encoded)?;
+        transaction.resolved_account_keys = self.lookup_tables.resolve(&mut transaction).await;
+        transaction.inner_instructions = transaction.normalize_inner_instructions();
+        transaction.failure = transaction.classify_failure();
+
+        Ok(transaction)
+    }
+
+    /// Fetches a finalized transaction by signature and decodes it, retrying
+    /// failed fetches under the retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the transaction
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be fetched after retrying
+    /// or converted
+    async fn fetch_transaction(&self, signature: &str) -> eyre::Result<Transaction> {
+        let parsed = Signature::from_str(signature)?;
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+        };
+        let fetched = self
+            .retry_policy
+            .retry(
+                &format!("Fetching transaction {}", signature),
+                is_retryable_rpc_error,
+                || self.client.get_transaction_with_config(&parsed, config),
+            )
+            .await?;
+
+        let mut transaction = self.decode_transaction(fetched.transaction).await?;
+        transaction.block_time = fetched
+            .block_time
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+            .map(bson::DateTime::from_chrono);
+        transaction.slot = Some(fetched.slot);
+
+        Ok(transaction)
+    }
+
+    /// Writes a prepared block to storage and sends its alert notifications.
+    ///
+    /// Transactions already stored are skipped along with their derived
+    /// records and alerts, so committing a block again changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a write fails
+    async fn commit_block(&self, prepared: PreparedBlock) -> eyre::Result<()> {
+        let slot = prepared.slot;
+
+        self.storage.upsert_block(&prepared.header).await?;
+        self.storage.insert_rewards(&prepared.rewards).await?;
+
+        match prepared.content {
+            BlockContent::Signatures(records) => {
+                self.storage.insert_signatures(&records).await?;
+                info!("Block Slot: {:?} stored {} signatures", slot, records.len());
+            }
+            BlockContent::Transactions(transactions) => {
+                let mut derived = DerivedRecords::default();
+                let mut signatures = Vec::with_capacity(transactions.len());
+
+                for (position, transaction, triggered) in transactions {
+                    // Derived before storage takes the transaction, but only
+                    // kept once it is inserted. A transaction already stored,
+                    // e.g. of a reprocessed slot, already has its records and
+                    // alerts.
+                    let records = DerivedRecords::from_transaction(&transaction, Some(position));
+                    let signature = transaction.signature.clone();
+                    if !self.storage.insert_transaction(transaction).await? {
+                        continue;
+                    }
+                    derived.append(records);
+                    signatures.push(signature);
+
+                    if !triggered.is_empty() {
+                        self.storage.insert_alerts(&triggered).await?;
+
+                        if let Some(url) = &prepared.config.alerts.webhook_url {
+                            tokio::spawn(alerts::notify(self.http.clone(), url.clone(), triggered));
+                        }
+                    }
+                }
+                derived.store(&self.storage).await?;
+                self.enrich(signatures);
+                info!("Block Slot: {:?} stored", slot);
+            }
+            BlockContent::Empty => {
+                log::warn!("Block {} has no transactions", prepared.header.parent_slot);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queues newly stored transactions for enrichment in the background, if
+    /// enrichment is enabled.
+    fn enrich(&self, signatures: Vec<String>) {
+        if let Some(enricher) = &self.enricher {
+            enricher.enqueue(self.storage.clone(), signatures);
+        }
+    }
+}
+
+/// Processes blocks and stores transactions.
+///
+/// This function runs in a separate task and handles the storage of
+/// transaction data from processed blocks. Up to `workers` blocks are decoded
+/// concurrently, while a single committer writes them to storage in the order
+/// they were received. The task finishes once the channel is closed and every
+/// queued block has been handled.
+///
+/// Blocks whose slot was stored recently, e.g. when the main loop and the
+/// catch-up task both deliver the slot at the boundary, are skipped. Blocks
+/// that fail to decode or store are recorded as backfill jobs rather than
+/// left as gaps.
+///
+/// # Arguments
+///
+/// * `store` - Dependencies for storing blocks
+/// * `config` - Runtime configuration providing the transaction filters and alert rules
+/// * `rx` - Channel receiver for block data
+/// * `pending` - Slots queued but not yet stored, cleared as blocks are handled
+/// * `workers` - Number of blocks prepared concurrently
+async fn process_block(
+    store: BlockStore,
+    config: watch::Receiver<Arc<Config>>,
+    mut rx: UnboundedReceiver<(Arc<UiConfirmedBlock>, u64)>,
+    pending: Arc<Mutex<BTreeSet<u64>>>,
+    workers: usize,
+) {
+    let stored = LruCache::new(STORED_SLOTS_CACHE_SIZE);
+    let handled = |slot: u64| {
+        pending
+            .lock()
+            .expect("Pending slots poisoned")
+            .remove(&slot);
+    };
+
+    // Handles of in-flight preparations, awaited in arrival order. The bound
+    // limits how many blocks are prepared ahead of the one being committed.
+    let (ordered_tx, mut ordered_rx) =
+        mpsc::channel::<(u64, JoinHandle<eyre::Result<PreparedBlock>>)>(workers.max(1));
+
+    let commit = async {
+        while let Some((slot, handle)) = ordered_rx.recv().await {
+            let result = match handle.await {
+                Ok(Ok(_)) if stored.get(&slot).is_some() => {
+                    info!("Block Slot: {:?} already stored", slot);
+                    Ok(())
+                }
+                Ok(Ok(prepared)) => store.commit_block(prepared).await,
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Ok(()) => {
+                    stored.insert(slot, ());
+                    handled(slot);
+                }
+                Err(err) => {
+                    error!("Error processing block {}: {:?}", slot, err);
+                    // Left pending if it can't be recorded, so it is recorded
+                    // when the pipeline is flushed instead
+                    match store
+                        .storage
+                        .insert_backfill_job(&BackfillJob::new(slot, slot))
+                        .await
+                    {
+                        Ok(()) => handled(slot),
+                        Err(e) => error!("Error recording block {} for backfill: {:?}", slot, e),
+                    }
+                }
+            }
+        }
+    };
+
+    let dispatch = async {
+        while let Some((block, slot)) = rx.recv().await {
+            if stored.get(&slot).is_some() {
+                info!("Block Slot: {:?} already stored", slot);
+                handled(slot);
+                continue;
+            }
+
+            let config = config.borrow().clone();
+            let store = store.clone();
+            let handle = tokio::spawn(async move {
+                store.archive_raw_block(&block, slot).await;
+                store.prepare_block(config, &block, slot).await
+            });
+
+            if ordered_tx.send((slot, handle)).await.is_err() {
+                break;
+            }
+        }
+
+        drop(ordered_tx);
+    };
+
+    tokio::join!(dispatch, commit);
+}
+
+/// Builds the compact records of a block's transactions.
+///
+/// Blocks fetched with signature details don't include transaction statuses,
+/// so they are looked up in batches.
+///
+/// # Errors
+///
+/// Returns an error if a signature is malformed or the status lookup fails
+async fn signature_records(
+    client: &RpcClient,
+    signatures: &[String],
+    slot: u64,
+    block_time: Option<i64>,
+) -> eyre::Result<Vec<SignatureRecord>> {
+    let block_time = block_time
+        .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+        .map(bson::DateTime::from_chrono);
+
+    let mut records = Vec::with_capacity(signatures.len());
+
+    for chunk in signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+        let parsed = chunk
+            .iter()
+            .map(|signature| Signature::from_str(signature))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let statuses = client
+            .get_signature_statuses_with_history(&parsed)
+            .await?
+            .value;
+
+        records.extend(
+            chunk
+                .iter()
+                .zip(statuses)
+                .map(|(signature, status)| SignatureRecord {
+                    signature: signature.clone(),
+                    slot,
+                    block_time,
+                    err: status.and_then(|status| status.err),
+                }),
+        );
+    }
+
+    Ok(records)
+}
+
+/// Handles missed block detection and processing.
+///
+/// This function runs in a separate task and ensures no blocks are missed
+/// during normal operation. If gaps are detected, it processes the missing blocks.
+/// Batches that can't be fetched are recorded as backfill jobs, like gaps too
+/// large to catch up on inline.
+///
+/// # Arguments
+///
+/// * `fetcher` - Fetcher for missed blocks
+/// * `storage` - Storage for recording backfill jobs
+/// * `store_queue` - Queue of blocks to store
+/// * `rx` - Channel receiver for inclusive ranges of missed slots
+/// * `shutdown` - Signal to stop fetching
+///
+/// # Returns
+///
+/// * `Vec<(u64, u64)>` - Ranges left unfetched because of a shutdown, or a
+///   failed fetch that couldn't be recorded
+async fn catch_up(
+    fetcher: BlockFetcher,
+    storage: Arc<Storage>,
+    store_queue: StoreQueue,
+    mut rx: UnboundedReceiver<(u64, u64)>,
+    shutdown: Option<watch::Receiver<bool>>,
+) -> Vec<(u64, u64)> {
+    let stopping = || shutdown.as_ref().is_some_and(|shutdown| *shutdown.borrow());
+    let mut unfetched = vec![];
+
+    while let Some((start_slot, end_slot)) = rx.recv().await {
+        if stopping() {
+            unfetched.push((start_slot, end_slot));
+            continue;
+        }
+
+        info!(
+            "Missing {} blocks {} -> {}",
+            end_slot - start_slot + 1,
+            start_slot,
+            end_slot
+        );
+
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(200));
+
+        let mut slot = start_slot;
+        while slot <= end_slot {
+            if stopping() {
+                unfetched.push((slot, end_slot));
+                break;
+            }
+
+            interval.tick().await;
+
+            let batch_end = end_slot.min(slot + fetcher.batch_size as u64 - 1);
+            let blocks = match fetcher.fetch(slot, batch_end).await {
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    error!("Error fetching blocks {} -> {}: {:?}", slot, batch_end, err);
+                    warn!(
+                        "Recording unindexed blocks {} -> {} for backfill",
+                        slot, batch_end
+                    );
+                    if let Err(e) = storage
+                        .insert_backfill_job(&BackfillJob::new(slot, batch_end))
+                        .await
+                    {
+                        error!("Error recording backfill job: {:?}", e);
+                        unfetched.push((slot, batch_end));
+                    }
+                    slot = batch_end + 1;
+                    continue;
+                }
+            };
+
+            // The store task has stopped, so nothing more can be queued
+            if let Err(err) = blocks
+                .into_iter()
+                .try_for_each(|(slot, block)| store_queue.send(block, slot))
+            {
+                error!("Error queueing blocks: {:?}", err);
+                unfetched.push((slot, end_slot));
+                break;
+            }
+
+            slot = batch_end + 1;
+        }
+    }
+
+    unfetched
+}
+
+/// Fetches ranges of blocks, batching `getBlock` calls into JSON-RPC batch
+/// requests to save round trips.
+#[derive(Clone)]
+struct BlockFetcher {
+    /// RPC client for single block fetches and retries
+    client: Arc<RpcClient>,
+    /// HTTP client for batch requests
+    http: reqwest::Client,
+    /// Block fetch configuration
+    config: RpcBlockConfig,
+    /// Number of blocks requested per batch
+    batch_size: usize,
+    /// Recently fetched blocks, served without a request
+    blocks: Arc<BlockCache>,
+    /// Timeouts and retries of block fetches
+    retry_policy: RetryPolicy,
+}
+
+/// Response to one call of a JSON-RPC batch request.
+#[derive(Deserialize)]
+pub(crate) struct BatchResponse {
+    /// Index of the call in the batch
+    id: usize,
+    /// Block, if the call succeeded
+    result: Option<UiConfirmedBlock>,
+    /// Error, if the call failed
+    error: Option<BatchError>,
+}
+
+/// Error of a failed JSON-RPC call.
+#[derive(Deserialize)]
+pub(crate) struct BatchError {
+    /// JSON-RPC error code
+    code: i64,
+    /// Error description
+    message: String,
+}
+
+impl BlockFetcher {
+    /// Fetches the blocks of an inclusive slot range, in slot order.
+    ///
+    /// Recently fetched blocks are served from memory and skipped slots are
+    /// left out. A single missing block is fetched under the retry policy,
+    /// and blocks that fail within a batch are retried individually under it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a block cannot be fetched after retrying
+    async fn fetch(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+    ) -> eyre::Result<Vec<(u64, Arc<UiConfirmedBlock>)>> {
+        let cached: Vec<_> = (start_slot..=end_slot)
+            .map(|slot| (slot, self.blocks.get(&slot)))
+            .collect();
+        let missing: Vec<u64> = cached
+            .iter()
+            .filter(|(_, block)| block.is_none())
+            .map(|(slot, _)| *slot)
+            .collect();
+
+        let mut results = match missing.as_slice() {
+            [] => vec![],
+            [slot] => vec![get_block(&self.client, self.config, *slot, &self.retry_policy).await],
+            _ => self.fetch_batch(&missing).await.unwrap_or_else(|e| {
+                warn!(
+                    "Batch request for blocks {} -> {} failed: {:?}",
+                    start_slot, end_slot, e
+                );
+                missing
+                    .iter()
+                    .map(|_| Err(eyre::eyre!("Batch request failed")))
+                    .collect()
+            }),
+        }
+        .into_iter();
+
+        let mut blocks = Vec::with_capacity(cached.len());
+        for (slot, block) in cached {
+            if let Some(block) = block {
+                blocks.push((slot, block));
+                continue;
+            }
+
+            let result = match results.next().expect("One result per missing slot") {
+                Ok(block) => Ok(block),
+                // A single block was already fetched under the retry policy
+                Err(e) if missing.len() == 1 || is_skipped_slot(&e) => Err(e),
+                Err(_) => get_block(&self.client, self.config, slot, &self.retry_policy).await,
+            };
+
+            match result {
+                Ok(block) => {
+                    let block = Arc::new(block);
+                    self.blocks.insert(slot, block.clone());
+                    blocks.push((slot, block));
+                }
+                Err(e) if is_skipped_slot(&e) => info!("Slot {} was skipped", slot),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    /// Requests blocks in a single JSON-RPC batch request.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Vec<eyre::Result<UiConfirmedBlock>>>` - Result of each
+    ///   call, in the order of `slots`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch request itself fails
+    async fn fetch_batch(
+        &self,
+        slots: &[u64],
+    ) -> eyre::Result<Vec<eyre::Result<UiConfirmedBlock>>> {
+        let request: Vec<_> = slots
+            .iter()
+            .enumerate()
+            .map(|(id, slot)| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "getBlock",
+                    "params": [slot, self.config],
+                })
+            })
+            .collect();
+
+        let responses: Vec<BatchResponse> = self
+            .http
+            .post(self.client.url())
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(batch_results(slots.len(), responses))
+    }
+}
+
+/// Matches the responses of a JSON-RPC batch request to its calls.
+///
+/// # Arguments
+///
+/// * `calls` - Number of calls in the batch
+/// * `responses` - Responses, in any order
+///
+/// # Returns
+///
+/// * `Vec<eyre::Result<UiConfirmedBlock>>` - Result of each call, by call id.
+///   Calls without a response are reported as failed
+pub(crate) fn batch_results(
+    calls: usize,
+    responses: Vec<BatchResponse>,
+) -> Vec<eyre::Result<UiConfirmedBlock>> {
+    let mut results: Vec<eyre::Result<UiConfirmedBlock>> = (0..calls)
+        .map(|_| Err(eyre::eyre!("Missing from batch response")))
+        .collect();
+
+    for response in responses {
+        let Some(result) = results.get_mut(response.id) else {
+            continue;
+        };
+        *result = match (response.result, response.error) {
+            (Some(block), _) => Ok(block),
+            (None, Some(error)) => Err(ClientError::from(ClientErrorKind::RpcError(
+                RpcError::RpcResponseError {
+                    code: error.code,
+                    message: error.message,
+                    data: RpcResponseErrorData::Empty,
+                },
+            ))
+            .into()),
+            (None, None) => Err(eyre::eyre!("Empty batch response")),
+        };
+    }
+
+    results
+}
+
+/// Fetches a block from the Solana blockchain, retrying failures other than
+/// skipped slots under the retry policy.
 ///
 /// # Arguments
 ///
 /// * `client` - RPC client for block fetching
 /// * `config` - Block fetch configuration
 /// * `slot` - Block slot to fetch
-/// * `interval` - Time between retries
-/// * `retries` - Number of retry attempts
+/// * `retry_policy` - Retries and backoff of failed fetches
 ///
 /// # Returns
 ///
@@ -314,19 +2194,33 @@ async fn get_block(
     client: &RpcClient,
     config: RpcBlockConfig,
     slot: u64,
-    interval: &mut tokio::time::Interval,
-    retries: u8,
+    retry_policy: &RetryPolicy,
 ) -> eyre::Result<UiConfirmedBlock> {
-    let mut error = None;
+    let block = retry_policy
+        .retry(
+            &format!("Fetching block {}", slot),
+            // Skipped slots will never have a block
+            |e| !is_skipped_slot_error(e) && is_retryable_rpc_error(e),
+            || client.get_block_with_config(slot, config),
+        )
+        .await?;
 
-    for _ in 0..=retries {
-        let block = client.get_block_with_config(slot, config).await;
-        match block {
-            Ok(block) => return Ok(block),
-            Err(e) => error = Some(e),
-        }
-        interval.tick().await;
-    }
+    Ok(block)
+}
+
+/// Checks whether an RPC error reports that no block was produced in the slot.
+fn is_skipped_slot_error(error: &ClientError) -> bool {
+    matches!(
+        error.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_SLOT_SKIPPED
+                || *code == JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+    )
+}
 
-    Err(error.unwrap().into())
+/// Checks whether a block fetch failed because the slot was skipped.
+pub(crate) fn is_skipped_slot(error: &eyre::Report) -> bool {
+    error
+        .downcast_ref::<ClientError>()
+        .is_some_and(is_skipped_slot_error)
 }
@@ -0,0 +1,129 @@
+//! Slot leader attribution from the cluster's leader schedule.
+//!
+//! The leader schedule of an epoch is fixed before the epoch starts, so each
+//! epoch's schedule is fetched once and kept while blocks of that epoch are
+//! indexed. Blocks of an epoch looked up concurrently, as at an epoch
+//! boundary, wait on the same fetch, and a failed fetch is attempted again by
+//! the next lookup. Schedules the RPC node no longer serves are cached as
+//! unknown, so blocks of old epochs are stored without a leader instead of
+//! being retried.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{config::RpcLeaderScheduleConfig, response::RpcLeaderSchedule};
+use solana_sdk::{commitment_config::CommitmentConfig, epoch_schedule::EpochSchedule};
+use tokio::sync::OnceCell;
+
+/// Maximum number of epoch schedules kept in the cache before it is cleared.
+const MAX_CACHED_EPOCHS: usize = 4;
+
+/// Leader identity of every slot of an epoch, by slot index, or `None` if
+/// the schedule is unknown.
+type EpochLeaders = Option<Arc<Vec<String>>>;
+
+/// Cache of epoch leader schedules.
+pub struct LeaderSchedules {
+    /// RPC client for fetching the epoch and leader schedules
+    client: Arc<RpcClient>,
+    /// Epoch schedule of the cluster, fetched on first use
+    epoch_schedule: OnceCell<EpochSchedule>,
+    /// Leaders of each epoch, keyed by epoch, fetched on first use
+    cache: Mutex<HashMap<u64, Arc<OnceCell<EpochLeaders>>>>,
+}
+
+impl LeaderSchedules {
+    /// Creates an empty leader schedule cache.
+    pub fn new(client: Arc<RpcClient>) -> Self {
+        Self {
+            client,
+            epoch_schedule: OnceCell::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the identity of the validator scheduled to produce a slot.
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<Option<String>>` - Validator identity, or `None` if the
+    ///   RPC node doesn't serve the schedule of the slot's epoch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the epoch or leader schedule cannot be fetched
+    pub async fn leader(&self, slot: u64) -> eyre::Result<Option<String>> {
+        let epoch_schedule = self
+            .epoch_schedule
+            .get_or_try_init(|| self.client.get_epoch_schedule())
+            .await?;
+        let epoch = epoch_schedule.get_epoch(slot);
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+
+        let cell = {
+            let mut cache = self.cache.lock().expect("Leader schedule cache poisoned");
+            if !cache.contains_key(&epoch) && cache.len() >= MAX_CACHED_EPOCHS {
+                cache.clear();
+            }
+            cache.entry(epoch).or_default().clone()
+        };
+
+        let leaders = cell
+            .get_or_try_init(|| async {
+                let schedule = self
+                    .client
+                    .get_leader_schedule_with_config(
+                        Some(first_slot),
+                        RpcLeaderScheduleConfig {
+                            identity: None,
+                            commitment: Some(CommitmentConfig::finalized()),
+                        },
+                    )
+                    .await?;
+
+                Ok::<_, eyre::Report>(schedule.map(|schedule| {
+                    Arc::new(leaders_by_slot_index(
+                        schedule,
+                        epoch_schedule.get_slots_in_epoch(epoch),
+                    ))
+                }))
+            })
+            .await?;
+
+        Ok(leaders.as_ref().and_then(|leaders| {
+            leaders
+                .get((slot - first_slot) as usize)
+                .filter(|leader| !leader.is_empty())
+                .cloned()
+        }))
+    }
+}
+
+/// Inverts a leader schedule into the leader of each slot of the epoch.
+///
+/// # Arguments
+///
+/// * `schedule` - Slot indexes of each validator identity, as returned by
+///   `getLeaderSchedule`
+/// * `slots_in_epoch` - Number of slots in the epoch
+///
+/// # Returns
+///
+/// * `Vec<String>` - Leader identity by slot index; slots missing from the
+///   schedule have an empty identity
+pub fn leaders_by_slot_index(schedule: RpcLeaderSchedule, slots_in_epoch: u64) -> Vec<String> {
+    let mut leaders = vec![String::new(); slots_in_epoch as usize];
+
+    for (identity, slot_indexes) in schedule {
+        for index in slot_indexes {
+            if let Some(leader) = leaders.get_mut(index) {
+                leader.clone_from(&identity);
+            }
+        }
+    }
+
+    leaders
+}
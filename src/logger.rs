@@ -1,8 +1,87 @@
 //! Logger configuration module for SolDag.
-use std::io::Write;
+//!
+//! Log lines written while serving an API request are prefixed with the
+//! request's id, so everything logged by the handler and the storage layer
+//! can be correlated with the request a client reports.
+use std::{future::Future, io::Write, sync::OnceLock};
 
-use env_logger::{fmt::Color, Builder, Env};
-use log::Level;
+use arc_swap::ArcSwap;
+use env_logger::{fmt::Color, Builder, Env, Logger};
+use log::{Level, Log, Metadata, Record};
+
+/// Default log filter used when `RUST_LOG` is not set.
+const DEFAULT_FILTER: &str = "warn,info";
+
+/// Logger whose filters can be replaced at runtime.
+struct ReloadableLogger {
+    /// Currently active logger
+    inner: ArcSwap<Logger>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.load().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.load().log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.load().flush()
+    }
+}
+
+static LOGGER: OnceLock<ReloadableLogger> = OnceLock::new();
+
+tokio::task_local! {
+    /// Id of the API request being served by the current task
+    static REQUEST_ID: String;
+}
+
+/// Runs `future` with `request_id` attached to everything it logs.
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// Returns the id of the API request being served by the current task, if any.
+pub fn request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Creates a logger builder with the application's output format.
+///
+/// Filters are read from the environment, and `filters`, when supplied,
+/// override them.
+fn builder(filters: Option<&str>) -> Builder {
+    let mut builder = Builder::from_env(Env::default().default_filter_or(DEFAULT_FILTER));
+
+    if let Some(filters) = filters {
+        builder.parse_filters(filters);
+    }
+
+    builder.format(|buf, record| {
+        let request = request_id()
+            .map(|id| format!("[{}] ", id))
+            .unwrap_or_default();
+
+        writeln!(
+            buf,
+            "{}{} {}{}",
+            match (record.level(), buf.style().set_bold(true)) {
+                (Level::Warn, style) => style.set_color(Color::Yellow).value("warning"),
+                (Level::Error, style) => style.set_color(Color::Red).value("error"),
+                (Level::Info, style) => style.set_color(Color::Green).value("info"),
+                (level, style) => style.value(level.as_str()),
+            },
+            buf.style().set_bold(true).value(":"),
+            request,
+            record.args()
+        )
+    });
+
+    builder
+}
 
 /// Sets up the application's logging configuration.
 ///
@@ -13,20 +92,26 @@ use log::Level;
 /// - Red for errors
 /// - Default color for other levels
 pub fn setup() {
-    Builder::from_env(Env::default().default_filter_or("warn,info"))
-        .format(|buf, record| {
-            writeln!(
-                buf,
-                "{}{} {}",
-                match (record.level(), buf.style().set_bold(true)) {
-                    (Level::Warn, style) => style.set_color(Color::Yellow).value("warning"),
-                    (Level::Error, style) => style.set_color(Color::Red).value("error"),
-                    (Level::Info, style) => style.set_color(Color::Green).value("info"),
-                    (level, style) => style.value(level.as_str()),
-                },
-                buf.style().set_bold(true).value(":"),
-                record.args()
-            )
-        })
-        .init();
+    let logger = builder(None).build();
+    let max_level = logger.filter();
+
+    let logger = LOGGER.get_or_init(|| ReloadableLogger {
+        inner: ArcSwap::from_pointee(logger),
+    });
+
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Replaces the active log filters.
+///
+/// `filters` uses the same syntax as `RUST_LOG`. Passing `None` restores the
+/// filters from the environment. Does nothing if [`setup`] has not been called.
+pub fn set_filters(filters: Option<&str>) {
+    if let Some(reloadable) = LOGGER.get() {
+        let logger = builder(filters).build();
+        log::set_max_level(logger.filter());
+        reloadable.inner.store(logger.into());
+    }
 }
@@ -0,0 +1,147 @@
+//! Address lookup table resolution for versioned transactions.
+//!
+//! Version 0 transactions reference some of their accounts through address
+//! lookup tables rather than listing them in the message. RPC nodes usually
+//! report the loaded addresses in the transaction metadata, but not all of
+//! them do, so missing addresses are resolved by fetching the lookup table
+//! accounts. Tables can only be extended, never modified, so cached tables
+//! stay valid and are only refetched when an index is out of range.
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use eyre::{eyre, OptionExt};
+use log::warn;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{address_lookup_table::state::AddressLookupTable, pubkey::Pubkey};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiAddressTableLookup, UiLoadedAddresses,
+};
+
+use crate::domain::models::transaction::Transaction;
+
+/// Maximum number of tables kept in the cache before it is cleared.
+const MAX_CACHED_TABLES: usize = 10_000;
+
+/// Cache of address lookup table contents.
+pub struct LookupTables {
+    /// RPC client for fetching lookup table accounts
+    client: Arc<RpcClient>,
+    /// Addresses stored in each table, keyed by table address
+    cache: Mutex<HashMap<String, Arc<Vec<String>>>>,
+}
+
+impl LookupTables {
+    /// Creates an empty lookup table cache.
+    pub fn new(client: Arc<RpcClient>) -> Self {
+        Self {
+            client,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolves the complete account list of a transaction.
+    ///
+    /// Addresses loaded from lookup tables are taken from the transaction
+    /// metadata when the RPC node reported them, and fetched otherwise. In the
+    /// latter case the metadata is filled in as well. A table that can't be
+    /// fetched only affects its transaction: the failure is logged and the
+    /// static account keys are returned, leaving the metadata untouched.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Static account keys, followed by the writable and
+    ///   readonly loaded addresses if they could be resolved
+    pub async fn resolve(&self, transaction: &mut Transaction) -> Vec<String> {
+        let lookups = match &transaction.message.address_table_lookups {
+            Some(lookups) if !lookups.is_empty() => lookups.clone(),
+            _ => return transaction.message.account_keys.clone(),
+        };
+
+        if !matches!(transaction.meta.loaded_addresses, OptionSerializer::Some(_)) {
+            match self.load(&lookups).await {
+                Ok(loaded) => transaction.meta.loaded_addresses = OptionSerializer::Some(loaded),
+                Err(e) => {
+                    warn!(
+                        "Error resolving lookup tables of transaction {}, using its static account keys: {:?}",
+                        transaction.signature, e
+                    );
+                    return transaction.message.account_keys.clone();
+                }
+            }
+        }
+
+        transaction
+            .account_keys()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Looks up the addresses a transaction loads from lookup tables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a lookup table cannot be fetched or doesn't
+    /// contain a referenced index
+    async fn load(&self, lookups: &[UiAddressTableLookup]) -> eyre::Result<UiLoadedAddresses> {
+        let mut loaded = UiLoadedAddresses {
+            writable: vec![],
+            readonly: vec![],
+        };
+
+        for lookup in lookups {
+            let indexes = lookup
+                .writable_indexes
+                .iter()
+                .chain(&lookup.readonly_indexes);
+            let max_index = indexes.max().copied().unwrap_or_default() as usize;
+            let table = self.table(&lookup.account_key, max_index).await?;
+
+            for (indexes, addresses) in [
+                (&lookup.writable_indexes, &mut loaded.writable),
+                (&lookup.readonly_indexes, &mut loaded.readonly),
+            ] {
+                addresses.extend(indexes.iter().map(|i| table[*i as usize].clone()));
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Returns the addresses of a lookup table holding at least `max_index + 1`
+    /// entries, fetching it if it isn't cached or has been extended since.
+    async fn table(&self, key: &str, max_index: usize) -> eyre::Result<Arc<Vec<String>>> {
+        if let Some(table) = self
+            .cache
+            .lock()
+            .expect("Lookup table cache poisoned")
+            .get(key)
+        {
+            if max_index < table.len() {
+                return Ok(table.clone());
+            }
+        }
+
+        let account = self.client.get_account(&Pubkey::from_str(key)?).await?;
+        let table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|e| eyre!("Invalid lookup table {}: {}", key, e))?;
+        let addresses: Arc<Vec<String>> =
+            Arc::new(table.addresses.iter().map(Pubkey::to_string).collect());
+
+        addresses
+            .get(max_index)
+            .ok_or_eyre("Lookup table index out of range")?;
+
+        let mut cache = self.cache.lock().expect("Lookup table cache poisoned");
+        if cache.len() >= MAX_CACHED_TABLES {
+            cache.clear();
+        }
+        cache.insert(key.to_string(), addresses.clone());
+
+        Ok(addresses)
+    }
+}
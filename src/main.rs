@@ -8,24 +8,58 @@
 //! The application is built with reliability in mind, featuring automatic service
 //! recovery and concurrent processing of blockchain data.
 
+use std::{sync::Arc, time::Duration};
+
 use clap::Parser;
-use log::{error, info};
+use log::{error, info, warn};
 
-use domain::storage::Storage;
+use config::ConfigHandle;
+use domain::{
+    migrations::TRANSACTION_SCHEMA_VERSION,
+    models::{backfill::BackfillJob, replay_job::ReplayJob},
+    storage::Storage,
+};
+use mongodb::bson;
+use network::{Network, Networks};
 
+mod aggregator;
+mod alerts;
 mod api;
+mod archive;
+mod bench;
+mod cache;
 mod cli;
+mod config;
+mod daemon;
 mod domain;
+mod dump;
+mod helius;
 pub mod indexer;
+mod leaders;
 mod logger;
+mod lookup_tables;
+#[cfg(any(test, feature = "mock-rpc"))]
+mod mock_rpc;
+mod network;
+mod prices;
+mod retry;
+mod stream;
+mod supervisor;
 #[cfg(test)]
 mod tests;
 
+/// Extra time services get to stop after the indexers' shutdown timeout,
+/// for recording undrained slots.
+const SHUTDOWN_MARGIN: Duration = Duration::from_secs(5);
+
+/// Number of archived blocks loaded and reprocessed at a time by `replay`.
+const REPLAY_BATCH_SIZE: usize = 100;
+
 /// Initializes application services and starts processing.
 ///
 /// This function sets up the environment, establishes database connections,
-/// and starts both the indexer and API services. It includes automatic
-/// retry logic for service recovery.
+/// and starts both the indexer and API services. Services are run by a
+/// supervisor that restarts them with exponential backoff.
 ///
 /// # Returns
 ///
@@ -37,45 +71,395 @@ mod tests;
 /// * Environment setup fails
 /// * Database connection fails
 /// * Service initialization fails
+/// * A service exhausts its restart budget
 async fn init() -> eyre::Result<()> {
     color_eyre::install()?;
 
     dotenv::dotenv().ok();
 
     let args = cli::Args::parse();
+    let retry_policy = args.retry_policy();
+    let storage_options = args.storage_options();
+    let enricher = args.helius_api_key()?.map(|api_key| {
+        helius::HeliusEnricher::new(args.helius_api_url.clone(), api_key, &retry_policy)
+    });
 
-    let storage = Storage::init("soldag").await?;
+    let _pid_file = args
+        .pid_file
+        .as_deref()
+        .map(daemon::PidFile::create)
+        .transpose()?;
 
-    let indexer =
-        indexer::Indexer::new(args.rpc_url, args.rpc_api_key.as_deref(), storage.clone()).await?;
-    let mut indexer_handle = tokio::spawn(indexer.clone().start(args.update_interval));
+    let config = ConfigHandle::init(args.config)?;
+    tokio::spawn(config.clone().reload_on_sighup());
 
-    let listener = || tokio::net::TcpListener::bind(args.api_listen);
+    let prices = args
+        .price_api_url
+        .clone()
+        .map(|url| prices::PriceOracle::new(url, args.price_api_key.clone(), &retry_policy));
 
-    let mut api_handle = tokio::spawn(api::start(
-        listener().await?,
-        storage.clone(),
-        indexer.clone(),
-    ));
+    let with_events = |mut network: Network| {
+        network.indexer = network
+            .indexer
+            .with_max_catch_up_slots(args.max_catch_up_slots)
+            .with_catch_up_batch_size(args.catch_up_batch_size)
+            .with_process_workers(args.process_workers)
+            .with_address_backfill_limit(args.address_backfill_limit)
+            .with_update_interval_bounds(args.min_update_interval, args.max_update_interval)
+            .with_detail(args.detail)
+            .with_raw_block_archive(args.archive_raw_blocks);
 
-    // retry 3 times
-    for _ in 1..=3 {
-        tokio::select! {
-            res = &mut indexer_handle => {
-                if let Ok(Err(e)) = res {
-                    error!("Indexer service failed: {}", e)
-                }
-                indexer_handle = tokio::spawn(indexer.clone().start(args.update_interval))
-            }
-            res = &mut api_handle => {
-                if let Ok(Err(e)) = res {
-                    error!("API service failed: {}", e)
-                }
-                api_handle = tokio::spawn(api::start(listener().await?, storage.clone(), indexer.clone()))
+        if args.change_stream {
+            network.with_events()
+        } else {
+            network
+        }
+    };
+
+    let mut primary = with_events(
+        Network::init(
+            &args.network_name,
+            Storage::init_with_options(network::PRIMARY_DATABASE, storage_options.clone()).await?,
+            args.rpc_url,
+            args.rpc_api_key.as_deref(),
+            config.clone(),
+            prices.clone(),
+            retry_policy.clone(),
+        )
+        .await?,
+    );
+    if let Some((url, from_slot)) = args.bootstrap_rpc_url.zip(args.bootstrap_from_slot) {
+        primary.indexer = primary.indexer.with_bootstrap(url, from_slot);
+    }
+    if let Some(enricher) = enricher {
+        primary.indexer = primary.indexer.with_enricher(enricher);
+    }
+
+    let mut networks = Networks::new(primary);
+
+    for network in args.networks {
+        networks.insert(with_events(
+            Network::init(
+                &network.name,
+                Storage::init_with_options(
+                    &format!("{}_{}", network::PRIMARY_DATABASE, network.name),
+                    storage_options.clone(),
+                )
+                .await?,
+                network.rpc_url,
+                args.rpc_api_key.as_deref(),
+                config.clone(),
+                prices.clone(),
+                retry_policy.clone(),
+            )
+            .await?,
+        ))?;
+    }
+
+    let networks = Arc::new(networks);
+
+    for network in networks.iter() {
+        network
+            .storage
+            .metrics
+            .set_slow_query_threshold(Duration::from_millis(args.slow_query_threshold));
+        network.storage.ping().await?;
+
+        if network.storage.check_schema_version().await? {
+            warn!(
+                "Network {} has transactions older than schema version {}; they are upgraded as they are read, or all at once with `soldag migrate`",
+                network.name,
+                TRANSACTION_SCHEMA_VERSION
+            );
+        }
+
+        if let Some(job) = network.storage.get_unfinished_replay().await? {
+            warn!(
+                "Network {} has an interrupted replay: slots {}..={} may be missing data until `soldag replay --from-slot {} --to-slot {}` is run again",
+                network.name,
+                job.next_slot,
+                job.to_slot,
+                job.from_slot,
+                job.to_slot
+            );
+        }
+    }
+
+    match args.command {
+        Some(cli::Command::Backfill(backfill)) => return run_backfill(&networks, backfill).await,
+        Some(cli::Command::Migrate) => return run_migrate(&networks).await,
+        Some(cli::Command::Replay(replay)) => return run_replay(&networks, replay).await,
+        Some(cli::Command::Export(export)) => return dump::export(&networks, &export.out).await,
+        Some(cli::Command::Import(import)) => return dump::import(&networks, &import.input).await,
+        Some(cli::Command::Bench(bench)) => return bench::run(&networks, bench).await,
+        None => {}
+    }
 
+    let mut supervisor = supervisor::Supervisor::new(
+        supervisor::RestartPolicy {
+            initial_backoff: Duration::from_millis(args.restart_initial_backoff),
+            max_backoff: Duration::from_millis(args.restart_max_backoff),
+            max_restarts: args.max_restarts,
+            window: Duration::from_secs(args.restart_window),
+        },
+        args.supervisor_webhook_url,
+    )
+    .with_shutdown_timeout(Duration::from_secs(args.shutdown_timeout) + SHUTDOWN_MARGIN);
+    let shutdown_timeout = Duration::from_secs(args.shutdown_timeout);
+
+    let sinks = if args.change_stream {
+        Some(
+            stream::Sinks::new(
+                args.stream_webhook_url,
+                args.kafka_brokers,
+                &args.kafka_topic,
+                &retry_policy,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    for network in networks.iter() {
+        if let Some(sinks) = &sinks {
+            let bridge = stream::Bridge::new(network, sinks.clone());
+            supervisor.spawn(format!("bridge:{}", network.name), move || {
+                bridge.clone().start()
+            });
+        }
+
+        if args.no_indexer {
+            continue;
+        }
+
+        let indexer = network
+            .indexer
+            .clone()
+            .with_shutdown(supervisor.shutdown_signal(), shutdown_timeout);
+        let update_interval = args.update_interval;
+        supervisor.spawn(format!("indexer:{}", network.name), move || {
+            indexer.clone().start(update_interval)
+        });
+
+        let storage = network.storage.clone();
+        let aggregation_interval = Duration::from_secs(args.aggregation_interval);
+        supervisor.spawn(format!("aggregator:{}", network.name), move || {
+            aggregator::start(storage.clone(), aggregation_interval)
+        });
+
+        if let Some(url) = &args.archive_url {
+            let archiver = archive::Archiver::new(
+                url,
+                &network.name,
+                network.storage.clone(),
+                archive::ArchiveOptions {
+                    interval: Duration::from_secs(args.archive_interval),
+                    delay: Duration::from_secs(args.archive_delay),
+                    delete: args.archive_delete,
+                },
+            )?;
+            supervisor.spawn(format!("archiver:{}", network.name), move || {
+                archiver.clone().start()
+            });
+        }
+    }
+
+    let primary = networks
+        .get(None)
+        .expect("Primary network is always registered");
+    let tenants =
+        api::tenants::Tenants::load(primary.storage.clone(), args.require_api_key).await?;
+
+    let state = api::AppState {
+        networks: networks.clone(),
+        config: config.clone(),
+        admin_token: args.admin_token,
+        tenants: Arc::new(tenants),
+    };
+
+    let api_listen = args.api_listen;
+    supervisor.spawn("api", move || {
+        let state = state.clone();
+        async move { api::start(tokio::net::TcpListener::bind(api_listen).await?, state).await }
+    });
+
+    // Storage and RPC endpoints have been checked and all services started
+    daemon::notify_ready();
+    if let Some(interval) = daemon::watchdog_interval() {
+        tokio::spawn(daemon::watchdog(interval));
+    }
+
+    let terminate = daemon::terminated();
+    supervisor
+        .run(async {
+            if let Err(e) = terminate.await {
+                error!("Error waiting for termination signals: {:?}", e);
+                std::future::pending::<()>().await;
             }
+        })
+        .await
+}
+
+/// Runs the pending backfill jobs of every network.
+///
+/// # Arguments
+///
+/// * `networks` - Networks to backfill
+/// * `args` - Optional slot range to record as a new job on the primary network
+///
+/// # Errors
+///
+/// Returns an error if a job cannot be recorded, loaded or completed
+async fn run_backfill(networks: &Networks, args: cli::BackfillArgs) -> eyre::Result<()> {
+    if let (Some(from_slot), Some(to_slot)) = (args.from_slot, args.to_slot) {
+        eyre::ensure!(
+            from_slot <= to_slot,
+            "--from-slot must not exceed --to-slot"
+        );
+
+        let primary = networks
+            .get(None)
+            .expect("Primary network is always registered");
+        primary
+            .storage
+            .insert_backfill_job(&BackfillJob::new(from_slot, to_slot))
+            .await?;
+    }
+
+    for network in networks.iter() {
+        let jobs = network.storage.get_pending_backfill_jobs().await?;
+        info!(
+            "{} pending backfill jobs for network {}",
+            jobs.len(),
+            network.name
+        );
+
+        for job in jobs {
+            network.indexer.backfill(job).await?;
         }
     }
+
+    Ok(())
+}
+
+/// Runs the `migrate` command: builds the indexes of every network, removing
+/// the duplicates unique indexes reject, and upgrades its stored
+/// transactions to the current schema version.
+///
+/// # Errors
+///
+/// Returns an error if an index can't be built, or a transaction can't be
+/// upgraded or written back
+async fn run_migrate(networks: &Networks) -> eyre::Result<()> {
+    for network in networks.iter() {
+        network.storage.create_indexes().await?;
+        let migrated = network.storage.migrate_transactions().await?;
+        info!(
+            "Migrated {} transactions of network {} to schema version {}",
+            migrated, network.name, TRANSACTION_SCHEMA_VERSION
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs the `replay` command: reprocesses the archived raw blocks of a slot
+/// range in batches, replacing what was stored for each slot, then
+/// recomputes the daily statistics of the days the blocks belong to.
+///
+/// Progress is recorded after every batch, so an interrupted replay of the
+/// same range resumes from the batch that didn't complete. Slots without an
+/// archived block are left untouched.
+///
+/// # Errors
+///
+/// Returns an error if the range is invalid, the network is unknown, another
+/// replay is unfinished, no block in the range is archived, or a batch fails
+/// to load or store
+async fn run_replay(networks: &Networks, args: cli::ReplayArgs) -> eyre::Result<()> {
+    eyre::ensure!(
+        args.from_slot <= args.to_slot,
+        "--from-slot must not exceed --to-slot"
+    );
+
+    let network = networks
+        .get(args.network.as_deref())
+        .ok_or_else(|| eyre::eyre!("Unknown network {:?}", args.network))?;
+
+    let mut job = match network.storage.get_unfinished_replay().await? {
+        Some(job) if (job.from_slot, job.to_slot) == (args.from_slot, args.to_slot) => {
+            info!(
+                "Resuming the interrupted replay of slots {}..={} from slot {}",
+                job.from_slot, job.to_slot, job.next_slot
+            );
+            job
+        }
+        Some(job) => eyre::bail!(
+            "The replay of slots {}..={} was interrupted at slot {}; run it again to complete it first",
+            job.from_slot,
+            job.to_slot,
+            job.next_slot
+        ),
+        None => ReplayJob::new(args.from_slot, args.to_slot),
+    };
+
+    let slots = network
+        .storage
+        .get_raw_block_slots(job.next_slot, job.to_slot)
+        .await?;
+    eyre::ensure!(
+        !slots.is_empty() || job.next_slot > job.from_slot,
+        "No archived blocks in slots {}..={}; blocks are archived with --archive-raw-blocks",
+        args.from_slot,
+        args.to_slot
+    );
+    info!(
+        "Replaying {} archived blocks of network {}",
+        slots.len(),
+        network.name
+    );
+    network.storage.save_replay_job(&job).await?;
+
+    for batch in slots.chunks(REPLAY_BATCH_SIZE) {
+        let mut blocks = Vec::with_capacity(batch.len());
+        for &slot in batch {
+            let block = network
+                .storage
+                .get_raw_block(slot)
+                .await?
+                .ok_or_else(|| eyre::eyre!("Archived block {} disappeared", slot))?;
+            blocks.push((slot, block));
+        }
+
+        let days: Vec<bson::DateTime> = blocks
+            .iter()
+            .filter_map(|(_, block)| block.block_time)
+            .filter_map(|time| chrono::DateTime::from_timestamp(time, 0))
+            .map(|time| bson::DateTime::from_chrono(aggregator::start_of_day(time)))
+            .collect();
+
+        let deleted = network.indexer.replay(blocks).await?;
+        job.advance(batch[batch.len() - 1], days);
+        network.storage.save_replay_job(&job).await?;
+        info!(
+            "Replayed slots {}..={}, replacing {} transactions",
+            batch[0],
+            batch[batch.len() - 1],
+            deleted
+        );
+    }
+
+    for day in &job.days {
+        let stats = network.storage.compute_daily_stats(day.to_chrono()).await?;
+        network.storage.upsert_daily_stats(&stats).await?;
+    }
+    info!("Recomputed daily statistics of {} days", job.days.len());
+
+    job.next_slot = job.to_slot.saturating_add(1);
+    job.completed_at = Some(bson::DateTime::now());
+    network.storage.save_replay_job(&job).await?;
+
     Ok(())
 }
 
@@ -89,9 +473,11 @@ async fn main() -> eyre::Result<()> {
 
     info!("SolDag started, initializing services....");
 
-    init().await?;
-
     if let Err(e) = init().await {
+        if e.downcast_ref::<supervisor::ServiceFailed>().is_some() {
+            std::process::exit(supervisor::EXIT_SERVICE_FAILED);
+        }
+
         error!("Initialization error: {}", e);
         std::process::exit(1);
     }
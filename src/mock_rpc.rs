@@ -0,0 +1,596 @@
+//! Deterministic mock of the Solana JSON-RPC API.
+//!
+//! Selecting an RPC URL with the `mock://` scheme starts this server in-process
+//! instead of connecting to a cluster, so the indexer, the API and the tests
+//! run fully offline. The mock chain starts at [`GENESIS_SLOT`] with
+//! [`INITIAL_SLOTS`] slots of history and produces a slot every
+//! [`SLOT_DURATION`]. Every tenth slot is skipped, and all other
+//! blocks hold [`TRANSACTIONS_PER_BLOCK`] SOL transfers between [`WALLETS`]
+//! wallets, with [`VALIDATORS`] validators taking turns as leader. Block
+//! contents, keys and signatures are derived from the slot alone, so the same
+//! slot always yields the same block.
+//!
+//! Only the methods the indexer uses are implemented. Transactions are
+//! returned in the requested encoding: JSON, base58 or base64.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use axum::{extract::State, routing::post, Json, Router};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_account_decoder_client_types::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_rpc_client_api::{
+    custom_error::{JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED},
+    response::{
+        Response, RpcBlockhash, RpcConfirmedTransactionStatusWithSignature, RpcInflationRate,
+        RpcPrioritizationFee, RpcResponseContext, RpcSupply, RpcVoteAccountInfo,
+        RpcVoteAccountStatus,
+    },
+};
+use solana_sdk::{
+    bs58,
+    epoch_info::EpochInfo,
+    epoch_schedule::EpochSchedule,
+    hash::{hashv, Hash},
+    instruction::CompiledInstruction,
+    message::Message,
+    pubkey::Pubkey,
+    signature::Signature,
+    system_program,
+};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionBinaryEncoding,
+    TransactionConfirmationStatus, UiCompiledInstruction, UiConfirmedBlock, UiMessage,
+    UiRawMessage, UiTransaction, UiTransactionEncoding, UiTransactionStatusMeta,
+};
+use url::Url;
+
+/// First slot of the mock chain.
+pub const GENESIS_SLOT: u64 = 300_000_000;
+
+/// Number of slots already produced when the server starts.
+pub const INITIAL_SLOTS: u64 = 1000;
+
+/// Unix timestamp of the genesis slot.
+pub const GENESIS_TIME: i64 = 1_741_737_600;
+
+/// Time between slots.
+pub const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// Number of transactions in each block.
+pub const TRANSACTIONS_PER_BLOCK: usize = 4;
+
+/// Number of wallets transfers are made between.
+pub const WALLETS: u64 = 8;
+
+/// Balance of every wallet, in lamports.
+pub const WALLET_BALANCE: u64 = 1_000_000_000_000;
+
+/// Fee charged by every transaction, in lamports.
+pub const FEE: u64 = 5000;
+
+/// Number of validators taking turns as slot leader.
+pub const VALIDATORS: u64 = 4;
+
+/// Number of consecutive slots each leader produces.
+const LEADER_SLOTS: u64 = 4;
+
+/// Slots per epoch of the mock chain, kept small so leader schedules are too.
+const SLOTS_PER_EPOCH: u64 = 8192;
+
+/// System program ID.
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+/// Owner of native programs.
+const NATIVE_LOADER_ID: &str = "NativeLoader1111111111111111111111111111111";
+
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for malformed parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// Result of a JSON-RPC call: a result value, or an error code and message.
+type CallResult = Result<Value, (i64, String)>;
+
+/// State of a running mock server.
+#[derive(Clone)]
+struct MockChain {
+    /// Time the server started
+    started: Instant,
+}
+
+impl MockChain {
+    /// Returns the latest slot produced.
+    fn tip(&self) -> u64 {
+        GENESIS_SLOT
+            + INITIAL_SLOTS
+            + (self.started.elapsed().as_millis() / SLOT_DURATION.as_millis()) as u64
+    }
+
+    /// Wraps a value with the response context of the latest slot.
+    fn with_context<T: serde::Serialize>(&self, value: T) -> Value {
+        json!(Response {
+            context: RpcResponseContext {
+                slot: self.tip(),
+                api_version: None,
+            },
+            value,
+        })
+    }
+}
+
+/// Starts a mock RPC server on a local port.
+///
+/// The server runs until the Tokio runtime it was started on shuts down.
+///
+/// # Returns
+///
+/// * `eyre::Result<Url>` - HTTP URL of the server
+///
+/// # Errors
+///
+/// Returns an error if no local port can be bound
+pub async fn spawn() -> eyre::Result<Url> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+    let addr: SocketAddr = listener.local_addr()?;
+
+    let app = Router::new()
+        .route("/", post(handle))
+        .with_state(MockChain {
+            started: Instant::now(),
+        });
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    Ok(Url::parse(&format!("http://{}", addr))?)
+}
+
+/// Handles a single JSON-RPC request or a batch of them.
+async fn handle(State(chain): State<MockChain>, Json(request): Json<Value>) -> Json<Value> {
+    Json(match request {
+        Value::Array(requests) => Value::Array(
+            requests
+                .iter()
+                .map(|request| respond(&chain, request))
+                .collect(),
+        ),
+        request => respond(&chain, &request),
+    })
+}
+
+/// Builds the JSON-RPC response to a request.
+fn respond(chain: &MockChain, request: &Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = request.get("params").cloned().unwrap_or(json!([]));
+
+    match call(chain, method, &params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "error": { "code": code, "message": message },
+            "id": id,
+        }),
+    }
+}
+
+/// Dispatches a JSON-RPC call.
+fn call(chain: &MockChain, method: &str, params: &Value) -> CallResult {
+    let tip = chain.tip();
+
+    match method {
+        "getHealth" => Ok(json!("ok")),
+        "getVersion" => Ok(json!({ "solana-core": "2.2.0", "feature-set": 0 })),
+        "getSlot" | "getBlockHeight" => Ok(json!(tip)),
+        "getLatestBlockhash" => Ok(chain.with_context(RpcBlockhash {
+            blockhash: blockhash(tip).to_string(),
+            last_valid_block_height: tip + 150,
+        })),
+        "getBlock" => get_block(tip, params),
+        "getTransaction" => Ok(get_transaction(
+            tip,
+            &string_param(params, 0)?,
+            encoding(params.get(1)),
+        )),
+        "getSignaturesForAddress" => get_signatures_for_address(tip, params),
+        "getAccountInfo" => Ok(chain.with_context(account(&string_param(params, 0)?))),
+        "getBalance" => Ok(chain.with_context(
+            account(&string_param(params, 0)?).map_or(0, |account| account.lamports),
+        )),
+        "getEpochInfo" => Ok(json!(EpochInfo {
+            epoch: tip / SLOTS_PER_EPOCH,
+            slot_index: tip % SLOTS_PER_EPOCH,
+            slots_in_epoch: SLOTS_PER_EPOCH,
+            absolute_slot: tip,
+            block_height: tip,
+            transaction_count: Some((tip - GENESIS_SLOT) * TRANSACTIONS_PER_BLOCK as u64),
+        })),
+        "getEpochSchedule" => Ok(json!(EpochSchedule::custom(
+            SLOTS_PER_EPOCH,
+            SLOTS_PER_EPOCH,
+            false
+        ))),
+        "getLeaderSchedule" => Ok(get_leader_schedule(tip, params)),
+        "getVoteAccounts" => Ok(json!(RpcVoteAccountStatus {
+            current: (0..VALIDATORS)
+                .map(|i| RpcVoteAccountInfo {
+                    vote_pubkey: key("vote", i).to_string(),
+                    node_pubkey: key("validator", i).to_string(),
+                    activated_stake: 1_000_000_000_000_000,
+                    commission: 5,
+                    epoch_vote_account: true,
+                    epoch_credits: vec![(tip / SLOTS_PER_EPOCH, 1_000_000, 990_000)],
+                    last_vote: tip,
+                    root_slot: tip.saturating_sub(32),
+                })
+                .collect(),
+            delinquent: vec![],
+        })),
+        "getInflationRate" => Ok(json!(RpcInflationRate {
+            total: 0.045,
+            validator: 0.045,
+            foundation: 0.0,
+            epoch: tip / SLOTS_PER_EPOCH,
+        })),
+        "getSupply" => Ok(chain.with_context(RpcSupply {
+            total: 600_000_000_000_000_000,
+            circulating: 500_000_000_000_000_000,
+            non_circulating: 100_000_000_000_000_000,
+            non_circulating_accounts: vec![],
+        })),
+        "getRecentPrioritizationFees" => Ok(json!((0..10)
+            .map(|i| RpcPrioritizationFee {
+                slot: tip - i,
+                prioritization_fee: 1000 * (i % 3),
+            })
+            .collect::<Vec<_>>())),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    }
+}
+
+/// Reads a string parameter.
+fn string_param(params: &Value, index: usize) -> Result<String, (i64, String)> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or((INVALID_PARAMS, format!("Missing parameter {}", index)))
+}
+
+/// Reads the transaction encoding of a call's configuration, given either
+/// as an object or as the encoding alone. Defaults to JSON, like the RPC.
+fn encoding(config: Option<&Value>) -> UiTransactionEncoding {
+    let encoding = match config {
+        Some(Value::Object(config)) => config.get("encoding"),
+        encoding => encoding,
+    };
+
+    encoding
+        .and_then(|encoding| serde_json::from_value(encoding.clone()).ok())
+        .unwrap_or(UiTransactionEncoding::Json)
+}
+
+/// Handles `getBlock`, honoring the `encoding`, `transactionDetails` and
+/// `rewards` options.
+fn get_block(tip: u64, params: &Value) -> CallResult {
+    let slot = params
+        .get(0)
+        .and_then(Value::as_u64)
+        .ok_or((INVALID_PARAMS, "Missing slot".to_string()))?;
+    let config = params.get(1).cloned().unwrap_or(json!({}));
+
+    let mut block = block(tip, slot, encoding(Some(&config)))?;
+    match config.get("transactionDetails").and_then(Value::as_str) {
+        Some("signatures") => {
+            block.transactions = None;
+            block.signatures = Some(
+                (0..TRANSACTIONS_PER_BLOCK)
+                    .map(|index| signature(slot, index).to_string())
+                    .collect(),
+            );
+        }
+        Some("none") => block.transactions = None,
+        _ => {}
+    }
+    if config.get("rewards").and_then(Value::as_bool) == Some(false) {
+        block.rewards = None;
+    }
+
+    Ok(json!(block))
+}
+
+/// Handles `getLeaderSchedule` for the epoch of the given slot, or of the
+/// latest slot.
+fn get_leader_schedule(tip: u64, params: &Value) -> Value {
+    let slot = params.get(0).and_then(Value::as_u64).unwrap_or(tip);
+    let first_slot = slot - slot % SLOTS_PER_EPOCH;
+
+    let mut schedule: HashMap<String, Vec<usize>> = HashMap::new();
+    for index in 0..SLOTS_PER_EPOCH {
+        schedule
+            .entry(leader(first_slot + index).to_string())
+            .or_default()
+            .push(index as usize);
+    }
+
+    json!(schedule)
+}
+
+/// Returns the identity of the leader of a slot.
+pub fn leader(slot: u64) -> Pubkey {
+    key("validator", (slot / LEADER_SLOTS) % VALIDATORS)
+}
+
+/// Handles `getTransaction`. Unknown signatures yield `null`.
+fn get_transaction(tip: u64, signature: &str, encoding: UiTransactionEncoding) -> Value {
+    let Some((slot, index)) = signature.parse().ok().and_then(|s| parse_signature(&s)) else {
+        return Value::Null;
+    };
+    if slot > tip || is_skipped(slot) || index >= TRANSACTIONS_PER_BLOCK {
+        return Value::Null;
+    }
+
+    json!(EncodedConfirmedTransactionWithStatusMeta {
+        slot,
+        transaction: transaction(slot, index, encoding),
+        block_time: Some(block_time(slot)),
+    })
+}
+
+/// Handles `getSignaturesForAddress`, newest first.
+fn get_signatures_for_address(tip: u64, params: &Value) -> CallResult {
+    let address = string_param(params, 0)?;
+    let config = params.get(1).cloned().unwrap_or(json!({}));
+    let limit = config
+        .get("limit")
+        .and_then(Value::as_u64)
+        .unwrap_or(1000)
+        .min(1000) as usize;
+
+    // Resume strictly before the given signature
+    let (mut slot, mut end) = (tip, TRANSACTIONS_PER_BLOCK);
+    if let Some(before) = config.get("before").and_then(Value::as_str) {
+        if let Some((before_slot, index)) = before.parse().ok().and_then(|s| parse_signature(&s)) {
+            (slot, end) = (before_slot.min(tip), index);
+        }
+    }
+
+    let Some(wallet) = (0..WALLETS).find(|i| key("wallet", *i).to_string() == address) else {
+        return Ok(json!([]));
+    };
+
+    let mut signatures = Vec::new();
+    while slot >= GENESIS_SLOT && signatures.len() < limit {
+        if !is_skipped(slot) {
+            for index in (0..end).rev() {
+                let (from, to) = transfer_wallets(slot, index);
+                if (from == wallet || to == wallet) && signatures.len() < limit {
+                    signatures.push(RpcConfirmedTransactionStatusWithSignature {
+                        signature: signature(slot, index).to_string(),
+                        slot,
+                        err: None,
+                        memo: None,
+                        block_time: Some(block_time(slot)),
+                        confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                    });
+                }
+            }
+        }
+        slot -= 1;
+        end = TRANSACTIONS_PER_BLOCK;
+    }
+
+    Ok(json!(signatures))
+}
+
+/// Returns whether a slot of the mock chain was skipped.
+pub fn is_skipped(slot: u64) -> bool {
+    slot % 10 == 9
+}
+
+/// Derives a key from a label and an index.
+pub fn key(label: &str, index: u64) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[b"mock", label.as_bytes(), &index.to_le_bytes()]).to_bytes())
+}
+
+/// Returns the hash of the block at `slot`.
+fn blockhash(slot: u64) -> Hash {
+    hashv(&[b"mock-block", &slot.to_le_bytes()])
+}
+
+/// Returns the Unix timestamp of the block at `slot`.
+fn block_time(slot: u64) -> i64 {
+    GENESIS_TIME
+        + (slot.saturating_sub(GENESIS_SLOT) * SLOT_DURATION.as_millis() as u64 / 1000) as i64
+}
+
+/// Returns the signature of the `index`th transaction of the block at `slot`.
+///
+/// The slot and index are encoded in the first bytes, followed by a checksum
+/// so that signatures from real clusters are not mistaken for mock ones.
+pub fn signature(slot: u64, index: usize) -> Signature {
+    let mut bytes = [0u8; 64];
+    bytes[..8].copy_from_slice(&slot.to_le_bytes());
+    bytes[8..16].copy_from_slice(&(index as u64).to_le_bytes());
+    let checksum = hashv(&[b"mock-signature", &bytes[..16]]);
+    bytes[16..48].copy_from_slice(checksum.as_ref());
+    Signature::from(bytes)
+}
+
+/// Recovers the slot and index encoded in a mock signature.
+fn parse_signature(signature: &Signature) -> Option<(u64, usize)> {
+    let bytes = signature.as_ref();
+    let slot = u64::from_le_bytes(bytes[..8].try_into().ok()?);
+    let index = u64::from_le_bytes(bytes[8..16].try_into().ok()?) as usize;
+
+    (*signature == self::signature(slot, index)).then_some((slot, index))
+}
+
+/// Returns the sending and receiving wallet indexes of a transfer.
+fn transfer_wallets(slot: u64, index: usize) -> (u64, u64) {
+    let from = (slot + index as u64) % WALLETS;
+    (from, (from + 1) % WALLETS)
+}
+
+/// Returns the amount of a transfer, in lamports.
+fn transfer_amount(slot: u64, index: usize) -> u64 {
+    1_000_000 * (1 + (slot + index as u64) % 100)
+}
+
+/// Builds the block at `slot` with its transactions in the given encoding.
+///
+/// # Errors
+///
+/// Returns the JSON-RPC error for skipped slots and slots not produced yet
+pub fn block(
+    tip: u64,
+    slot: u64,
+    encoding: UiTransactionEncoding,
+) -> Result<UiConfirmedBlock, (i64, String)> {
+    if slot > tip || slot < GENESIS_SLOT {
+        return Err((
+            JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+            format!("Block not available for slot {}", slot),
+        ));
+    }
+    if is_skipped(slot) {
+        return Err((
+            JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+            format!(
+                "Slot {} was skipped, or missing due to ledger jump to recent snapshot",
+                slot
+            ),
+        ));
+    }
+
+    let mut parent_slot = slot - 1;
+    while is_skipped(parent_slot) {
+        parent_slot -= 1;
+    }
+
+    Ok(UiConfirmedBlock {
+        previous_blockhash: blockhash(parent_slot).to_string(),
+        blockhash: blockhash(slot).to_string(),
+        parent_slot,
+        transactions: Some(
+            (0..TRANSACTIONS_PER_BLOCK)
+                .map(|index| transaction(slot, index, encoding))
+                .collect(),
+        ),
+        signatures: None,
+        rewards: Some(vec![]),
+        num_reward_partitions: None,
+        block_time: Some(block_time(slot)),
+        block_height: Some(slot - GENESIS_SLOT),
+    })
+}
+
+/// Builds the `index`th transaction of the block at `slot`, a SOL transfer
+/// between two wallets, in the given encoding.
+fn transaction(
+    slot: u64,
+    index: usize,
+    encoding: UiTransactionEncoding,
+) -> EncodedTransactionWithStatusMeta {
+    let (from, to) = transfer_wallets(slot, index);
+    let amount = transfer_amount(slot, index);
+
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(amount.to_le_bytes());
+
+    let signature = signature(slot, index);
+    let message = Message::new_with_compiled_instructions(
+        1,
+        0,
+        1,
+        vec![key("wallet", from), key("wallet", to), system_program::id()],
+        blockhash(slot - 1),
+        vec![CompiledInstruction::new_from_raw_parts(2, data, vec![0, 1])],
+    );
+
+    // Wire format: compact signature count, signatures, then the message
+    let mut bytes = vec![1];
+    bytes.extend(signature.as_ref());
+    bytes.extend(message.serialize());
+
+    let transaction = match encoding {
+        UiTransactionEncoding::Base64 => EncodedTransaction::Binary(
+            BASE64_STANDARD.encode(bytes),
+            TransactionBinaryEncoding::Base64,
+        ),
+        UiTransactionEncoding::Base58 => EncodedTransaction::Binary(
+            bs58::encode(bytes).into_string(),
+            TransactionBinaryEncoding::Base58,
+        ),
+        UiTransactionEncoding::Binary => {
+            EncodedTransaction::LegacyBinary(bs58::encode(bytes).into_string())
+        }
+        _ => EncodedTransaction::Json(UiTransaction {
+            signatures: vec![signature.to_string()],
+            message: UiMessage::Raw(UiRawMessage {
+                header: message.header,
+                account_keys: message.account_keys.iter().map(Pubkey::to_string).collect(),
+                recent_blockhash: message.recent_blockhash.to_string(),
+                instructions: message
+                    .instructions
+                    .iter()
+                    .map(|instruction| UiCompiledInstruction {
+                        program_id_index: instruction.program_id_index,
+                        accounts: instruction.accounts.clone(),
+                        data: bs58::encode(&instruction.data).into_string(),
+                        stack_height: None,
+                    })
+                    .collect(),
+                address_table_lookups: None,
+            }),
+        }),
+    };
+
+    EncodedTransactionWithStatusMeta {
+        transaction,
+        meta: Some(UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee: FEE,
+            pre_balances: vec![WALLET_BALANCE, WALLET_BALANCE, 1],
+            post_balances: vec![WALLET_BALANCE - amount - FEE, WALLET_BALANCE + amount, 1],
+            inner_instructions: OptionSerializer::Some(vec![]),
+            log_messages: OptionSerializer::Some(vec![
+                format!("Program {} invoke [1]", SYSTEM_PROGRAM_ID),
+                format!("Program {} success", SYSTEM_PROGRAM_ID),
+            ]),
+            pre_token_balances: OptionSerializer::Some(vec![]),
+            post_token_balances: OptionSerializer::Some(vec![]),
+            rewards: OptionSerializer::Some(vec![]),
+            loaded_addresses: OptionSerializer::Skip,
+            return_data: OptionSerializer::Skip,
+            compute_units_consumed: OptionSerializer::Some(150),
+        }),
+        version: None,
+    }
+}
+
+/// Returns a canned account: the System program or one of the wallets.
+fn account(address: &str) -> Option<UiAccount> {
+    let (lamports, owner, executable) = if address == SYSTEM_PROGRAM_ID {
+        (1, NATIVE_LOADER_ID, true)
+    } else if (0..WALLETS).any(|i| key("wallet", i).to_string() == address) {
+        (WALLET_BALANCE, SYSTEM_PROGRAM_ID, false)
+    } else {
+        return None;
+    };
+
+    Some(UiAccount {
+        lamports,
+        data: UiAccountData::Binary(String::new(), UiAccountEncoding::Base64),
+        owner: owner.to_string(),
+        executable,
+        rent_epoch: u64::MAX,
+        space: Some(0),
+    })
+}
@@ -0,0 +1,135 @@
+//! Network registry for running several Solana clusters in one process.
+//!
+//! Each configured network has its own RPC endpoint, its own indexer instance
+//! and its own MongoDB database, so data from different clusters never mixes.
+//! The primary network keeps the original `soldag` database name; additional
+//! networks are stored in `soldag_<name>`.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use tokio::sync::broadcast;
+use url::Url;
+
+use crate::{
+    config::ConfigHandle,
+    domain::{models::transaction::Transaction, storage::Storage},
+    indexer::Indexer,
+    prices::PriceOracle,
+    retry::RetryPolicy,
+};
+
+/// Database name used by the primary network.
+pub const PRIMARY_DATABASE: &str = "soldag";
+
+/// Number of transaction events buffered for slow stream subscribers.
+pub const EVENT_BUFFER: usize = 1024;
+
+/// A single indexed Solana cluster.
+#[derive(Clone)]
+pub struct Network {
+    /// Name used to select the network in API requests
+    pub name: String,
+    /// Storage holding this network's indexed data
+    pub storage: Arc<Storage>,
+    /// Indexer fetching this network's blocks
+    pub indexer: Indexer,
+    /// Newly stored transactions, published by the change stream bridge
+    /// when it is enabled
+    pub events: Option<broadcast::Sender<Arc<Transaction>>>,
+}
+
+impl Network {
+    /// Connects to a network's RPC endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Network name
+    /// * `storage` - Storage of the network's database
+    /// * `rpc_url` - URL of the network's Solana RPC endpoint
+    /// * `rpc_api_key` - Optional API key for RPC access
+    /// * `config` - Runtime configuration handle
+    /// * `prices` - Optional USD price source for transfer enrichment
+    /// * `retry_policy` - Timeouts and retries of the network's RPC calls
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC endpoint is unreachable
+    pub async fn init(
+        name: &str,
+        storage: Arc<Storage>,
+        rpc_url: Url,
+        rpc_api_key: Option<&str>,
+        config: ConfigHandle,
+        prices: Option<Arc<PriceOracle>>,
+        retry_policy: RetryPolicy,
+    ) -> eyre::Result<Self> {
+        let mut indexer = Indexer::new_with_retry_policy(
+            rpc_url,
+            rpc_api_key,
+            storage.clone(),
+            config,
+            retry_policy,
+        )
+        .await?;
+
+        if let Some(prices) = prices {
+            indexer = indexer.with_prices(prices);
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            storage,
+            indexer,
+            events: None,
+        })
+    }
+
+    /// Enables publishing of this network's transaction events.
+    pub fn with_events(mut self) -> Self {
+        self.events = Some(broadcast::channel(EVENT_BUFFER).0);
+        self
+    }
+}
+
+/// All networks served by this process.
+pub struct Networks {
+    /// Name of the network used when a request does not specify one
+    primary: String,
+    /// Networks keyed by name
+    networks: BTreeMap<String, Network>,
+}
+
+impl Networks {
+    /// Creates a registry containing only the primary network.
+    pub fn new(primary: Network) -> Self {
+        Self {
+            primary: primary.name.clone(),
+            networks: BTreeMap::from([(primary.name.clone(), primary)]),
+        }
+    }
+
+    /// Adds a network to the registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a network with the same name already exists
+    pub fn insert(&mut self, network: Network) -> eyre::Result<()> {
+        if self.networks.contains_key(&network.name) {
+            eyre::bail!("Duplicate network name: {}", network.name);
+        }
+
+        self.networks.insert(network.name.clone(), network);
+
+        Ok(())
+    }
+
+    /// Looks up a network by name, falling back to the primary network.
+    pub fn get(&self, name: Option<&str>) -> Option<&Network> {
+        self.networks.get(name.unwrap_or(&self.primary))
+    }
+
+    /// Iterates over all networks in name order.
+    pub fn iter(&self) -> impl Iterator<Item = &Network> {
+        self.networks.values()
+    }
+}
@@ -0,0 +1,164 @@
+//! USD price lookups for transfer enrichment.
+//!
+//! Prices are fetched from a Birdeye-compatible historical price API
+//! (`GET <url>?address=<mint>&address_type=token&type=1m&time_from=<t>&time_to=<t>`)
+//! for the minute of the block time, and cached per mint per minute, so a busy
+//! block with many transfers of the same token costs a single request, and
+//! blocks processed well after they were produced (for example during
+//! catch-up) are valued at the price of their time.
+
+use std::{sync::Arc, time::Duration};
+
+use log::warn;
+use mongodb::bson;
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    cache::LruCache,
+    domain::models::transfer::Transfer,
+    retry::{is_retryable_http_error, RetryPolicy},
+};
+
+/// Mint address of wrapped SOL, used to price native transfers.
+pub const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Number of mint and minute prices kept in the cache.
+const PRICE_CACHE_SIZE: usize = 1024;
+
+/// Longest time a price lookup may take, retries included, before the
+/// transfer is left unvalued.
+const PRICE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Price point in the price API response.
+#[derive(Deserialize)]
+struct PricePoint {
+    /// Price in USD
+    value: f64,
+}
+
+/// Price points of the requested interval.
+#[derive(Deserialize)]
+struct PriceHistory {
+    /// Price points, oldest first
+    #[serde(default)]
+    items: Vec<PricePoint>,
+}
+
+/// Price API response body.
+#[derive(Deserialize)]
+struct PriceResponse {
+    /// Price history, `null` for unknown mints
+    data: Option<PriceHistory>,
+}
+
+/// Cached USD price source.
+pub struct PriceOracle {
+    /// HTTP client for price API requests
+    client: reqwest::Client,
+    /// Price API endpoint
+    url: Url,
+    /// Optional price API key, sent in the `X-API-KEY` header
+    api_key: Option<String>,
+    /// Timeouts and retries of price API requests
+    retry_policy: RetryPolicy,
+    /// Prices keyed by mint and minute (Unix time / 60)
+    cache: LruCache<(String, i64), Option<f64>>,
+}
+
+impl PriceOracle {
+    /// Creates a price oracle backed by the given price API endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Historical price API endpoint
+    /// * `api_key` - Optional price API key
+    /// * `retry_policy` - Timeouts and retries of price API requests
+    pub fn new(url: Url, api_key: Option<String>, retry_policy: &RetryPolicy) -> Arc<Self> {
+        Arc::new(Self {
+            client: retry_policy.http_client(),
+            url,
+            api_key,
+            retry_policy: retry_policy.clone(),
+            cache: LruCache::new(PRICE_CACHE_SIZE),
+        })
+    }
+
+    /// Returns the USD price of a mint at the given time, if known.
+    ///
+    /// Lookups are bounded by a timeout. Failures are logged and cached as
+    /// unknown for that minute, so a failing price API doesn't slow down
+    /// block processing.
+    pub async fn usd_price(&self, mint: &str, at: bson::DateTime) -> Option<f64> {
+        let minute = at.timestamp_millis().div_euclid(60_000);
+        let key = (mint.to_string(), minute);
+
+        if let Some(price) = self.cache.get(&key) {
+            return price;
+        }
+
+        let price = match tokio::time::timeout(PRICE_LOOKUP_TIMEOUT, self.fetch(mint, minute)).await
+        {
+            Ok(Ok(price)) => price,
+            Ok(Err(e)) => {
+                warn!("Error fetching price for {}: {:?}", mint, e);
+                None
+            }
+            Err(_) => {
+                warn!("Fetching price for {} timed out", mint);
+                None
+            }
+        };
+
+        self.cache.insert(key, price);
+
+        price
+    }
+
+    /// Requests the price of a mint during a minute from the price API,
+    /// retrying failed requests under the retry policy.
+    async fn fetch(&self, mint: &str, minute: i64) -> eyre::Result<Option<f64>> {
+        let mut url = self.url.clone();
+        url.query_pairs_mut()
+            .append_pair("address", mint)
+            .append_pair("address_type", "token")
+            .append_pair("type", "1m")
+            .append_pair("time_from", &(minute * 60).to_string())
+            .append_pair("time_to", &(minute * 60 + 59).to_string());
+
+        let response: PriceResponse = self
+            .retry_policy
+            .retry(
+                &format!("Fetching price for {}", mint),
+                is_retryable_http_error,
+                || async {
+                    let mut request = self.client.get(url.clone());
+                    if let Some(api_key) = &self.api_key {
+                        request = request.header("X-API-KEY", api_key);
+                    }
+                    request.send().await?.error_for_status()?.json().await
+                },
+            )
+            .await?;
+
+        Ok(response
+            .data
+            .and_then(|history| history.items.first().map(|point| point.value)))
+    }
+
+    /// Annotates a transfer with its USD value at block time.
+    ///
+    /// Transfers without a block time are left untouched.
+    pub async fn enrich(&self, transfer: &mut Transfer, block_time: Option<bson::DateTime>) {
+        let Some(block_time) = block_time else {
+            return;
+        };
+
+        let mint = transfer.mint.as_deref().unwrap_or(WRAPPED_SOL_MINT);
+
+        transfer.usd_value = self
+            .usd_price(mint, block_time)
+            .await
+            .map(|price| price * transfer.ui_amount());
+    }
+}
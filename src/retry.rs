@@ -0,0 +1,149 @@
+//! Timeout and retry policy for calls to external services.
+//!
+//! A single [`RetryPolicy`] bounds how long RPC requests and connection
+//! attempts may take, and how often and how patiently failed calls are
+//! retried, so the indexer's main loop, catch-up, account fetches and health
+//! checks all behave the same way when an RPC node is slow or flaky.
+
+use std::{future::Future, time::Duration};
+
+use log::warn;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_request::RpcError,
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+/// JSON-RPC error codes of requests the server will never accept: invalid
+/// request, method not found and invalid params.
+const INVALID_REQUEST_CODES: [i64; 3] = [-32600, -32601, -32602];
+
+/// Timeouts and retry behavior of external calls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Longest time a single request may take, connection included
+    pub request_timeout: Duration,
+    /// Longest time establishing a connection may take
+    pub connect_timeout: Duration,
+    /// Number of times a failed call is retried
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub backoff_base: Duration,
+    /// Longest delay between retries
+    pub backoff_max: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            max_retries: 5,
+            backoff_base: Duration::from_millis(200),
+            backoff_max: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay before retry number `attempt`, counted from 0.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.backoff_base
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.backoff_max)
+    }
+
+    /// Runs an operation, retrying failures the policy allows with
+    /// exponential backoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Description of the operation, for logs
+    /// * `is_retryable` - Whether a failure may succeed when retried
+    /// * `operation` - Operation to run, called once per attempt
+    ///
+    /// # Returns
+    ///
+    /// * `Result<T, E>` - The first success, or the last failure
+    pub async fn retry<T, E, F, Fut>(
+        &self,
+        name: &str,
+        is_retryable: impl Fn(&E) -> bool,
+        mut operation: F,
+    ) -> Result<T, E>
+    where
+        E: std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        "{} failed, retrying in {:?} ({}/{}): {}",
+                        name,
+                        delay,
+                        attempt + 1,
+                        self.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Creates an RPC client that applies the policy's timeouts.
+    pub fn rpc_client(&self, url: String) -> RpcClient {
+        let client = reqwest_rpc::Client::builder()
+            .default_headers(HttpSender::default_headers())
+            .timeout(self.request_timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_idle_timeout(self.request_timeout)
+            .build()
+            .expect("Valid RPC HTTP client configuration");
+
+        RpcClient::new_sender(
+            HttpSender::new_with_client(url, client),
+            RpcClientConfig::with_commitment(CommitmentConfig::default()),
+        )
+    }
+
+    /// Creates an HTTP client that applies the policy's timeouts.
+    pub fn http_client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(self.request_timeout)
+            .connect_timeout(self.connect_timeout)
+            .build()
+            .expect("Valid HTTP client configuration")
+    }
+}
+
+/// Checks whether a failed RPC call may succeed when retried. Requests the
+/// node rejected as malformed are not retried.
+pub fn is_retryable_rpc_error(error: &ClientError) -> bool {
+    !matches!(
+        error.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if INVALID_REQUEST_CODES.contains(code)
+    )
+}
+
+/// Checks whether a failed HTTP request may succeed when retried: requests
+/// that timed out, couldn't connect, were rate limited or hit a server error.
+pub fn is_retryable_http_error(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => {
+            status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+        }
+        None => !error.is_decode(),
+    }
+}
@@ -0,0 +1,307 @@
+//! Change stream bridge for downstream consumers.
+//!
+//! When the indexer runs in a separate process, an API node has no direct
+//! view of newly indexed transactions. The bridge tails the MongoDB change
+//! stream of the transactions collection and republishes every insert to the
+//! configured sinks: WebSocket subscribers of the API, a Kafka topic and any
+//! number of webhooks. The bridge runs under the service supervisor, and a
+//! restarted bridge resumes the stream after the last published event, so no
+//! inserts are missed across reconnects.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use eyre::Context;
+use futures::StreamExt;
+use log::{error, info, warn};
+use mongodb::change_stream::event::ResumeToken;
+use reqwest::header::CONTENT_TYPE;
+use rskafka::{
+    client::{
+        partition::{Compression, PartitionClient, UnknownTopicHandling},
+        ClientBuilder,
+    },
+    record::Record,
+};
+use serde::Serialize;
+use tokio::sync::{broadcast, mpsc};
+use url::Url;
+
+use crate::{
+    domain::{
+        models::transaction::Transaction,
+        storage::{read_transaction, Storage},
+    },
+    network::Network,
+    retry::{is_retryable_http_error, RetryPolicy},
+};
+
+/// Largest number of events waiting to be delivered to each sink.
+const SINK_QUEUE_CAPACITY: usize = 1024;
+
+/// Number of requests in flight to each webhook.
+const WEBHOOK_CONCURRENCY: usize = 8;
+
+/// Largest number of events produced to Kafka in one request.
+const KAFKA_BATCH_SIZE: usize = 100;
+
+/// Transaction insert as published to Kafka and webhooks.
+#[derive(Debug, Serialize)]
+pub struct TransactionEvent<'a> {
+    /// Network the transaction was indexed on
+    pub network: &'a str,
+    /// The inserted transaction
+    pub transaction: &'a Transaction,
+}
+
+impl TransactionEvent<'_> {
+    /// Builds the Kafka record for this event, keyed by signature so all
+    /// events of a transaction land on the same partition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be serialized
+    pub fn to_record(&self) -> eyre::Result<Record> {
+        Ok(Record {
+            key: Some(self.transaction.signature.clone().into_bytes()),
+            value: Some(serde_json::to_vec(self)?),
+            headers: BTreeMap::from([("network".to_string(), self.network.as_bytes().to_vec())]),
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+/// Sinks that transaction events are republished to.
+///
+/// Each sink is fed from a bounded queue by its own delivery task, so a slow
+/// sink doesn't hold up the change stream.
+#[derive(Clone, Default)]
+pub struct Sinks {
+    /// Delivery queues of the webhooks, with their URLs
+    webhooks: Vec<(Url, mpsc::Sender<Arc<Vec<u8>>>)>,
+    /// Delivery queue of the Kafka topic
+    kafka: Option<mpsc::Sender<Record>>,
+}
+
+impl Sinks {
+    /// Creates the sinks and starts their delivery tasks, connecting to Kafka
+    /// when brokers are given.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhooks` - URLs that each event is POSTed to
+    /// * `kafka_brokers` - Bootstrap brokers, Kafka is disabled when empty
+    /// * `kafka_topic` - Topic events are produced to, on partition 0
+    /// * `retry_policy` - Timeouts and retries of webhook requests
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Kafka client cannot be created
+    pub async fn new(
+        webhooks: Vec<Url>,
+        kafka_brokers: Vec<String>,
+        kafka_topic: &str,
+        retry_policy: &RetryPolicy,
+    ) -> eyre::Result<Self> {
+        let kafka = if kafka_brokers.is_empty() {
+            None
+        } else {
+            let client = ClientBuilder::new(kafka_brokers)
+                .build()
+                .await
+                .wrap_err("Error connecting to Kafka")?;
+            let partition = client
+                .partition_client(kafka_topic, 0, UnknownTopicHandling::Retry)
+                .await
+                .wrap_err_with(|| format!("Error opening Kafka topic {}", kafka_topic))?;
+
+            let (queue, records) = mpsc::channel(SINK_QUEUE_CAPACITY);
+            tokio::spawn(produce(partition, records));
+            Some(queue)
+        };
+
+        let client = retry_policy.http_client();
+        let webhooks = webhooks
+            .into_iter()
+            .map(|url| {
+                let (queue, events) = mpsc::channel(SINK_QUEUE_CAPACITY);
+                tokio::spawn(deliver(
+                    client.clone(),
+                    retry_policy.clone(),
+                    url.clone(),
+                    events,
+                ));
+                (url, queue)
+            })
+            .collect();
+
+        Ok(Self { webhooks, kafka })
+    }
+
+    /// Queues an event for every sink.
+    ///
+    /// Events for a webhook whose queue is full are dropped with a warning.
+    /// Kafka events wait for room in the queue instead, so the topic receives
+    /// every event in order.
+    async fn publish(&self, event: &TransactionEvent<'_>) {
+        if !self.webhooks.is_empty() {
+            match serde_json::to_vec(event) {
+                Ok(body) => {
+                    let body = Arc::new(body);
+                    for (url, queue) in &self.webhooks {
+                        if queue.try_send(body.clone()).is_err() {
+                            warn!("Stream webhook {} is falling behind, dropping event", url);
+                        }
+                    }
+                }
+                Err(e) => error!("Error serializing event: {:?}", e),
+            }
+        }
+
+        if let Some(kafka) = &self.kafka {
+            match event.to_record() {
+                Ok(record) => {
+                    if kafka.send(record).await.is_err() {
+                        error!("Kafka delivery task stopped, dropping event");
+                    }
+                }
+                Err(e) => error!("Error producing event to Kafka: {:?}", e),
+            }
+        }
+    }
+}
+
+/// POSTs queued events to a webhook, a few at a time, retrying failed
+/// requests under the retry policy.
+async fn deliver(
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+    url: Url,
+    events: mpsc::Receiver<Arc<Vec<u8>>>,
+) {
+    futures::stream::unfold(events, |mut events| async move {
+        events.recv().await.map(|event| (event, events))
+    })
+    .for_each_concurrent(WEBHOOK_CONCURRENCY, |body| {
+        let (client, retry_policy, url) = (&client, &retry_policy, &url);
+        async move {
+            let result = retry_policy
+                .retry(
+                    &format!("Delivering event to {}", url),
+                    is_retryable_http_error,
+                    || async {
+                        client
+                            .post(url.clone())
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(body.to_vec())
+                            .send()
+                            .await?
+                            .error_for_status()
+                    },
+                )
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(e) if e.is_status() => {
+                    warn!("Stream webhook {} rejected event: {}", url, e)
+                }
+                Err(e) => error!("Error delivering event to {}: {}", url, e),
+            }
+        }
+    })
+    .await;
+}
+
+/// Produces queued events to Kafka, in order and in batches of what has
+/// accumulated since the previous request.
+async fn produce(kafka: PartitionClient, mut records: mpsc::Receiver<Record>) {
+    let mut batch = Vec::with_capacity(KAFKA_BATCH_SIZE);
+    while records.recv_many(&mut batch, KAFKA_BATCH_SIZE).await > 0 {
+        let count = batch.len();
+        if let Err(e) = kafka
+            .produce(std::mem::take(&mut batch), Compression::NoCompression)
+            .await
+        {
+            error!("Error producing {} events to Kafka: {:?}", count, e);
+        }
+    }
+}
+
+/// Republishes a network's transaction inserts.
+#[derive(Clone)]
+pub struct Bridge {
+    network: String,
+    storage: Arc<Storage>,
+    events: Option<broadcast::Sender<Arc<Transaction>>>,
+    sinks: Sinks,
+    /// Token of the last event seen, shared by clones so a restarted bridge
+    /// resumes where the failed one stopped
+    resume: Arc<Mutex<Option<ResumeToken>>>,
+}
+
+impl Bridge {
+    /// Creates a bridge for a network.
+    ///
+    /// WebSocket subscribers are served from the network's event channel,
+    /// see [`Network::with_events`].
+    pub fn new(network: &Network, sinks: Sinks) -> Self {
+        Self {
+            network: network.name.clone(),
+            storage: network.storage.clone(),
+            events: network.events.clone(),
+            sinks,
+            resume: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Tails the change stream until it fails or is closed.
+    ///
+    /// The stream is opened after the last event seen by this bridge or any
+    /// of its clones, so a bridge restarted by the supervisor doesn't miss
+    /// inserts made while it was down.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change stream cannot be opened or read
+    pub async fn start(self) -> eyre::Result<()> {
+        info!("Starting change stream bridge for network {}", self.network);
+
+        let resume = self.resume.lock().expect("Resume token poisoned").clone();
+        let mut stream = self
+            .storage
+            .watch_transactions(resume)
+            .await
+            .wrap_err_with(|| format!("Error opening change stream of {}", self.network))?;
+
+        while stream.is_alive() {
+            let Some(event) = stream.next_if_any().await? else {
+                continue;
+            };
+
+            if let Some(document) = event.full_document {
+                let transaction = read_transaction(document)?;
+
+                self.sinks
+                    .publish(&TransactionEvent {
+                        network: &self.network,
+                        transaction: &transaction,
+                    })
+                    .await;
+
+                if let Some(events) = &self.events {
+                    // No subscribers is not an error
+                    let _ = events.send(Arc::new(transaction));
+                }
+            }
+
+            // A restarted bridge resumes after the last published event
+            *self.resume.lock().expect("Resume token poisoned") = Some(event.id);
+        }
+
+        Ok(())
+    }
+}
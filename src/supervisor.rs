@@ -0,0 +1,299 @@
+//! Supervision of long-running services.
+//!
+//! Indexers and the API server are expected to run indefinitely. When one of
+//! them fails or exits, the supervisor restarts it after an exponentially
+//! growing delay. Each service has a restart budget: once it has been
+//! restarted too often within the budget window, the failure is considered
+//! permanent, an alert is sent and the process exits with
+//! [`EXIT_SERVICE_FAILED`] so an external process manager can take over.
+//!
+//! On termination, services are signalled to stop and given a grace period
+//! to finish in-flight work before they are aborted.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::{
+    sync::watch,
+    task::{Id, JoinSet},
+};
+use url::Url;
+
+/// Process exit code used when a service failed permanently.
+pub const EXIT_SERVICE_FAILED: i32 = 2;
+
+/// Future of a running service.
+pub type ServiceFuture = Pin<Box<dyn Future<Output = eyre::Result<()>> + Send>>;
+
+/// Starts a fresh instance of a service.
+type ServiceFactory = Box<dyn Fn() -> ServiceFuture + Send + Sync>;
+
+/// When and how often failed services are restarted.
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+    /// Longest delay between restarts
+    pub max_backoff: Duration,
+    /// Restarts allowed within `window` before a failure is permanent
+    pub max_restarts: usize,
+    /// Period over which restarts are counted
+    pub window: Duration,
+}
+
+impl RestartPolicy {
+    /// Returns the delay before a restart, given the number of restarts
+    /// already made within the window.
+    pub fn backoff(&self, restarts: usize) -> Duration {
+        let factor = 2u32.saturating_pow(restarts.min(31) as u32);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Recent restarts of a service.
+#[derive(Debug, Default)]
+pub struct RestartBudget {
+    /// Times of restarts within the window, oldest first
+    restarts: VecDeque<Instant>,
+}
+
+impl RestartBudget {
+    /// Records a failure at `now` and decides whether to restart.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Duration>` - Delay before restarting, or `None` if the
+    ///   budget is exhausted
+    pub fn record_failure(&mut self, policy: &RestartPolicy, now: Instant) -> Option<Duration> {
+        while let Some(restart) = self.restarts.front() {
+            if now.duration_since(*restart) < policy.window {
+                break;
+            }
+            self.restarts.pop_front();
+        }
+
+        if self.restarts.len() >= policy.max_restarts {
+            return None;
+        }
+
+        let backoff = policy.backoff(self.restarts.len());
+        self.restarts.push_back(now);
+
+        Some(backoff)
+    }
+
+    /// Number of restarts within the window.
+    pub fn restarts(&self) -> usize {
+        self.restarts.len()
+    }
+}
+
+/// A service that exhausted its restart budget.
+#[derive(Debug, Serialize)]
+pub struct ServiceFailed {
+    /// Name of the service
+    pub service: String,
+    /// Restarts made within the budget window
+    pub restarts: usize,
+    /// Last error of the service
+    pub error: String,
+}
+
+impl fmt::Display for ServiceFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Service {} failed permanently after {} restarts: {}",
+            self.service, self.restarts, self.error
+        )
+    }
+}
+
+impl std::error::Error for ServiceFailed {}
+
+/// Runs services and restarts them according to a [`RestartPolicy`].
+pub struct Supervisor {
+    /// Restart policy shared by all services
+    policy: RestartPolicy,
+    /// Webhook notified when a service fails permanently
+    alert_url: Option<Url>,
+    /// Factories of registered services, by name
+    factories: HashMap<String, ServiceFactory>,
+    /// Restart budgets of registered services, by name
+    budgets: HashMap<String, RestartBudget>,
+    /// Running services
+    tasks: JoinSet<eyre::Result<()>>,
+    /// Names of running services, by task id
+    names: HashMap<Id, String>,
+    /// Signal asking services to stop
+    shutdown: watch::Sender<bool>,
+    /// Time services are given to stop before they are aborted
+    shutdown_timeout: Duration,
+}
+
+impl Supervisor {
+    /// Creates a supervisor without services.
+    pub fn new(policy: RestartPolicy, alert_url: Option<Url>) -> Self {
+        Self {
+            policy,
+            alert_url,
+            factories: HashMap::new(),
+            budgets: HashMap::new(),
+            tasks: JoinSet::new(),
+            names: HashMap::new(),
+            shutdown: watch::Sender::new(false),
+            shutdown_timeout: Duration::ZERO,
+        }
+    }
+
+    /// Sets how long services are given to stop on termination.
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Returns a signal that turns true once services should stop.
+    pub fn shutdown_signal(&self) -> watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
+    /// Registers and starts a service.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Unique name of the service, used in logs and alerts
+    /// * `factory` - Starts a fresh instance of the service
+    pub fn spawn<F, Fut>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = eyre::Result<()>> + Send + 'static,
+    {
+        let name = name.into();
+        info!("Starting service {}", name);
+
+        self.factories
+            .insert(name.clone(), Box::new(move || Box::pin(factory())));
+        self.budgets.insert(name.clone(), RestartBudget::default());
+        self.start(name, Duration::ZERO);
+    }
+
+    /// Supervises the registered services until one fails permanently or
+    /// `terminate` resolves.
+    ///
+    /// # Arguments
+    ///
+    /// * `terminate` - Resolves when the process should shut down
+    ///
+    /// # Returns
+    ///
+    /// * `eyre::Result<()>` - `Ok` once terminated or no services are left
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ServiceFailed`] error once a service exhausts its restart budget
+    pub async fn run(mut self, terminate: impl Future<Output = ()>) -> eyre::Result<()> {
+        tokio::pin!(terminate);
+
+        loop {
+            let result = tokio::select! {
+                _ = &mut terminate => {
+                    self.stop().await;
+                    return Ok(());
+                }
+                result = self.tasks.join_next_with_id() => match result {
+                    Some(result) => result,
+                    None => return Ok(()),
+                },
+            };
+
+            let (id, error) = match result {
+                Ok((id, Ok(()))) => (id, "Service exited".to_string()),
+                Ok((id, Err(e))) => (id, e.to_string()),
+                Err(e) => (e.id(), e.to_string()),
+            };
+
+            let Some(name) = self.names.remove(&id) else {
+                continue;
+            };
+            let budget = self
+                .budgets
+                .get_mut(&name)
+                .expect("Supervised services have a budget");
+
+            match budget.record_failure(&self.policy, Instant::now()) {
+                Some(backoff) => {
+                    warn!(
+                        "Service {} stopped: {}. Restarting in {:?}",
+                        name, error, backoff
+                    );
+                    self.start(name, backoff);
+                }
+                None => {
+                    let failure = ServiceFailed {
+                        restarts: budget.restarts(),
+                        service: name,
+                        error,
+                    };
+                    error!("{}", failure);
+                    self.alert(&failure).await;
+
+                    return Err(failure.into());
+                }
+            }
+        }
+    }
+
+    /// Signals services to stop and waits for them for the shutdown timeout,
+    /// aborting those still running afterwards.
+    async fn stop(&mut self) {
+        info!("Stopping services...");
+        self.shutdown.send_replace(true);
+
+        let drain = async { while self.tasks.join_next().await.is_some() {} };
+        if tokio::time::timeout(self.shutdown_timeout, drain)
+            .await
+            .is_err()
+        {
+            warn!("Timed out waiting for services to stop, aborting");
+            self.tasks.abort_all();
+        }
+    }
+
+    /// Starts an instance of a registered service after `delay`.
+    fn start(&mut self, name: String, delay: Duration) {
+        let service = (self.factories[&name])();
+        let handle = self.tasks.spawn(async move {
+            tokio::time::sleep(delay).await;
+            service.await
+        });
+        self.names.insert(handle.id(), name);
+    }
+
+    /// Notifies the alert webhook of a permanent failure, if configured.
+    async fn alert(&self, failure: &ServiceFailed) {
+        let Some(url) = &self.alert_url else {
+            return;
+        };
+
+        let result = reqwest::Client::new()
+            .post(url.clone())
+            .json(failure)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        if let Err(e) = result {
+            error!("Error delivering service failure alert to {}: {}", url, e);
+        }
+    }
+}
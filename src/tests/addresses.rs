@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use mongodb::bson;
+
+use crate::{
+    api::{
+        addresses::CounterpartyQuery,
+        query::{Paginated, Validate},
+    },
+    domain::models::{
+        address_summary::{AddressActivity, CounterpartyLink},
+        counterparty::Counterparty,
+        sol_transfer::SolTransfer,
+    },
+    tests::helpers::{create_mock_transaction, get_global_state},
+};
+
+const PAYER: &str = "Payer11111111111111111111111111111111111111";
+const OTHER: &str = "Other11111111111111111111111111111111111111";
+const PROGRAM: &str = "11111111111111111111111111111111";
+
+fn sol_transfer(from: &str, to: &str) -> SolTransfer {
+    SolTransfer {
+        from: from.to_string(),
+        to: to.to_string(),
+        lamports: 1,
+        signature: "sig".to_string(),
+        index: 0,
+        slot: Some(1),
+        block_time: None,
+    }
+}
+
+#[test]
+fn test_address_activity_accumulates() {
+    let mut activity = BTreeMap::new();
+
+    for (index, slot) in [(0, 20), (1, 10)] {
+        let mut transaction = create_mock_transaction(index);
+        transaction.slot = Some(slot);
+        transaction.block_time = Some(bson::DateTime::from_millis(slot as i64 * 1000));
+        transaction.meta.fee = 5000;
+        transaction.message.account_keys = vec![
+            PAYER.to_string(),
+            OTHER.to_string(),
+            PROGRAM.to_string(),
+            OTHER.to_string(),
+        ];
+        AddressActivity::record(&mut activity, &transaction);
+    }
+
+    let payer = &activity[PAYER];
+    assert_eq!(payer.transaction_count, 2);
+    assert_eq!(payer.fees_paid, 10_000);
+    assert_eq!(payer.first_slot, Some(10));
+    assert_eq!(payer.last_slot, Some(20));
+    assert_eq!(payer.first_seen, Some(bson::DateTime::from_millis(10_000)));
+    assert_eq!(payer.last_seen, Some(bson::DateTime::from_millis(20_000)));
+
+    // Repeated keys count once per transaction, and only the payer pays fees
+    let other = &activity[OTHER];
+    assert_eq!(other.transaction_count, 2);
+    assert_eq!(other.fees_paid, 0);
+    assert_eq!(activity.len(), 3);
+}
+
+#[test]
+fn test_counterparty_links_from_transfers() {
+    let links = CounterpartyLink::from_transfers(&[
+        sol_transfer(PAYER, OTHER),
+        sol_transfer(OTHER, PAYER),
+        sol_transfer(PAYER, PAYER),
+    ]);
+
+    assert_eq!(
+        links,
+        vec![
+            CounterpartyLink {
+                address: OTHER.to_string(),
+                counterparty: PAYER.to_string(),
+            },
+            CounterpartyLink {
+                address: PAYER.to_string(),
+                counterparty: OTHER.to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_counterparties_aggregation() {
+    let storage = get_global_state().await.storage.clone();
+    let address = uuid::Uuid::new_v4().to_string();
+    // Signatures are made unique, as stored records are unique per
+    // signature and index
+    let transfer =
+        |from: &str, to: &str, lamports: u64, signature: &str, index: u32, slot: u64| SolTransfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            lamports,
+            signature: format!("{}-{}", address, signature),
+            index,
+            slot: Some(slot),
+            block_time: None,
+        };
+
+    storage
+        .insert_sol_transfers(&[
+            transfer(&address, OTHER, 100, "a", 0, 10),
+            transfer(&address, OTHER, 50, "a", 1, 10),
+            transfer(OTHER, &address, 30, "b", 0, 20),
+            transfer(PAYER, &address, 7, "c", 0, 30),
+            transfer(&address, &address, 1, "d", 0, 40),
+        ])
+        .await
+        .unwrap();
+
+    let (counterparties, next) = storage
+        .get_counterparties(&address, None, None, 1, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        counterparties,
+        vec![Counterparty {
+            address: OTHER.to_string(),
+            transfer_count: 3,
+            transaction_count: 2,
+            sent_lamports: 150,
+            received_lamports: 30,
+            first_slot: 10,
+            last_slot: 20,
+        }]
+    );
+    assert_eq!(next, Some(1));
+
+    // Slot bounds restrict the transfers counted
+    let (counterparties, next) = storage
+        .get_counterparties(&address, Some(20), None, 10, 0)
+        .await
+        .unwrap();
+    let addresses: Vec<&str> = counterparties.iter().map(|c| c.address.as_str()).collect();
+    assert_eq!(addresses, vec![OTHER, PAYER]);
+    assert_eq!(counterparties[0].transfer_count, 1);
+    assert_eq!(next, None);
+}
+
+#[test]
+fn test_counterparty_query_from_query_string() {
+    let parse = |query: &str| {
+        let uri: http::Uri = format!("/addresses/a/counterparties?{}", query)
+            .parse()
+            .unwrap();
+        axum::extract::Query::<Paginated<CounterpartyQuery>>::try_from_uri(&uri)
+            .map_err(|e| e.body_text())
+            .and_then(|query| query.0.validate())
+    };
+
+    assert!(parse("count=5&from_slot=10&to_slot=20").is_ok());
+    assert!(parse("from_slot=20&to_slot=10").is_err());
+}
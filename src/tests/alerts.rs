@@ -0,0 +1,111 @@
+use mongodb::bson::DateTime;
+use solana_sdk::bs58;
+use solana_transaction_status_client_types::UiCompiledInstruction;
+
+use crate::{
+    alerts,
+    config::{AlertRule, Filters},
+    domain::models::{
+        alert::Alert,
+        transfer::{extract_transfers, SYSTEM_PROGRAM_ID},
+    },
+    tests::helpers::{create_mock_transaction, get_global_state},
+};
+
+fn create_sol_transfer(lamports: u64) -> crate::domain::models::transaction::Transaction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(lamports.to_le_bytes());
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec![
+        "Sender1111111111111111111111111111111111111".to_string(),
+        "Receiver111111111111111111111111111111111111".to_string(),
+        SYSTEM_PROGRAM_ID.to_string(),
+    ];
+    transaction.message.instructions = vec![UiCompiledInstruction {
+        program_id_index: 2,
+        accounts: vec![0, 1],
+        data: bs58::encode(data).into_string(),
+        stack_height: None,
+    }];
+    transaction
+}
+
+#[test]
+fn test_sol_transfer_extraction() {
+    let transfers = extract_transfers(&create_sol_transfer(2_500_000_000));
+
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].mint, None);
+    assert_eq!(
+        transfers[0].source,
+        "Sender1111111111111111111111111111111111111"
+    );
+    assert_eq!(transfers[0].amount, 2_500_000_000);
+    assert_eq!(transfers[0].ui_amount(), 2.5);
+}
+
+#[test]
+fn test_alert_rule_threshold() {
+    let rules = vec![
+        AlertRule {
+            name: "whale".to_string(),
+            asset: AlertRule::SOL.to_string(),
+            min_amount: 1000.0,
+        },
+        AlertRule {
+            name: "usdc".to_string(),
+            asset: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            min_amount: 1.0,
+        },
+    ];
+
+    let below = create_sol_transfer(999 * 1_000_000_000);
+    assert!(alerts::evaluate(&rules, &below, 1).is_empty());
+
+    let above = create_sol_transfer(1000 * 1_000_000_000);
+    let triggered = alerts::evaluate(&rules, &above, 1);
+    assert_eq!(triggered.len(), 1);
+    assert_eq!(triggered[0].rule, "whale");
+    assert_eq!(triggered[0].slot, 1);
+}
+
+#[tokio::test]
+async fn test_alerts_scoped_by_program() {
+    let storage = get_global_state().await.storage.clone();
+    let rule = format!("program_scope_{}", DateTime::now().timestamp_millis());
+
+    let mut transaction = create_sol_transfer(1_000_000_000);
+    transaction.signature = format!("{}_signature", rule);
+    storage.insert_transaction(transaction).await.unwrap();
+
+    let alert = |signature: &str| Alert {
+        rule: rule.clone(),
+        signature: signature.to_string(),
+        slot: 1,
+        block_time: None,
+        transfer: extract_transfers(&create_sol_transfer(1_000_000_000)).remove(0),
+        created_at: DateTime::now(),
+    };
+    storage
+        .insert_alerts(&[
+            alert(&format!("{}_signature", rule)),
+            alert(&format!("{}_unknown", rule)),
+        ])
+        .await
+        .unwrap();
+
+    // Only the alert whose transaction invokes the scope's program is visible
+    let scope = Filters {
+        addresses: Default::default(),
+        programs: [SYSTEM_PROGRAM_ID.to_string()].into(),
+    };
+    let (alerts, next) = storage
+        .get_alerts(Some(rule.clone()), Some(&scope), 10, 0)
+        .await
+        .unwrap();
+
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].signature, format!("{}_signature", rule));
+    assert_eq!(next, None);
+}
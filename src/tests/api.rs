@@ -1,6 +1,32 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use http::{Request, StatusCode};
+use solana_sdk::pubkey::Pubkey;
+use tower::ServiceExt;
 use url::Url;
 
-use crate::{api, indexer::Indexer, tests::helpers::get_global_state};
+use crate::{
+    api::{
+        self,
+        query::{Filtered, Paginated, Validate},
+        request_id::{self, REQUEST_ID_HEADER},
+        rewards::RewardQuery,
+        tenants::Tenants,
+        AppState, TransactionQuery, TOTAL_COUNT_HEADER,
+    },
+    config::ConfigHandle,
+    domain::models::balance_change::BalanceChange,
+    indexer::Indexer,
+    network::{Network, Networks},
+    retry::RetryPolicy,
+    tests::helpers::{get_global_state, spawn_api},
+};
 
 #[tokio::test]
 async fn test_fetch_transactions() {
@@ -11,14 +37,28 @@ async fn test_fetch_transactions() {
     let storage = get_global_state().await.storage.clone();
 
     let indexer = Indexer::new(
-        Url::parse("https://api.mainnet-beta.solana.com").unwrap(),
+        Url::parse("mock://").unwrap(),
         None,
         storage.clone(),
+        ConfigHandle::default(),
     )
     .await
     .unwrap();
 
-    tokio::spawn(api::start(listener, storage, indexer));
+    tokio::spawn(api::start(
+        listener,
+        AppState {
+            networks: Arc::new(Networks::new(Network {
+                name: "mainnet".to_string(),
+                storage: storage.clone(),
+                indexer,
+                events: None,
+            })),
+            config: ConfigHandle::default(),
+            admin_token: None,
+            tenants: Arc::new(Tenants::load(storage.clone(), false).await.unwrap()),
+        },
+    ));
 
     let response = reqwest::Client::new()
         .get(format!("http://{}/transactions", api_listen))
@@ -29,6 +69,39 @@ async fn test_fetch_transactions() {
     response.error_for_status().unwrap();
 }
 
+#[tokio::test]
+async fn test_transaction_total_count() {
+    let api_listen = spawn_api().await;
+    let fetch = |query: &str| {
+        reqwest::get(format!(
+            "http://{}/transactions?count=1{}",
+            api_listen, query
+        ))
+    };
+
+    // Counted by default, in the body and the header
+    let response = fetch("").await.unwrap().error_for_status().unwrap();
+    let header = response.headers()[TOTAL_COUNT_HEADER]
+        .to_str()
+        .unwrap()
+        .parse::<u64>()
+        .unwrap();
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], header);
+    assert!(body["count"].as_u64().unwrap() <= 1);
+
+    // Left out of both when the count is skipped
+    let response = fetch("&include_total=false")
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    assert!(!response.headers().contains_key(TOTAL_COUNT_HEADER));
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body.get("total").is_none());
+    assert!(body["count"].as_u64().unwrap() <= 1);
+}
+
 #[tokio::test]
 async fn test_fetch_account() {
     let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
@@ -38,14 +111,28 @@ async fn test_fetch_account() {
 
     let storage = get_global_state().await.storage.clone();
     let indexer = Indexer::new(
-        Url::parse("https://api.mainnet-beta.solana.com").unwrap(),
+        Url::parse("mock://").unwrap(),
         None,
         storage.clone(),
+        ConfigHandle::default(),
     )
     .await
     .unwrap();
 
-    tokio::spawn(api::start(listener, storage.clone(), indexer.clone()));
+    tokio::spawn(api::start(
+        listener,
+        AppState {
+            networks: Arc::new(Networks::new(Network {
+                name: "mainnet".to_string(),
+                storage: storage.clone(),
+                indexer: indexer.clone(),
+                events: None,
+            })),
+            config: ConfigHandle::default(),
+            admin_token: None,
+            tenants: Arc::new(Tenants::load(storage.clone(), false).await.unwrap()),
+        },
+    ));
 
     let test_pubkey = "2y51bo8nuGLGzGCV4zr2zJuD2Ddu7myaRV3bjjw6GP9y";
     let response = reqwest::Client::new()
@@ -59,3 +146,311 @@ async fn test_fetch_account() {
 
     response.error_for_status().unwrap();
 }
+
+#[tokio::test]
+async fn test_send_invalid_transaction() {
+    let api_listen = spawn_api().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{}/transactions/send", api_listen))
+        .json(&serde_json::json!({ "transaction": "AAAA" }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_account_commitment() {
+    let api_listen = spawn_api().await;
+    let url = |commitment: &str| {
+        format!(
+            "http://{}/accounts?pubkey=11111111111111111111111111111111&commitment={}",
+            api_listen, commitment
+        )
+    };
+
+    let response = reqwest::get(url("processed"))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    let response = reqwest::get(url("latest"))
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_simulate_invalid_transaction() {
+    let api_listen = spawn_api().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{}/transactions/simulate", api_listen))
+        .json(&serde_json::json!({ "transaction": "AAAA", "commitment": "confirmed" }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_parse_projection_fields() {
+    assert_eq!(
+        api::parse_fields("signature, block_time,meta.fee").unwrap(),
+        vec!["signature", "block_time", "meta.fee"]
+    );
+
+    assert!(api::parse_fields("").is_err());
+    assert!(api::parse_fields("meta..fee").is_err());
+    assert!(api::parse_fields("$where").is_err());
+    assert!(api::parse_fields(&vec!["signature"; 33].join(",")).is_err());
+}
+
+#[test]
+fn test_etag_matching() {
+    let etag = api::etag::tag(br#"{"data":1}"#);
+    assert_eq!(etag, api::etag::tag(br#"{"data":1}"#));
+    assert_ne!(etag, api::etag::tag(br#"{"data":2}"#));
+
+    assert!(api::etag::matches(&etag, &etag));
+    assert!(api::etag::matches(&format!("W/{}", etag), &etag));
+    assert!(api::etag::matches(&format!("\"other\", {}", etag), &etag));
+    assert!(api::etag::matches("*", &etag));
+    assert!(!api::etag::matches("\"other\"", &etag));
+}
+
+#[test]
+fn test_paginated_query_validation() {
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<Paginated<api::DailyStatsQuery>>(value)
+            .unwrap()
+            .validate()
+    };
+
+    assert!(parse(serde_json::json!({ "count": 50, "from": "01/01/2025" })).is_ok());
+    assert!(parse(serde_json::json!({ "count": 0 })).is_err());
+    assert!(parse(serde_json::json!({ "count": 1_000_000 })).is_err());
+    assert!(parse(serde_json::json!({ "offset": 10_000_000 })).is_err());
+    assert!(parse(serde_json::json!({ "from": "31/12/2025", "to": "01/01/2025" })).is_err());
+    assert!(parse(serde_json::json!({ "from": "01/01/2020", "to": "01/01/2025" })).is_err());
+    assert!(parse(serde_json::json!({ "from": "2025-01-01" })).is_err());
+}
+
+#[test]
+fn test_numeric_filters_from_query_string() {
+    let parse = |query: &str| {
+        let uri: http::Uri = format!("/blocks/count?{}", query).parse().unwrap();
+        axum::extract::Query::<Filtered<api::BlockQuery>>::try_from_uri(&uri)
+            .map_err(|e| e.body_text())
+            .and_then(|query| query.0.data.validate())
+    };
+
+    assert!(parse("from_slot=10&to_slot=20").is_ok());
+    assert!(parse("from_slot=20&to_slot=10").is_err());
+    assert!(parse("from_slot=latest").is_err());
+}
+
+#[test]
+fn test_reward_type_validation() {
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<Paginated<RewardQuery>>(value)
+            .unwrap()
+            .validate()
+    };
+
+    assert!(parse(serde_json::json!({})).is_ok());
+    assert!(parse(serde_json::json!({ "reward_type": "staking" })).is_ok());
+    assert!(parse(serde_json::json!({ "reward_type": "airdrop" })).is_err());
+}
+
+#[test]
+fn test_enriched_flag_from_query_string() {
+    let parse = |query: &str| {
+        let uri: http::Uri = format!("/transactions?{}", query).parse().unwrap();
+        axum::extract::Query::<Paginated<TransactionQuery>>::try_from_uri(&uri)
+            .map_err(|e| e.body_text())
+    };
+
+    assert!(parse("count=5&enriched=true").is_ok());
+    assert!(parse("enriched=false").is_ok());
+    assert!(parse("enriched=yes").is_err());
+}
+
+#[test]
+fn test_include_total_flag_from_query_string() {
+    let parse = |query: &str| {
+        let uri: http::Uri = format!("/transactions?{}", query).parse().unwrap();
+        axum::extract::Query::<Paginated<TransactionQuery>>::try_from_uri(&uri)
+            .map_err(|e| e.body_text())
+    };
+
+    assert!(parse("count=5&include_total=false").is_ok());
+    assert!(parse("include_total=true").is_ok());
+    assert!(parse("include_total=maybe").is_err());
+}
+
+#[tokio::test]
+async fn test_request_id_propagation() {
+    let app = Router::new()
+        .route(
+            "/fail",
+            get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "Error".to_string()) }),
+        )
+        .layer(middleware::from_fn(request_id::assign));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/fail")
+                .header(REQUEST_ID_HEADER, "client-42")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "client-42");
+    let body = axum::body::to_bytes(response.into_body(), 1024)
+        .await
+        .unwrap();
+    assert_eq!(&body[..], b"Error (request id: client-42)");
+
+    // Unusable ids are replaced with generated ones
+    let response = app
+        .oneshot(
+            Request::get("/fail")
+                .header(REQUEST_ID_HEADER, "bad id")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(id).is_ok());
+}
+
+#[tokio::test]
+async fn test_account_falls_back_to_indexed_state() {
+    let storage = get_global_state().await.storage.clone();
+    let pubkey = Pubkey::new_unique().to_string();
+    let rejected = Pubkey::new_unique().to_string();
+
+    // Two changes in the same slot: the later transaction holds the balance
+    for (pubkey, transaction_index, post_balance) in [
+        (&pubkey, 7, 2_000_000),
+        (&pubkey, 2, 5_000_000),
+        (&rejected, 0, 2_000_000),
+    ] {
+        storage
+            .insert_balance_changes(&[BalanceChange {
+                pubkey: pubkey.clone(),
+                signature: uuid::Uuid::new_v4().to_string(),
+                index: 0,
+                slot: Some(1234),
+                transaction_index: Some(transaction_index),
+                pre_balance: 0,
+                post_balance,
+                change: post_balance as i64,
+                block_time: None,
+            }])
+            .await
+            .unwrap();
+    }
+
+    // RPC node that passes the startup health check, then rejects lookups of
+    // one account and fails every other call
+    let rejected_param = rejected.clone();
+    let rpc = Router::new().route(
+        "/",
+        post(move |Json(request): Json<serde_json::Value>| async move {
+            match request["method"].as_str() {
+                Some("getHealth") => Ok(Json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "result": "ok",
+                    "id": request["id"],
+                }))),
+                _ if request["params"][0] == rejected_param.as_str() => {
+                    Ok(Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "error": { "code": -32602, "message": "Invalid param" },
+                        "id": request["id"],
+                    })))
+                }
+                _ => Err(StatusCode::BAD_GATEWAY),
+            }
+        }),
+    );
+    let rpc_listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let rpc_url = Url::parse(&format!("http://{}", rpc_listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move { axum::serve(rpc_listener, rpc).await });
+
+    // RPC calls aren't retried, so the failing node is noticed quickly
+    let indexer = Indexer::new_with_retry_policy(
+        rpc_url,
+        None,
+        storage.clone(),
+        ConfigHandle::default(),
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let api_listen = listener.local_addr().unwrap();
+    tokio::spawn(api::start(
+        listener,
+        AppState {
+            networks: Arc::new(Networks::new(Network {
+                name: "mainnet".to_string(),
+                storage: storage.clone(),
+                indexer,
+                events: None,
+            })),
+            config: ConfigHandle::default(),
+            admin_token: None,
+            tenants: Arc::new(Tenants::load(storage, false).await.unwrap()),
+        },
+    ));
+
+    let response: serde_json::Value =
+        reqwest::get(format!("http://{}/accounts?pubkey={}", api_listen, pubkey))
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+    assert_eq!(response["stale"], true);
+    assert_eq!(response["slot"], 1234);
+    assert_eq!(response["data"]["lamports"], 2_000_000);
+
+    // Accounts the indexer never saw can't be served
+    let response = reqwest::get(format!(
+        "http://{}/accounts?pubkey={}",
+        api_listen,
+        Pubkey::new_unique()
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Errors of a reachable node are returned rather than masked
+    let response = reqwest::get(format!(
+        "http://{}/accounts?pubkey={}",
+        api_listen, rejected
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
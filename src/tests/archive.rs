@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use arrow_array::{Array, StringArray};
+use chrono::{TimeZone, Utc};
+use mongodb::bson::DateTime;
+
+use crate::{
+    archive::{closed_windows, schema, to_record_batch},
+    tests::helpers::create_mock_transaction,
+};
+
+#[test]
+fn test_closed_windows() {
+    let earliest = Utc.with_ymd_and_hms(2025, 3, 10, 15, 30, 0).unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 3, 13, 0, 30, 0).unwrap();
+    let hour = Duration::from_secs(3600);
+
+    // The day ending at midnight on the 13th closed less than an hour ago
+    let windows = closed_windows(earliest, None, now, hour);
+    assert_eq!(
+        windows
+            .iter()
+            .map(|(start, _)| start.format("%d").to_string())
+            .collect::<Vec<_>>(),
+        vec!["10", "11"]
+    );
+    assert!(windows
+        .iter()
+        .all(|(start, end)| (*end - *start).num_days() == 1));
+
+    let resume = Utc.with_ymd_and_hms(2025, 3, 12, 0, 0, 0).unwrap();
+    assert!(closed_windows(earliest, Some(resume), now, hour).is_empty());
+    assert_eq!(
+        closed_windows(earliest, Some(resume), now, Duration::ZERO),
+        vec![(resume, Utc.with_ymd_and_hms(2025, 3, 13, 0, 0, 0).unwrap())]
+    );
+}
+
+#[test]
+fn test_transactions_to_record_batch() {
+    let mut transaction = create_mock_transaction(1);
+    transaction.block_time = Some(DateTime::now());
+    transaction.memos = vec!["invoice-1".to_string()];
+
+    let batch = to_record_batch(&[transaction, create_mock_transaction(2)])
+        .expect("Failed to build record batch");
+
+    assert_eq!(batch.schema(), schema());
+    assert_eq!(batch.num_rows(), 2);
+
+    let signatures = batch
+        .column_by_name("signature")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(signatures.value(1), "signature_2");
+
+    let block_time = batch.column_by_name("block_time").unwrap();
+    assert!(block_time.is_valid(0));
+    assert!(block_time.is_null(1));
+}
@@ -0,0 +1,47 @@
+use crate::{
+    api::{
+        balances::BalanceHistoryQuery,
+        query::{Paginated, Validate},
+    },
+    domain::models::balance_change::BalanceChange,
+    tests::helpers::create_mock_transaction,
+};
+
+#[test]
+fn test_balance_changes_from_transaction() {
+    let mut transaction = create_mock_transaction(0);
+    transaction.slot = Some(42);
+    transaction.message.account_keys = vec![
+        "Sender1111111111111111111111111111111111111".to_string(),
+        "Receiver111111111111111111111111111111111111".to_string(),
+        "11111111111111111111111111111111".to_string(),
+    ];
+    transaction.meta.pre_balances = vec![10_000_000, 0, 1];
+    transaction.meta.post_balances = vec![8_995_000, 1_000_000, 1];
+
+    let changes = BalanceChange::from_transaction(&transaction, Some(3));
+
+    // The program's balance didn't change and is left out
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].pubkey, transaction.message.account_keys[0]);
+    assert_eq!(changes[0].change, -1_005_000);
+    assert_eq!(changes[0].post_balance, 8_995_000);
+    assert_eq!(changes[1].pubkey, transaction.message.account_keys[1]);
+    assert_eq!(changes[1].change, 1_000_000);
+    assert!(changes.iter().all(|change| change.slot == Some(42)
+        && change.transaction_index == Some(3)
+        && change.signature == transaction.signature));
+}
+
+#[test]
+fn test_balance_history_validation() {
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<Paginated<BalanceHistoryQuery>>(value)
+            .unwrap()
+            .validate()
+    };
+
+    assert!(parse(serde_json::json!({})).is_ok());
+    assert!(parse(serde_json::json!({ "from_slot": 10, "to_slot": 20 })).is_ok());
+    assert!(parse(serde_json::json!({ "from_slot": 20, "to_slot": 10 })).is_err());
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use crate::{
+    bench::{synthetic_block, LatencyStats},
+    domain::models::{transaction::Transaction, transfer::extract_transfers},
+};
+
+#[test]
+fn test_latency_percentiles() {
+    assert_eq!(LatencyStats::from_samples(vec![]), None);
+
+    let latencies = (1..=100).rev().map(Duration::from_millis).collect();
+    let stats = LatencyStats::from_samples(latencies).unwrap();
+
+    assert_eq!(stats.samples, 100);
+    assert_eq!(stats.p50, Duration::from_millis(50));
+    assert_eq!(stats.p90, Duration::from_millis(90));
+    assert_eq!(stats.p99, Duration::from_millis(99));
+    assert_eq!(stats.max, Duration::from_millis(100));
+}
+
+#[test]
+fn test_synthetic_block() {
+    let block = synthetic_block(42, 3);
+    let transactions = block.transactions.unwrap();
+    assert_eq!(transactions.len(), 3);
+
+    let transaction = Transaction::try_from(transactions[0].clone()).unwrap();
+    let transfers = extract_transfers(&transaction);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].amount, 1_000_000);
+}
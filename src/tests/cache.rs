@@ -0,0 +1,47 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::cache::{LruCache, TtlCache};
+
+#[tokio::test]
+async fn test_ttl_cache_refetches_after_expiry() {
+    let cache = TtlCache::new(Duration::from_millis(50));
+    let fetches = AtomicU64::new(0);
+    let fetch = || async { Ok(fetches.fetch_add(1, Ordering::SeqCst)) };
+
+    assert_eq!(cache.get_or_try_fetch(fetch).await.unwrap(), 0);
+    assert_eq!(cache.get_or_try_fetch(fetch).await.unwrap(), 0);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(cache.get_or_try_fetch(fetch).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_ttl_cache_does_not_store_errors() {
+    let cache = TtlCache::new(Duration::from_secs(60));
+
+    let result = cache
+        .get_or_try_fetch(|| async { Err::<u64, _>(eyre::eyre!("unavailable")) })
+        .await;
+    assert!(result.is_err());
+
+    assert_eq!(cache.get_or_try_fetch(|| async { Ok(7) }).await.unwrap(), 7);
+}
+
+#[test]
+fn test_lru_cache_evicts_least_recently_used() {
+    let cache = LruCache::new(2);
+
+    assert!(cache.insert(1, "a"));
+    assert!(cache.insert(2, "b"));
+    assert!(!cache.insert(1, "a"));
+    assert_eq!(cache.get(&1), Some("a"));
+
+    assert!(cache.insert(3, "c"));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.get(&1), Some("a"));
+    assert_eq!(cache.get(&3), Some("c"));
+}
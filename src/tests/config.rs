@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::{Config, Filters, RateLimit},
+    tests::helpers::create_mock_transaction,
+};
+
+#[test]
+fn test_config_parsing() {
+    let config: Config = toml::from_str(
+        r#"
+        log_level = "debug"
+
+        [filters]
+        addresses = ["4aRX4tq2mm5XS2PUUtJPcXUPvgrza5jvjKmoMZzUKcLM"]
+
+        [rate_limit]
+        requests_per_second = 10
+        burst = 20
+        "#,
+    )
+    .expect("Failed to parse config");
+
+    assert_eq!(config.log_level.as_deref(), Some("debug"));
+    assert_eq!(config.filters.addresses.len(), 1);
+    assert!(config.filters.programs.is_empty());
+    assert_eq!(
+        config.rate_limit,
+        Some(RateLimit {
+            requests_per_second: 10,
+            burst: 20
+        })
+    );
+
+    assert!(toml::from_str::<Config>("unknown = 1").is_err());
+}
+
+#[test]
+fn test_filters_matching() {
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec![
+        "Sender1111111111111111111111111111111111111".to_string(),
+        "11111111111111111111111111111111".to_string(),
+    ];
+    transaction.message.instructions = vec![
+        solana_transaction_status_client_types::UiCompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![0],
+            data: String::new(),
+            stack_height: None,
+        },
+    ];
+
+    assert!(Filters::default().matches(&transaction));
+
+    let by_address = Filters {
+        addresses: HashSet::from(["Sender1111111111111111111111111111111111111".to_string()]),
+        programs: HashSet::new(),
+    };
+    assert!(by_address.matches(&transaction));
+
+    let by_program = Filters {
+        addresses: HashSet::new(),
+        programs: HashSet::from(["11111111111111111111111111111111".to_string()]),
+    };
+    assert!(by_program.matches(&transaction));
+
+    let unrelated = Filters {
+        addresses: HashSet::from(["Other11111111111111111111111111111111111111".to_string()]),
+        programs: HashSet::new(),
+    };
+    assert!(!unrelated.matches(&transaction));
+}
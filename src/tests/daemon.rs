@@ -0,0 +1,34 @@
+use std::os::unix::net::UnixDatagram;
+
+use crate::daemon::{notify, PidFile};
+
+#[test]
+fn test_pid_file_removed_on_drop() {
+    let path = std::env::temp_dir().join(format!("soldag-{}.pid", uuid::Uuid::new_v4()));
+
+    let pid_file = PidFile::create(&path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        format!("{}\n", std::process::id())
+    );
+
+    drop(pid_file);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_notify_sends_state_to_socket() {
+    let path = std::env::temp_dir().join(format!("soldag-{}.sock", uuid::Uuid::new_v4()));
+    let socket = UnixDatagram::bind(&path).unwrap();
+
+    std::env::set_var("NOTIFY_SOCKET", &path);
+    let notified = notify("READY=1");
+    std::env::remove_var("NOTIFY_SOCKET");
+
+    assert!(notified.unwrap());
+    let mut buffer = [0; 64];
+    let len = socket.recv(&mut buffer).unwrap();
+    assert_eq!(&buffer[..len], b"READY=1");
+
+    std::fs::remove_file(&path).unwrap();
+}
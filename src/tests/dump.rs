@@ -0,0 +1,24 @@
+use mongodb::bson::{self, doc, oid::ObjectId};
+
+use crate::dump::{decode_document, encode_document};
+
+#[test]
+fn test_document_round_trip() {
+    let document = doc! {
+        "_id": ObjectId::new(),
+        "signature": "abc",
+        "slot": 320_000_000i64,
+        "index": 3i32,
+        "fee": 0.5,
+        "block_time": bson::DateTime::from_millis(1_741_737_600_000),
+        "completed_at": null,
+        "meta": { "pre_balances": [1i64, 2i64] },
+    };
+
+    let line = encode_document(document.clone());
+    assert!(!line.contains('\n'));
+    assert_eq!(decode_document(&line).unwrap(), document);
+
+    assert!(decode_document("[1, 2]").is_err());
+    assert!(decode_document("{").is_err());
+}
@@ -0,0 +1,151 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::post,
+    Json, Router,
+};
+use serde_json::Value;
+use url::Url;
+
+use crate::{helius::HeliusEnricher, retry::RetryPolicy};
+
+/// Request bodies and API keys received by the mock enhanced transactions API.
+type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+async fn start_helius_api(requests: Requests) -> Url {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = Router::new()
+        .route(
+            "/v0/transactions",
+            post(
+                |State(requests): State<Requests>,
+                 Query(params): Query<std::collections::HashMap<String, String>>,
+                 Json(body): Json<Value>| async move {
+                    let signatures: Vec<String> =
+                        serde_json::from_value(body["transactions"].clone()).unwrap();
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((params["api-key"].clone(), body));
+
+                    // The API omits transactions it doesn't know
+                    Json(
+                        signatures
+                            .iter()
+                            .map(|signature| {
+                                if signature == "unknown" {
+                                    return Value::Null;
+                                }
+                                serde_json::json!({
+                                    "signature": signature,
+                                    "type": "TRANSFER",
+                                    "source": "SYSTEM_PROGRAM",
+                                    "description": "alice transferred 1 SOL to bob.",
+                                    "fee": 5000,
+                                    "tokenTransfers": [{
+                                        "fromUserAccount": "alice",
+                                        "toUserAccount": "bob",
+                                        "fromTokenAccount": "alice-ata",
+                                        "toTokenAccount": null,
+                                        "tokenAmount": 2.5,
+                                        "mint": "mint",
+                                        "tokenStandard": "Fungible"
+                                    }],
+                                    "nativeTransfers": [{
+                                        "fromUserAccount": "alice",
+                                        "toUserAccount": "bob",
+                                        "amount": 1_000_000_000u64
+                                    }]
+                                })
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                },
+            ),
+        )
+        .with_state(requests);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    Url::parse(&format!("http://{}/v0/transactions", addr)).unwrap()
+}
+
+#[tokio::test]
+async fn test_helius_enrichment_fetch() {
+    let requests = Requests::default();
+    let enricher = HeliusEnricher::new(
+        start_helius_api(requests.clone()).await,
+        "secret",
+        &RetryPolicy::default(),
+    );
+
+    let mut signatures: Vec<String> = (0..149).map(|i| format!("sig{}", i)).collect();
+    signatures.push("unknown".to_string());
+
+    let enrichments = enricher.fetch(&signatures).await.unwrap();
+
+    assert_eq!(enrichments.len(), 149);
+    let enrichment = &enrichments[0];
+    assert_eq!(enrichment.signature, "sig0");
+    assert_eq!(enrichment.kind, "TRANSFER");
+    assert_eq!(enrichment.source.as_deref(), Some("SYSTEM_PROGRAM"));
+    assert_eq!(enrichment.description, "alice transferred 1 SOL to bob.");
+    assert_eq!(enrichment.token_transfers[0].token_amount, 2.5);
+    assert_eq!(enrichment.token_transfers[0].to_token_account, None);
+    assert_eq!(enrichment.native_transfers[0].amount, 1_000_000_000);
+
+    // Signatures are sent in batches of 100, authenticated with the API key
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests.iter().all(|(api_key, _)| api_key == "secret"));
+    assert_eq!(requests[0].1["transactions"].as_array().unwrap().len(), 100);
+    assert_eq!(requests[1].1["transactions"].as_array().unwrap().len(), 50);
+}
+
+#[tokio::test]
+async fn test_helius_enrichment_retries_without_leaking_api_key() {
+    // The API is unavailable once, then rejects the key
+    let calls = Arc::new(AtomicUsize::new(0));
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route(
+            "/v0/transactions",
+            post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::UNAUTHORIZED,
+                }
+            }),
+        )
+        .with_state(calls.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let enricher = HeliusEnricher::new(
+        Url::parse(&format!("http://{}/v0/transactions", addr)).unwrap(),
+        "secret",
+        &RetryPolicy {
+            backoff_base: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        },
+    );
+
+    let error = enricher.fetch(&["sig".to_string()]).await.unwrap_err();
+
+    // Server errors are retried, rejected requests aren't
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(!format!("{:?}", error).contains("secret"));
+}
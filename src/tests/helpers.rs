@@ -2,10 +2,19 @@ use solana_sdk::message::MessageHeader;
 use solana_transaction_status_client_types::{
     option_serializer::OptionSerializer, UiRawMessage, UiTransactionStatusMeta,
 };
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::{Notify, OnceCell};
+use url::Url;
 
-use crate::domain::{models::transaction::Transaction, storage::Storage};
+use crate::{
+    api::{self, tenants::Tenants, AppState},
+    config::ConfigHandle,
+    domain::{
+        migrations::TRANSACTION_SCHEMA_VERSION, models::transaction::Transaction, storage::Storage,
+    },
+    indexer::Indexer,
+    network::{Network, Networks},
+};
 
 static TEST_STATE: OnceCell<TestState> = OnceCell::const_new();
 
@@ -61,5 +70,51 @@ pub fn create_mock_transaction(index: u64) -> Transaction {
         message: create_mock_message(),
         meta: create_mock_meta(),
         block_time: None,
+        memos: vec![],
+        compute_unit_price: None,
+        slot: None,
+        resolved_account_keys: vec![],
+        inner_instructions: vec![],
+        failure: None,
+        parsed_instructions: vec![],
+        compressed_meta: None,
+        schema_version: TRANSACTION_SCHEMA_VERSION,
     }
 }
+
+/// Starts an API server backed by the mock cluster and the test database.
+pub async fn spawn_api() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let api_listen = listener.local_addr().unwrap();
+
+    let storage = get_global_state().await.storage.clone();
+    let indexer = Indexer::new(
+        Url::parse("mock://").unwrap(),
+        None,
+        storage.clone(),
+        ConfigHandle::default(),
+    )
+    .await
+    .unwrap();
+
+    let tenants = Arc::new(Tenants::load(storage.clone(), false).await.unwrap());
+
+    tokio::spawn(api::start(
+        listener,
+        AppState {
+            networks: Arc::new(Networks::new(Network {
+                name: "mainnet".to_string(),
+                storage,
+                indexer,
+                events: None,
+            })),
+            config: ConfigHandle::default(),
+            admin_token: None,
+            tenants,
+        },
+    ));
+
+    api_listen
+}
@@ -1,13 +1,24 @@
 use url::Url;
 
-use crate::{domain::storage::Storage, indexer::Indexer, tests::helpers::get_global_state};
+use crate::{
+    config::ConfigHandle,
+    domain::{
+        models::{address_backfill::AddressBackfill, backfill::BackfillJob, replay_job::ReplayJob},
+        storage::{Storage, TransactionFilter},
+    },
+    indexer::{
+        adapt_update_interval, batch_results, is_skipped_slot, plan_catch_up, slot_ranges, CatchUp,
+        Indexer,
+    },
+    tests::helpers::get_global_state,
+};
 
 #[tokio::test]
 async fn test_indexer_initialization() {
     let storage = get_global_state().await.storage.clone();
-    let rpc_url = Url::parse("https://api.mainnet-beta.solana.com").unwrap();
+    let rpc_url = Url::parse("mock://").unwrap();
 
-    let indexer = Indexer::new(rpc_url, None, storage).await;
+    let indexer = Indexer::new(rpc_url, None, storage, ConfigHandle::default()).await;
     assert!(indexer.is_ok());
 }
 
@@ -16,15 +27,15 @@ async fn test_account_retrieval() {
     let storage = Storage::init("soldag_test")
         .await
         .expect("Failed to initialize storage");
-    let rpc_url = Url::parse("https://api.mainnet-beta.solana.com").unwrap();
+    let rpc_url = Url::parse("mock://").unwrap();
 
-    let indexer = Indexer::new(rpc_url, None, storage)
+    let indexer = Indexer::new(rpc_url, None, storage, ConfigHandle::default())
         .await
         .expect("Failed to create indexer");
 
     // Test with a known Solana system program account
     let result = indexer
-        .get_account("11111111111111111111111111111111".to_string())
+        .get_account("11111111111111111111111111111111".to_string(), None)
         .await;
 
     assert!(result.is_ok());
@@ -37,7 +48,7 @@ async fn test_indexer_with_invalid_url() {
     let storage = get_global_state().await.storage.clone();
     let invalid_url = Url::parse("https://invalid.solana.endpoint").unwrap();
 
-    let result = Indexer::new(invalid_url, None, storage).await;
+    let result = Indexer::new(invalid_url, None, storage, ConfigHandle::default()).await;
     assert!(result.is_err());
 }
 
@@ -45,10 +56,15 @@ async fn test_indexer_with_invalid_url() {
 async fn test_block_processing() {
     let state = get_global_state().await;
 
-    let rpc_url = Url::parse("https://api.mainnet-beta.solana.com").unwrap();
-    let indexer = Indexer::new(rpc_url, None, state.storage.clone())
-        .await
-        .expect("Failed to create indexer");
+    let rpc_url = Url::parse("mock://").unwrap();
+    let indexer = Indexer::new(
+        rpc_url,
+        None,
+        state.storage.clone(),
+        ConfigHandle::default(),
+    )
+    .await
+    .expect("Failed to create indexer");
 
     // Start the indexer with a short update interval
     let handle = tokio::spawn(async move { indexer.start(100).await });
@@ -56,11 +72,12 @@ async fn test_block_processing() {
     // Let it process a few blocks
     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-    let (transactions, _) = state
+    let transactions = state
         .storage
-        .get_transactions(None, None, 10, 0)
+        .get_transactions(&TransactionFilter::default(), 10, 0, false)
         .await
-        .expect("Failed to retrieve transactions");
+        .expect("Failed to retrieve transactions")
+        .items;
 
     state.notifier.notify_waiters();
 
@@ -72,3 +89,100 @@ async fn test_block_processing() {
         .expect_err("Indexer should have been cancelled");
     assert!(err.is_cancelled(), "{err}");
 }
+
+#[test]
+fn test_catch_up_plan() {
+    assert_eq!(plan_catch_up(100, 101, 10), CatchUp::None);
+    assert_eq!(plan_catch_up(100, 100, 10), CatchUp::None);
+    assert_eq!(plan_catch_up(100, 111, 10), CatchUp::Inline(101, 110));
+    assert_eq!(plan_catch_up(100, 112, 10), CatchUp::Backfill(101, 111));
+}
+
+#[test]
+fn test_batch_results() {
+    let responses = serde_json::from_value(serde_json::json!([
+        { "jsonrpc": "2.0", "id": 1, "error": { "code": -32007, "message": "Slot 2 was skipped" } },
+        { "jsonrpc": "2.0", "id": 0, "error": { "code": -32004, "message": "Block not available" } },
+        { "jsonrpc": "2.0", "id": 7, "result": null },
+    ]))
+    .unwrap();
+
+    let results = batch_results(3, responses);
+    assert_eq!(results.len(), 3);
+    assert!(matches!(&results[0], Err(e) if !is_skipped_slot(e)));
+    assert!(matches!(&results[1], Err(e) if is_skipped_slot(e)));
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn test_adapt_update_interval() {
+    let bounds = (100, 2000);
+
+    assert_eq!(adapt_update_interval(400, 1, bounds), 400);
+    assert_eq!(adapt_update_interval(400, 0, bounds), 800);
+    assert_eq!(adapt_update_interval(1500, 0, bounds), 2000);
+    assert_eq!(adapt_update_interval(400, 5, bounds), 200);
+    assert_eq!(adapt_update_interval(150, 5, bounds), 100);
+}
+
+#[test]
+fn test_slot_ranges() {
+    assert_eq!(slot_ranges([]), vec![]);
+    assert_eq!(
+        slot_ranges([3, 4, 5, 8, 10, 11]),
+        vec![(3, 5), (8, 8), (10, 11)]
+    );
+}
+
+#[test]
+fn test_address_backfill_progress() {
+    let mut job = AddressBackfill::new("4aRX4tq2mm5XS2PUUtJPcXUPvgrza5jvjKmoMZzUKcLM".into());
+    let page = |range: std::ops::Range<u32>| range.map(|i| format!("sig{}", i)).collect::<Vec<_>>();
+
+    job.advance(&page(0..3), 10);
+    assert_eq!(job.before.as_deref(), Some("sig2"));
+    assert_eq!(job.fetched, 3);
+    assert!(job.completed_at.is_none());
+
+    // The limit completes the job
+    job.advance(&page(3..10), 10);
+    assert_eq!(job.before.as_deref(), Some("sig9"));
+    assert!(job.completed_at.is_some());
+
+    // An empty page means the oldest signature was reached
+    let mut job = AddressBackfill::new("4aRX4tq2mm5XS2PUUtJPcXUPvgrza5jvjKmoMZzUKcLM".into());
+    job.advance(&page(0..2), 10);
+    job.advance(&[], 10);
+    assert_eq!(job.before.as_deref(), Some("sig1"));
+    assert_eq!(job.fetched, 2);
+    assert!(job.completed_at.is_some());
+}
+
+#[test]
+fn test_replay_job_progress() {
+    let day = |millis| mongodb::bson::DateTime::from_millis(millis);
+    let mut job = ReplayJob::new(100, 300);
+    assert_eq!(job.next_slot, 100);
+
+    job.advance(199, [day(86_400_000), day(0), day(86_400_000)]);
+    job.advance(300, [day(86_400_000)]);
+
+    // Each day is recomputed once
+    assert_eq!(job.next_slot, 301);
+    assert_eq!(job.days, vec![day(0), day(86_400_000)]);
+    assert!(job.completed_at.is_none());
+}
+
+#[test]
+fn test_bootstrap_job() {
+    let job = BackfillJob::bootstrap(100, 200);
+    assert!(job.bootstrap);
+    assert_eq!(job.next_slot, 100);
+    assert_eq!(job.end_slot, 200);
+
+    // Jobs recorded before bootstrapping existed are regular backfill jobs
+    let mut document = mongodb::bson::to_document(&BackfillJob::new(1, 2)).unwrap();
+    document.remove("bootstrap");
+    let job: BackfillJob = mongodb::bson::from_document(document).unwrap();
+    assert!(!job.bootstrap);
+}
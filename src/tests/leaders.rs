@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{extract::State, routing::post, Json, Router};
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::epoch_schedule::EpochSchedule;
+
+use crate::{
+    leaders::{leaders_by_slot_index, LeaderSchedules},
+    mock_rpc::{self, GENESIS_SLOT},
+};
+
+#[test]
+fn test_leaders_by_slot_index() {
+    let schedule = HashMap::from([
+        ("ValidatorA".to_string(), vec![0, 1, 4]),
+        ("ValidatorB".to_string(), vec![2, 3, 9]),
+    ]);
+
+    let leaders = leaders_by_slot_index(schedule, 6);
+
+    // Slots outside the epoch are ignored, slots missing from the schedule are empty
+    assert_eq!(
+        leaders,
+        vec![
+            "ValidatorA",
+            "ValidatorA",
+            "ValidatorB",
+            "ValidatorB",
+            "ValidatorA",
+            ""
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_leader_lookup() {
+    let url = mock_rpc::spawn().await.unwrap();
+    let leaders = LeaderSchedules::new(Arc::new(RpcClient::new(url.to_string())));
+
+    for slot in [GENESIS_SLOT, GENESIS_SLOT + 5, GENESIS_SLOT + 900] {
+        assert_eq!(
+            leaders.leader(slot).await.unwrap(),
+            Some(mock_rpc::leader(slot).to_string())
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_leader_lookups_share_one_fetch() {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // The first schedule fetch fails, later ones answer slowly
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/",
+            post(
+                |State(fetches): State<Arc<AtomicUsize>>, Json(request): Json<Value>| async move {
+                    let id = request["id"].clone();
+                    Json(match request["method"].as_str() {
+                        Some("getEpochSchedule") => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "result": EpochSchedule::custom(32, 32, false),
+                        }),
+                        _ if fetches.fetch_add(1, Ordering::SeqCst) == 0 => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": -32000, "message": "Node is behind" },
+                        }),
+                        _ => {
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            json!({
+                                "jsonrpc": "2.0",
+                                "id": id,
+                                "result": { "ValidatorA": (0..32).collect::<Vec<_>>() },
+                            })
+                        }
+                    })
+                },
+            ),
+        )
+        .with_state(fetches.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let leaders = LeaderSchedules::new(Arc::new(RpcClient::new(format!("http://{}", addr))));
+
+    // A failed fetch isn't cached as an unknown schedule
+    assert!(leaders.leader(64).await.is_err());
+
+    let lookups = futures::future::join_all((64..72).map(|slot| leaders.leader(slot))).await;
+    for leader in lookups {
+        assert_eq!(leader.unwrap(), Some("ValidatorA".to_string()));
+    }
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use crate::domain::metrics::{render, StorageMetrics};
+
+#[tokio::test]
+async fn test_storage_metrics_record_and_render() {
+    let metrics = StorageMetrics::default();
+
+    metrics.record("insert_transaction", Duration::from_millis(3), false);
+    metrics.record("insert_transaction", Duration::from_millis(300), false);
+    let result = metrics
+        .observe("get_block", async {
+            Err::<(), _>(eyre::eyre!("unavailable"))
+        })
+        .await;
+    assert!(result.is_err());
+
+    let stats = metrics.snapshot();
+    assert_eq!(stats["insert_transaction"].calls, 2);
+    assert_eq!(stats["insert_transaction"].errors, 0);
+    assert_eq!(stats["get_block"].errors, 1);
+
+    let output = render([("mainnet", &metrics)]);
+    let labels = r#"network="mainnet",operation="insert_transaction""#;
+    assert!(output.contains(&format!("soldag_storage_operations_total{{{labels}}} 2")));
+    assert!(output.contains(&format!(
+        "soldag_storage_operation_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1"
+    )));
+    assert!(output.contains(&format!(
+        "soldag_storage_operation_duration_seconds_bucket{{{labels},le=\"0.5\"}} 2"
+    )));
+    assert!(output
+        .contains(r#"soldag_storage_errors_total{network="mainnet",operation="get_block"} 1"#));
+}
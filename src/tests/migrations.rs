@@ -0,0 +1,64 @@
+use mongodb::bson::{self, doc, Document};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiLoadedAddresses,
+};
+
+use crate::{
+    domain::{
+        migrations::{transaction_schema_version, upgrade_transaction, TRANSACTION_SCHEMA_VERSION},
+        models::transaction::Transaction,
+    },
+    tests::helpers::create_mock_transaction,
+};
+
+const PAYER: &str = "Payer11111111111111111111111111111111111111";
+const PROGRAM: &str = "Program1111111111111111111111111111111111111";
+const LOADED: &str = "Loaded111111111111111111111111111111111111";
+
+/// Stores a transaction the way builds without schema versions did.
+fn unversioned_transaction() -> Document {
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec![PAYER.to_string(), PROGRAM.to_string()];
+    transaction.meta.loaded_addresses = OptionSerializer::Some(UiLoadedAddresses {
+        writable: vec![LOADED.to_string()],
+        readonly: vec![],
+    });
+
+    let mut document = bson::to_document(&transaction).unwrap();
+    document.remove("schema_version");
+    document.remove("resolved_account_keys");
+    document
+}
+
+#[test]
+fn test_unversioned_transaction_is_upgraded() {
+    let mut document = unversioned_transaction();
+    assert_eq!(transaction_schema_version(&document), 1);
+
+    assert!(upgrade_transaction(&mut document).unwrap());
+    assert_eq!(
+        transaction_schema_version(&document),
+        TRANSACTION_SCHEMA_VERSION
+    );
+
+    let transaction: Transaction = bson::from_document(document).unwrap();
+    assert_eq!(transaction.resolved_account_keys, [PAYER, PROGRAM, LOADED]);
+}
+
+#[test]
+fn test_current_transaction_is_unchanged() {
+    let mut document = unversioned_transaction();
+    upgrade_transaction(&mut document).unwrap();
+    let upgraded = document.clone();
+
+    assert!(!upgrade_transaction(&mut document).unwrap());
+    assert_eq!(document, upgraded);
+}
+
+#[test]
+fn test_newer_transaction_is_rejected() {
+    let mut document = unversioned_transaction();
+    document.extend(doc! { "schema_version": TRANSACTION_SCHEMA_VERSION as i64 + 1 });
+
+    assert!(upgrade_transaction(&mut document).is_err());
+}
@@ -0,0 +1,120 @@
+use solana_account_decoder_client_types::token::UiTokenAmount;
+use solana_sdk::{bs58, transaction::TransactionError};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiCompiledInstruction, UiTransactionTokenBalance,
+};
+
+use crate::{
+    api::{
+        mints::SupplyEventQuery,
+        query::{Paginated, Validate},
+    },
+    domain::models::{
+        supply_event::{SupplyEvent, SupplyEventKind},
+        transaction::Transaction,
+        transfer::TOKEN_PROGRAM_IDS,
+    },
+    tests::helpers::create_mock_transaction,
+};
+
+const AUTHORITY: &str = "Authority1111111111111111111111111111111111";
+const MINT: &str = "Mint111111111111111111111111111111111111111";
+const ACCOUNT: &str = "TokenAccount1111111111111111111111111111111";
+
+fn token_balance(account_index: u8, amount: u64) -> UiTransactionTokenBalance {
+    UiTransactionTokenBalance {
+        account_index,
+        mint: MINT.to_string(),
+        ui_token_amount: UiTokenAmount {
+            ui_amount: None,
+            decimals: 6,
+            amount: amount.to_string(),
+            ui_amount_string: String::new(),
+        },
+        owner: OptionSerializer::Some(AUTHORITY.to_string()),
+        program_id: OptionSerializer::Skip,
+    }
+}
+
+/// Builds a transaction whose token program instructions have the given data
+/// and account indexes, into `[authority, mint, account, token program]`.
+fn token_transaction(instructions: Vec<(Vec<u8>, Vec<u8>)>) -> Transaction {
+    let mut transaction = create_mock_transaction(0);
+    transaction.slot = Some(42);
+    transaction.message.account_keys = vec![
+        AUTHORITY.to_string(),
+        MINT.to_string(),
+        ACCOUNT.to_string(),
+        TOKEN_PROGRAM_IDS[0].to_string(),
+    ];
+    transaction.message.instructions = instructions
+        .into_iter()
+        .map(|(data, accounts)| UiCompiledInstruction {
+            program_id_index: 3,
+            accounts,
+            data: bs58::encode(data).into_string(),
+            stack_height: None,
+        })
+        .collect();
+    transaction.meta.pre_token_balances = OptionSerializer::Some(vec![token_balance(2, 500)]);
+    transaction.meta.post_token_balances = OptionSerializer::Some(vec![]);
+    transaction
+}
+
+fn instruction_data(tag: u8, amount: u64, decimals: Option<u8>) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend(amount.to_le_bytes());
+    data.extend(decimals);
+    data
+}
+
+#[test]
+fn test_mint_and_burn_events() {
+    let transaction = token_transaction(vec![
+        // MintToChecked: [mint, destination, authority]
+        (instruction_data(14, 1_000, Some(6)), vec![1, 2, 0]),
+        // Burn: [account, mint, authority]; the account is closed afterwards,
+        // so its decimals come from the balances before the transaction
+        (instruction_data(8, 1_500, None), vec![2, 1, 0]),
+        // Transfer is not a supply change
+        (instruction_data(3, 10, None), vec![2, 2, 0]),
+    ]);
+
+    let events = SupplyEvent::from_transaction(&transaction);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].kind, SupplyEventKind::Mint);
+    assert_eq!(events[0].mint, MINT);
+    assert_eq!(events[0].account, ACCOUNT);
+    assert_eq!(events[0].authority.as_deref(), Some(AUTHORITY));
+    assert_eq!(events[0].amount, 1_000);
+    assert_eq!(events[0].decimals, 6);
+    assert_eq!(events[0].slot, Some(42));
+    assert_eq!(events[1].kind, SupplyEventKind::Burn);
+    assert_eq!(events[1].mint, MINT);
+    assert_eq!(events[1].account, ACCOUNT);
+    assert_eq!(events[1].amount, 1_500);
+    assert_eq!(events[1].decimals, 6);
+}
+
+#[test]
+fn test_failed_transactions_have_no_supply_events() {
+    let mut transaction =
+        token_transaction(vec![(instruction_data(15, 1_500, Some(6)), vec![2, 1, 0])]);
+    transaction.meta.err = Some(TransactionError::InsufficientFundsForFee);
+
+    assert!(SupplyEvent::from_transaction(&transaction).is_empty());
+}
+
+#[test]
+fn test_supply_event_query_validation() {
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<Paginated<SupplyEventQuery>>(value)
+            .map_err(|e| e.to_string())
+            .and_then(|query| query.validate())
+    };
+
+    assert!(parse(serde_json::json!({ "kind": "burn" })).is_ok());
+    assert!(parse(serde_json::json!({ "kind": "transfer" })).is_err());
+    assert!(parse(serde_json::json!({ "from_slot": 20, "to_slot": 10 })).is_err());
+}
@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use mongodb::bson::doc;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+};
+use solana_rpc_client_api::config::RpcBlockConfig;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_transaction_status_client_types::{
+    EncodedTransaction, TransactionBinaryEncoding, TransactionDetails, UiTransactionEncoding,
+};
+use url::Url;
+
+use crate::{
+    config::ConfigHandle,
+    domain::{
+        models::{transaction::Transaction, transfer::extract_transfers},
+        storage::{Storage, TransactionFilter},
+    },
+    indexer::{batch_results, is_skipped_slot, Indexer},
+    mock_rpc::{self, GENESIS_SLOT, INITIAL_SLOTS, TRANSACTIONS_PER_BLOCK},
+};
+
+#[test]
+fn test_mock_blocks_are_deterministic() {
+    let tip = GENESIS_SLOT + 100;
+
+    let block = mock_rpc::block(tip, GENESIS_SLOT, UiTransactionEncoding::Json).unwrap();
+    assert_eq!(
+        block,
+        mock_rpc::block(tip, GENESIS_SLOT, UiTransactionEncoding::Json).unwrap()
+    );
+
+    let transactions = block.transactions.unwrap();
+    assert_eq!(transactions.len(), TRANSACTIONS_PER_BLOCK);
+    for transaction in transactions {
+        let transaction = Transaction::try_from(transaction).unwrap();
+        assert_eq!(transaction.meta.fee, mock_rpc::FEE);
+        assert_eq!(extract_transfers(&transaction).len(), 1);
+    }
+
+    // Skipped and future slots have no block
+    assert!(mock_rpc::is_skipped(GENESIS_SLOT + 9));
+    assert!(mock_rpc::block(tip, GENESIS_SLOT + 9, UiTransactionEncoding::Json).is_err());
+    assert!(mock_rpc::block(tip, tip + 1, UiTransactionEncoding::Json).is_err());
+}
+
+#[tokio::test]
+async fn test_mock_rpc_server() {
+    let url = mock_rpc::spawn().await.unwrap();
+    let client = RpcClient::new(url.to_string());
+
+    client.get_health().await.unwrap();
+    assert!(client.get_slot().await.unwrap() >= GENESIS_SLOT);
+
+    let config = RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        transaction_details: Some(TransactionDetails::Full),
+        rewards: Some(true),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    };
+    let block = client
+        .get_block_with_config(GENESIS_SLOT, config)
+        .await
+        .unwrap();
+    let transactions = block.transactions.unwrap();
+    assert_eq!(transactions.len(), TRANSACTIONS_PER_BLOCK);
+
+    // Base64 transactions decode to the same transactions as JSON ones
+    let json = mock_rpc::block(
+        GENESIS_SLOT + 100,
+        GENESIS_SLOT,
+        UiTransactionEncoding::Json,
+    )
+    .unwrap();
+    for (binary, json) in transactions.into_iter().zip(json.transactions.unwrap()) {
+        assert!(matches!(
+            binary.transaction,
+            EncodedTransaction::Binary(_, TransactionBinaryEncoding::Base64)
+        ));
+        let binary = Transaction::try_from(binary).unwrap();
+        let json = Transaction::try_from(json).unwrap();
+        assert_eq!(binary.signature, json.signature);
+        assert_eq!(binary.message, json.message);
+        assert_eq!(extract_transfers(&binary).len(), 1);
+    }
+
+    let skipped = client
+        .get_block_with_config(GENESIS_SLOT + 9, config)
+        .await
+        .map_err(eyre::Report::from)
+        .unwrap_err();
+    assert!(is_skipped_slot(&skipped));
+
+    // Address history pages back through the canned transfers
+    let wallet = mock_rpc::key("wallet", 0);
+    let page = client
+        .get_signatures_for_address_with_config(
+            &wallet,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(3),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 3);
+    assert!(page.windows(2).all(|pair| pair[0].slot >= pair[1].slot));
+
+    let transaction = client
+        .get_transaction(
+            &page[0].signature.parse().unwrap(),
+            UiTransactionEncoding::Json,
+        )
+        .await
+        .unwrap();
+    assert_eq!(transaction.slot, page[0].slot);
+
+    // Batch requests are answered call by call
+    let request: Vec<_> = [GENESIS_SLOT, GENESIS_SLOT + 9]
+        .iter()
+        .enumerate()
+        .map(|(id, slot)| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "getBlock",
+                "params": [slot, config],
+            })
+        })
+        .collect();
+    let responses = reqwest::Client::new()
+        .post(url)
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let results = batch_results(2, responses);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[tokio::test]
+async fn test_indexer_with_mock_rpc() {
+    let storage: Arc<Storage> = Storage::init("soldag_test").await.unwrap();
+    let indexer = Indexer::new(
+        Url::parse("mock://").unwrap(),
+        None,
+        storage,
+        ConfigHandle::default(),
+    )
+    .await
+    .unwrap();
+
+    let account = indexer
+        .get_account("11111111111111111111111111111111".to_string(), None)
+        .await
+        .unwrap();
+    assert!(account.executable);
+
+    let account = indexer
+        .get_account(
+            "11111111111111111111111111111111".to_string(),
+            Some(CommitmentLevel::Processed),
+        )
+        .await
+        .unwrap();
+    assert!(account.executable);
+}
+
+#[tokio::test]
+async fn test_replay_stops_at_undecodable_block() {
+    let storage: Arc<Storage> = Storage::init("soldag_test").await.unwrap();
+    let indexer = Indexer::new(
+        Url::parse("mock://").unwrap(),
+        None,
+        storage.clone(),
+        ConfigHandle::default(),
+    )
+    .await
+    .unwrap();
+
+    let tip = GENESIS_SLOT + INITIAL_SLOTS;
+    let slot = GENESIS_SLOT + 1;
+    let block = mock_rpc::block(tip, slot, UiTransactionEncoding::Json).unwrap();
+    indexer.replay(vec![(slot, block.clone())]).await.unwrap();
+
+    let mut corrupt = mock_rpc::block(tip, slot + 1, UiTransactionEncoding::Json).unwrap();
+    corrupt.transactions.as_mut().unwrap()[0].transaction =
+        EncodedTransaction::LegacyBinary("not base58".to_string());
+    assert!(indexer
+        .replay(vec![(slot, block), (slot + 1, corrupt)])
+        .await
+        .is_err());
+
+    // Nothing was deleted, since the batch didn't decode
+    let filter = TransactionFilter {
+        slot: Some(slot),
+        ..Default::default()
+    };
+    let page = storage
+        .get_transactions(&filter, 10, 0, false)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), TRANSACTIONS_PER_BLOCK);
+}
+
+#[tokio::test]
+async fn test_reprocessed_block_adds_no_records() {
+    let storage: Arc<Storage> = Storage::init("soldag_test_indexes").await.unwrap();
+    storage.create_indexes().await.unwrap();
+    let indexer = Indexer::new(
+        Url::parse("mock://").unwrap(),
+        None,
+        storage.clone(),
+        ConfigHandle::default(),
+    )
+    .await
+    .unwrap();
+
+    let tip = GENESIS_SLOT + INITIAL_SLOTS;
+    let slot = GENESIS_SLOT + 2;
+    let block = Arc::new(mock_rpc::block(tip, slot, UiTransactionEncoding::Json).unwrap());
+    for _ in 0..2 {
+        indexer
+            .process_blocks(vec![(slot, block.clone())])
+            .await
+            .unwrap();
+    }
+
+    let transfers = storage
+        .sol_transfers
+        .count_documents(doc! { "slot": slot as i64 })
+        .await
+        .unwrap();
+    assert_eq!(transfers, TRANSACTIONS_PER_BLOCK as u64);
+}
@@ -1,4 +1,30 @@
+mod addresses;
+mod alerts;
 mod api;
+mod archive;
+mod balances;
+mod bench;
+mod cache;
+mod config;
+mod daemon;
+mod dump;
+mod helius;
 pub mod helpers;
 mod indexer;
+mod leaders;
+mod metrics;
+mod migrations;
+mod mints;
+mod mock_rpc;
+mod prices;
+mod retry;
+mod search;
 mod storage;
+mod stream;
+mod summary;
+mod supervisor;
+mod swaps;
+mod tenants;
+mod token_account;
+mod transaction;
+mod transfers;
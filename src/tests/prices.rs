@@ -0,0 +1,80 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{extract::Query, routing::get, Json, Router};
+use mongodb::bson::DateTime;
+use url::Url;
+
+use crate::{
+    domain::models::transfer::{Transfer, SOL_DECIMALS},
+    prices::{PriceOracle, WRAPPED_SOL_MINT},
+    retry::RetryPolicy,
+};
+
+async fn start_price_api(requests: Arc<AtomicUsize>) -> Url {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = Router::new().route(
+        "/price",
+        get(
+            move |Query(params): Query<std::collections::HashMap<String, String>>| async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                if params["address"] != WRAPPED_SOL_MINT {
+                    return Json(serde_json::json!({ "data": null, "success": false }));
+                }
+                // The price at the requested minute: 150.5 USD, then 100 USD
+                // from Unix time 6000
+                let time: i64 = params["time_from"].parse().unwrap();
+                let value = if time < 6000 { 150.5 } else { 100.0 };
+                Json(serde_json::json!({
+                    "data": { "items": [{ "address": WRAPPED_SOL_MINT, "unixTime": time, "value": value }] },
+                    "success": true
+                }))
+            },
+        ),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    Url::parse(&format!("http://{}/price", addr)).unwrap()
+}
+
+#[tokio::test]
+async fn test_price_enrichment_is_cached() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let oracle = PriceOracle::new(
+        start_price_api(requests.clone()).await,
+        None,
+        &RetryPolicy::default(),
+    );
+
+    let mut transfer = Transfer {
+        mint: None,
+        source: "source".to_string(),
+        destination: "destination".to_string(),
+        amount: 2_000_000_000,
+        decimals: SOL_DECIMALS,
+        usd_value: None,
+    };
+    let block_time = Some(DateTime::from_millis(5_990_000));
+
+    oracle.enrich(&mut transfer, block_time).await;
+    oracle.enrich(&mut transfer, block_time).await;
+
+    assert_eq!(transfer.usd_value, Some(301.0));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // Prices are historical, looked up by the minute of the block time
+    let later = oracle
+        .usd_price(WRAPPED_SOL_MINT, DateTime::from_millis(6_000_000))
+        .await;
+    assert_eq!(later, Some(100.0));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    let unknown = oracle.usd_price("UnknownMint", DateTime::now()).await;
+    assert_eq!(unknown, None);
+}
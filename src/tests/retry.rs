@@ -0,0 +1,70 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use crate::retry::RetryPolicy;
+
+fn policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        backoff_base: Duration::from_millis(1),
+        backoff_max: Duration::from_millis(4),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_backoff_doubles_up_to_ceiling() {
+    let policy = policy(5);
+
+    assert_eq!(policy.backoff(0), Duration::from_millis(1));
+    assert_eq!(policy.backoff(1), Duration::from_millis(2));
+    assert_eq!(policy.backoff(2), Duration::from_millis(4));
+    assert_eq!(policy.backoff(3), Duration::from_millis(4));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(4));
+}
+
+#[tokio::test]
+async fn test_retry_until_success() {
+    let attempts = AtomicU32::new(0);
+
+    let result = policy(5)
+        .retry(
+            "Flaky call",
+            |_: &String| true,
+            || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err("unavailable".to_string()),
+                    attempt => Ok(attempt),
+                }
+            },
+        )
+        .await;
+
+    assert_eq!(result, Ok(2));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retry_gives_up() {
+    let attempts = AtomicU32::new(0);
+    let call = || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>("unavailable".to_string())
+    };
+
+    assert!(policy(2)
+        .retry("Failing call", |_| true, call)
+        .await
+        .is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    // Failures that can't succeed are not retried
+    attempts.store(0, Ordering::SeqCst);
+    assert!(policy(2)
+        .retry("Failing call", |_| false, call)
+        .await
+        .is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
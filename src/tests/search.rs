@@ -0,0 +1,31 @@
+use solana_sdk::{bs58, pubkey::Pubkey};
+
+use crate::api::search::SearchTarget;
+
+#[test]
+fn test_search_query_classification() {
+    assert_eq!(
+        SearchTarget::classify(" 312345678 "),
+        Some(SearchTarget::Slot(312345678))
+    );
+
+    let signature = bs58::encode([7u8; 64]).into_string();
+    assert_eq!(
+        SearchTarget::classify(&signature),
+        Some(SearchTarget::Signature(signature.clone()))
+    );
+    assert_eq!(
+        SearchTarget::classify(&signature[..20]),
+        Some(SearchTarget::SignaturePrefix(signature[..20].to_string()))
+    );
+
+    let address = Pubkey::new_unique().to_string();
+    assert_eq!(
+        SearchTarget::classify(&address),
+        Some(SearchTarget::Hash(address.clone()))
+    );
+
+    assert_eq!(SearchTarget::classify(""), None);
+    assert_eq!(SearchTarget::classify("5VERv"), None);
+    assert_eq!(SearchTarget::classify("0xdeadbeef"), None);
+}
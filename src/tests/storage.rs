@@ -1,8 +1,28 @@
-use chrono::Utc;
-use mongodb::bson::DateTime;
+use std::collections::BTreeMap;
+
+use chrono::{Days, Utc};
+use mongodb::{
+    bson::{doc, DateTime},
+    options::{Acknowledgment, ClientOptions, ReadConcern, ReadPreference, SelectionCriteria},
+};
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
 
 use crate::{
-    domain::{models::transaction::Transaction, storage::Storage},
+    aggregator::start_of_day,
+    bench::synthetic_block,
+    cli::WriteConcernArg,
+    domain::{
+        migrations::TRANSACTION_SCHEMA_VERSION,
+        models::{
+            address_summary::AddressActivity, label::AddressLabel, reward::BlockReward,
+            signature::SignatureRecord, transaction::Transaction,
+        },
+        storage::{
+            BlockFilter, ConnectionOptions, QueryReadConcern, QueryReadPreference, Storage,
+            StorageOptions, TransactionFilter,
+        },
+    },
     tests::helpers::{create_mock_message, create_mock_meta, create_mock_transaction},
 };
 
@@ -26,6 +46,15 @@ async fn test_transaction_insertion_and_retrieval() {
         message: create_mock_message(),
         meta: create_mock_meta(),
         block_time: Some(DateTime::from_chrono(Utc::now())),
+        memos: vec![],
+        compute_unit_price: None,
+        slot: None,
+        resolved_account_keys: vec![],
+        inner_instructions: vec![],
+        failure: None,
+        parsed_instructions: vec![],
+        compressed_meta: None,
+        schema_version: TRANSACTION_SCHEMA_VERSION,
     };
 
     // Test insertion
@@ -34,14 +63,23 @@ async fn test_transaction_insertion_and_retrieval() {
     assert!(result.is_ok());
 
     // Test retrieval by signature
-    let (transactions, next) = storage
-        .get_transactions(Some(tx_signature.clone()), None, 10, 0)
+    let page = storage
+        .get_transactions(
+            &TransactionFilter {
+                id: Some(tx_signature.clone()),
+                ..Default::default()
+            },
+            10,
+            0,
+            true,
+        )
         .await
         .expect("Failed to retrieve transaction");
 
-    assert_eq!(transactions.len(), 1);
-    assert_eq!(transactions[0].signature, tx_signature);
-    assert!(next.is_none());
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].signature, tx_signature);
+    assert!(page.next.is_none());
+    assert_eq!(page.total, Some(1));
 }
 
 #[tokio::test]
@@ -60,11 +98,559 @@ async fn test_transaction_pagination() {
     }
 
     // Test pagination
-    let (transactions, next) = storage
-        .get_transactions(None, None, 10, 0)
+    let page = storage
+        .get_transactions(&TransactionFilter::default(), 10, 0, true)
+        .await
+        .expect("Failed to retrieve transactions");
+
+    assert_eq!(page.items.len(), 10);
+    assert_eq!(page.next, Some(10));
+    assert!(page.total.is_some_and(|total| total >= 20));
+
+    // Without the count, the next page is still detected
+    let page = storage
+        .get_transactions(&TransactionFilter::default(), 10, 0, false)
         .await
         .expect("Failed to retrieve transactions");
 
-    assert_eq!(transactions.len(), 10);
-    assert_eq!(next, Some(10));
+    assert_eq!(page.items.len(), 10);
+    assert_eq!(page.next, Some(10));
+    assert!(page.total.is_none());
+}
+
+#[tokio::test]
+async fn test_daily_stats_aggregation() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    // Pick a day no other test writes to
+    let offset = uuid::Uuid::new_v4().as_u128() % 10_000;
+    let day = start_of_day(Utc::now())
+        .checked_sub_days(Days::new(10_000 + offset as u64))
+        .unwrap();
+
+    for (i, failed) in [false, true].into_iter().enumerate() {
+        let mut transaction = create_mock_transaction(i as u64);
+        transaction.block_time = Some(DateTime::from_chrono(day));
+        transaction.meta.fee = 5000;
+        transaction.message.account_keys = vec![format!("address_{}", i), "shared".to_string()];
+        if failed {
+            transaction.meta.err = Some(TransactionError::AccountNotFound);
+        }
+        storage
+            .insert_transaction(transaction)
+            .await
+            .expect("Failed to insert");
+    }
+
+    let stats = storage
+        .compute_daily_stats(day)
+        .await
+        .expect("Failed to aggregate");
+
+    assert_eq!(stats.transaction_count, 2);
+    assert_eq!(stats.total_fees, 10_000);
+    assert_eq!(stats.failed_count, 1);
+    assert_eq!(stats.unique_addresses, 3);
+    assert_eq!(stats.failure_rate, 0.5);
+
+    storage
+        .upsert_daily_stats(&stats)
+        .await
+        .expect("Failed to store stats");
+
+    let (stored, _) = storage
+        .get_daily_stats(Some(day), Some(day), 10, 0)
+        .await
+        .expect("Failed to retrieve stats");
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].transaction_count, 2);
+}
+
+#[test]
+fn test_memo_filter_escapes_regex() {
+    let filter = TransactionFilter {
+        memo_contains: Some("order (1.5)".to_string()),
+        ..Default::default()
+    };
+
+    let query = filter.to_document();
+    let memos = query.get_document("memos").expect("Missing memo filter");
+
+    assert_eq!(memos.get_str("$regex").unwrap(), r"order \(1\.5\)");
+    assert_eq!(memos.get_str("$options").unwrap(), "i");
+}
+
+#[test]
+fn test_time_range_filter() {
+    let from = Utc::now();
+    let filter = TransactionFilter {
+        from: Some(from),
+        ..Default::default()
+    };
+
+    let query = filter.to_document();
+    let range = query
+        .get_document("block_time")
+        .expect("Missing block_time filter");
+
+    assert_eq!(
+        range.get_datetime("$gte").unwrap(),
+        &DateTime::from_chrono(from)
+    );
+    assert!(!range.contains_key("$lte"));
+}
+
+#[test]
+fn test_block_filter() {
+    assert!(BlockFilter::default().to_document().is_empty());
+
+    let filter = BlockFilter {
+        from_slot: Some(100),
+        to_slot: Some(200),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        filter.to_document(),
+        mongodb::bson::doc! { "slot": { "$gte": 100_i64, "$lte": 200_i64 } }
+    );
+}
+
+#[tokio::test]
+async fn test_label_upsert_lookup_and_delete() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    let address = uuid::Uuid::new_v4().to_string();
+    let mut label = AddressLabel {
+        address: address.clone(),
+        label: "Exchange hot wallet".to_string(),
+        tags: vec!["exchange".to_string()],
+        updated_at: DateTime::now(),
+    };
+
+    storage
+        .upsert_label(&label)
+        .await
+        .expect("Failed to store label");
+
+    label.label = "Exchange cold wallet".to_string();
+    storage
+        .upsert_label(&label)
+        .await
+        .expect("Failed to replace label");
+
+    let labels = storage
+        .get_labels_for([address.as_str(), "unlabeled"])
+        .await
+        .expect("Failed to look up labels");
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[&address].label, "Exchange cold wallet");
+
+    assert!(storage
+        .delete_label(&address)
+        .await
+        .expect("Failed to delete label"));
+    assert!(storage
+        .get_label(&address)
+        .await
+        .expect("Failed to fetch label")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_signature_record_round_trip() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    let record = SignatureRecord {
+        signature: uuid::Uuid::new_v4().to_string(),
+        slot: 42,
+        block_time: Some(DateTime::now()),
+        err: Some(TransactionError::AccountNotFound),
+    };
+
+    storage
+        .insert_signatures(std::slice::from_ref(&record))
+        .await
+        .expect("Failed to store signature");
+
+    let stored = storage
+        .get_signature(&record.signature)
+        .await
+        .expect("Failed to fetch signature");
+    assert_eq!(stored, Some(record));
+}
+
+#[tokio::test]
+async fn test_duplicate_signature_records_are_skipped() {
+    let storage = Storage::init("soldag_test_indexes")
+        .await
+        .expect("Failed to initialize storage");
+    storage
+        .create_indexes()
+        .await
+        .expect("Failed to create indexes");
+
+    let record = SignatureRecord {
+        signature: uuid::Uuid::new_v4().to_string(),
+        slot: 42,
+        block_time: None,
+        err: None,
+    };
+    for _ in 0..2 {
+        storage
+            .insert_signatures(std::slice::from_ref(&record))
+            .await
+            .expect("Failed to store signature");
+    }
+
+    let count = storage
+        .signatures
+        .count_documents(doc! { "signature": &record.signature })
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_duplicate_rewards_are_skipped() {
+    let storage = Storage::init("soldag_test_indexes")
+        .await
+        .expect("Failed to initialize storage");
+    storage
+        .create_indexes()
+        .await
+        .expect("Failed to create indexes");
+
+    let pubkey = uuid::Uuid::new_v4().to_string();
+    let reward = |reward_type: &str| BlockReward {
+        slot: 42,
+        pubkey: pubkey.clone(),
+        lamports: 1000,
+        post_balance: 5000,
+        reward_type: Some(reward_type.to_string()),
+        commission: None,
+        block_time: None,
+    };
+    let rewards = [reward("Fee"), reward("Rent")];
+
+    for _ in 0..2 {
+        storage
+            .insert_rewards(&rewards)
+            .await
+            .expect("Failed to store rewards");
+    }
+
+    let count = storage
+        .rewards
+        .count_documents(doc! { "pubkey": &pubkey })
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn test_duplicate_transaction_is_skipped() {
+    // Kept apart from the shared test database, whose tests reuse signatures
+    let storage = Storage::init("soldag_test_indexes")
+        .await
+        .expect("Failed to initialize storage");
+    storage
+        .create_indexes()
+        .await
+        .expect("Failed to create indexes");
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.signature = uuid::Uuid::new_v4().to_string();
+    let signature = transaction.signature.clone();
+    assert!(storage.insert_transaction(transaction).await.unwrap());
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.signature = signature.clone();
+    assert!(!storage.insert_transaction(transaction).await.unwrap());
+
+    let filter = TransactionFilter {
+        id: Some(signature),
+        ..Default::default()
+    };
+    let page = storage
+        .get_transactions(&filter, 10, 0, true)
+        .await
+        .unwrap();
+    assert_eq!(page.total, Some(1));
+}
+
+#[tokio::test]
+async fn test_unique_indexes_remove_existing_duplicates() {
+    // Starts without indexes, like a database written by an older version
+    let storage = Storage::init("soldag_test_dedupe")
+        .await
+        .expect("Failed to initialize storage");
+    storage.transactions.drop().await.unwrap();
+
+    let signature = uuid::Uuid::new_v4().to_string();
+    let duplicates = (0..2).map(|_| {
+        let mut transaction = create_mock_transaction(0);
+        transaction.signature = signature.clone();
+        transaction
+    });
+    storage.transactions.insert_many(duplicates).await.unwrap();
+
+    storage
+        .create_indexes()
+        .await
+        .expect("Failed to create indexes");
+
+    let count = storage
+        .transactions
+        .count_documents(doc! { "signature": &signature })
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_unnumbered_records_are_numbered_once() {
+    // Starts without indexes, like a database written by an older version
+    let storage = Storage::init("soldag_test_dedupe")
+        .await
+        .expect("Failed to initialize storage");
+    storage.sol_transfers.drop().await.unwrap();
+
+    let transfer = |signature: &str, lamports: i64| {
+        doc! { "from": "a", "to": "b", "lamports": lamports, "signature": signature, "slot": 1_i64 }
+    };
+    // The first transaction was stored twice, the second once
+    storage
+        .sol_transfers
+        .clone_with_type::<mongodb::bson::Document>()
+        .insert_many([
+            transfer("first", 1),
+            transfer("first", 2),
+            transfer("first", 1),
+            transfer("first", 2),
+            transfer("second", 1),
+        ])
+        .await
+        .unwrap();
+
+    storage
+        .create_indexes()
+        .await
+        .expect("Failed to create indexes");
+
+    let transfers = storage
+        .get_sol_transfers(&Default::default(), 10, 0)
+        .await
+        .unwrap()
+        .0;
+    let mut numbered: Vec<_> = transfers
+        .iter()
+        .map(|transfer| {
+            (
+                transfer.signature.as_str(),
+                transfer.index,
+                transfer.lamports,
+            )
+        })
+        .collect();
+    numbered.sort();
+    assert_eq!(
+        numbered,
+        vec![("first", 0, 1), ("first", 1, 2), ("second", 0, 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_compressed_transaction_round_trip() {
+    let storage = Storage::init_with_options(
+        "soldag_test",
+        StorageOptions {
+            compression_level: Some(3),
+            drop_log_messages: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Failed to initialize storage");
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.signature = uuid::Uuid::new_v4().to_string();
+    transaction.meta.log_messages = OptionSerializer::Some(vec!["Program log: hi".to_string()]);
+    transaction.meta.inner_instructions = OptionSerializer::Some(vec![]);
+    let signature = transaction.signature.clone();
+    storage.insert_transaction(transaction).await.unwrap();
+
+    let filter = TransactionFilter {
+        id: Some(signature),
+        ..Default::default()
+    };
+    let transactions = storage
+        .get_transactions(&filter, 1, 0, false)
+        .await
+        .unwrap()
+        .items;
+
+    let meta = &transactions[0].meta;
+    assert_eq!(meta.log_messages, OptionSerializer::None);
+    assert_eq!(meta.inner_instructions, OptionSerializer::Some(vec![]));
+}
+
+#[tokio::test]
+async fn test_raw_block_round_trip() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    let slot = uuid::Uuid::new_v4().as_u64_pair().0 >> 1;
+    let block = synthetic_block(slot, 3);
+
+    assert!(storage.get_raw_block(slot).await.unwrap().is_none());
+
+    storage.insert_raw_block(slot, &block).await.unwrap();
+    // Archiving a block again keeps the first copy
+    storage.insert_raw_block(slot, &block).await.unwrap();
+
+    let archived = storage.get_raw_block(slot).await.unwrap().unwrap();
+    assert_eq!(
+        serde_json::to_value(&archived).unwrap(),
+        serde_json::to_value(&block).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_delete_slots_reverts_summaries() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    let slot = uuid::Uuid::new_v4().as_u64_pair().0 >> 1;
+    let address = uuid::Uuid::new_v4().to_string();
+    let transaction = |index: u64, slot: u64| {
+        let mut transaction = create_mock_transaction(index);
+        transaction.signature = uuid::Uuid::new_v4().to_string();
+        transaction.message.account_keys = vec![address.clone()];
+        transaction.meta.fee = 5000;
+        transaction.slot = Some(slot);
+        transaction
+    };
+    let transactions = [transaction(0, slot), transaction(1, slot + 1)];
+
+    let mut activity = BTreeMap::new();
+    for transaction in transactions {
+        AddressActivity::record(&mut activity, &transaction);
+        storage.insert_transaction(transaction).await.unwrap();
+    }
+    storage.update_address_summaries(&activity).await.unwrap();
+
+    assert_eq!(storage.delete_slots(&[slot]).await.unwrap(), 1);
+
+    let summary = storage
+        .get_address_summary(&address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.transaction_count, 1);
+    assert_eq!(summary.fees_paid, 5000);
+
+    let filter = TransactionFilter {
+        address: Some(address.clone()),
+        ..Default::default()
+    };
+    let remaining = storage
+        .get_transactions(&filter, 10, 0, false)
+        .await
+        .unwrap()
+        .items;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].slot, Some(slot + 1));
+}
+
+#[test]
+fn test_query_routing_options() {
+    assert!(matches!(
+        SelectionCriteria::from(QueryReadPreference::SecondaryPreferred),
+        SelectionCriteria::ReadPreference(ReadPreference::SecondaryPreferred { options: None })
+    ));
+    assert!(matches!(
+        SelectionCriteria::from(QueryReadPreference::default()),
+        SelectionCriteria::ReadPreference(ReadPreference::Primary)
+    ));
+    assert_eq!(
+        ReadConcern::from(QueryReadConcern::Majority),
+        ReadConcern::majority()
+    );
+
+    assert!(matches!(
+        "majority".parse::<WriteConcernArg>(),
+        Ok(WriteConcernArg(Acknowledgment::Majority))
+    ));
+    assert!(matches!(
+        "2".parse::<WriteConcernArg>(),
+        Ok(WriteConcernArg(Acknowledgment::Nodes(2)))
+    ));
+    assert!("all".parse::<WriteConcernArg>().is_err());
+}
+
+#[tokio::test]
+async fn test_connection_options_fill_unset_settings() {
+    let mut options = ClientOptions::parse("mongodb://localhost:27017/?maxPoolSize=200")
+        .await
+        .unwrap();
+    let connection = ConnectionOptions::default();
+    connection.apply(&mut options);
+
+    // Settings from the connection string are kept
+    assert_eq!(options.max_pool_size, Some(200));
+    assert_eq!(options.min_pool_size, Some(connection.min_pool_size));
+    assert_eq!(
+        options.server_selection_timeout,
+        Some(connection.server_selection_timeout)
+    );
+    assert_eq!(options.connect_timeout, Some(connection.connect_timeout));
+    assert_eq!(options.max_idle_time, Some(connection.max_idle_time));
+}
+
+#[tokio::test]
+async fn test_transaction_fields_of_outdated_transactions_are_upgraded() {
+    let storage = Storage::init("soldag_test")
+        .await
+        .expect("Failed to initialize storage");
+
+    // A transaction stored before account keys were resolved
+    let mut transaction = create_mock_transaction(0);
+    transaction.signature = format!("outdated_fields_{}", DateTime::now().timestamp_millis());
+    transaction.message.account_keys = vec!["payer".to_string(), "program".to_string()];
+    let mut document = mongodb::bson::to_document(&transaction).unwrap();
+    document.remove("schema_version");
+    document.remove("resolved_account_keys");
+    storage
+        .transactions
+        .clone_with_type::<mongodb::bson::Document>()
+        .insert_one(document)
+        .await
+        .unwrap();
+
+    let filter = TransactionFilter {
+        id: Some(transaction.signature.clone()),
+        ..Default::default()
+    };
+    let fields = ["resolved_account_keys".to_string(), "meta.fee".to_string()];
+    let page = storage
+        .get_transaction_fields(&filter, &fields, 10, 0, false)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        page.items,
+        [doc! {
+            "resolved_account_keys": ["payer", "program"],
+            "meta": { "fee": transaction.meta.fee as i64 },
+        }]
+    );
 }
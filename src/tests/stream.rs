@@ -0,0 +1,20 @@
+use crate::{stream::TransactionEvent, tests::helpers::create_mock_transaction};
+
+#[test]
+fn test_kafka_record_is_keyed_by_signature() {
+    let transaction = create_mock_transaction(7);
+    let event = TransactionEvent {
+        network: "devnet",
+        transaction: &transaction,
+    };
+
+    let record = event.to_record().expect("Failed to build record");
+
+    assert_eq!(record.key.as_deref(), Some("signature_7".as_bytes()));
+    assert_eq!(record.headers["network"], b"devnet");
+
+    let value: serde_json::Value =
+        serde_json::from_slice(&record.value.expect("Missing value")).unwrap();
+    assert_eq!(value["network"], "devnet");
+    assert_eq!(value["transaction"]["signature"], "signature_7");
+}
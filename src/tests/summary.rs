@@ -0,0 +1,110 @@
+use solana_account_decoder_client_types::token::UiTokenAmount;
+use solana_sdk::{bs58, message::MessageHeader};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiCompiledInstruction, UiTransactionTokenBalance,
+};
+
+use crate::{
+    domain::models::{
+        summary::{TransactionKind, TransactionStatus, TransactionSummary, DEX_PROGRAM_IDS},
+        transfer::{SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_IDS},
+    },
+    tests::helpers::create_mock_transaction,
+};
+
+const PAYER: &str = "Sender1111111111111111111111111111111111111";
+const RECEIVER: &str = "Receiver111111111111111111111111111111111111";
+const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+fn instruction(program_id_index: u8, accounts: Vec<u8>, data: Vec<u8>) -> UiCompiledInstruction {
+    UiCompiledInstruction {
+        program_id_index,
+        accounts,
+        data: bs58::encode(data).into_string(),
+        stack_height: None,
+    }
+}
+
+#[test]
+fn test_transfer_summary() {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(1_000_000u64.to_le_bytes());
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.meta.fee = 5000;
+    transaction.message.header = MessageHeader {
+        num_required_signatures: 1,
+        ..Default::default()
+    };
+    transaction.message.account_keys = vec![
+        PAYER.to_string(),
+        RECEIVER.to_string(),
+        SYSTEM_PROGRAM_ID.to_string(),
+    ];
+    transaction.message.instructions = vec![instruction(2, vec![0, 1], data)];
+
+    let summary = TransactionSummary::from_transaction(&transaction);
+
+    assert_eq!(summary.kind, TransactionKind::Transfer);
+    assert_eq!(summary.status, TransactionStatus::Success);
+    assert_eq!(summary.fee, 5000);
+    assert_eq!(summary.fee_payer.as_deref(), Some(PAYER));
+    assert_eq!(summary.participants, vec![PAYER, RECEIVER]);
+    assert_eq!(summary.transfers[0].amount, 1_000_000);
+    assert_eq!(summary.programs.len(), 1);
+    assert_eq!(summary.programs[0].name.as_deref(), Some("System Program"));
+}
+
+#[test]
+fn test_mint_and_swap_summary() {
+    let mut data = vec![7];
+    data.extend(42u64.to_le_bytes());
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec![
+        PAYER.to_string(),
+        RECEIVER.to_string(),
+        MINT.to_string(),
+        TOKEN_PROGRAM_IDS[0].to_string(),
+    ];
+    transaction.message.instructions = vec![instruction(3, vec![2, 1, 0], data)];
+    transaction.meta.post_token_balances =
+        OptionSerializer::Some(vec![UiTransactionTokenBalance {
+            account_index: 1,
+            mint: MINT.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 6,
+                amount: "42".to_string(),
+                ui_amount_string: "0.000042".to_string(),
+            },
+            owner: OptionSerializer::Skip,
+            program_id: OptionSerializer::Skip,
+        }]);
+
+    let summary = TransactionSummary::from_transaction(&transaction);
+
+    assert_eq!(summary.kind, TransactionKind::Mint);
+    assert_eq!(summary.mints.len(), 1);
+    assert_eq!(summary.mints[0].mint, MINT);
+    assert_eq!(summary.mints[0].destination, RECEIVER);
+    assert_eq!(summary.mints[0].amount, 42);
+    assert_eq!(summary.mints[0].decimals, 6);
+
+    // Invoking a DEX makes the transaction a swap, whatever else it does
+    transaction
+        .message
+        .account_keys
+        .push(DEX_PROGRAM_IDS[0].to_string());
+    transaction
+        .message
+        .instructions
+        .push(instruction(4, vec![], vec![]));
+
+    let summary = TransactionSummary::from_transaction(&transaction);
+    assert_eq!(summary.kind, TransactionKind::Swap);
+    assert_eq!(
+        summary.programs[1].name.as_deref(),
+        Some("Jupiter Aggregator v6")
+    );
+}
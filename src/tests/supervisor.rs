@@ -0,0 +1,67 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::supervisor::{RestartBudget, RestartPolicy, ServiceFailed, Supervisor};
+
+fn policy() -> RestartPolicy {
+    RestartPolicy {
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        max_restarts: 3,
+        window: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn test_restart_backoff_and_budget() {
+    let policy = policy();
+    let mut budget = RestartBudget::default();
+    let now = Instant::now();
+
+    assert_eq!(
+        budget.record_failure(&policy, now),
+        Some(Duration::from_millis(10))
+    );
+    assert_eq!(
+        budget.record_failure(&policy, now),
+        Some(Duration::from_millis(20))
+    );
+    assert_eq!(
+        budget.record_failure(&policy, now),
+        Some(Duration::from_millis(40))
+    );
+    assert_eq!(budget.record_failure(&policy, now), None);
+
+    // Restarts outside the window no longer count against the budget
+    let later = now + policy.window;
+    assert_eq!(
+        budget.record_failure(&policy, later),
+        Some(Duration::from_millis(10))
+    );
+
+    assert_eq!(policy.backoff(10), policy.max_backoff);
+}
+
+#[tokio::test]
+async fn test_supervisor_escalates_permanent_failure() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let mut supervisor = Supervisor::new(policy(), None);
+
+    let counter = starts.clone();
+    supervisor.spawn("failing", move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { Err(eyre::eyre!("unavailable")) }
+    });
+
+    let err = supervisor.run(std::future::pending()).await.unwrap_err();
+    let failure = err.downcast_ref::<ServiceFailed>().unwrap();
+
+    assert_eq!(failure.service, "failing");
+    assert_eq!(failure.restarts, 3);
+    assert_eq!(starts.load(Ordering::SeqCst), 4);
+}
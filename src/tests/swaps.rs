@@ -0,0 +1,163 @@
+use solana_account_decoder_client_types::token::UiTokenAmount;
+use solana_sdk::bs58;
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, UiCompiledInstruction, UiTransactionTokenBalance,
+};
+
+use crate::{
+    api::{
+        query::{Paginated, Validate},
+        swaps::SwapQuery,
+    },
+    domain::models::{
+        swap::{Swap, NATIVE_MINT, SWAP_DECODERS},
+        transaction::Transaction,
+    },
+    tests::helpers::create_mock_transaction,
+};
+
+const TRADER: &str = "Trader1111111111111111111111111111111111111";
+const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCaXQNvdr4vDLh6aoWd";
+
+fn token_balance(
+    account_index: u8,
+    mint: &str,
+    amount: u64,
+    decimals: u8,
+) -> UiTransactionTokenBalance {
+    UiTransactionTokenBalance {
+        account_index,
+        mint: mint.to_string(),
+        ui_token_amount: UiTokenAmount {
+            ui_amount: None,
+            decimals,
+            amount: amount.to_string(),
+            ui_amount_string: String::new(),
+        },
+        owner: OptionSerializer::Some(TRADER.to_string()),
+        program_id: OptionSerializer::Skip,
+    }
+}
+
+/// Builds a transaction invoking `program_id` with `data`, in which the
+/// trader's balances move from `pre` to `post`.
+fn swap_transaction(
+    program_id: &str,
+    data: Vec<u8>,
+    (pre_lamports, post_lamports): (u64, u64),
+    pre: Vec<UiTransactionTokenBalance>,
+    post: Vec<UiTransactionTokenBalance>,
+) -> Transaction {
+    let mut transaction = create_mock_transaction(0);
+    transaction.slot = Some(42);
+    transaction.message.account_keys = vec![
+        TRADER.to_string(),
+        "TokenAccountA111111111111111111111111111111".to_string(),
+        "TokenAccountB111111111111111111111111111111".to_string(),
+        program_id.to_string(),
+    ];
+    transaction.message.instructions = vec![UiCompiledInstruction {
+        program_id_index: 3,
+        accounts: vec![0, 1, 2],
+        data: bs58::encode(data).into_string(),
+        stack_height: None,
+    }];
+    transaction.meta.fee = 5000;
+    transaction.meta.pre_balances = vec![pre_lamports, 2_039_280, 2_039_280, 1];
+    transaction.meta.post_balances = vec![post_lamports, 2_039_280, 2_039_280, 1];
+    transaction.meta.pre_token_balances = OptionSerializer::Some(pre);
+    transaction.meta.post_token_balances = OptionSerializer::Some(post);
+    transaction
+}
+
+#[test]
+fn test_token_for_sol_swap() {
+    let raydium = SWAP_DECODERS[1].program_id;
+    let mut data = vec![9];
+    data.extend(25_000_000u64.to_le_bytes());
+    data.extend(0u64.to_le_bytes());
+
+    let transaction = swap_transaction(
+        raydium,
+        data,
+        (1_000_000_000, 1_149_995_000),
+        vec![token_balance(1, USDC, 30_000_000, 6)],
+        vec![token_balance(1, USDC, 5_000_000, 6)],
+    );
+
+    let swap = Swap::from_transaction(&transaction).unwrap();
+    assert_eq!(swap.dex, "Raydium AMM v4");
+    assert_eq!(swap.trader, TRADER);
+    assert_eq!(swap.input_mint, USDC);
+    assert_eq!(swap.input_amount, 25_000_000);
+    assert_eq!(swap.input_decimals, 6);
+    assert_eq!(swap.output_mint, NATIVE_MINT);
+    assert_eq!(swap.output_amount, 150_000_000);
+    assert_eq!(swap.output_decimals, 9);
+    assert_eq!(swap.slot, Some(42));
+}
+
+#[test]
+fn test_token_for_token_swap_ignores_rent() {
+    let jupiter = SWAP_DECODERS[0].program_id;
+    let mut data = vec![229, 23, 203, 151, 122, 227, 173, 42];
+    data.extend([0; 16]);
+
+    // Opening the output token account costs rent, which isn't a SOL sale
+    let transaction = swap_transaction(
+        jupiter,
+        data,
+        (1_000_000_000, 1_000_000_000 - 5000 - 2_039_280),
+        vec![token_balance(1, USDC, 30_000_000, 6)],
+        vec![
+            token_balance(1, USDC, 0, 6),
+            token_balance(2, BONK, 1_234_567_890, 5),
+        ],
+    );
+
+    let swap = Swap::from_transaction(&transaction).unwrap();
+    assert_eq!(swap.dex, "Jupiter Aggregator v6");
+    assert_eq!(swap.input_mint, USDC);
+    assert_eq!(swap.input_amount, 30_000_000);
+    assert_eq!(swap.output_mint, BONK);
+    assert_eq!(swap.output_amount, 1_234_567_890);
+}
+
+#[test]
+fn test_non_swaps_are_ignored() {
+    let raydium = SWAP_DECODERS[1].program_id;
+    let pre = vec![token_balance(1, USDC, 30_000_000, 6)];
+    let post = vec![token_balance(1, USDC, 5_000_000, 6)];
+
+    // Deposits into the pool aren't swaps
+    let deposit = swap_transaction(
+        raydium,
+        vec![3],
+        (1_000_000_000, 900_000_000),
+        pre.clone(),
+        post.clone(),
+    );
+    assert_eq!(Swap::from_transaction(&deposit), None);
+
+    // Failed swaps moved nothing
+    let mut failed = swap_transaction(raydium, vec![9], (1_000_000_000, 1_149_995_000), pre, post);
+    failed.meta.err = Some(solana_sdk::transaction::TransactionError::AccountInUse);
+    assert_eq!(Swap::from_transaction(&failed), None);
+}
+
+#[test]
+fn test_swap_query_validation() {
+    let parse = |value: serde_json::Value| {
+        serde_json::from_value::<Paginated<SwapQuery>>(value)
+            .unwrap()
+            .validate()
+    };
+
+    assert!(parse(serde_json::json!({ "mint": USDC })).is_ok());
+    assert!(parse(serde_json::json!({ "program_id": SWAP_DECODERS[4].program_id })).is_ok());
+    assert!(
+        parse(serde_json::json!({ "program_id": "11111111111111111111111111111111" })).is_err()
+    );
+    assert!(parse(serde_json::json!({ "from_slot": 20, "to_slot": 10 })).is_err());
+}
@@ -0,0 +1,55 @@
+use mongodb::bson::{doc, DateTime};
+
+use crate::{
+    config::Filters,
+    domain::{models::api_key::ApiKey, storage::TransactionFilter},
+};
+
+fn create_api_key(endpoints: &[&str]) -> ApiKey {
+    ApiKey {
+        key: "sk_test".to_string(),
+        name: "test".to_string(),
+        rate_limit: None,
+        endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+        scope: None,
+        created_at: DateTime::now(),
+    }
+}
+
+#[test]
+fn test_api_key_endpoint_prefixes() {
+    let unrestricted = create_api_key(&[]);
+    assert!(unrestricted.allows("/transactions"));
+    assert!(unrestricted.allows("/admin/reload"));
+
+    let restricted = create_api_key(&["/transactions", "/labels/"]);
+    assert!(restricted.allows("/transactions"));
+    assert!(restricted.allows("/transactions/simulate"));
+    assert!(restricted.allows("/labels/11111111111111111111111111111111"));
+    assert!(!restricted.allows("/transactionsx"));
+    assert!(!restricted.allows("/accounts"));
+}
+
+#[test]
+fn test_scoped_transaction_filter() {
+    let filter = TransactionFilter {
+        scope: Some(Filters {
+            addresses: ["Vote111111111111111111111111111111111111111".to_string()].into(),
+            programs: Default::default(),
+        }),
+        ..Default::default()
+    };
+
+    let keys = vec!["Vote111111111111111111111111111111111111111"];
+    assert_eq!(
+        filter.to_document(),
+        doc! {
+            "$or": [
+                { "message.accountKeys": { "$in": &keys } },
+                { "meta.loadedAddresses.writable": { "$in": &keys } },
+                { "meta.loadedAddresses.readonly": { "$in": &keys } },
+                { "resolved_account_keys": { "$in": &keys } },
+            ]
+        }
+    );
+}
@@ -0,0 +1,38 @@
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::domain::models::token_account::{TokenAccount, TokenAccountState, TOKEN_ACCOUNT_LEN};
+
+#[test]
+fn test_decode_token_account() {
+    let program = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let (mint, owner, delegate) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&1_500u64.to_le_bytes());
+    data[72..76].copy_from_slice(&[1, 0, 0, 0]);
+    data[76..108].copy_from_slice(delegate.as_ref());
+    data[108] = 2;
+    data[121..129].copy_from_slice(&500u64.to_le_bytes());
+
+    let account = TokenAccount::decode(&program, &data).unwrap();
+    assert_eq!(account.mint, mint.to_string());
+    assert_eq!(account.owner, owner.to_string());
+    assert_eq!(account.amount, 1_500);
+    assert_eq!(account.delegate, Some(delegate.to_string()));
+    assert_eq!(account.delegated_amount, 500);
+    assert_eq!(account.state, TokenAccountState::Frozen);
+    assert_eq!(account.is_native, None);
+    assert_eq!(account.close_authority, None);
+
+    // Mints and accounts of other programs aren't decoded
+    assert!(TokenAccount::decode(&program, &data[..82]).is_none());
+    assert!(TokenAccount::decode(&Pubkey::default(), &data).is_none());
+}
@@ -0,0 +1,415 @@
+use std::sync::Arc;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    bs58,
+    hash::Hash,
+    instruction::{CompiledInstruction, InstructionError},
+    message::{Message, MessageHeader, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::TransactionError,
+};
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    TransactionBinaryEncoding, UiAddressTableLookup, UiCompiledInstruction, UiInnerInstructions,
+    UiInstruction, UiLoadedAddresses,
+};
+
+use crate::{
+    domain::models::{
+        failure::TransactionFailure,
+        fee_stats::FeeStats,
+        transaction::{
+            extract_compute_unit_price, extract_memos, InnerInstruction, Transaction,
+            COMPUTE_BUDGET_PROGRAM_ID, MEMO_PROGRAM_IDS,
+        },
+    },
+    lookup_tables::LookupTables,
+    tests::helpers::{create_mock_message, create_mock_meta, create_mock_transaction},
+};
+
+#[test]
+fn test_memo_extraction() {
+    let mut message = create_mock_message();
+    message.account_keys = vec![
+        "4aRX4tq2mm5XS2PUUtJPcXUPvgrza5jvjKmoMZzUKcLM".to_string(),
+        MEMO_PROGRAM_IDS[0].to_string(),
+        "11111111111111111111111111111111".to_string(),
+    ];
+    message.instructions = vec![
+        UiCompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![0],
+            data: bs58::encode("invoice #42").into_string(),
+            stack_height: None,
+        },
+        UiCompiledInstruction {
+            program_id_index: 2,
+            accounts: vec![0],
+            data: bs58::encode([2, 0, 0, 0]).into_string(),
+            stack_height: None,
+        },
+    ];
+
+    assert_eq!(extract_memos(&message), vec!["invoice #42".to_string()]);
+}
+
+#[test]
+fn test_compute_unit_price_extraction() {
+    let mut message = create_mock_message();
+    message.account_keys = vec![
+        "4aRX4tq2mm5XS2PUUtJPcXUPvgrza5jvjKmoMZzUKcLM".to_string(),
+        COMPUTE_BUDGET_PROGRAM_ID.to_string(),
+    ];
+    assert_eq!(extract_compute_unit_price(&message), None);
+
+    let mut data = vec![3];
+    data.extend(25_000u64.to_le_bytes());
+    message.instructions = vec![
+        UiCompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![],
+            data: bs58::encode([2, 64, 13, 3, 0]).into_string(),
+            stack_height: None,
+        },
+        UiCompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![],
+            data: bs58::encode(data).into_string(),
+            stack_height: None,
+        },
+    ];
+
+    assert_eq!(extract_compute_unit_price(&message), Some(25_000));
+}
+
+#[test]
+fn test_fee_stats_percentiles() {
+    assert_eq!(FeeStats::from_samples(vec![]), None);
+
+    let stats = FeeStats::from_samples((1..=100).rev().collect()).unwrap();
+    assert_eq!(stats.samples, 100);
+    assert_eq!(stats.min, 1);
+    assert_eq!(stats.median, 50);
+    assert_eq!(stats.p75, 75);
+    assert_eq!(stats.p90, 90);
+    assert_eq!(stats.max, 100);
+}
+
+#[tokio::test]
+async fn test_resolve_uses_reported_loaded_addresses() {
+    // Unreachable endpoint: resolution must not need the RPC node
+    let client = Arc::new(RpcClient::new("http://127.0.0.1:1".to_string()));
+    let lookup_tables = LookupTables::new(client);
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec!["payer".to_string()];
+    transaction.message.address_table_lookups = Some(vec![UiAddressTableLookup {
+        account_key: "table".to_string(),
+        writable_indexes: vec![0],
+        readonly_indexes: vec![1],
+    }]);
+    transaction.meta.loaded_addresses = OptionSerializer::Some(UiLoadedAddresses {
+        writable: vec!["pool".to_string()],
+        readonly: vec!["program".to_string()],
+    });
+
+    let keys = lookup_tables.resolve(&mut transaction).await;
+
+    assert_eq!(keys, ["payer", "pool", "program"]);
+}
+
+#[tokio::test]
+async fn test_resolve_falls_back_to_static_keys() {
+    // Unreachable endpoint: the lookup table can't be fetched
+    let client = Arc::new(RpcClient::new("http://127.0.0.1:1".to_string()));
+    let lookup_tables = LookupTables::new(client);
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec!["payer".to_string()];
+    transaction.message.address_table_lookups = Some(vec![UiAddressTableLookup {
+        account_key: "AddressLookupTab1e1111111111111111111111111".to_string(),
+        writable_indexes: vec![0],
+        readonly_indexes: vec![],
+    }]);
+    transaction.meta.loaded_addresses = OptionSerializer::Skip;
+
+    let keys = lookup_tables.resolve(&mut transaction).await;
+
+    assert_eq!(keys, ["payer"]);
+    assert_eq!(transaction.meta.loaded_addresses, OptionSerializer::Skip);
+}
+
+#[test]
+fn test_inner_instruction_normalization() {
+    let mut transaction = create_mock_transaction(0);
+    transaction.message.account_keys = vec![
+        "payer".to_string(),
+        "router".to_string(),
+        "token".to_string(),
+    ];
+    transaction.meta.loaded_addresses = OptionSerializer::Some(UiLoadedAddresses {
+        writable: vec!["pool".to_string()],
+        readonly: vec![],
+    });
+    transaction.meta.inner_instructions = OptionSerializer::Some(vec![UiInnerInstructions {
+        index: 0,
+        instructions: vec![
+            UiInstruction::Compiled(UiCompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![3, 0],
+                data: "3Bxs4h24hBtQy9rw".to_string(),
+                stack_height: Some(2),
+            }),
+            // Out of range account index
+            UiInstruction::Compiled(UiCompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![9],
+                data: String::new(),
+                stack_height: Some(2),
+            }),
+        ],
+    }]);
+
+    assert_eq!(
+        transaction.normalize_inner_instructions(),
+        [InnerInstruction {
+            index: 0,
+            program_id: "token".to_string(),
+            accounts: vec!["pool".to_string(), "payer".to_string()],
+            data: "3Bxs4h24hBtQy9rw".to_string(),
+            stack_height: Some(2),
+        }]
+    );
+}
+
+#[test]
+fn test_failure_classification() {
+    let programs = [
+        "ComputeBudget111111111111111111111111111111",
+        "swap_program",
+    ];
+
+    assert_eq!(
+        TransactionFailure::classify(
+            &TransactionError::InstructionError(1, InstructionError::Custom(6001)),
+            &programs,
+        ),
+        TransactionFailure {
+            category: "Custom".to_string(),
+            instruction_index: Some(1),
+            program_id: Some("swap_program".to_string()),
+            code: Some(6001),
+        }
+    );
+
+    let failure = TransactionFailure::classify(
+        &TransactionError::InstructionError(0, InstructionError::InsufficientFunds),
+        &programs,
+    );
+    assert_eq!(failure.category, "InsufficientFunds");
+    assert_eq!(failure.code, None);
+
+    let failure = TransactionFailure::classify(&TransactionError::BlockhashNotFound, &programs);
+    assert_eq!(failure.category, "BlockhashNotFound");
+    assert_eq!(failure.program_id, None);
+}
+
+#[test]
+fn test_binary_transaction_decoding() {
+    let payer = Pubkey::new_unique();
+    let memo_program = Pubkey::from_str_const(MEMO_PROGRAM_IDS[0]);
+    let message = VersionedMessage::Legacy(Message::new_with_compiled_instructions(
+        1,
+        0,
+        1,
+        vec![payer, memo_program],
+        Hash::new_unique(),
+        vec![CompiledInstruction::new_from_raw_parts(
+            1,
+            b"invoice #42".to_vec(),
+            vec![0],
+        )],
+    ));
+    let signature = Signature::from([7; 64]);
+
+    // Wire format: compact signature count, signatures, then the message
+    let mut bytes = vec![1];
+    bytes.extend(signature.as_ref());
+    bytes.extend(message.serialize());
+    let blob = bs58::encode(bytes).into_string();
+
+    for encoded in [
+        EncodedTransaction::LegacyBinary(blob.clone()),
+        EncodedTransaction::Binary(blob, TransactionBinaryEncoding::Base58),
+    ] {
+        let transaction = Transaction::try_from(EncodedTransactionWithStatusMeta {
+            transaction: encoded,
+            meta: Some(create_mock_meta()),
+            version: None,
+        })
+        .expect("Binary transactions decode");
+
+        assert_eq!(transaction.signature, signature.to_string());
+        assert_eq!(
+            transaction.message.header,
+            MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            }
+        );
+        assert_eq!(
+            transaction.message.account_keys,
+            vec![payer.to_string(), memo_program.to_string()]
+        );
+        assert_eq!(transaction.message.address_table_lookups, None);
+        assert_eq!(transaction.memos, vec!["invoice #42".to_string()]);
+    }
+
+    let invalid = Transaction::try_from(EncodedTransactionWithStatusMeta {
+        transaction: EncodedTransaction::LegacyBinary("not a transaction".to_string()),
+        meta: Some(create_mock_meta()),
+        version: None,
+    });
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn test_json_parsed_transaction_conversion() {
+    let unknown_program = "Prog111111111111111111111111111111111111111";
+    let encoded: EncodedTransactionWithStatusMeta = serde_json::from_value(serde_json::json!({
+        "transaction": {
+            "signatures": ["sig"],
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Payer11111111111111111111111111111111111111", "signer": true, "writable": true, "source": "transaction" },
+                    { "pubkey": "Receiver111111111111111111111111111111111111", "signer": false, "writable": true, "source": "transaction" },
+                    { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+                    { "pubkey": unknown_program, "signer": false, "writable": false, "source": "transaction" },
+                    { "pubkey": "Loaded1111111111111111111111111111111111111", "signer": false, "writable": true, "source": "lookupTable" },
+                ],
+                "recentBlockhash": "11111111111111111111111111111111",
+                "instructions": [
+                    {
+                        "program": "system",
+                        "programId": "11111111111111111111111111111111",
+                        "parsed": { "type": "transfer", "info": { "lamports": 5 } },
+                        "stackHeight": null,
+                    },
+                    {
+                        "programId": unknown_program,
+                        "accounts": ["Payer11111111111111111111111111111111111111", "Loaded1111111111111111111111111111111111111"],
+                        "data": "3Bxs4h24hBtQy9rw",
+                        "stackHeight": null,
+                    },
+                ],
+                "addressTableLookups": [
+                    { "accountKey": "Table11111111111111111111111111111111111111", "writableIndexes": [0], "readonlyIndexes": [] },
+                ],
+            },
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "innerInstructions": [{
+                "index": 1,
+                "instructions": [{
+                    "programId": unknown_program,
+                    "accounts": ["Receiver111111111111111111111111111111111111"],
+                    "data": "3Bxs4h24hBtQy9rw",
+                    "stackHeight": 2,
+                }],
+            }],
+        },
+        "version": 0,
+    }))
+    .expect("Valid jsonParsed transaction");
+
+    let transaction = Transaction::try_from(encoded).expect("jsonParsed transactions convert");
+
+    assert_eq!(
+        transaction.message.header,
+        MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 2,
+        }
+    );
+    assert_eq!(transaction.message.account_keys.len(), 4);
+    assert_eq!(
+        transaction.account_keys()[4],
+        "Loaded1111111111111111111111111111111111111"
+    );
+
+    // The fully decoded transfer keeps only its program in the message
+    assert_eq!(transaction.message.instructions[0].program_id_index, 2);
+    assert!(transaction.message.instructions[0].accounts.is_empty());
+    assert_eq!(transaction.parsed_instructions.len(), 1);
+    assert_eq!(transaction.parsed_instructions[0].index, 0);
+    assert_eq!(transaction.parsed_instructions[0].program, "system");
+    assert_eq!(
+        transaction.parsed_instructions[0].parsed["info"]["lamports"],
+        5
+    );
+
+    // Partially decoded instructions are compiled against the account list
+    assert_eq!(transaction.message.instructions[1].program_id_index, 3);
+    assert_eq!(transaction.message.instructions[1].accounts, vec![0, 4]);
+    assert_eq!(
+        transaction.normalize_inner_instructions(),
+        vec![InnerInstruction {
+            index: 1,
+            program_id: unknown_program.to_string(),
+            accounts: vec!["Receiver111111111111111111111111111111111111".to_string()],
+            data: "3Bxs4h24hBtQy9rw".to_string(),
+            stack_height: Some(2),
+        }]
+    );
+}
+
+#[test]
+fn test_compress_round_trip() {
+    let logs = vec!["Program log: Instruction: Transfer".repeat(10); 20];
+    let inner = vec![UiInnerInstructions {
+        index: 0,
+        instructions: vec![UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![0],
+            data: "3Bxs4h24hBtQy9rw".to_string(),
+            stack_height: Some(2),
+        })],
+    }];
+    let mut transaction = create_mock_transaction(0);
+    transaction.meta.log_messages = OptionSerializer::Some(logs.clone());
+    transaction.meta.inner_instructions = OptionSerializer::Some(inner.clone());
+    let uncompressed = mongodb::bson::to_vec(&transaction).unwrap().len();
+
+    transaction.compress(3).unwrap();
+
+    // The bulky fields are only stored in compressed form
+    let document = mongodb::bson::to_document(&transaction).unwrap();
+    let meta = document.get_document("meta").unwrap();
+    assert!(!meta.contains_key("logMessages"));
+    assert!(!meta.contains_key("innerInstructions"));
+    assert!(mongodb::bson::to_vec(&document).unwrap().len() < uncompressed);
+
+    let mut stored: Transaction = mongodb::bson::from_document(document).unwrap();
+    stored.decompress().unwrap();
+
+    assert_eq!(stored.meta.log_messages, OptionSerializer::Some(logs));
+    assert_eq!(
+        stored.meta.inner_instructions,
+        OptionSerializer::Some(inner)
+    );
+    assert!(stored.compressed_meta.is_none());
+
+    // Uncompressed transactions are left untouched
+    let mut plain = create_mock_transaction(1);
+    plain.decompress().unwrap();
+    assert_eq!(plain.meta.log_messages, OptionSerializer::Skip);
+}
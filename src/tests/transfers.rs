@@ -0,0 +1,107 @@
+use solana_sdk::{bs58, transaction::TransactionError};
+use solana_transaction_status_client_types::UiCompiledInstruction;
+
+use crate::{
+    api::{
+        query::{Paginated, Validate},
+        transfers::{AddressSolTransferQuery, SolTransferQuery},
+    },
+    domain::{
+        models::{
+            sol_transfer::SolTransfer,
+            transaction::Transaction,
+            transfer::{SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_IDS},
+        },
+        storage::SolTransferFilter,
+    },
+    tests::helpers::create_mock_transaction,
+};
+
+const SENDER: &str = "Sender1111111111111111111111111111111111111";
+const RECEIVER: &str = "Receiver111111111111111111111111111111111111";
+
+/// Builds a transaction with a SOL transfer and an SPL token transfer.
+fn transfer_transaction(lamports: u64) -> Transaction {
+    let mut sol_data = 2u32.to_le_bytes().to_vec();
+    sol_data.extend(lamports.to_le_bytes());
+    let mut token_data = vec![3];
+    token_data.extend(10u64.to_le_bytes());
+
+    let mut transaction = create_mock_transaction(0);
+    transaction.slot = Some(42);
+    transaction.message.account_keys = vec![
+        SENDER.to_string(),
+        RECEIVER.to_string(),
+        SYSTEM_PROGRAM_ID.to_string(),
+        TOKEN_PROGRAM_IDS[0].to_string(),
+    ];
+    transaction.message.instructions = vec![
+        UiCompiledInstruction {
+            program_id_index: 2,
+            accounts: vec![0, 1],
+            data: bs58::encode(sol_data).into_string(),
+            stack_height: None,
+        },
+        UiCompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0, 1, 0],
+            data: bs58::encode(token_data).into_string(),
+            stack_height: None,
+        },
+    ];
+    transaction
+}
+
+#[test]
+fn test_sol_transfers_from_transaction() {
+    let transfers = SolTransfer::from_transaction(&transfer_transaction(1_500_000_000));
+
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].from, SENDER);
+    assert_eq!(transfers[0].to, RECEIVER);
+    assert_eq!(transfers[0].lamports, 1_500_000_000);
+    assert_eq!(transfers[0].slot, Some(42));
+
+    let mut failed = transfer_transaction(1_500_000_000);
+    failed.meta.err = Some(TransactionError::InsufficientFundsForFee);
+    assert!(SolTransfer::from_transaction(&failed).is_empty());
+}
+
+#[test]
+fn test_sol_transfer_filter() {
+    let filter = SolTransferFilter {
+        address: Some(SENDER.to_string()),
+        min_lamports: Some(1_000),
+        to_slot: Some(50),
+        ..Default::default()
+    };
+
+    let query = filter.to_document();
+    assert_eq!(query.get_array("$or").unwrap().len(), 2);
+    let lamports = query.get_document("lamports").unwrap();
+    assert_eq!(lamports.get_i64("$gte").unwrap(), 1_000);
+    assert!(lamports.get("$lte").is_none());
+    assert_eq!(
+        query.get_document("slot").unwrap().get_i64("$lte").unwrap(),
+        50
+    );
+}
+
+#[test]
+fn test_sol_transfer_query_validation() {
+    fn parse<Q: serde::de::DeserializeOwned + Validate>(query: &str) -> Result<(), String> {
+        let uri: http::Uri = format!("/sol-transfers?{}", query).parse().unwrap();
+        axum::extract::Query::<Paginated<Q>>::try_from_uri(&uri)
+            .map_err(|e| e.body_text())
+            .and_then(|query| query.0.validate())
+    }
+
+    assert_eq!(
+        parse::<SolTransferQuery>("from=abc&min_lamports=10&max_lamports=20"),
+        Ok(())
+    );
+    assert!(parse::<SolTransferQuery>("min_lamports=20&max_lamports=10").is_err());
+    assert!(parse::<SolTransferQuery>("from_slot=20&to_slot=10").is_err());
+    assert!(parse::<AddressSolTransferQuery>("direction=in&min_lamports=1").is_ok());
+    assert!(parse::<AddressSolTransferQuery>("direction=sideways").is_err());
+}